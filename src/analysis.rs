@@ -0,0 +1,919 @@
+use crate::Midi;
+use crate::flat::FlatTrack;
+use crate::flat::FLAG_CHORD;
+use crate::flat::FLAG_REST;
+use crate::flat::FLAG_START;
+use crate::flat::FLAG_TIED;
+use crate::flat::FLAG_TRIPLET;
+use crate::parsing::Track;
+use crate::parsing::TrackKind;
+use crate::parsing::duration::NoteDuration;
+use crate::parsing::events::EventFile;
+use crate::parsing::events::EventKind;
+use crate::parsing::measure;
+use crate::parsing::symbols::Note;
+use crate::parsing::symbols::TimeSignature;
+use crate::parsing::tempo::TempoMap;
+
+/// Summary statistics of the notes in a track or piece.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TrackStats {
+    /// The number of notes played. Tied notes count once, and every note of a chord counts.
+    pub note_count: usize,
+    /// The lowest note value played, if there are any notes.
+    pub min_pitch: Option<u8>,
+    /// The highest note value played, if there are any notes.
+    pub max_pitch: Option<u8>,
+    /// The average note value played, if there are any notes.
+    pub mean_pitch: Option<f32>,
+    /// The number of written notes of each duration from whole notes to hundred twenty-eighth
+    /// notes, ignoring dots. The pieces of a tied note each count.
+    pub duration_histogram: [usize; 8],
+    /// The average velocity of the notes played, if there are any notes.
+    pub mean_velocity: Option<f32>,
+    /// The number of beats the notes and rests last.
+    pub total_beats: f32,
+    /// The share of `total_beats` spent resting, from 0 to 100.
+    pub rest_percentage: f32,
+}
+
+/// The number of notes sounding at once over the course of a track.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PolyphonyProfile {
+    /// The most notes that sound at once.
+    pub max: usize,
+    /// The average number of notes sounding at once, weighted by how long each lasts.
+    pub mean: f32,
+    /// The number of notes sounding from each beat on which it changes, as `(beat, count)` pairs.
+    pub series: Vec<(f32, usize)>,
+}
+
+impl PolyphonyProfile {
+    /// Whether an instrument that plays one note at a time could play the track.
+    pub fn is_monophonic(&self) -> bool {
+        return self.max <= 1;
+    }
+}
+
+/// A graded difficulty level.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DifficultyLevel {
+    Beginner,
+    Intermediate,
+    Advanced,
+}
+
+impl DifficultyLevel {
+    /// Converts the enum to a string.
+    pub fn to_string(&self) -> &str {
+        match self {
+            DifficultyLevel::Beginner => return "beginner",
+            DifficultyLevel::Intermediate => return "intermediate",
+            DifficultyLevel::Advanced => return "advanced",
+        }
+    }
+}
+
+/// How hard a track is to play, along with the measurements the rating is made from.
+/// 
+/// Every measurement is scaled from 0 (easy) to 1 (hard).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Difficulty {
+    /// The overall rating from 0 (easiest) to 100 (hardest).
+    pub score: f32,
+    /// The level `score` falls in. Scores under 35 are for beginners and scores of 65 or more are
+    /// advanced.
+    pub level: DifficultyLevel,
+    /// How many notes are played each second, where 8 or more is hardest.
+    pub speed: f32,
+    /// The distance between the lowest and highest notes, where three octaves or more is hardest.
+    pub range: f32,
+    /// The average leap between melody notes, where a fifth or more is hardest.
+    pub leaps: f32,
+    /// How varied the durations are and how many notes start off the eighth note grid.
+    pub rhythm: f32,
+    /// The most notes played at once, where four or more is hardest.
+    pub polyphony: f32,
+}
+
+/// Options for `find_pattern`.
+/// 
+/// Options are set with builder-style methods:
+/// 
+/// ```
+/// use beatblox_midi::analysis::PatternOptions;
+/// 
+/// let options = PatternOptions::new().transpose(true);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PatternOptions {
+    /// Whether or not the pattern also matches when it's played starting on another pitch.
+    pub transpose: bool,
+    /// Whether or not the pattern matches regardless of how long its notes are.
+    pub ignore_rhythm: bool,
+}
+
+impl PatternOptions {
+    /// Creates options that only match the pattern exactly.
+    pub fn new() -> Self {
+        return PatternOptions::default();
+    }
+
+    /// Sets whether or not the pattern also matches when it's played starting on another pitch.
+    pub fn transpose(mut self, transpose: bool) -> Self {
+        self.transpose = transpose;
+        return self;
+    }
+
+    /// Sets whether or not the pattern matches regardless of how long its notes are.
+    pub fn ignore_rhythm(mut self, ignore: bool) -> Self {
+        self.ignore_rhythm = ignore;
+        return self;
+    }
+}
+
+/// An occurrence of a pattern in a track.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PatternMatch {
+    /// The position in `Track::notes` of the entry the occurrence starts in.
+    pub position: usize,
+    /// The beat on which the occurrence starts, counted from the start of the track.
+    pub beat: f32,
+    /// The number of semitones the occurrence lies above the pattern.
+    pub transposition: i16,
+}
+
+/// Whether a hairpin gets louder or softer.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HairpinDirection {
+    Crescendo,
+    Diminuendo,
+}
+
+impl HairpinDirection {
+    /// Converts the enum to a string.
+    pub fn to_string(&self) -> &str {
+        match self {
+            HairpinDirection::Crescendo => return "crescendo",
+            HairpinDirection::Diminuendo => return "diminuendo",
+        }
+    }
+}
+
+/// A crescendo or diminuendo found in a track. See `hairpins`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hairpin {
+    /// Whether the notes get louder or softer.
+    pub direction: HairpinDirection,
+    /// The beat on which the first note of the hairpin starts, counted from the start of the
+    /// track.
+    pub start: f32,
+    /// The beat on which the last note of the hairpin starts, counted from the start of the
+    /// track.
+    pub end: f32,
+    /// The velocity of the first note of the hairpin.
+    pub start_velocity: u8,
+    /// The velocity of the last note of the hairpin.
+    pub end_velocity: u8,
+}
+
+/// Options that control how sustained a change in velocity must be to count as a hairpin.
+/// 
+/// ```
+/// use beatblox_midi::analysis::HairpinOptions;
+/// 
+/// let options = HairpinOptions::new().min_notes(6).min_change(24);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct HairpinOptions {
+    /// The fewest onsets a hairpin may span.
+    pub min_notes: usize,
+    /// The least the velocity must change from the first onset of a hairpin to its last.
+    pub min_change: u8,
+}
+
+impl HairpinOptions {
+    /// Creates options that find hairpins of at least 4 onsets whose velocity changes by at
+    /// least 16.
+    pub fn new() -> Self {
+        return HairpinOptions { min_notes: 4, min_change: 16 };
+    }
+
+    /// Sets the fewest onsets a hairpin may span.
+    pub fn min_notes(mut self, notes: usize) -> Self {
+        self.min_notes = notes;
+        return self;
+    }
+
+    /// Sets the least the velocity must change over a hairpin.
+    pub fn min_change(mut self, change: u8) -> Self {
+        self.min_change = change;
+        return self;
+    }
+}
+
+impl Default for HairpinOptions {
+    fn default() -> Self {
+        return HairpinOptions::new();
+    }
+}
+
+/// A cell of notes that is repeated back to back in a track, such as a riff or a drum groove.
+/// See `ostinatos`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ostinato {
+    /// The position in `Track::notes` of the first entry of the first cell.
+    pub position: usize,
+    /// The number of entries of `Track::notes` in the cell.
+    pub cell_length: usize,
+    /// The number of times the cell is played in a row, counting the first.
+    pub repeats: usize,
+    /// The beat on which the first cell starts, counted from the start of the track.
+    pub beat: f32,
+    /// The number of beats the cell lasts.
+    pub cell_beats: f32,
+}
+
+/// Options that control how short and how often repeated a cell must be to count as an
+/// ostinato.
+/// 
+/// ```
+/// use beatblox_midi::analysis::OstinatoOptions;
+/// 
+/// let options = OstinatoOptions::new().max_cell_length(16).min_repeats(8);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct OstinatoOptions {
+    /// The most entries of `Track::notes` a cell may hold.
+    pub max_cell_length: usize,
+    /// The fewest times a cell must be played in a row.
+    pub min_repeats: usize,
+}
+
+impl OstinatoOptions {
+    /// Creates options that find cells of up to 8 entries played at least 4 times in a row.
+    pub fn new() -> Self {
+        return OstinatoOptions { max_cell_length: 8, min_repeats: 4 };
+    }
+
+    /// Sets the most entries a cell may hold.
+    pub fn max_cell_length(mut self, length: usize) -> Self {
+        self.max_cell_length = length;
+        return self;
+    }
+
+    /// Sets the fewest times a cell must be played in a row.
+    pub fn min_repeats(mut self, repeats: usize) -> Self {
+        self.min_repeats = repeats;
+        return self;
+    }
+}
+
+impl Default for OstinatoOptions {
+    fn default() -> Self {
+        return OstinatoOptions::new();
+    }
+}
+
+/// A note of the melody of a track. See `get_melody`.
+pub(crate) struct MelodyNote {
+    /// The position in `Track::notes` of the entry the note is in.
+    pub(crate) position: usize,
+    /// The beat on which the note starts.
+    pub(crate) onset: f32,
+    /// The value of the note, or of the top note of its chord.
+    pub(crate) value: u8,
+    /// The number of beats until the next entry, so tied notes are counted whole.
+    pub(crate) length: f32,
+}
+
+/// Gathers the statistics of a track, reading durations in the given beat type.
+pub fn track_stats(track: &Track, beat_type: u8) -> TrackStats {
+    return flat_track_stats(&FlatTrack::new(track, beat_type));
+}
+
+/// Gathers the statistics of a flattened track. See `track_stats`.
+pub fn flat_track_stats(flat: &FlatTrack) -> TrackStats {
+    let mut stats = TrackStats::default();
+    let mut pitch_sum = 0.0;
+    let mut velocity_sum = 0.0;
+    let mut rest_beats = 0.0;
+    for i in 0..flat.len() {
+        if flat.flags[i] & FLAG_REST != 0 {
+            rest_beats += flat.durations[i];
+        }
+        let Some(value) = flat.pitches[i].filter(|_| flat.is_struck(i)) else {
+            continue;
+        };
+        stats.note_count += 1;
+        stats.min_pitch = Some(stats.min_pitch.map_or(value, |p| p.min(value)));
+        stats.max_pitch = Some(stats.max_pitch.map_or(value, |p| p.max(value)));
+        pitch_sum += value as f32;
+        velocity_sum += flat.velocities[i] as f32;
+    }
+    if stats.note_count > 0 {
+        stats.mean_pitch = Some(pitch_sum / stats.note_count as f32);
+        stats.mean_velocity = Some(velocity_sum / stats.note_count as f32);
+    }
+    stats.duration_histogram = flat_duration_histogram(flat);
+    stats.total_beats = flat.beats;
+    if flat.beats > 0.0 {
+        stats.rest_percentage = rest_beats / flat.beats * 100.0;
+    }
+    return stats;
+}
+
+/// Gathers the statistics of every note track in a piece together.
+/// 
+/// `total_beats` is the length of the longest track, and `rest_percentage` is the share of the
+/// tracks' combined length spent resting.
+pub fn piece_stats(midi: &Midi) -> TrackStats {
+    let beat_type = midi.beat_type();
+    let mut stats = TrackStats::default();
+    let mut pitch_sum = 0.0;
+    let mut velocity_sum = 0.0;
+    let mut rest_beats = 0.0;
+    let mut combined_beats = 0.0;
+    for track in midi.tracks.iter().filter(|t| t.kind == TrackKind::Notes) {
+        let track_stats = track_stats(track, beat_type);
+        let count = track_stats.note_count as f32;
+        stats.note_count += track_stats.note_count;
+        stats.min_pitch = match (stats.min_pitch, track_stats.min_pitch) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        stats.max_pitch = stats.max_pitch.max(track_stats.max_pitch);
+        pitch_sum += track_stats.mean_pitch.unwrap_or(0.0) * count;
+        velocity_sum += track_stats.mean_velocity.unwrap_or(0.0) * count;
+        for (total, n) in stats.duration_histogram.iter_mut().zip(track_stats.duration_histogram) {
+            *total += n;
+        }
+        stats.total_beats = stats.total_beats.max(track_stats.total_beats);
+        rest_beats += track_stats.rest_percentage / 100.0 * track_stats.total_beats;
+        combined_beats += track_stats.total_beats;
+    }
+    if stats.note_count > 0 {
+        stats.mean_pitch = Some(pitch_sum / stats.note_count as f32);
+        stats.mean_velocity = Some(velocity_sum / stats.note_count as f32);
+    }
+    if combined_beats > 0.0 {
+        stats.rest_percentage = rest_beats / combined_beats * 100.0;
+    }
+    return stats;
+}
+
+/// Counts the notes played on each pitch class of a track, from C to B. Tied notes count once.
+/// 
+/// Percussion tracks have no pitches, so every count is 0.
+pub fn pitch_class_histogram(track: &Track) -> [usize; 12] {
+    return flat_pitch_class_histogram(&FlatTrack::new(track, 2));
+}
+
+/// Counts the notes played on each pitch class of a flattened track. See
+/// `pitch_class_histogram`.
+pub fn flat_pitch_class_histogram(flat: &FlatTrack) -> [usize; 12] {
+    let mut histogram = [0; 12];
+    if flat.percussion {
+        return histogram;
+    }
+    for i in (0..flat.len()).filter(|i| flat.is_struck(*i)) {
+        if let Some(value) = flat.pitches[i] {
+            histogram[value as usize % 12] += 1;
+        }
+    }
+    return histogram;
+}
+
+/// Counts the written notes of each duration in a track, from whole notes to hundred
+/// twenty-eighth notes, ignoring dots. Rests are left out, and the pieces of a tied note each
+/// count.
+pub fn duration_histogram(track: &Track) -> [usize; 8] {
+    return flat_duration_histogram(&FlatTrack::new(track, 2));
+}
+
+/// Counts the written notes of each duration in a flattened track. See `duration_histogram`.
+pub fn flat_duration_histogram(flat: &FlatTrack) -> [usize; 8] {
+    let mut histogram = [0; 8];
+    for i in (0..flat.len()).filter(|i| flat.flags[*i] & FLAG_REST == 0) {
+        if let Some(index) = get_duration_index(&flat.written_duration(i).duration) {
+            histogram[index] += 1;
+        }
+    }
+    return histogram;
+}
+
+/// Counts the onsets in each measure of a track, e.g. for showing where a piece gets busy.
+/// 
+/// A chord counts as a single onset unless `count_chord_notes` is set, in which case each of its
+/// notes counts. Tied notes count once, on the measure they start in, and measures without any
+/// onsets count 0.
+pub fn density_per_measure(
+    track: &Track,
+    time_signatures: &[TimeSignature],
+    ticks_per_beat: f32,
+    count_chord_notes: bool
+) -> Vec<usize> {
+    let beat_type = time_signatures.first().map(|t| t.beat_type).unwrap_or(2);
+    let flat = FlatTrack::new(track, beat_type);
+    return flat_density_per_measure(&flat, time_signatures, ticks_per_beat, count_chord_notes);
+}
+
+/// Counts the onsets in each measure of a flattened track. See `density_per_measure`.
+pub fn flat_density_per_measure(
+    flat: &FlatTrack,
+    time_signatures: &[TimeSignature],
+    ticks_per_beat: f32,
+    count_chord_notes: bool
+) -> Vec<usize> {
+    let end = flat.beats.max(flat.length);
+    let measures = measure::measure_starts(time_signatures, ticks_per_beat, end)
+        .iter()
+        .filter(|start| **start < end - 1e-4)
+        .count();
+    let mut density = vec![0; measures];
+    let mut previous = None;
+    for i in (0..flat.len()).filter(|i| flat.is_struck(*i)) {
+        let onset = flat.onsets[i];
+        // The notes of a chord are struck together, so only the first counts unless asked.
+        let new_onset = flat.flags[i] & FLAG_START != 0 || previous != Some(onset);
+        previous = Some(onset);
+        if !new_onset && !count_chord_notes {
+            continue;
+        }
+        let (measure, _) = measure::measure_at(time_signatures, ticks_per_beat, onset);
+        let m = measure as usize - 1;
+        if m >= density.len() {
+            density.resize(m + 1, 0);
+        }
+        density[m] += 1;
+    }
+    return density;
+}
+
+/// Finds the number of notes sounding at once over the course of a track, reading durations in
+/// the given beat type.
+pub fn polyphony_profile(track: &Track, beat_type: u8) -> PolyphonyProfile {
+    return flat_polyphony_profile(&FlatTrack::new(track, beat_type));
+}
+
+/// Finds the number of notes sounding at once over the course of a flattened track. See
+/// `polyphony_profile`.
+pub fn flat_polyphony_profile(flat: &FlatTrack) -> PolyphonyProfile {
+    let mut changes: Vec<(f32, i32)> = Vec::new();
+    for i in 0..flat.len() {
+        if flat.flags[i] & FLAG_REST == 0 && flat.durations[i] > 0.0 {
+            changes.push((flat.onsets[i], 1));
+            changes.push((flat.onsets[i] + flat.durations[i], -1));
+        }
+    }
+    // Notes that end on a beat are counted before notes that start on it.
+    changes.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+
+    let mut profile = PolyphonyProfile::default();
+    let mut count: i32 = 0;
+    let mut weighted = 0.0;
+    let mut previous = 0.0;
+    for (i, (beat, change)) in changes.iter().enumerate() {
+        weighted += count as f32 * (beat - previous);
+        previous = *beat;
+        count += change;
+        if changes.get(i + 1).is_none_or(|(next, _)| next > beat) {
+            if profile.series.last().is_none_or(|(_, c)| *c != count as usize) {
+                profile.series.push((*beat, count as usize));
+            }
+            profile.max = profile.max.max(count as usize);
+        }
+    }
+    let end = flat.beats.max(flat.length);
+    if end > 0.0 {
+        profile.mean = weighted / end;
+    }
+    return profile;
+}
+
+/// Finds the crescendos and diminuendos of a track, reading durations in the given beat type.
+/// 
+/// A hairpin is a run of onsets whose velocities never fall (for a crescendo) or never rise
+/// (for a diminuendo), spanning at least `HairpinOptions::min_notes` onsets and changing by at
+/// least `HairpinOptions::min_change`. The notes of a chord are taken as one onset at the
+/// velocity of the loudest, and the later pieces of tied notes are ignored. A hairpin may start
+/// on the onset the one before it ends on.
+pub fn hairpins(track: &Track, beat_type: u8, options: &HairpinOptions) -> Vec<Hairpin> {
+    return flat_hairpins(&FlatTrack::new(track, beat_type), options);
+}
+
+/// Finds the crescendos and diminuendos of a flattened track. See `hairpins`.
+pub fn flat_hairpins(flat: &FlatTrack, options: &HairpinOptions) -> Vec<Hairpin> {
+    let mut onsets: Vec<(f32, u8)> = Vec::new();
+    for i in (0..flat.len()).filter(|i| flat.is_struck(*i)) {
+        match onsets.last_mut() {
+            Some((onset, velocity)) if *onset == flat.onsets[i] => {
+                *velocity = (*velocity).max(flat.velocities[i]);
+            },
+            _ => onsets.push((flat.onsets[i], flat.velocities[i])),
+        }
+    }
+    let run_end = |start: usize, rising: bool| {
+        let mut end = start;
+        while end + 1 < onsets.len() {
+            let (current, next) = (onsets[end].1, onsets[end + 1].1);
+            if (rising && next < current) || (!rising && next > current) {
+                break;
+            }
+            end += 1;
+        }
+        return end;
+    };
+    let mut hairpins = Vec::new();
+    let mut start = 0;
+    while start + 1 < onsets.len() {
+        let found = [HairpinDirection::Crescendo, HairpinDirection::Diminuendo]
+            .into_iter()
+            .map(|direction| (direction, run_end(start, direction == HairpinDirection::Crescendo)))
+            .find(|(_, end)| {
+                let change = onsets[*end].1.abs_diff(onsets[start].1);
+                let long_enough = *end > start && end - start + 1 >= options.min_notes;
+                return long_enough && change >= options.min_change;
+            });
+        match found {
+            Some((direction, end)) => {
+                hairpins.push(Hairpin {
+                    direction,
+                    start: onsets[start].0,
+                    end: onsets[end].0,
+                    start_velocity: onsets[start].1,
+                    end_velocity: onsets[end].1,
+                });
+                start = end;
+            },
+            None => start += 1,
+        }
+    }
+    return hairpins;
+}
+
+/// Finds the cells of notes that are repeated back to back in a track, reading durations in
+/// the given beat type, so a run of repeats can be written once with a repeat count.
+/// 
+/// Cells are made of whole entries of `Track::notes`, and repeat when their pitches and
+/// durations are the same; velocities are ignored. Where a run could be read with cells of
+/// several lengths, the reading that covers the most entries is taken, and then the one with
+/// the shortest cell. Cells of only rests aren't ostinatos.
+pub fn ostinatos(track: &Track, beat_type: u8, options: &OstinatoOptions) -> Vec<Ostinato> {
+    let keys: Vec<(u32, Vec<_>)> = track.notes
+        .iter()
+        .map(|wrapper| {
+            let notes = wrapper.notes()
+                .iter()
+                .map(|n| (n.value(), n.duration.get_beat_count(beat_type).to_bits()))
+                .collect();
+            return (wrapper.beat_count(beat_type).to_bits(), notes);
+        })
+        .collect();
+    let mut starts = Vec::with_capacity(track.notes.len());
+    let mut beat = 0.0;
+    for wrapper in &track.notes {
+        starts.push(beat);
+        beat += wrapper.beat_count(beat_type);
+    }
+
+    let mut ostinatos = Vec::new();
+    let mut i = 0;
+    while i < keys.len() {
+        let mut best: Option<(usize, usize)> = None;
+        for length in 1..=options.max_cell_length.min(keys.len() - i) {
+            let cell = &keys[i..i + length];
+            if cell.iter().all(|(_, notes)| notes.iter().all(|(value, _)| value.is_none())) {
+                continue;
+            }
+            let mut repeats = 1;
+            while keys.get(i + repeats * length..i + (repeats + 1) * length) == Some(cell) {
+                repeats += 1;
+            }
+            let covered = repeats * length;
+            if repeats >= options.min_repeats && best.is_none_or(|(l, r)| covered > l * r) {
+                best = Some((length, repeats));
+            }
+        }
+        match best {
+            Some((cell_length, repeats)) => {
+                ostinatos.push(Ostinato {
+                    position: i,
+                    cell_length,
+                    repeats,
+                    beat: starts[i],
+                    cell_beats: starts.get(i + cell_length).copied().unwrap_or(beat) - starts[i],
+                });
+                i += cell_length * repeats;
+            },
+            None => i += 1,
+        }
+    }
+    return ostinatos;
+}
+
+/// Finds every occurrence of the melodic pattern `pattern` in a track, reading durations in the
+/// given beat type. Rests in the pattern are ignored.
+/// 
+/// The top note of each chord is taken as the melody, and tied notes are compared by their
+/// combined length. See `PatternOptions` for how loosely the pattern is matched.
+pub fn find_pattern(
+    track: &Track,
+    pattern: &[Note],
+    options: &PatternOptions,
+    beat_type: u8
+) -> Vec<PatternMatch> {
+    let pattern: Vec<(u8, &Note)> = pattern.iter().filter_map(|n| Some((n.value()?, n))).collect();
+    let melody = get_melody(&FlatTrack::new(track, beat_type));
+    let mut matches = Vec::new();
+    if pattern.is_empty() || pattern.len() > melody.len() {
+        return matches;
+    }
+    for window in melody.windows(pattern.len()) {
+        let transposition = window[0].value as i16 - pattern[0].0 as i16;
+        if transposition != 0 && !options.transpose {
+            continue;
+        }
+        let found = window.iter().zip(&pattern).all(|(note, (value, expected))| {
+            let pitch = note.value as i16 - *value as i16 == transposition;
+            let beats = expected.duration.get_beat_count(beat_type);
+            return pitch && (options.ignore_rhythm || (note.length - beats).abs() < 1e-3);
+        });
+        if found {
+            matches.push(PatternMatch {
+                position: window[0].position,
+                beat: window[0].onset,
+                transposition,
+            });
+        }
+    }
+    return matches;
+}
+
+/// Scores how alike the melodies of two tracks are from 0 (nothing alike) to 1 (the same),
+/// reading durations in the given beat type.
+/// 
+/// The score is 1 less the edit distance between the melodies over the length of the longer
+/// one. Adding or removing a note costs 1, and changing a note costs 0.5 for a wrong pitch and
+/// 0.5 for a wrong length. See `get_melody` for how the melody is found.
+pub fn track_similarity(track: &Track, other: &Track, beat_type: u8) -> f32 {
+    let melody = get_melody(&FlatTrack::new(track, beat_type));
+    return get_melody_similarity(&melody, &get_melody(&FlatTrack::new(other, beat_type)));
+}
+
+/// Scores how alike two pieces are from 0 to 1.
+/// 
+/// Each note track is paired with the most similar note track of the other piece (see
+/// `track_similarity`), and the score is the average over the tracks of both pieces. Durations
+/// are read in the beat type of their own piece.
+pub fn piece_similarity(midi: &Midi, other: &Midi) -> f32 {
+    let get_melodies = |midi: &Midi| {
+        let beat_type = midi.beat_type();
+        return midi.tracks
+            .iter()
+            .filter(|t| t.kind == TrackKind::Notes)
+            .map(|t| get_melody(&FlatTrack::new(t, beat_type)))
+            .collect::<Vec<_>>();
+    };
+    let (melodies, others) = (get_melodies(midi), get_melodies(other));
+    if melodies.is_empty() && others.is_empty() {
+        return 1.0;
+    }
+    let best = |melody: &Vec<MelodyNote>, candidates: &[Vec<MelodyNote>]| {
+        return candidates
+            .iter()
+            .map(|c| get_melody_similarity(melody, c))
+            .fold(0.0, f32::max);
+    };
+    let total = melodies.iter().map(|m| best(m, &others)).sum::<f32>()
+        + others.iter().map(|m| best(m, &melodies)).sum::<f32>();
+    return total / (melodies.len() + others.len()) as f32;
+}
+
+/// A helper function for scoring how alike two melodies are. See `track_similarity`.
+fn get_melody_similarity(a: &[MelodyNote], b: &[MelodyNote]) -> f32 {
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    let mut previous: Vec<f32> = (0..=b.len()).map(|j| j as f32).collect();
+    for (i, x) in a.iter().enumerate() {
+        let mut row = vec![i as f32 + 1.0];
+        for (j, y) in b.iter().enumerate() {
+            let mut substitution = 0.0;
+            if x.value != y.value {
+                substitution += 0.5;
+            }
+            if (x.length - y.length).abs() > 1e-3 {
+                substitution += 0.5;
+            }
+            let cost = (previous[j] + substitution)
+                .min(previous[j + 1] + 1.0)
+                .min(row[j] + 1.0);
+            row.push(cost);
+        }
+        previous = row;
+    }
+    return 1.0 - previous[b.len()] / longest as f32;
+}
+
+/// Rates how hard a track is to play at `bpm` beats per minute, reading durations in the given
+/// beat type.
+/// 
+/// The score is a weighted average of the track's speed (30%), leaps (20%), rhythm (20%), range
+/// (15%), and polyphony (15%). See `Difficulty` for how each is measured.
+pub fn difficulty(track: &Track, bpm: u32, beat_type: u8) -> Difficulty {
+    return flat_difficulty(&FlatTrack::new(track, beat_type), bpm);
+}
+
+/// Rates how hard a flattened track is to play at `bpm` beats per minute. See `difficulty`.
+pub fn flat_difficulty(flat: &FlatTrack, bpm: u32) -> Difficulty {
+    let melody = get_melody(flat);
+    let stats = flat_track_stats(flat);
+    let seconds = stats.total_beats * 60.0 / bpm.max(1) as f32;
+    let speed = match seconds > 0.0 {
+        true => (stats.note_count as f32 / seconds / 8.0).min(1.0),
+        false => 0.0,
+    };
+    let range = match (stats.min_pitch, stats.max_pitch) {
+        (Some(min), Some(max)) => ((max - min) as f32 / 36.0).min(1.0),
+        _ => 0.0,
+    };
+    let leaps = match melody.len() > 1 {
+        true => {
+            let total: f32 = melody
+                .windows(2)
+                .map(|w| (w[1].value as f32 - w[0].value as f32).abs())
+                .sum();
+            (total / (melody.len() - 1) as f32 / 7.0).min(1.0)
+        },
+        false => 0.0,
+    };
+    let durations = stats.duration_histogram.iter().filter(|n| **n > 0).count();
+    let offbeat = melody.iter().filter(|n| (n.onset * 2.0).fract().abs() > 1e-3).count();
+    let rhythm = match melody.is_empty() {
+        true => 0.0,
+        false => {
+            let variety = (durations.saturating_sub(1) as f32 / 4.0).min(1.0);
+            (variety + offbeat as f32 / melody.len() as f32) / 2.0
+        },
+    };
+    let polyphony = (flat_polyphony_profile(flat).max.saturating_sub(1) as f32 / 3.0)
+        .min(1.0);
+    let score = 100.0 * (
+        0.3 * speed + 0.2 * leaps + 0.2 * rhythm + 0.15 * range + 0.15 * polyphony
+    );
+    let level = match score {
+        s if s < 35.0 => DifficultyLevel::Beginner,
+        s if s < 65.0 => DifficultyLevel::Intermediate,
+        _ => DifficultyLevel::Advanced,
+    };
+    return Difficulty { score, level, speed, range, leaps, rhythm, polyphony };
+}
+
+/// Estimates the tempo a piece was actually played at over time, from the gaps between its
+/// onsets, for files recorded from live playing without a click. Returns `(seconds, bpm)` pairs,
+/// one for each onset after the first few.
+/// 
+/// Onsets within 30 milliseconds of each other are heard as one. The most common gap between
+/// onsets is taken as the steady note value, and the beat length is followed outward from it,
+/// comparing each gap to the nearest simple multiple or fraction of the current beat (such as
+/// half, double, or a triplet) to see how much it was stretched. The curve is smoothed with the
+/// median of the surrounding eight gaps, and scaled by powers of two to be nearest the tempo of
+/// the file's tempo map, which otherwise stays the same when a performance speeds up or slows
+/// down.
+pub fn performed_tempo_curve(events: &EventFile) -> Vec<(f32, f32)> {
+    let ticks_per_beat = events.ticks_per_beat.max(1) as f64;
+    let tempo_map = TempoMap::from_events(events);
+    let seconds_at = |tick: u32| tempo_map.seconds_at(tick as f64 / ticks_per_beat);
+    let nominal_bpm = tempo_map.bpm_at(0.0);
+
+    let mut ticks: Vec<u32> = events.tracks
+        .iter()
+        .flatten()
+        .filter(|e| matches!(e.kind, EventKind::NoteOn { velocity, .. } if velocity > 0))
+        .map(|e| e.tick)
+        .collect();
+    ticks.sort_unstable();
+    let mut onsets: Vec<f32> = Vec::new();
+    for tick in ticks {
+        let seconds = seconds_at(tick) as f32;
+        if onsets.last().is_none_or(|last| seconds - last > 0.03) {
+            onsets.push(seconds);
+        }
+    }
+    let gaps: Vec<f32> = onsets.windows(2).map(|w| w[1] - w[0]).collect();
+    if gaps.is_empty() {
+        return Vec::new();
+    }
+
+    // The most common gap, within 10%, is taken as the steady note value to start from. The
+    // gaps within 10% of each gap are counted once, as a range of the sorted gaps.
+    let mut sorted = gaps.clone();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let counts: Vec<usize> = gaps
+        .iter()
+        .map(|g| {
+            let low = sorted.partition_point(|o| o / g - 1.0 <= -0.1);
+            let high = sorted.partition_point(|o| o / g - 1.0 < 0.1);
+            return high - low;
+        })
+        .collect();
+    let (start, unit) = gaps
+        .iter()
+        .copied()
+        .enumerate()
+        .max_by_key(|(i, _)| counts[*i])
+        .unwrap();
+    let mut base_bpm = 60.0 / unit;
+    while base_bpm > nominal_bpm * std::f32::consts::SQRT_2 {
+        base_bpm /= 2.0;
+    }
+    while base_bpm < nominal_bpm / std::f32::consts::SQRT_2 {
+        base_bpm *= 2.0;
+    }
+    // The local tempo at each gap, following the beat forwards and then backwards from `start`.
+    let mut local = vec![base_bpm; gaps.len()];
+    let order = (start..gaps.len()).chain((0..start).rev());
+    let mut beat = unit;
+    for i in order {
+        if i + 1 == start {
+            beat = unit;
+        }
+        beat *= get_stretch(gaps[i] / beat);
+        local[i] = base_bpm * unit / beat;
+    }
+
+    let mut curve = Vec::new();
+    for (i, onset) in onsets.iter().enumerate().skip(1) {
+        let mut window = local[i.saturating_sub(4)..(i + 3).min(local.len())].to_vec();
+        window.sort_by(|a, b| a.total_cmp(b));
+        curve.push((*onset, window[window.len() / 2]));
+    }
+    return curve;
+}
+
+/// Returns how much a gap that is `ratio` times the beat was stretched from the nearest simple
+/// note value.
+fn get_stretch(ratio: f32) -> f32 {
+    const RATIOS: [f32; 10] = [0.25, 1.0 / 3.0, 0.5, 2.0 / 3.0, 0.75, 1.0, 1.5, 2.0, 3.0, 4.0];
+    let nearest = RATIOS
+        .iter()
+        .copied()
+        .min_by(|a, b| (ratio / a).ln().abs().total_cmp(&(ratio / b).ln().abs()))
+        .unwrap();
+    return ratio / nearest;
+}
+
+/// Returns the melody of a flattened track, taking the top note of each chord.
+pub(crate) fn get_melody(flat: &FlatTrack) -> Vec<MelodyNote> {
+    let mut melody: Vec<MelodyNote> = Vec::new();
+    let mut position = 0;
+    // The onset of every entry, and of every note of a triplet, that a melody note can start on.
+    let mut starts: Vec<(usize, f32)> = Vec::new();
+    for i in 0..flat.len() {
+        let start = flat.flags[i] & FLAG_START != 0;
+        if start && i > 0 {
+            position += 1;
+        }
+        let triplet_note = flat.flags[i] & (FLAG_TRIPLET | FLAG_CHORD | FLAG_TIED) == FLAG_TRIPLET;
+        if start || triplet_note {
+            starts.push((position, flat.onsets[i]));
+        }
+        let Some(value) = flat.pitches[i].filter(|_| flat.is_struck(i)) else {
+            continue;
+        };
+        let (position, onset) = *starts.last().unwrap();
+        match melody.last_mut() {
+            Some(last) if last.position == position && last.onset == onset => {
+                last.value = last.value.max(value);
+            },
+            _ => melody.push(MelodyNote { position, onset, value, length: 0.0 }),
+        }
+    }
+    // Each melody note lasts until the entry or triplet note after it starts.
+    for note in melody.iter_mut() {
+        note.length = starts
+            .iter()
+            .map(|(_, onset)| *onset)
+            .find(|onset| *onset > note.onset + 1e-6)
+            .unwrap_or(flat.beats) - note.onset;
+    }
+    return melody;
+}
+
+/// Gets the position of a duration in a duration histogram, from whole notes to hundred
+/// twenty-eighth notes.
+fn get_duration_index(duration: &NoteDuration) -> Option<usize> {
+    match duration {
+        NoteDuration::WHOLE => return Some(0),
+        NoteDuration::HALF => return Some(1),
+        NoteDuration::QUARTER => return Some(2),
+        NoteDuration::EIGHTH => return Some(3),
+        NoteDuration::SIXTEENTH => return Some(4),
+        NoteDuration::THIRTYSECOND => return Some(5),
+        NoteDuration::SIXTYFOURTH => return Some(6),
+        NoteDuration::HUNDREDTWENTYEIGHTH => return Some(7),
+        NoteDuration::NaN => return None,
+    }
+}
@@ -0,0 +1,238 @@
+#![allow(clippy::needless_return)]
+
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::duration;
+use beatblox_midi::parsing::duration::DurationType;
+use beatblox_midi::parsing::midicsv;
+use beatblox_midi::parsing::options::ParseOptions;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: beatblox-midi <command> <file> [arguments] [options]
+
+Commands:
+    inspect <file>                        Print the tempo, signatures, and tracks of the file
+    parse <file> [--json | --csv | --lists | --blocks | --musicxml]
+                                          Print the parsed notes, as JSON by default, with
+                                          --lists written as nested JSON arrays
+    export <file> --format <format>       Write the piece in another format, where <format> is
+                                          json, csv, lists, blocks, musicxml, midicsv, or smf
+    transpose <file> <semitones>          Transpose every non-percussion track and write the
+                                          piece as a midi file
+    slice <file> <start> <end>            Keep measures <start> through <end>, counting from 1,
+                                          and write the piece as a midi file
+    stats <file>                          Print the statistics of the piece and each track
+
+Options:
+    -o, --output <file>                   Write to <file> instead of standard output
+    --precision <duration>                The shortest duration notes are snapped to: whole,
+                                          half, quarter, eighth, sixteenth, thirty-second,
+                                          sixty-fourth, or hundred-twenty-eighth
+    --triplet                             Scan for triplets
+    -h, --help                            Print this message
+";
+
+/// The parsed command line.
+struct Args {
+    /// The subcommand to run.
+    command: String,
+    /// The midi file to read.
+    file: String,
+    /// The positional arguments after the file.
+    values: Vec<String>,
+    /// The flags given without a value, such as `--json`.
+    flags: Vec<String>,
+    /// The export format, if one was given.
+    format: Option<String>,
+    /// The file to write to, if one was given.
+    output: Option<String>,
+    /// The options the file is parsed with.
+    options: ParseOptions,
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        print!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+    match read_args(args).and_then(|args| run(&args)) {
+        Ok(()) => return ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return ExitCode::FAILURE;
+        },
+    }
+}
+
+/// Splits the command line into the subcommand, its arguments, and its options.
+fn read_args(args: Vec<String>) -> Result<Args, String> {
+    let mut positional = Vec::new();
+    let mut flags = Vec::new();
+    let (mut format, mut output) = (None, None);
+    let mut options = ParseOptions::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--format" => format = Some(value()?),
+            "-o" | "--output" => output = Some(value()?),
+            "--precision" => options = options.precision(get_precision(&value()?)?),
+            "--triplet" => options = options.triplet(true),
+            // Negative numbers are values, so `transpose song.mid -3` works.
+            _ if arg.starts_with('-') && arg.parse::<f32>().is_err() => flags.push(arg),
+            _ => positional.push(arg),
+        }
+    }
+    if positional.len() < 2 {
+        return Err(String::from("expected a command and a file, see --help"));
+    }
+    let values = positional.split_off(2);
+    let file = positional.pop().unwrap_or_default();
+    let command = positional.pop().unwrap_or_default();
+    return Ok(Args { command, file, values, flags, format, output, options });
+}
+
+/// Runs the subcommand of the command line.
+fn run(args: &Args) -> Result<(), String> {
+    let mut midi = Midi::parse_with_options(args.file.clone(), &args.options)
+        .map_err(|e| format!("couldn't parse {}: {}", args.file, e))?;
+    match args.command.as_str() {
+        "inspect" => return write(args, inspect(&midi).as_bytes()),
+        "parse" => {
+            let format = match args.flags.first().map(|flag| flag.as_str()) {
+                None | Some("--json") => "json",
+                Some("--csv") => "csv",
+                Some("--lists") => "lists",
+                Some("--blocks") => "blocks",
+                Some("--musicxml") => "musicxml",
+                Some(flag) => return Err(format!("unknown option {}", flag)),
+            };
+            return write(args, &export(&midi, format)?);
+        },
+        "export" => {
+            let format = args.format.as_deref().ok_or("export needs a --format")?;
+            return write(args, &export(&midi, format)?);
+        },
+        "transpose" => {
+            let semitones: i8 = get_value(args, 0, "semitones")?;
+            for track in midi.tracks_mut() {
+                track.transpose(semitones);
+            }
+            return write(args, &midi.to_smf_bytes());
+        },
+        "slice" => {
+            let start: u32 = get_value(args, 0, "start")?;
+            let end: u32 = get_value(args, 1, "end")?;
+            if start == 0 || end < start {
+                return Err(String::from("slices need 1 <= start <= end"));
+            }
+            return write(args, &midi.slice_measures(start, end).to_smf_bytes());
+        },
+        "stats" => return write(args, stats(&midi).as_bytes()),
+        command => return Err(format!("unknown command {}, see --help", command)),
+    }
+}
+
+/// Describes the tempo, signatures, and tracks of a piece.
+fn inspect(midi: &Midi) -> String {
+    let beat_type = midi.beat_type();
+    let metadata = midi.metadata();
+    let mut out = format!("Format: {}\n", metadata.format.to_string());
+    if let Some(name) = &metadata.sequence_name {
+        out += &format!("Name: {}\n", name);
+    }
+    out += &format!("Ticks per beat: {}\n", midi.ticks_per_beat());
+    out += &format!("BPM: {}\n", midi.bpm());
+    for time_signature in midi.time_signatures() {
+        let (count, kind) = (time_signature.beat_count, 1 << time_signature.beat_type);
+        let tick = time_signature.time_of_occurance;
+        out += &format!("Time signature: {}/{} at tick {}\n", count, kind, tick);
+    }
+    for key_signature in midi.key_signatures() {
+        let tick = key_signature.time_of_occurance;
+        out += &format!("Key: {} at tick {}\n", key_signature.name(), tick);
+    }
+    out += &format!("Tracks: {}\n", midi.tracks().len());
+    for track in midi.tracks() {
+        let instrument = track.instrument.as_deref().unwrap_or("none");
+        let notes = track.stats(beat_type).note_count;
+        out += &format!("  {}: {} ({} notes, instrument {})\n", track.index, track.name, notes,
+            instrument);
+    }
+    return out;
+}
+
+/// Gathers the statistics of a piece and each of its tracks.
+fn stats(midi: &Midi) -> String {
+    let beat_type = midi.beat_type();
+    let mut out = String::new();
+    let named = std::iter::once((String::from("Piece"), midi.stats()))
+        .chain(midi.tracks().iter().map(|track| (track.name.clone(), track.stats(beat_type))));
+    for (name, stats) in named {
+        out += &format!("{}\n", name);
+        out += &format!("  notes: {}\n", stats.note_count);
+        if let (Some(min), Some(max)) = (stats.min_pitch, stats.max_pitch) {
+            out += &format!("  range: {} to {}\n", min, max);
+        }
+        if let Some(mean) = stats.mean_velocity {
+            out += &format!("  mean velocity: {:.1}\n", mean);
+        }
+        out += &format!("  beats: {}\n", stats.total_beats);
+        out += &format!("  rests: {:.1}%\n", stats.rest_percentage);
+    }
+    return out;
+}
+
+/// Converts a piece to `format`.
+fn export(midi: &Midi, format: &str) -> Result<Vec<u8>, String> {
+    match format {
+        "json" => return Ok(midi.to_json().into_bytes()),
+        "csv" => return Ok(midi.to_csv().into_bytes()),
+        "lists" => return Ok(midi.to_netsblox_lists().to_json().into_bytes()),
+        "blocks" => return Ok(midi.to_beatblox_blocks().into_bytes()),
+        "musicxml" | "xml" => return Ok(midi.to_musicxml().into_bytes()),
+        "midicsv" => return Ok(midicsv::write_midicsv(&midi.to_events()).into_bytes()),
+        "smf" | "mid" | "midi" => return Ok(midi.to_smf_bytes()),
+        _ => return Err(format!("unsupported format {}", format)),
+    }
+}
+
+/// Writes `bytes` to the output file, or to standard output if there isn't one.
+fn write(args: &Args, bytes: &[u8]) -> Result<(), String> {
+    match &args.output {
+        Some(output) => {
+            let error = |e| format!("couldn't write {}: {}", output, e);
+            return std::fs::write(output, bytes).map_err(error);
+        },
+        None => {
+            use std::io::Write;
+            return std::io::stdout().write_all(bytes).map_err(|e| e.to_string());
+        },
+    }
+}
+
+/// Reads the positional argument at `index` after the file.
+fn get_value<T: std::str::FromStr>(args: &Args, index: usize, name: &str) -> Result<T, String> {
+    let value = args.values
+        .get(index)
+        .ok_or(format!("{} needs a <{}>", args.command, name))?;
+    return value.parse().map_err(|_| format!("{} isn't a valid <{}>", value, name));
+}
+
+/// Reads a precision given on the command line.
+fn get_precision(name: &str) -> Result<DurationType, String> {
+    match name {
+        "whole" => return Ok(duration::whole()),
+        "half" => return Ok(duration::half()),
+        "quarter" => return Ok(duration::quarter()),
+        "eighth" => return Ok(duration::eighth()),
+        "sixteenth" => return Ok(duration::sixteenth()),
+        "thirty-second" | "thirtysecond" => return Ok(duration::thirty_second()),
+        "sixty-fourth" | "sixtyfourth" => return Ok(duration::sixty_fourth()),
+        "hundred-twenty-eighth" | "hundredtwentyeighth" => {
+            return Ok(duration::hundred_twenty_eighth());
+        },
+        _ => return Err(format!("unknown precision {}", name)),
+    }
+}
@@ -0,0 +1,204 @@
+use crate::Midi;
+use crate::parsing::Track;
+use crate::parsing::TrackKind;
+use crate::parsing::duration::DurationType;
+use crate::parsing::general_midi;
+use crate::parsing::metadata::MidiMetadata;
+use crate::parsing::metadata::SmfFormat;
+use crate::parsing::parse_note_data;
+use crate::parsing::symbols::KeySignature;
+use crate::parsing::symbols::NoteModifier;
+use crate::parsing::symbols::NoteWrapper;
+use crate::parsing::symbols::TimeSignature;
+
+/// The number of ticks in each beat of a built `Midi` object.
+pub const BUILDER_TICKS_PER_BEAT: u16 = 480;
+
+/// A note, rest (no values), or chord waiting to be built, with its written duration.
+#[derive(Clone)]
+struct Entry {
+    values: Vec<u8>,
+    duration: DurationType,
+    velocity: u8,
+}
+
+/// Builds a `Midi` object from scratch.
+/// 
+/// Settings and tracks are added with builder-style methods. Notes are added to the most
+/// recently started track:
+/// 
+/// ```
+/// use beatblox_midi::builder::MidiBuilder;
+/// use beatblox_midi::parsing::duration::quarter;
+/// use beatblox_midi::parsing::duration::half;
+/// 
+/// let midi = MidiBuilder::new()
+///     .bpm(120)
+///     .time_signature(4, 4)
+///     .track("Lead")
+///     .note(60, quarter(), 90)
+///     .rest(quarter())
+///     .chord(&[60, 64, 67], half(), 80)
+///     .build();
+/// ```
+/// 
+/// Durations are written durations, so a quarter note stays a quarter note in any time
+/// signature. Beats are counted in the beat of the time signature, as in a parsed file.
+#[derive(Clone)]
+pub struct MidiBuilder {
+    bpm: u32,
+    time_signature: TimeSignature,
+    key_signature: Option<KeySignature>,
+    tracks: Vec<(Track, Vec<Entry>)>,
+}
+
+impl MidiBuilder {
+    /// Creates a builder for an empty piece in 4/4 time at 120 beats per minute.
+    pub fn new() -> Self {
+        MidiBuilder {
+            bpm: 120,
+            time_signature: TimeSignature::default(),
+            key_signature: None,
+            tracks: Vec::new(),
+        }
+    }
+
+    /// Sets the tempo in beats per minute.
+    pub fn bpm(mut self, bpm: u32) -> Self {
+        self.bpm = bpm.max(1);
+        return self;
+    }
+
+    /// Sets the time signature, e.g. `time_signature(6, 8)`.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if `denominator` is not a power of two.
+    pub fn time_signature(mut self, numerator: u8, denominator: u8) -> Self {
+        assert!(denominator.is_power_of_two(), "time signature denominators must be powers of two");
+        self.time_signature = TimeSignature {
+            beat_count: numerator,
+            beat_type: denominator.trailing_zeros() as u8,
+            time_of_occurance: 0,
+        };
+        return self;
+    }
+
+    /// Sets the key signature by its number of sharps (positive) or flats (negative).
+    pub fn key_signature(mut self, sharps: i8, minor: bool) -> Self {
+        self.key_signature = Some(KeySignature { sharps, minor, time_of_occurance: 0 });
+        return self;
+    }
+
+    /// Starts a new track. Notes added after this go to the new track.
+    pub fn track(mut self, name: &str) -> Self {
+        let track = Track::new(String::from(name), Vec::new());
+        self.tracks.push((track, Vec::new()));
+        return self;
+    }
+
+    /// Sets the General MIDI program of the current track.
+    pub fn program(mut self, program: u8) -> Self {
+        let track = &mut self.current_track().0;
+        track.program = Some(program);
+        track.instrument = general_midi::instrument_name(program).map(String::from);
+        return self;
+    }
+
+    /// Marks the current track as a percussion track.
+    pub fn percussion(mut self) -> Self {
+        let track = &mut self.current_track().0;
+        track.percussion = true;
+        track.instrument = Some(String::from("Percussion"));
+        return self;
+    }
+
+    /// Adds a note to the current track.
+    pub fn note(mut self, value: u8, duration: DurationType, velocity: u8) -> Self {
+        self.current_track().1.push(Entry { values: vec![value], duration, velocity });
+        return self;
+    }
+
+    /// Adds a rest to the current track.
+    pub fn rest(mut self, duration: DurationType) -> Self {
+        self.current_track().1.push(Entry { values: Vec::new(), duration, velocity: 0 });
+        return self;
+    }
+
+    /// Adds a chord to the current track.
+    pub fn chord(mut self, values: &[u8], duration: DurationType, velocity: u8) -> Self {
+        self.current_track().1.push(Entry { values: values.to_vec(), duration, velocity });
+        return self;
+    }
+
+    /// Builds the `Midi` object.
+    pub fn build(self) -> Midi {
+        let beat_type = self.time_signature.beat_type;
+        let mut tracks = Vec::new();
+        for (index, (mut track, entries)) in self.tracks.into_iter().enumerate() {
+            track.notes = entries.iter().map(|e| build_entry(e, beat_type)).collect();
+            track.index = index;
+            track.length = track.notes.iter().map(|n| n.beat_count(beat_type)).sum();
+            track.end_of_track = (track.length * BUILDER_TICKS_PER_BEAT as f32).round() as u32;
+            let has_notes = track.notes.iter().flat_map(|n| n.notes()).any(|n| !n.is_rest());
+            track.kind = if has_notes { TrackKind::Notes } else { TrackKind::Empty };
+            tracks.push(track);
+        }
+        return Midi {
+            bmp: self.bpm,
+            microseconds_per_beat: 60_000_000 / self.bpm,
+            time_signatures: vec![self.time_signature],
+            key_signatures: self.key_signature.into_iter().collect(),
+            markers: Vec::new(),
+            sysex_events: Vec::new(),
+            metadata: MidiMetadata {
+                format: SmfFormat::Parallel,
+                track_count: tracks.len(),
+                sequence_name: None,
+                copyright: None,
+                text: Vec::new(),
+            },
+            ticks_per_beat: BUILDER_TICKS_PER_BEAT as f32,
+            tracks,
+            merged_track: None,
+            raw_events: None,
+            raw_fingerprints: Vec::new(),
+            sequences: Vec::new(),
+            swing: None,
+            retained_timing: None,
+        };
+    }
+
+    /// Returns the track notes are currently added to, starting an unnamed track if there is
+    /// none.
+    fn current_track(&mut self) -> &mut (Track, Vec<Entry>) {
+        if self.tracks.is_empty() {
+            let name = String::from("Track 0");
+            self.tracks.push((Track::new(name, Vec::new()), Vec::new()));
+        }
+        return self.tracks.last_mut().unwrap();
+    }
+}
+
+impl Default for MidiBuilder {
+    fn default() -> Self {
+        return MidiBuilder::new();
+    }
+}
+
+/// Builds the `NoteWrapper` for a note, rest, or chord, counting its beats in `beat_type` so
+/// that it keeps its written duration.
+fn build_entry(entry: &Entry, beat_type: u8) -> NoteWrapper {
+    let Entry { values, duration, velocity } = entry;
+    let (beats, velocity) = (duration.get_beat_count(beat_type), *velocity);
+    match values.as_slice() {
+        [] => return parse_note_data((None, 0), beats, beat_type),
+        [value] => return parse_note_data((Some(*value), velocity), beats, beat_type),
+        _ => {
+            let chord = values.iter()
+                .map(|v| parse_note_data((Some(*v), velocity), beats, beat_type))
+                .collect();
+            return NoteWrapper::ModifiedNote(NoteModifier::Chord(chord));
+        },
+    }
+}
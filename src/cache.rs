@@ -0,0 +1,138 @@
+use crate::Midi;
+use crate::error::ParseError;
+use crate::parsing::Scratch;
+use crate::parsing::options::ParseOptions;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::sync::Arc;
+
+/// A cache of parsed midi files for services that parse the same files over and over.
+/// 
+/// Parsed pieces are stored under a hash of the file's bytes and the options they were parsed
+/// with, so a file is only parsed again if its contents or the options change. Each piece keeps
+/// the bytes it was parsed from, and a piece is only returned for the same bytes, so files whose
+/// hashes collide are never mixed up. The progress
+/// function, cancel token, and parse time limit of the options aren't part of the key. Failed
+/// parses aren't stored.
+/// 
+/// Once the cache holds `capacity` pieces, the piece that was used least recently is dropped to
+/// make room for the next one.
+/// 
+/// ```no_run
+/// use beatblox_midi::cache::MidiCache;
+/// use beatblox_midi::parsing::options::ParseOptions;
+/// 
+/// let mut cache = MidiCache::new(64);
+/// let bytes = std::fs::read("a.mid").unwrap();
+/// let midi = cache.get_or_parse(&bytes, &ParseOptions::new()).unwrap();
+/// // The second call returns the same piece without parsing the file again.
+/// let again = cache.get_or_parse(&bytes, &ParseOptions::new()).unwrap();
+/// midi.print();
+/// again.print();
+/// ```
+pub struct MidiCache {
+    /// The most pieces the cache holds.
+    capacity: usize,
+    /// The parsed pieces and the bytes they were parsed from, keyed by the hash of their bytes
+    /// and options.
+    entries: HashMap<u64, (Vec<u8>, Arc<Midi>)>,
+    /// The keys of the cached pieces, from least to most recently used.
+    order: VecDeque<u64>,
+    /// The buffers used while parsing tracks.
+    scratch: Scratch,
+}
+
+impl MidiCache {
+    /// Creates an empty cache that holds at most `capacity` pieces.
+    pub fn new(capacity: usize) -> MidiCache {
+        MidiCache {
+            capacity,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            scratch: Scratch::default(),
+        }
+    }
+
+    /// Returns the piece parsed from `bytes` with `options`, parsing it and storing the result
+    /// if it isn't in the cache.
+    pub fn get_or_parse(
+        &mut self,
+        bytes: &[u8],
+        options: &ParseOptions
+    ) -> Result<Arc<Midi>, ParseError> {
+        let key = MidiCache::get_key(bytes, options);
+        if let Some(midi) = self.get(key, bytes) {
+            self.touch(key);
+            return Ok(midi);
+        }
+        let midi = Arc::new(Midi::parse_bytes_with_scratch(bytes, options, &mut self.scratch)?);
+        if self.capacity == 0 {
+            return Ok(midi);
+        }
+        // A piece under the same key was parsed from other bytes, and is replaced.
+        if self.entries.remove(&key).is_some() {
+            self.order.retain(|k| *k != key);
+        }
+        if self.entries.len() >= self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key, (bytes.to_vec(), Arc::clone(&midi)));
+        self.order.push_back(key);
+        return Ok(midi);
+    }
+
+    /// Whether or not the piece parsed from `bytes` with `options` is in the cache.
+    pub fn contains(&self, bytes: &[u8], options: &ParseOptions) -> bool {
+        return self.get(MidiCache::get_key(bytes, options), bytes).is_some();
+    }
+
+    /// Returns the most pieces the cache holds.
+    pub fn capacity(&self) -> usize {
+        return self.capacity;
+    }
+
+    /// Returns the number of pieces in the cache.
+    pub fn len(&self) -> usize {
+        return self.entries.len();
+    }
+
+    /// Whether or not the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        return self.entries.is_empty();
+    }
+
+    /// Removes every piece from the cache.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+
+    /// Hashes the bytes of a file together with the options it's parsed with.
+    fn get_key(bytes: &[u8], options: &ParseOptions) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        bytes.hash(&mut hasher);
+        options.fingerprint(&mut hasher);
+        return hasher.finish();
+    }
+
+    /// Returns the piece under `key` if it was parsed from `bytes`.
+    fn get(&self, key: u64, bytes: &[u8]) -> Option<Arc<Midi>> {
+        return self.entries
+            .get(&key)
+            .filter(|(parsed, _)| parsed.as_slice() == bytes)
+            .map(|(_, midi)| Arc::clone(midi));
+    }
+
+    /// Marks the piece under `key` as the most recently used.
+    fn touch(&mut self, key: u64) {
+        if let Some(position) = self.order.iter().position(|k| *k == key) {
+            self.order.remove(position);
+        }
+        self.order.push_back(key);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::parsing::options::Limit;
+use std::error::Error;
+use std::fmt;
+
+/// Represents an error that occurred while reading or parsing a midi file.
+///
+/// New variants may be added in future releases, so matches on this enum must include a
+/// wildcard arm.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// The midi file could not be read from disk.
+    Io(std::io::Error),
+    /// The file contents are not a valid standard midi file.
+    Midly(midly::Error),
+    /// The file uses SMPTE timecode timing instead of metrical (ticks per beat) timing.
+    UnsupportedTiming,
+    /// A track drifted off the bar lines shared by the rest of the score.
+    BarlineDivergence {
+        /// The name of the track that diverged.
+        track: String,
+        /// The measure in which the divergence was found, starting from 1.
+        measure: u32,
+        /// The beat within the measure at which the divergence was found, starting from 0.
+        beat: f32,
+    },
+    /// A note written in the text notation could not be read.
+    Notation {
+        /// The note that could not be read.
+        token: String,
+        /// A description of the problem.
+        message: String,
+    },
+    /// The bytes passed to `Midi::from_msgpack` are not a serialized `Midi`.
+    #[cfg(feature = "msgpack")]
+    Msgpack(rmp_serde::decode::Error),
+    /// A line of midicsv text could not be read.
+    Csv {
+        /// The line on which the problem was found, starting from 1.
+        line: usize,
+        /// A description of the problem.
+        message: String,
+    },
+    /// The original timing of the piece is needed, e.g. to requantize it, but it wasn't parsed
+    /// with `ParseOptions::retain_timing`.
+    TimingNotRetained,
+    /// The parse was cancelled through `ParseOptions::cancel_token`.
+    Cancelled,
+    /// The file went over one of the limits set with `ParseOptions::limits`.
+    LimitExceeded(Limit),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Io(e) => write!(f, "could not read midi file: {}", e),
+            ParseError::Midly(e) => write!(f, "file is not a valid standard midi file: {}", e),
+            ParseError::UnsupportedTiming => write!(
+                f,
+                "SMPTE timecode timing is not supported; re-export the file with metrical timing"
+            ),
+            ParseError::BarlineDivergence { track, measure, beat } => write!(
+                f,
+                "track \"{}\" diverges from the score's bar lines at measure {}, beat {}; \
+                try parsing with a different precision",
+                track, measure, beat
+            ),
+            ParseError::Notation { token, message } => {
+                write!(f, "could not read note \"{}\": {}", token, message)
+            },
+            #[cfg(feature = "msgpack")]
+            ParseError::Msgpack(e) => write!(f, "bytes are not a serialized midi object: {}", e),
+            ParseError::Csv { line, message } => write!(f, "midicsv line {}: {}", line, message),
+            ParseError::TimingNotRetained => {
+                write!(f, "the original timing of the piece was not retained")
+            },
+            ParseError::Cancelled => write!(f, "parsing was cancelled"),
+            ParseError::LimitExceeded(limit) => {
+                write!(f, "the file exceeds the {} limit", limit.to_string())
+            },
+        }
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseError::Io(e) => Some(e),
+            ParseError::Midly(e) => Some(e),
+            #[cfg(feature = "msgpack")]
+            ParseError::Msgpack(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ParseError {
+    fn from(e: std::io::Error) -> Self {
+        ParseError::Io(e)
+    }
+}
+
+impl From<midly::Error> for ParseError {
+    fn from(e: midly::Error) -> Self {
+        ParseError::Midly(e)
+    }
+}
+
+#[cfg(feature = "msgpack")]
+impl From<rmp_serde::decode::Error> for ParseError {
+    fn from(e: rmp_serde::decode::Error) -> Self {
+        ParseError::Msgpack(e)
+    }
+}
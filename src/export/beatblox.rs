@@ -0,0 +1,122 @@
+//! BeatBlox block definitions for a parsed midi file.
+//! 
+//! Every `NoteWrapper` maps to exactly one block:
+//! 
+//! | `NoteWrapper`                       | block       |
+//! |-------------------------------------|-------------|
+//! | `PlainNote`                         | `"note"`    |
+//! | `Rest`                              | `"rest"`    |
+//! | `ModifiedNote(NoteModifier::Chord)`    | `"chord"`   |
+//! | `ModifiedNote(NoteModifier::TiedNote)` | `"tie"`     |
+//! | `ModifiedNote(NoteModifier::Triplet)`  | `"triplet"` |
+//! 
+//! Note blocks hold a `"pitch"` (e.g. "F#4"), a `"duration"` (e.g. "Dotted Quarter"), and a
+//! `"velocity"`. Rest blocks only hold a `"duration"`. Chord, tie, and triplet blocks hold the
+//! blocks they group in `"blocks"`, and accented notes (see `Note::accent`) are held in the
+//! `"blocks"` of an `"accent"` block. Each track becomes a script:
+//! 
+//! ```text
+//! {
+//!   "format": "beatblox-blocks",
+//!   "version": 1,
+//!   "tempo": 120,
+//!   "time_signature": [4, 4],
+//!   "scripts": [{ "track": "Piano", "instrument": "Acoustic Grand Piano", "percussion": false,
+//!                 "blocks": [{ "block": "note", "pitch": "C4", "duration": "Quarter",
+//!                              "velocity": 100 }, ...] }]
+//! }
+//! ```
+
+use crate::Midi;
+use crate::export::json_option;
+use crate::export::json_string;
+use crate::parsing::Track;
+use crate::parsing::duration::DurationType;
+use crate::parsing::duration::NoteDuration;
+use crate::parsing::duration::NoteDurationModifier;
+use crate::parsing::symbols::NoteModifier;
+use crate::parsing::symbols::NoteWrapper;
+
+/// The version of the block format produced by `Midi::to_beatblox_blocks`.
+pub const BEATBLOX_BLOCKS_VERSION: u32 = 1;
+
+/// Converts a `Midi` object to BeatBlox block definitions.
+pub fn midi_to_blocks(midi: &Midi) -> String {
+    let time_signature = midi.time_signatures.first().copied().unwrap_or_default();
+    let scripts: Vec<String> = midi.tracks.iter().map(track_to_script).collect();
+    return format!(
+        "{{\"format\":\"beatblox-blocks\",\"version\":{},\"tempo\":{},\
+        \"time_signature\":[{},{}],\"scripts\":[{}]}}",
+        BEATBLOX_BLOCKS_VERSION,
+        midi.bmp,
+        time_signature.beat_count,
+        2_u32.pow(time_signature.beat_type as u32),
+        scripts.join(",")
+    );
+}
+
+/// Converts a `Track` object to a BeatBlox script.
+fn track_to_script(track: &Track) -> String {
+    let blocks: Vec<String> = track.notes.iter().map(wrapper_to_block).collect();
+    return format!(
+        "{{\"track\":{},\"instrument\":{},\"percussion\":{},\"blocks\":[{}]}}",
+        json_string(&track.name),
+        json_option(track.instrument.as_deref(), json_string),
+        track.percussion,
+        blocks.join(",")
+    );
+}
+
+/// Converts a `NoteWrapper` object to a BeatBlox block.
+pub fn wrapper_to_block(wrapper: &NoteWrapper) -> String {
+    match wrapper {
+        NoteWrapper::PlainNote(n) => {
+            let block = format!(
+                "{{\"block\":\"note\",\"pitch\":{},\"duration\":{},\"velocity\":{}}}",
+                json_string(&n.name()),
+                json_string(&get_block_duration(&n.duration)),
+                n.velocity
+            );
+            if n.accent {
+                return format!("{{\"block\":\"accent\",\"blocks\":[{}]}}", block);
+            }
+            return block;
+        },
+        NoteWrapper::Rest(r) => {
+            return format!(
+                "{{\"block\":\"rest\",\"duration\":{}}}",
+                json_string(&get_block_duration(&r.duration))
+            );
+        },
+        NoteWrapper::ModifiedNote(m) => {
+            let (block, contents) = match m {
+                NoteModifier::Chord(v) => ("chord", v),
+                NoteModifier::TiedNote(v) => ("tie", v),
+                NoteModifier::Triplet(v) => ("triplet", v),
+            };
+            let blocks: Vec<String> = contents.iter().map(wrapper_to_block).collect();
+            return format!("{{\"block\":\"{}\",\"blocks\":[{}]}}", block, blocks.join(","));
+        },
+    }
+}
+
+/// Returns the name BeatBlox uses for a duration, e.g. "Dotted Quarter".
+pub fn get_block_duration(duration: &DurationType) -> String {
+    let base = match duration.duration {
+        NoteDuration::WHOLE => "Whole",
+        NoteDuration::HALF => "Half",
+        NoteDuration::QUARTER => "Quarter",
+        NoteDuration::EIGHTH => "Eighth",
+        NoteDuration::SIXTEENTH => "Sixteenth",
+        NoteDuration::THIRTYSECOND => "ThirtySecond",
+        NoteDuration::SIXTYFOURTH => "SixtyFourth",
+        NoteDuration::HUNDREDTWENTYEIGHTH => "HundredTwentyEighth",
+        NoteDuration::NaN => "Unknown",
+    };
+    match duration.modifier {
+        NoteDurationModifier::None => return String::from(base),
+        NoteDurationModifier::Dotted => return format!("Dotted {}", base),
+        NoteDurationModifier::DoubleDotted => return format!("Double Dotted {}", base),
+        NoteDurationModifier::TripleDotted => return format!("Triple Dotted {}", base),
+    }
+}
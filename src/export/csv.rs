@@ -0,0 +1,91 @@
+use crate::Midi;
+use crate::parsing::Track;
+use crate::parsing::measure;
+use crate::parsing::symbols::NoteModifier;
+use crate::parsing::symbols::NoteWrapper;
+use crate::parsing::symbols::TimeSignature;
+
+/// The header row of the CSV produced by `Midi::to_csv` and `Track::to_csv`.
+pub const CSV_HEADER: &str =
+    "track,measure,beat,onset,pitch,name,duration,velocity,group,group_type";
+
+/// Converts every track of a `Midi` object to CSV, with a single header row.
+pub fn midi_to_csv(midi: &Midi) -> String {
+    let mut csv = format!("{}\n", CSV_HEADER);
+    for track in &midi.tracks {
+        write_rows(&mut csv, track, &midi.time_signatures, midi.ticks_per_beat);
+    }
+    return csv;
+}
+
+/// Converts a `Track` object to CSV, with a header row.
+pub fn track_to_csv(
+    track: &Track,
+    time_signatures: &[TimeSignature],
+    ticks_per_beat: f32
+) -> String {
+    let mut csv = format!("{}\n", CSV_HEADER);
+    write_rows(&mut csv, track, time_signatures, ticks_per_beat);
+    return csv;
+}
+
+/// Appends one row for every note in `track` to `csv`.
+/// 
+/// Rests are skipped. Notes inside of a chord, tie, or triplet share a group id, which is unique
+/// within the track, and the group type of the outermost grouping.
+fn write_rows(
+    csv: &mut String,
+    track: &Track,
+    time_signatures: &[TimeSignature],
+    ticks_per_beat: f32
+) {
+    let beat_type = match time_signatures.first() {
+        Some(time_signature) => time_signature.beat_type,
+        None => 2,
+    };
+    let name = escape_csv(&track.name);
+    let mut beat = 0.0;
+    let mut group_id = 0;
+    for wrapper in &track.notes {
+        let group = match wrapper {
+            NoteWrapper::ModifiedNote(m) => {
+                group_id += 1;
+                let group_type = match m {
+                    NoteModifier::Chord(_) => "chord",
+                    NoteModifier::TiedNote(_) => "tie",
+                    NoteModifier::Triplet(_) => "triplet",
+                };
+                format!("{},{}", group_id, group_type)
+            },
+            _ => String::from(","),
+        };
+        wrapper.for_each_note(beat, beat_type, &mut |note, onset, length| {
+            let Some(value) = note.value() else {
+                return;
+            };
+            let (measure, beat_in_measure) =
+                measure::measure_at(time_signatures, ticks_per_beat, onset);
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{}\n",
+                name,
+                measure,
+                beat_in_measure,
+                onset,
+                value,
+                note.name(),
+                length,
+                note.velocity,
+                group
+            ));
+        });
+        beat += wrapper.beat_count(beat_type);
+    }
+}
+
+/// Quotes a CSV field if it contains a comma, quote, or line break.
+pub fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        return format!("\"{}\"", field.replace('"', "\"\""));
+    }
+    return String::from(field);
+}
@@ -0,0 +1,182 @@
+//! A stable JSON representation of a parsed midi file.
+//! 
+//! The schema is written by hand rather than derived, so field names only change when
+//! `JSON_SCHEMA_VERSION` does. Version 1 has the following shape:
+//! 
+//! ```text
+//! {
+//!   "version": 1,
+//!   "ticks_per_beat": 384,
+//!   "bpm": 120,
+//!   "time_signatures": [{ "beat_count": 4, "beat_type": 2, "tick": 0 }],
+//!   "key_signatures": [{ "sharps": -1, "minor": false, "tick": 0 }],
+//!   "markers": [{ "text": "Verse", "tick": 0, "beat": 0 }],
+//!   "tracks": [{
+//!     "name": "Piano", "index": 1, "instrument": "Acoustic Grand Piano", "program": 0,
+//!     "percussion": false, "length": 8, "notes": [ <element>, ... ]
+//!   }]
+//! }
+//! ```
+//! 
+//! Every `<element>` has a `"type"` of `"note"`, `"rest"`, `"chord"`, `"tie"`, or `"triplet"`.
+//! Notes and rests hold their length in beats (quarter notes) and as a written duration:
+//! 
+//! ```text
+//! { "type": "note", "pitch": 60, "name": "C4", "beats": 1.5, "duration": "quarter note",
+//!   "modifier": "dotted", "velocity": 100, "dynamic": "f", "slur": false, "fermata": false,
+//!   "arpeggiated": false, "beam": null, "glissando": null, "vibrato": null, "lyric": null }
+//! { "type": "rest", "beats": 1, "duration": "quarter note", "modifier": "" }
+//! ```
+//! 
+//! The `"dynamic"` of a note is `null` unless the piece has been through `Midi::mark_dynamics`,
+//! `"slur"` is whether the note is slurred into the next (see `ParseOptions::slurs`),
+//! `"fermata"` is whether it's held past its written length (see `Midi::mark_fermatas`), and
+//! `"arpeggiated"` is whether it's part of a strummed chord (see `ParseOptions::arpeggios`).
+//! `"beam"` is `"begin"`, `"continue"`, or `"end"` for beamed notes once the piece has been
+//! through `Midi::mark_beams`, and `null` otherwise. A
+//! `"glissando"` slides from one pitch to another over part of the note, as in
+//! `{ "start": 60, "target": 64, "start_beat": 0, "end_beat": 0.5 }`, and a `"vibrato"` is
+//! given as `{ "source": "pitch bend", "rate": 5.5, "depth": 30 }` (see `Vibrato`).
+//! Chords, ties, and triplets hold their contents in `"notes"`. The beats of the notes inside
+//! of a triplet are their written (dupal) lengths; the triplet as a whole lasts two thirds of
+//! their sum, which is given in the triplet's own `"beats"` field.
+
+use crate::Midi;
+use crate::export::json_number;
+use crate::export::json_option;
+use crate::export::json_string;
+use crate::parsing::Track;
+use crate::parsing::expression::Glissando;
+use crate::parsing::expression::Vibrato;
+use crate::parsing::symbols::Note;
+use crate::parsing::symbols::NoteModifier;
+use crate::parsing::symbols::NoteWrapper;
+
+/// The version of the JSON schema produced by `Midi::to_json`.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// Converts a `Midi` object to JSON.
+pub fn midi_to_json(midi: &Midi) -> String {
+    let time_signatures: Vec<String> = midi.time_signatures.iter().map(|t| {
+        return format!(
+            "{{\"beat_count\":{},\"beat_type\":{},\"tick\":{}}}",
+            t.beat_count, t.beat_type, t.time_of_occurance
+        );
+    }).collect();
+    let key_signatures: Vec<String> = midi.key_signatures.iter().map(|k| {
+        return format!(
+            "{{\"sharps\":{},\"minor\":{},\"tick\":{}}}",
+            k.sharps, k.minor, k.time_of_occurance
+        );
+    }).collect();
+    let markers: Vec<String> = midi.markers.iter().map(|m| {
+        return format!(
+            "{{\"text\":{},\"tick\":{},\"beat\":{}}}",
+            json_string(&m.text), m.tick, json_number(m.beat)
+        );
+    }).collect();
+    let beat_type = midi.beat_type();
+    let tracks: Vec<String> = midi.tracks.iter().map(|t| track_to_json(t, beat_type)).collect();
+    return format!(
+        "{{\"version\":{},\"ticks_per_beat\":{},\"bpm\":{},\"time_signatures\":[{}],\
+        \"key_signatures\":[{}],\"markers\":[{}],\"tracks\":[{}]}}",
+        JSON_SCHEMA_VERSION,
+        json_number(midi.ticks_per_beat),
+        midi.bmp,
+        time_signatures.join(","),
+        key_signatures.join(","),
+        markers.join(","),
+        tracks.join(",")
+    );
+}
+
+/// Converts a `Track` object to JSON.
+fn track_to_json(track: &Track, beat_type: u8) -> String {
+    let notes: Vec<String> = track.notes.iter().map(|n| wrapper_to_json(n, beat_type)).collect();
+    return format!(
+        "{{\"name\":{},\"index\":{},\"instrument\":{},\"program\":{},\"percussion\":{},\
+        \"length\":{},\"notes\":[{}]}}",
+        json_string(&track.name),
+        track.index,
+        json_option(track.instrument.as_deref(), json_string),
+        json_option(track.program, |p| p.to_string()),
+        track.percussion,
+        json_number(track.length),
+        notes.join(",")
+    );
+}
+
+/// Converts a `NoteWrapper` object to JSON.
+fn wrapper_to_json(wrapper: &NoteWrapper, beat_type: u8) -> String {
+    match wrapper {
+        NoteWrapper::PlainNote(n) => return note_to_json(n, beat_type),
+        NoteWrapper::Rest(r) => {
+            return format!(
+                "{{\"type\":\"rest\",\"beats\":{},\"duration\":{},\"modifier\":{}}}",
+                json_number(r.duration.get_beat_count(beat_type)),
+                json_string(r.duration.duration.to_string()),
+                json_string(r.duration.modifier.to_string())
+            );
+        },
+        NoteWrapper::ModifiedNote(m) => {
+            let (kind, contents) = match m {
+                NoteModifier::Chord(v) => ("chord", v),
+                NoteModifier::TiedNote(v) => ("tie", v),
+                NoteModifier::Triplet(v) => ("triplet", v),
+            };
+            let notes: Vec<String> = contents.iter()
+                .map(|n| wrapper_to_json(n, beat_type))
+                .collect();
+            return format!(
+                "{{\"type\":\"{}\",\"beats\":{},\"notes\":[{}]}}",
+                kind,
+                json_number(wrapper.beat_count(beat_type)),
+                notes.join(",")
+            );
+        },
+    }
+}
+
+/// Converts a `Note` object to JSON.
+fn note_to_json(note: &Note, beat_type: u8) -> String {
+    return format!(
+        "{{\"type\":\"note\",\"pitch\":{},\"name\":{},\"beats\":{},\"duration\":{},\
+        \"modifier\":{},\"velocity\":{},\"dynamic\":{},\"slur\":{},\"fermata\":{},\
+        \"arpeggiated\":{},\"beam\":{},\"glissando\":{},\"vibrato\":{},\"lyric\":{}}}",
+        json_option(note.value(), |v| v.to_string()),
+        json_string(&note.name()),
+        json_number(note.duration.get_beat_count(beat_type)),
+        json_string(note.duration.duration.to_string()),
+        json_string(note.duration.modifier.to_string()),
+        note.velocity,
+        json_option(note.dynamic, |d| json_string(d.to_string())),
+        note.slur,
+        note.fermata,
+        note.arpeggiated,
+        json_option(note.beam, |b| json_string(b.to_string())),
+        json_option(note.glissando.as_ref(), glissando_to_json),
+        json_option(note.vibrato.as_ref(), vibrato_to_json),
+        json_option(note.lyric.as_deref(), json_string)
+    );
+}
+
+/// Converts a `Glissando` object to JSON.
+fn glissando_to_json(glissando: &Glissando) -> String {
+    return format!(
+        "{{\"start\":{},\"target\":{},\"start_beat\":{},\"end_beat\":{}}}",
+        glissando.start,
+        glissando.target,
+        json_number(glissando.start_beat),
+        json_number(glissando.end_beat)
+    );
+}
+
+/// Converts a `Vibrato` object to JSON.
+fn vibrato_to_json(vibrato: &Vibrato) -> String {
+    return format!(
+        "{{\"source\":{},\"rate\":{},\"depth\":{}}}",
+        json_string(vibrato.source.to_string()),
+        json_option(vibrato.rate, json_number),
+        json_number(vibrato.depth)
+    );
+}
@@ -0,0 +1,48 @@
+pub mod beatblox;
+pub mod csv;
+pub mod json;
+pub mod musicxml;
+pub mod netsblox;
+pub mod osc;
+pub mod smf;
+
+/// Escapes a string so it can be written inside of a JSON string literal.
+pub fn escape_json(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    return escaped;
+}
+
+/// Formats a string as a JSON string literal.
+pub(crate) fn json_string(s: &str) -> String {
+    return format!("\"{}\"", escape_json(s));
+}
+
+/// Formats a number as a JSON number, using `null` for values JSON cannot represent.
+pub(crate) fn json_number(n: f32) -> String {
+    if !n.is_finite() {
+        return String::from("null");
+    }
+    return format!("{}", n);
+}
+
+/// Formats an optional value as JSON, using `null` for `None`.
+pub(crate) fn json_option<T, F>(value: Option<T>, f: F) -> String
+where
+    F: FnOnce(T) -> String
+{
+    match value {
+        Some(v) => return f(v),
+        None => return String::from("null"),
+    }
+}
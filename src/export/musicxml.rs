@@ -0,0 +1,367 @@
+//! A MusicXML representation of a parsed midi file.
+//! 
+//! The piece is laid out with `score::build_score` and written as a `score-partwise` document,
+//! with one `<part>` for each note track. Parts written on a grand staff hold both staves, and
+//! each staff is written as its own voice. Durations are counted in `DIVISIONS` per quarter
+//! note, which is fine enough for every written duration and for the notes of triplets.
+
+use crate::Midi;
+use crate::parsing::duration::NoteDuration;
+use crate::parsing::duration::NoteDurationModifier;
+use crate::parsing::pitch::SpelledPitch;
+use crate::parsing::symbols::KeySignature;
+use crate::parsing::symbols::Note;
+use crate::parsing::symbols::NoteModifier;
+use crate::parsing::symbols::NoteWrapper;
+use crate::score::Clef;
+use crate::score::Measure;
+use crate::score::Part;
+use crate::score::build_score;
+
+/// The number of divisions a quarter note is split into. A triple dotted hundred twenty eighth
+/// note lasts 45 divisions, and two thirds of it in a triplet lasts 30.
+pub const DIVISIONS: u32 = 768;
+
+/// A note along with the ties that join it to its neighbours.
+struct Sounding<'a> {
+    note: &'a Note,
+    tie_start: bool,
+    tie_stop: bool,
+}
+
+/// The notes that share a stem, i.e. a single note or the notes of a chord.
+struct Stem<'a> {
+    notes: Vec<Sounding<'a>>,
+    /// The share of its written length the stem lasts, e.g. two thirds inside of a triplet.
+    scale: f32,
+    /// Whether the stem starts a triplet.
+    tuplet_start: bool,
+    /// Whether the stem ends a triplet.
+    tuplet_stop: bool,
+}
+
+/// Converts a `Midi` object into a MusicXML document.
+pub fn midi_to_musicxml(midi: &Midi) -> String {
+    let score = build_score(midi);
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n");
+    out += "<!DOCTYPE score-partwise PUBLIC \"-//Recordare//DTD MusicXML 4.0 Partwise//EN\" \
+        \"http://www.musicxml.org/dtds/partwise.dtd\">\n";
+    out += "<score-partwise version=\"4.0\">\n";
+    if let Some(name) = &midi.metadata.sequence_name {
+        out += &format!("  <work><work-title>{}</work-title></work>\n", escape_xml(name));
+    }
+    out += "  <part-list>\n";
+    for (i, part) in score.parts.iter().enumerate() {
+        out += &format!("    <score-part id=\"P{}\">\n", i + 1);
+        out += &format!("      <part-name>{}</part-name>\n", escape_xml(&part.name));
+        out += "    </score-part>\n";
+    }
+    out += "  </part-list>\n";
+    for (i, part) in score.parts.iter().enumerate() {
+        out += &format!("  <part id=\"P{}\">\n", i + 1);
+        let tempo = if i == 0 { Some(midi.bmp) } else { None };
+        write_part(&mut out, part, tempo);
+        out += "  </part>\n";
+    }
+    out += "</score-partwise>\n";
+    return out;
+}
+
+/// Writes the measures of a part. The tempo is written at the start of the part if it's given.
+fn write_part(out: &mut String, part: &Part, tempo: Option<u32>) {
+    let Some(first) = part.staves.first().and_then(|s| s.voices.first()) else {
+        return;
+    };
+    for (m, measure) in first.measures.iter().enumerate() {
+        *out += &format!("    <measure number=\"{}\">\n", measure.number);
+        let previous = m.checked_sub(1).map(|p| &first.measures[p]);
+        write_attributes(out, part, measure, previous);
+        if let (Some(bpm), None) = (tempo, previous) {
+            *out += "      <direction placement=\"above\">\n";
+            *out += "        <direction-type><metronome><beat-unit>quarter</beat-unit>";
+            *out += &format!("<per-minute>{}</per-minute></metronome></direction-type>\n", bpm);
+            *out += &format!("        <sound tempo=\"{}\"/>\n", bpm);
+            *out += "      </direction>\n";
+        }
+        if measure.section.is_some() && previous.map(|p| &p.section) != Some(&measure.section) {
+            let section = measure.section.as_deref().unwrap_or_default();
+            *out += "      <direction placement=\"above\">\n";
+            *out += &format!(
+                "        <direction-type><rehearsal>{}</rehearsal></direction-type>\n",
+                escape_xml(section)
+            );
+            *out += "      </direction>\n";
+        }
+        let quarters_per_beat = 2f32.powi(2 - measure.time_signature.beat_type as i32);
+        let mut backup = 0;
+        for (s, staff) in part.staves.iter().enumerate() {
+            let staff_number = if part.staves.len() > 1 { Some(s + 1) } else { None };
+            if backup > 0 {
+                *out += &format!("      <backup><duration>{}</duration></backup>\n", backup);
+            }
+            let notes = &staff.voices[0].measures[m].notes;
+            backup = 0;
+            if notes.is_empty() {
+                let duration = (measure.length * quarters_per_beat * DIVISIONS as f32).round();
+                *out += "      <note>\n        <rest measure=\"yes\"/>\n";
+                *out += &format!("        <duration>{}</duration>\n", duration as u32);
+                *out += &format!("        <voice>{}</voice>\n", s + 1);
+                if let Some(number) = staff_number {
+                    *out += &format!("        <staff>{}</staff>\n", number);
+                }
+                *out += "      </note>\n";
+                backup = duration as u32;
+                continue;
+            }
+            for wrapper in notes {
+                for stem in get_stems(wrapper) {
+                    let voice = (s + 1, staff_number);
+                    let key = measure.key_signature;
+                    backup += write_stem(out, &stem, part.percussion, key, voice);
+                }
+            }
+        }
+        *out += "    </measure>\n";
+    }
+}
+
+/// Writes the divisions, key, time, and clefs of a measure, if it's the first measure or any of
+/// them changed since the `previous` measure.
+fn write_attributes(out: &mut String, part: &Part, measure: &Measure, previous: Option<&Measure>) {
+    let time = &measure.time_signature;
+    let time_changed = previous.is_none_or(|p| {
+        return (p.time_signature.beat_count, p.time_signature.beat_type)
+            != (time.beat_count, time.beat_type);
+    });
+    let key_changed = previous.is_none_or(|p| {
+        let key = |m: &Measure| m.key_signature.map(|k| (k.sharps, k.minor));
+        return key(p) != key(measure);
+    });
+    if !time_changed && !key_changed {
+        return;
+    }
+    *out += "      <attributes>\n";
+    if previous.is_none() {
+        *out += &format!("        <divisions>{}</divisions>\n", DIVISIONS);
+    }
+    if let (true, Some(key)) = (key_changed, measure.key_signature) {
+        let mode = if key.minor { "minor" } else { "major" };
+        *out += &format!(
+            "        <key><fifths>{}</fifths><mode>{}</mode></key>\n",
+            key.sharps, mode
+        );
+    }
+    if time_changed {
+        *out += &format!(
+            "        <time><beats>{}</beats><beat-type>{}</beat-type></time>\n",
+            time.beat_count, 1u32 << time.beat_type
+        );
+    }
+    if previous.is_none() {
+        if part.staves.len() > 1 {
+            *out += &format!("        <staves>{}</staves>\n", part.staves.len());
+        }
+        for (s, staff) in part.staves.iter().enumerate() {
+            let number = if part.staves.len() > 1 {
+                format!(" number=\"{}\"", s + 1)
+            } else {
+                String::new()
+            };
+            let (sign, line) = match staff.clef {
+                Clef::Treble => ("G", "<line>2</line>"),
+                Clef::Bass => ("F", "<line>4</line>"),
+                Clef::Percussion => ("percussion", ""),
+            };
+            *out += &format!("        <clef{}><sign>{}</sign>{}</clef>\n", number, sign, line);
+        }
+    }
+    *out += "      </attributes>\n";
+}
+
+/// Splits a `NoteWrapper` into the stems it's written with, marking the ties and triplets
+/// between them.
+fn get_stems(wrapper: &NoteWrapper) -> Vec<Stem<'_>> {
+    match wrapper {
+        NoteWrapper::PlainNote(n) | NoteWrapper::Rest(n) => {
+            let note = Sounding { note: n, tie_start: false, tie_stop: false };
+            return vec![Stem {
+                notes: vec![note],
+                scale: 1.0,
+                tuplet_start: false,
+                tuplet_stop: false,
+            }];
+        },
+        NoteWrapper::ModifiedNote(NoteModifier::Chord(c)) => {
+            // The members of a chord are zipped together, so tied chords share their stems.
+            let mut stems: Vec<Stem> = Vec::new();
+            for member in c {
+                for (i, stem) in get_stems(member).into_iter().enumerate() {
+                    match stems.get_mut(i) {
+                        Some(shared) => shared.notes.extend(stem.notes),
+                        None => stems.push(stem),
+                    }
+                }
+            }
+            return stems;
+        },
+        NoteWrapper::ModifiedNote(NoteModifier::TiedNote(t)) => {
+            let mut stems: Vec<Stem> = Vec::new();
+            for piece in t {
+                let mut piece_stems = get_stems(piece);
+                if let (Some(last), Some(first)) = (stems.last_mut(), piece_stems.first_mut()) {
+                    for sounding in last.notes.iter_mut().filter(|s| !s.note.is_rest()) {
+                        sounding.tie_start = true;
+                    }
+                    for sounding in first.notes.iter_mut().filter(|s| !s.note.is_rest()) {
+                        sounding.tie_stop = true;
+                    }
+                }
+                stems.extend(piece_stems);
+            }
+            return stems;
+        },
+        NoteWrapper::ModifiedNote(NoteModifier::Triplet(tr)) => {
+            let mut stems: Vec<Stem> = tr.iter().flat_map(get_stems).collect();
+            for stem in &mut stems {
+                stem.scale *= 2.0 / 3.0;
+            }
+            if let Some(first) = stems.first_mut() {
+                first.tuplet_start = true;
+            }
+            if let Some(last) = stems.last_mut() {
+                last.tuplet_stop = true;
+            }
+            return stems;
+        },
+    }
+}
+
+/// Writes the notes of a stem in the given voice and staff, and returns the number of divisions
+/// it lasts. Pitches that haven't been spelled are spelled in `key`.
+fn write_stem(
+    out: &mut String,
+    stem: &Stem,
+    percussion: bool,
+    key: Option<KeySignature>,
+    (voice, staff): (usize, Option<usize>)
+) -> u32 {
+    let mut length = 0;
+    for (i, sounding) in stem.notes.iter().enumerate() {
+        let note = sounding.note;
+        let quarters = note.duration.get_beat_count(2) * stem.scale;
+        let duration = (quarters * DIVISIONS as f32).round() as u32;
+        if i == 0 {
+            length = duration;
+        }
+        *out += "      <note>\n";
+        if i > 0 {
+            *out += "        <chord/>\n";
+        }
+        match &note.pitch {
+            Some(pitch) => {
+                let spelled = pitch.spelling
+                    .unwrap_or_else(|| SpelledPitch::spell(pitch.value, key));
+                let (tag, step, octave) = match percussion {
+                    true => ("unpitched", "display-step", "display-octave"),
+                    false => ("pitch", "step", "octave"),
+                };
+                *out += &format!("        <{}><{}>{}</{}>", tag, step, spelled.letter.to_string(),
+                    step);
+                let alter = spelled.accidental.semitones();
+                if alter != 0 && !percussion {
+                    *out += &format!("<alter>{}</alter>", alter);
+                }
+                *out += &format!("<{}>{}</{}></{}>\n", octave, spelled.octave, octave, tag);
+            },
+            None => *out += "        <rest/>\n",
+        }
+        *out += &format!("        <duration>{}</duration>\n", duration);
+        if sounding.tie_stop {
+            *out += "        <tie type=\"stop\"/>\n";
+        }
+        if sounding.tie_start {
+            *out += "        <tie type=\"start\"/>\n";
+        }
+        *out += &format!("        <voice>{}</voice>\n", voice);
+        if let Some(name) = get_type(&note.duration.duration) {
+            *out += &format!("        <type>{}</type>\n", name);
+        }
+        let dots = match note.duration.modifier {
+            NoteDurationModifier::None => 0,
+            NoteDurationModifier::Dotted => 1,
+            NoteDurationModifier::DoubleDotted => 2,
+            NoteDurationModifier::TripleDotted => 3,
+        };
+        for _ in 0..dots {
+            *out += "        <dot/>\n";
+        }
+        if stem.scale < 1.0 {
+            *out += "        <time-modification><actual-notes>3</actual-notes>\
+                <normal-notes>2</normal-notes></time-modification>\n";
+        }
+        if let Some(number) = staff {
+            *out += &format!("        <staff>{}</staff>\n", number);
+        }
+        if let (0, Some(beam)) = (i, note.beam) {
+            *out += &format!("        <beam number=\"1\">{}</beam>\n", beam.to_string());
+        }
+        let mut notations = String::new();
+        if sounding.tie_stop {
+            notations += "<tied type=\"stop\"/>";
+        }
+        if sounding.tie_start {
+            notations += "<tied type=\"start\"/>";
+        }
+        if i == 0 && stem.tuplet_start {
+            notations += "<tuplet type=\"start\"/>";
+        }
+        if i == 0 && stem.tuplet_stop {
+            notations += "<tuplet type=\"stop\"/>";
+        }
+        if note.accent {
+            notations += "<articulations><accent/></articulations>";
+        }
+        if note.fermata {
+            notations += "<fermata/>";
+        }
+        if !notations.is_empty() {
+            *out += &format!("        <notations>{}</notations>\n", notations);
+        }
+        if let Some(lyric) = &note.lyric {
+            *out += &format!("        <lyric><text>{}</text></lyric>\n", escape_xml(lyric));
+        }
+        *out += "      </note>\n";
+    }
+    return length;
+}
+
+/// Returns the name MusicXML gives a duration, e.g. "16th".
+fn get_type(duration: &NoteDuration) -> Option<&'static str> {
+    match duration {
+        NoteDuration::WHOLE => return Some("whole"),
+        NoteDuration::HALF => return Some("half"),
+        NoteDuration::QUARTER => return Some("quarter"),
+        NoteDuration::EIGHTH => return Some("eighth"),
+        NoteDuration::SIXTEENTH => return Some("16th"),
+        NoteDuration::THIRTYSECOND => return Some("32nd"),
+        NoteDuration::SIXTYFOURTH => return Some("64th"),
+        NoteDuration::HUNDREDTWENTYEIGHTH => return Some("128th"),
+        NoteDuration::NaN => return None,
+    }
+}
+
+/// Escapes a string so it can be written as XML text or inside of an attribute.
+fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    return escaped;
+}
@@ -0,0 +1,101 @@
+use crate::Midi;
+use crate::export::json_number;
+use crate::export::json_string;
+use crate::parsing::Track;
+use crate::parsing::measure;
+use crate::parsing::symbols::Note;
+use crate::parsing::symbols::NoteModifier;
+use crate::parsing::symbols::NoteWrapper;
+
+/// A value inside of a NetsBlox list.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ListValue {
+    Number(f32),
+    Text(String),
+    List(Vec<ListValue>),
+}
+
+impl ListValue {
+    /// Writes the value as JSON, with lists as arrays.
+    pub fn to_json(&self) -> String {
+        match self {
+            ListValue::Number(n) => return json_number(*n),
+            ListValue::Text(s) => return json_string(s),
+            ListValue::List(items) => {
+                let items: Vec<String> = items.iter().map(|item| item.to_json()).collect();
+                return format!("[{}]", items.join(","));
+            },
+        }
+    }
+}
+
+/// Converts a `Midi` object into the nested lists NetsBlox blocks consume.
+/// 
+/// The result is a list of tracks, each of which is a list of measures, each of which is a list
+/// of `[pitch, duration, velocity]` entries. See `Midi::to_netsblox_lists` for the layout of an
+/// entry.
+pub fn midi_to_lists(midi: &Midi) -> ListValue {
+    let beat_type = midi.beat_type();
+    let tracks = midi.tracks.iter().map(|t| track_to_lists(midi, t, beat_type)).collect();
+    return ListValue::List(tracks);
+}
+
+/// Converts a `Track` object into a list of measures.
+fn track_to_lists(midi: &Midi, track: &Track, beat_type: u8) -> ListValue {
+    let mut measures: Vec<Vec<ListValue>> = Vec::new();
+    let mut beat = 0.0;
+    for wrapper in &track.notes {
+        let (measure, _) = measure::measure_at(&midi.time_signatures, midi.ticks_per_beat, beat);
+        while measures.len() < measure as usize {
+            measures.push(Vec::new());
+        }
+        let entries = &mut measures[measure as usize - 1];
+        match wrapper {
+            NoteWrapper::ModifiedNote(NoteModifier::Triplet(tr)) => {
+                for n in tr {
+                    entries.push(get_entry(n, n.beat_count(beat_type) * 2.0 / 3.0));
+                }
+            },
+            _ => entries.push(get_entry(wrapper, wrapper.beat_count(beat_type))),
+        }
+        beat += wrapper.beat_count(beat_type);
+    }
+    let measures = measures.into_iter().map(ListValue::List).collect();
+    return ListValue::List(measures);
+}
+
+/// Builds the `[pitch, duration, velocity]` entry for a `NoteWrapper` that lasts `beats` beats.
+fn get_entry(wrapper: &NoteWrapper, beats: f32) -> ListValue {
+    let (pitch, velocity) = get_pitch(wrapper);
+    return ListValue::List(vec![pitch, ListValue::Number(beats), ListValue::Number(velocity)]);
+}
+
+/// Returns the pitch and velocity a `NoteWrapper` starts with.
+/// 
+/// Rests have the pitch "Rest" and chords have a list of pitches. Tied notes take the pitch of
+/// their first note, since the entry already holds their combined duration.
+fn get_pitch(wrapper: &NoteWrapper) -> (ListValue, f32) {
+    match wrapper {
+        NoteWrapper::PlainNote(Note { pitch: Some(pitch), velocity, .. }) => {
+            return (ListValue::Number(pitch.value as f32), *velocity as f32);
+        },
+        NoteWrapper::PlainNote(_) | NoteWrapper::Rest(_) => {
+            return (ListValue::Text(String::from("Rest")), 0.0);
+        },
+        NoteWrapper::ModifiedNote(NoteModifier::Chord(c)) => {
+            let notes: Vec<_> = c.iter()
+                .flat_map(|n| n.notes())
+                .filter_map(|n| Some((n.value()?, n)))
+                .collect();
+            let pitches = notes.iter().map(|(v, _)| ListValue::Number(*v as f32)).collect();
+            let velocity = notes.iter().map(|(_, n)| n.velocity).max().unwrap_or(0);
+            return (ListValue::List(pitches), velocity as f32);
+        },
+        NoteWrapper::ModifiedNote(NoteModifier::TiedNote(t) | NoteModifier::Triplet(t)) => {
+            match t.first() {
+                Some(first) => return get_pitch(first),
+                None => return (ListValue::Text(String::from("Rest")), 0.0),
+            }
+        },
+    }
+}
@@ -0,0 +1,122 @@
+//! Open Sound Control output, for driving SuperCollider or Max patches with a parsed piece.
+//! 
+//! Every note becomes a `/note` message with the arguments `pitch` (int), `velocity` (int),
+//! `duration` (float, in beats), and `beat` (float, the onset counted from the start of the
+//! piece). Tied notes are sent once with their combined duration, the notes of a chord are sent
+//! separately with the same onset, and the notes of a triplet have their actual (two thirds)
+//! lengths. Rests aren't sent.
+
+use crate::Midi;
+use crate::flat::FLAG_START;
+use crate::flat::FLAG_TIED;
+use crate::flat::FlatTrack;
+use std::io;
+use std::net::ToSocketAddrs;
+use std::net::UdpSocket;
+use std::time::Duration;
+use std::time::Instant;
+
+/// The OSC address notes are sent to.
+pub const OSC_NOTE_ADDRESS: &str = "/note";
+
+/// A note to be sent as a `/note` message.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OscNote {
+    /// The position of the track the note is in, in `Midi::tracks`.
+    pub track: usize,
+    /// The midi value of the note.
+    pub pitch: u8,
+    /// The velocity of the note.
+    pub velocity: u8,
+    /// The number of beats the note lasts.
+    pub duration: f32,
+    /// The beat on which the note starts, counted from the start of the piece.
+    pub beat: f32,
+}
+
+/// Returns the notes of every track of a piece, in the order they start.
+pub fn get_osc_notes(midi: &Midi) -> Vec<OscNote> {
+    let beat_type = midi.beat_type();
+    let mut notes = Vec::new();
+    for (track, flat) in midi.tracks.iter().map(|t| FlatTrack::new(t, beat_type)).enumerate() {
+        for i in (0..flat.len()).filter(|i| flat.is_struck(*i)) {
+            // The pieces of a tied note follow it in the same entry of `Track::notes`, each
+            // starting where the last ended.
+            let entry_end = (i + 1..flat.len())
+                .find(|j| flat.flags[*j] & FLAG_START != 0)
+                .unwrap_or(flat.len());
+            let mut end = flat.onsets[i] + flat.durations[i];
+            for j in i + 1..entry_end {
+                let tied = flat.flags[j] & FLAG_TIED != 0 && flat.pitches[j] == flat.pitches[i];
+                if tied && (flat.onsets[j] - end).abs() < 1e-4 {
+                    end += flat.durations[j];
+                }
+            }
+            notes.push(OscNote {
+                track,
+                pitch: flat.pitches[i].unwrap_or_default(),
+                velocity: flat.velocities[i],
+                duration: end - flat.onsets[i],
+                beat: flat.onsets[i],
+            });
+        }
+    }
+    notes.sort_by(|a, b| a.beat.total_cmp(&b.beat));
+    return notes;
+}
+
+/// Encodes a note as a `/note` OSC message.
+pub fn note_message(note: &OscNote) -> Vec<u8> {
+    let mut message = Vec::new();
+    write_string(&mut message, OSC_NOTE_ADDRESS);
+    write_string(&mut message, ",iiff");
+    message.extend_from_slice(&(note.pitch as i32).to_be_bytes());
+    message.extend_from_slice(&(note.velocity as i32).to_be_bytes());
+    message.extend_from_slice(&note.duration.to_be_bytes());
+    message.extend_from_slice(&note.beat.to_be_bytes());
+    return message;
+}
+
+/// Encodes every note of a piece as a single OSC bundle, to be handled at once.
+pub fn midi_to_osc_bundle(midi: &Midi) -> Vec<u8> {
+    let mut bundle = Vec::new();
+    write_string(&mut bundle, "#bundle");
+    // The time tag 1 means "immediately".
+    bundle.extend_from_slice(&1u64.to_be_bytes());
+    for note in get_osc_notes(midi) {
+        let message = note_message(&note);
+        bundle.extend_from_slice(&(message.len() as i32).to_be_bytes());
+        bundle.extend_from_slice(&message);
+    }
+    return bundle;
+}
+
+/// Sends every note of a piece to `target` over UDP as it's played, following its tempo changes
+/// (see `Midi::tempo_map`). This blocks until the last note has been sent.
+pub fn stream_osc<A: ToSocketAddrs>(
+    midi: &Midi,
+    socket: &UdpSocket,
+    target: A
+) -> io::Result<()> {
+    let target = target
+        .to_socket_addrs()?
+        .next()
+        .ok_or(io::Error::new(io::ErrorKind::InvalidInput, "no address to send to"))?;
+    let tempo_map = midi.tempo_map();
+    let start = Instant::now();
+    for note in get_osc_notes(midi) {
+        let due = Duration::from_secs_f64(tempo_map.seconds_at(note.beat as f64));
+        if let Some(wait) = due.checked_sub(start.elapsed()) {
+            std::thread::sleep(wait);
+        }
+        socket.send_to(&note_message(&note), target)?;
+    }
+    return Ok(());
+}
+
+/// Writes an OSC string, which is null terminated and padded to a multiple of four bytes.
+fn write_string(bytes: &mut Vec<u8>, s: &str) {
+    bytes.extend_from_slice(s.as_bytes());
+    let padding = 4 - s.len() % 4;
+    bytes.extend(std::iter::repeat_n(0, padding));
+}
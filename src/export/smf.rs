@@ -0,0 +1,308 @@
+use crate::Midi;
+use crate::builder::BUILDER_TICKS_PER_BEAT;
+use crate::parsing::Track;
+use crate::parsing::TrackKind;
+use crate::parsing::events::Event;
+use crate::parsing::events::EventFile;
+use crate::parsing::events::EventKind;
+use crate::parsing::events::TextKind;
+use crate::parsing::general_midi::PERCUSSION_CHANNEL;
+use crate::parsing::metadata::SmfFormat;
+use crate::parsing::symbols::NoteModifier;
+use crate::parsing::symbols::NoteWrapper;
+use crate::parsing::symbols::TimeSignature;
+
+/// A note to be written out, with its start and end in ticks.
+struct NoteSpan {
+    key: u8,
+    velocity: u8,
+    start: u32,
+    end: u32,
+    lyric: Option<String>,
+}
+
+/// Converts a `Midi` object back into midi events.
+/// 
+/// The first track holds the tempo, time signatures, key signatures, markers, and file-level
+/// text. Every other track that isn't a conductor track gets a track of its own, played on its
+/// own channel (or the percussion channel for percussion tracks).
+pub fn midi_to_events(midi: &Midi) -> EventFile {
+    if let Some(raw) = &midi.raw_events {
+        return get_lossless_events(midi, raw);
+    }
+    let ticks_per_beat = midi.ticks_per_beat.round() as u16;
+    let beat_type = midi.beat_type();
+    let mut tracks = vec![get_conductor_events(midi)];
+    let mut channel: u8 = 0;
+    for track in &midi.tracks {
+        if track.kind == TrackKind::Conductor {
+            continue;
+        }
+        let track_channel = match track.percussion {
+            true => PERCUSSION_CHANNEL,
+            false => {
+                if channel == PERCUSSION_CHANNEL {
+                    channel += 1;
+                }
+                let c = channel % 16;
+                channel += 1;
+                c
+            },
+        };
+        let tpb = midi.ticks_per_beat;
+        tracks.push(get_track_events(track, track_channel, beat_type, tpb, midi.swing));
+    }
+    let end = tracks.iter().filter_map(|t| t.last()).map(|e| e.tick).max().unwrap_or(0);
+    tracks[0].push(Event { tick: end, kind: EventKind::EndOfTrack });
+    return EventFile { format: SmfFormat::Parallel, ticks_per_beat, tracks };
+}
+
+/// Converts a single track into midi events, with a conductor track holding `bpm` and
+/// `time_signature`.
+/// 
+/// The track is played on the first channel, or the percussion channel if it's a percussion
+/// track.
+pub fn track_to_events(track: &Track, bpm: u32, time_signature: &TimeSignature) -> EventFile {
+    let ticks_per_beat = BUILDER_TICKS_PER_BEAT as f32;
+    let channel = if track.percussion { PERCUSSION_CHANNEL } else { 0 };
+    let beat_type = time_signature.beat_type;
+    let events = get_track_events(track, channel, beat_type, ticks_per_beat, None);
+    let end = events.last().map(|e| e.tick).unwrap_or(0);
+    let conductor = vec![
+        Event { tick: 0, kind: EventKind::Tempo(60_000_000 / bpm.max(1)) },
+        Event {
+            tick: 0,
+            kind: EventKind::TimeSignature(time_signature.beat_count, beat_type, 24, 8),
+        },
+        Event { tick: end, kind: EventKind::EndOfTrack },
+    ];
+    return EventFile {
+        format: SmfFormat::Parallel,
+        ticks_per_beat: BUILDER_TICKS_PER_BEAT,
+        tracks: vec![conductor, events],
+    };
+}
+
+/// Converts a `Midi` object parsed in lossless mode back into midi events.
+/// 
+/// Every track whose notes haven't changed since it was parsed (and every track that was left out
+/// of `Midi::tracks`) keeps its original events. Tracks whose notes have changed keep their
+/// original events other than notes and lyrics, which are written from the notes instead.
+/// 
+/// A format 0 file stays a single track. Its tracks were split by channel when it was parsed, so
+/// a changed track only has the notes on its own channel written again.
+fn get_lossless_events(midi: &Midi, raw: &EventFile) -> EventFile {
+    let beat_type = midi.beat_type();
+    let channels = match raw.format {
+        SmfFormat::SingleTrack => Some(get_channel_order(raw)),
+        _ => None,
+    };
+    let mut file = raw.clone();
+    for track in &midi.tracks {
+        let fingerprint = crate::parsing::get_note_fingerprint(track, beat_type);
+        if midi.raw_fingerprints.contains(&(track.index, fingerprint)) {
+            continue;
+        }
+        // The first track of a split format 0 file holds every event that isn't a channel
+        // message, and each of the others holds a channel.
+        let (events, split_channel) = match &channels {
+            Some(channels) => match track.index.checked_sub(1).and_then(|i| channels.get(i)) {
+                Some(channel) => (file.tracks.get_mut(0), Some(*channel)),
+                None => continue,
+            },
+            None => (file.tracks.get_mut(track.index), None),
+        };
+        let Some(events) = events else {
+            continue;
+        };
+        let channel = split_channel.unwrap_or_else(|| events.iter().find_map(|e| match e.kind {
+            EventKind::NoteOn { channel, .. } | EventKind::NoteOff { channel, .. } => Some(channel),
+            _ => None,
+        }).unwrap_or(0));
+        let end = events.iter().map(|e| e.tick).max().unwrap_or(0);
+        events.retain(|e| match e.kind {
+            EventKind::NoteOn { channel: c, .. } | EventKind::NoteOff { channel: c, .. } => {
+                split_channel.is_some_and(|channel| channel != c)
+            },
+            EventKind::Text(TextKind::Lyric, _) => split_channel.is_some(),
+            EventKind::EndOfTrack => false,
+            _ => true,
+        });
+        let tpb = midi.ticks_per_beat;
+        let mut notes = get_note_events(track, channel, beat_type, tpb, midi.swing);
+        if split_channel.is_some() {
+            // Lyrics aren't tied to a channel, so those of a format 0 file are kept as they are.
+            notes.retain(|e| !matches!(e.kind, EventKind::Text(TextKind::Lyric, _)));
+        }
+        events.append(&mut notes);
+        events.sort_by_key(|e| e.tick);
+        let end = events.iter().map(|e| e.tick).max().unwrap_or(0).max(end);
+        events.push(Event { tick: end, kind: EventKind::EndOfTrack });
+    }
+    return file;
+}
+
+/// Returns the channels used in the only track of a format 0 file, in the order they're first
+/// used, which is the order of the tracks it's split into.
+fn get_channel_order(file: &EventFile) -> Vec<u8> {
+    let mut channels = Vec::new();
+    for event in file.tracks.iter().flatten() {
+        let channel = match event.kind {
+            EventKind::NoteOff { channel, .. }
+            | EventKind::NoteOn { channel, .. }
+            | EventKind::PolyPressure { channel, .. }
+            | EventKind::Controller { channel, .. }
+            | EventKind::ProgramChange { channel, .. }
+            | EventKind::ChannelPressure { channel, .. }
+            | EventKind::PitchBend { channel, .. } => channel,
+            _ => continue,
+        };
+        if !channels.contains(&channel) {
+            channels.push(channel);
+        }
+    }
+    return channels;
+}
+
+/// Returns the events of the conductor track, excluding its `EndOfTrack` event.
+fn get_conductor_events(midi: &Midi) -> Vec<Event> {
+    let mut events = Vec::new();
+    let mut text = |kind, s: &str| {
+        events.push(Event { tick: 0, kind: EventKind::Text(kind, s.as_bytes().to_vec()) });
+    };
+    if let Some(name) = &midi.metadata.sequence_name {
+        text(TextKind::TrackName, name);
+    }
+    if let Some(copyright) = &midi.metadata.copyright {
+        text(TextKind::Copyright, copyright);
+    }
+    for t in &midi.metadata.text {
+        text(TextKind::Text, t);
+    }
+    events.push(Event { tick: 0, kind: EventKind::Tempo(midi.microseconds_per_beat) });
+    for t in &midi.time_signatures {
+        events.push(Event {
+            tick: t.time_of_occurance,
+            kind: EventKind::TimeSignature(t.beat_count, t.beat_type, 24, 8),
+        });
+    }
+    for k in &midi.key_signatures {
+        events.push(Event {
+            tick: k.time_of_occurance,
+            kind: EventKind::KeySignature(k.sharps, k.minor),
+        });
+    }
+    for m in &midi.markers {
+        let kind = EventKind::Text(TextKind::Marker, m.text.as_bytes().to_vec());
+        events.push(Event { tick: m.tick, kind });
+    }
+    events.sort_by_key(|e| e.tick);
+    return events;
+}
+
+/// Returns the events of a single track, including its `EndOfTrack` event.
+fn get_track_events(
+    track: &Track,
+    channel: u8,
+    beat_type: u8,
+    ticks_per_beat: f32,
+    swing: Option<f32>
+) -> Vec<Event> {
+    let mut events = Vec::new();
+    let name = track.track_name.as_ref().unwrap_or(&track.name);
+    let kind = EventKind::Text(TextKind::TrackName, name.as_bytes().to_vec());
+    events.push(Event { tick: 0, kind });
+    if let Some(instrument_name) = &track.instrument_name {
+        let kind = EventKind::Text(TextKind::InstrumentName, instrument_name.as_bytes().to_vec());
+        events.push(Event { tick: 0, kind });
+    }
+    if let Some(program) = track.program {
+        events.push(Event { tick: 0, kind: EventKind::ProgramChange { channel, program } });
+    }
+    events.append(&mut get_note_events(track, channel, beat_type, ticks_per_beat, swing));
+
+    let end = (track.length * ticks_per_beat).round() as u32;
+    let end = events.iter().map(|e| e.tick).max().unwrap_or(0).max(end);
+    events.push(Event { tick: end, kind: EventKind::EndOfTrack });
+    return events;
+}
+
+/// Returns the note and lyric events of a track, sorted by the tick they occur on.
+/// 
+/// Notes are moved onto the swung positions given by `swing`. See `Midi::apply_swing`.
+fn get_note_events(
+    track: &Track,
+    channel: u8,
+    beat_type: u8,
+    ticks_per_beat: f32,
+    swing: Option<f32>
+) -> Vec<Event> {
+    let to_tick = |beat: f32| (swing_beat(beat, swing) * ticks_per_beat).round() as u32;
+    let mut beat = 0.0;
+    let mut notes = Vec::new();
+    for wrapper in &track.notes {
+        let mut spans = Vec::new();
+        wrapper.for_each_note(beat, beat_type, &mut |note, onset, length| {
+            let Some(key) = note.value() else {
+                return;
+            };
+            spans.push(NoteSpan {
+                key,
+                velocity: note.velocity,
+                start: to_tick(onset),
+                end: to_tick(onset + length),
+                lyric: note.lyric.clone(),
+            });
+        });
+        if let NoteWrapper::ModifiedNote(NoteModifier::TiedNote(_)) = wrapper {
+            spans = join_tied_spans(spans);
+        }
+        notes.append(&mut spans);
+        beat += wrapper.beat_count(beat_type);
+    }
+
+    // Note offs are ordered before note ons on the same tick so repeated notes aren't cut off.
+    let mut note_events = Vec::new();
+    for note in notes {
+        if let Some(lyric) = note.lyric {
+            let kind = EventKind::Text(TextKind::Lyric, lyric.into_bytes());
+            note_events.push((note.start, 1, Event { tick: note.start, kind }));
+        }
+        let (key, velocity) = (note.key, note.velocity);
+        let on = EventKind::NoteOn { channel, key, velocity };
+        let off = EventKind::NoteOff { channel, key, velocity: 0 };
+        note_events.push((note.start, 2, Event { tick: note.start, kind: on }));
+        note_events.push((note.end, 0, Event { tick: note.end, kind: off }));
+    }
+    note_events.sort_by_key(|(tick, order, _)| (*tick, *order));
+    return note_events.into_iter().map(|(_, _, e)| e).collect();
+}
+
+/// Moves a beat onto its swung position, where the second eighth note of each beat is delayed so
+/// the first is `ratio` times as long as the second.
+fn swing_beat(beat: f32, swing: Option<f32>) -> f32 {
+    let ratio = match swing {
+        Some(ratio) => ratio,
+        None => return beat,
+    };
+    let offbeat = ratio / (ratio + 1.0);
+    let whole = beat.floor();
+    let fraction = beat - whole;
+    if fraction <= 0.5 {
+        return whole + fraction * 2.0 * offbeat;
+    }
+    return whole + offbeat + (fraction - 0.5) * 2.0 * (1.0 - offbeat);
+}
+
+/// Joins the segments of tied notes into single notes that sound for the combined length.
+fn join_tied_spans(spans: Vec<NoteSpan>) -> Vec<NoteSpan> {
+    let mut joined: Vec<NoteSpan> = Vec::new();
+    for span in spans {
+        let previous = joined.iter_mut().rev().find(|s| s.key == span.key && s.end == span.start);
+        match previous {
+            Some(previous) => previous.end = span.end,
+            None => joined.push(span),
+        }
+    }
+    return joined;
+}
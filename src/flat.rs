@@ -0,0 +1,244 @@
+use crate::analysis;
+use crate::analysis::Difficulty;
+use crate::analysis::PolyphonyProfile;
+use crate::analysis::TrackStats;
+use crate::parsing::Track;
+use crate::parsing::duration::DurationType;
+use crate::parsing::measure;
+use crate::parsing::measure::MetricPosition;
+use crate::parsing::symbols::NoteModifier;
+use crate::parsing::symbols::NoteWrapper;
+use crate::parsing::symbols::TimeSignature;
+
+/// Set on the first note of each entry of `Track::notes`.
+pub const FLAG_START: u8 = 1;
+/// Set on rests.
+pub const FLAG_REST: u8 = 2;
+/// Set on the later pieces of a tied note, which continue the note before them instead of being
+/// struck again.
+pub const FLAG_TIED: u8 = 4;
+/// Set on the notes of a chord after its first, which start together with the note before them.
+pub const FLAG_CHORD: u8 = 8;
+/// Set on the notes of a triplet.
+pub const FLAG_TRIPLET: u8 = 16;
+
+/// A track stored as parallel vectors with one element for each note, including rests and the
+/// pieces of tied notes, for analyzing many tracks without following the tree of `NoteWrapper`s.
+/// 
+/// Notes are stored in the order `NoteWrapper::for_each_note` visits them, and the `FLAG_`
+/// constants in `flags` record the structure of the tree, so `FlatTrack::to_notes` can rebuild
+/// it. Only the value, duration, and velocity of each note are kept; spellings, lyrics, pitch
+/// bends, and pressure are not.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FlatTrack {
+    /// The value of each note, or `None` for rests.
+    pub pitches: Vec<Option<u8>>,
+    /// The beat on which each note starts, counted from the start of the track.
+    pub onsets: Vec<f32>,
+    /// The number of beats each note lasts, with triplets scaled by two thirds.
+    pub durations: Vec<f32>,
+    /// The velocity of each note.
+    pub velocities: Vec<u8>,
+    /// The `FLAG_` constants set on each note.
+    pub flags: Vec<u8>,
+    /// The beat type the durations are read in.
+    pub beat_type: u8,
+    /// The number of beats the notes and rests of the track last.
+    pub beats: f32,
+    /// The length of the track in beats, taken from its `EndOfTrack` event.
+    pub length: f32,
+    /// Whether or not the track is a percussion track.
+    pub percussion: bool,
+}
+
+impl FlatTrack {
+    /// Flattens the notes of a track, reading durations in the given beat type.
+    pub fn new(track: &Track, beat_type: u8) -> FlatTrack {
+        let mut flat = FlatTrack {
+            beat_type,
+            length: track.length,
+            percussion: track.percussion,
+            ..FlatTrack::default()
+        };
+        for wrapper in &track.notes {
+            flat.push(wrapper, flat.beats, 1.0, FLAG_START);
+            flat.beats += wrapper.beat_count(beat_type);
+        }
+        return flat;
+    }
+
+    /// Returns the number of notes, including rests and the pieces of tied notes.
+    pub fn len(&self) -> usize {
+        return self.pitches.len();
+    }
+
+    /// Whether or not the track has no notes or rests.
+    pub fn is_empty(&self) -> bool {
+        return self.pitches.is_empty();
+    }
+
+    /// Whether or not the note at `i` is struck, i.e. it isn't a rest or the later piece of a tied
+    /// note.
+    pub fn is_struck(&self, i: usize) -> bool {
+        return self.flags[i] & (FLAG_REST | FLAG_TIED) == 0;
+    }
+
+    /// Rebuilds the tree of `NoteWrapper`s the track was flattened from.
+    /// 
+    /// The notes of an entry that start together become a chord, and an entry with several
+    /// onsets becomes a triplet if its notes are flagged as one, or a tied note otherwise.
+    pub fn to_notes(&self) -> Vec<NoteWrapper> {
+        let mut notes = Vec::new();
+        let mut start = 0;
+        while start < self.len() {
+            let end = (start + 1..self.len())
+                .find(|i| self.flags[*i] & FLAG_START != 0)
+                .unwrap_or(self.len());
+            let mut slots = Vec::new();
+            let mut slot_start = start;
+            while slot_start < end {
+                let slot_end = (slot_start + 1..end)
+                    .find(|i| self.flags[*i] & FLAG_CHORD == 0)
+                    .unwrap_or(end);
+                slots.push(self.get_slot(slot_start, slot_end));
+                slot_start = slot_end;
+            }
+            let wrapper = match slots.len() {
+                1 => slots.remove(0),
+                _ if self.flags[start] & FLAG_TRIPLET != 0 => {
+                    NoteWrapper::ModifiedNote(NoteModifier::Triplet(slots))
+                },
+                _ => NoteWrapper::ModifiedNote(NoteModifier::TiedNote(slots)),
+            };
+            notes.push(wrapper);
+            start = end;
+        }
+        return notes;
+    }
+
+    /// Gathers the statistics of the track. See `analysis::track_stats`.
+    pub fn stats(&self) -> TrackStats {
+        return analysis::flat_track_stats(self);
+    }
+
+    /// Counts the notes played on each pitch class of the track, from C to B. See
+    /// `analysis::pitch_class_histogram`.
+    pub fn pitch_class_histogram(&self) -> [usize; 12] {
+        return analysis::flat_pitch_class_histogram(self);
+    }
+
+    /// Counts the written notes of each duration in the track. See
+    /// `analysis::duration_histogram`.
+    pub fn duration_histogram(&self) -> [usize; 8] {
+        return analysis::flat_duration_histogram(self);
+    }
+
+    /// Counts the onsets in each measure of the track. See `analysis::density_per_measure`.
+    pub fn density_per_measure(
+        &self,
+        time_signatures: &[TimeSignature],
+        ticks_per_beat: f32,
+        count_chord_notes: bool
+    ) -> Vec<usize> {
+        return analysis::flat_density_per_measure(
+            self,
+            time_signatures,
+            ticks_per_beat,
+            count_chord_notes
+        );
+    }
+
+    /// Returns the measure, beat, and subdivision on which each note starts, in the same order as
+    /// `onsets`. See `measure::metric_position`.
+    /// 
+    /// `time_signatures` and `ticks_per_beat` can be taken from `Midi::time_signatures` and
+    /// `Midi::ticks_per_beat`.
+    pub fn metric_positions(
+        &self,
+        time_signatures: &[TimeSignature],
+        ticks_per_beat: f32
+    ) -> Vec<MetricPosition> {
+        return self.onsets
+            .iter()
+            .map(|onset| measure::metric_position(time_signatures, ticks_per_beat, *onset))
+            .collect();
+    }
+
+    /// Finds the number of notes sounding at once over the course of the track. See
+    /// `analysis::polyphony_profile`.
+    pub fn polyphony_profile(&self) -> PolyphonyProfile {
+        return analysis::flat_polyphony_profile(self);
+    }
+
+    /// Rates how hard the track is to play at `bpm` beats per minute. See
+    /// `analysis::difficulty`.
+    pub fn difficulty(&self, bpm: u32) -> Difficulty {
+        return analysis::flat_difficulty(self, bpm);
+    }
+
+    /// Returns the duration the note at `i` is written with.
+    pub(crate) fn written_duration(&self, i: usize) -> DurationType {
+        let mut beats = self.durations[i];
+        if self.flags[i] & FLAG_TRIPLET != 0 {
+            beats *= 1.5;
+        }
+        return DurationType::beat_type_map((beats * 128.0).round() / 128.0, self.beat_type);
+    }
+
+    /// Appends the notes of a `NoteWrapper` that starts on `onset`, scaling their lengths by
+    /// `scale` and setting `flags` on the first of them.
+    fn push(&mut self, wrapper: &NoteWrapper, onset: f32, scale: f32, flags: u8) {
+        match wrapper {
+            NoteWrapper::PlainNote(n) | NoteWrapper::Rest(n) => {
+                let rest = if n.is_rest() { FLAG_REST } else { 0 };
+                self.pitches.push(n.value());
+                self.onsets.push(onset);
+                self.durations.push(n.duration.get_beat_count(self.beat_type) * scale);
+                self.velocities.push(n.velocity);
+                self.flags.push(flags | rest);
+            },
+            NoteWrapper::ModifiedNote(NoteModifier::Chord(c)) => {
+                for (i, n) in c.iter().enumerate() {
+                    let flags = if i == 0 { flags } else { flags & !FLAG_START | FLAG_CHORD };
+                    self.push(n, onset, scale, flags);
+                }
+            },
+            NoteWrapper::ModifiedNote(NoteModifier::TiedNote(t)) => {
+                let mut beat = onset;
+                for (i, n) in t.iter().enumerate() {
+                    let flags = match i {
+                        0 => flags,
+                        _ => flags & !(FLAG_START | FLAG_CHORD) | FLAG_TIED,
+                    };
+                    self.push(n, beat, scale, flags);
+                    beat += n.beat_count(self.beat_type) * scale;
+                }
+            },
+            NoteWrapper::ModifiedNote(NoteModifier::Triplet(tr)) => {
+                let mut beat = onset;
+                for (i, n) in tr.iter().enumerate() {
+                    let flags = match i {
+                        0 => flags | FLAG_TRIPLET,
+                        _ => flags & !(FLAG_START | FLAG_CHORD) | FLAG_TRIPLET,
+                    };
+                    self.push(n, beat, scale * 2.0 / 3.0, flags);
+                    beat += n.beat_count(self.beat_type) * scale * 2.0 / 3.0;
+                }
+            },
+        }
+    }
+
+    /// Rebuilds the note or chord made of the notes from `start` up to `end`.
+    fn get_slot(&self, start: usize, end: usize) -> NoteWrapper {
+        let mut notes: Vec<NoteWrapper> = (start..end)
+            .map(|i| {
+                let (value, velocity) = (self.pitches[i], self.velocities[i]);
+                return NoteWrapper::build_note_wrapper(value, self.written_duration(i), velocity);
+            })
+            .collect();
+        if notes.len() == 1 {
+            return notes.remove(0);
+        }
+        return NoteWrapper::ModifiedNote(NoteModifier::Chord(notes));
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::HashSet;
+use crate::Midi;
+use crate::parsing::TrackKind;
+use crate::parsing::measure;
+
+/// The phrase lengths in measures that `detect_form` tries, longest first.
+const PHRASE_LENGTHS: [u32; 3] = [8, 4, 2];
+
+/// How similar two phrases must be, from 0 to 1, to be given the same label by `detect_form`.
+pub const DEFAULT_SIMILARITY_THRESHOLD: f32 = 0.8;
+
+/// A section of a piece, made up of whole measures.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Section {
+    /// The label of the section, where sections with the same label repeat each other.
+    pub label: char,
+    /// The first measure of the section, counting from 1.
+    pub start_measure: u32,
+    /// The last measure of the section, counting from 1.
+    pub end_measure: u32,
+    /// The beat on which the section starts, counted from the start of the piece.
+    pub start_beat: f32,
+    /// The beat on which the section ends, counted from the start of the piece.
+    pub end_beat: f32,
+    /// How similar the section is to the first section with the same label, from 0 to 1. This is
+    /// 1 for exact repeats and for the first section with each label.
+    pub similarity: f32,
+}
+
+/// The structure of a piece, e.g. AABA.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Form {
+    /// The sections of the piece in order.
+    pub sections: Vec<Section>,
+}
+
+/// A section of a piece named by a marker, e.g. "Chorus 2".
+#[derive(Clone, Debug, PartialEq)]
+pub struct NamedSection {
+    /// The text of the marker that starts the section.
+    pub name: String,
+    /// The first measure of the section, counting from 1.
+    pub start_measure: u32,
+    /// The last measure of the section, counting from 1.
+    pub end_measure: u32,
+}
+
+impl Form {
+    /// Returns the labels of the sections in order, e.g. "AABA".
+    pub fn labels(&self) -> String {
+        return self.sections.iter().map(|s| s.label).collect();
+    }
+}
+
+/// Finds the structure of a piece by splitting it into phrases and labeling the phrases that
+/// repeat, exactly or nearly, with the same letter.
+/// 
+/// The longest phrase length of 8, 4, or 2 measures at which at least half of the phrases repeat
+/// is used, or 4 measures if none do.
+pub fn detect_form(midi: &Midi) -> Form {
+    let measures = get_measure_contents(midi);
+    let count = measures.len() as u32;
+    let phrase_length = PHRASE_LENGTHS
+        .iter()
+        .copied()
+        .filter(|length| *length * 2 <= count)
+        .find(|length| {
+            let form = get_form(midi, &measures, *length, DEFAULT_SIMILARITY_THRESHOLD);
+            let repeated = form.sections
+                .iter()
+                .filter(|s| form.sections.iter().filter(|o| o.label == s.label).count() > 1)
+                .count();
+            repeated * 2 >= form.sections.len()
+        })
+        .unwrap_or(4);
+    return get_form(midi, &measures, phrase_length, DEFAULT_SIMILARITY_THRESHOLD);
+}
+
+/// Finds the structure of a piece using phrases of `phrase_length` measures, giving phrases at
+/// least `threshold` similar (from 0 to 1) the same label. See `detect_form`.
+pub fn detect_form_with_length(midi: &Midi, phrase_length: u32, threshold: f32) -> Form {
+    let measures = get_measure_contents(midi);
+    return get_form(midi, &measures, phrase_length.max(1), threshold);
+}
+
+/// Splits a piece into the sections named by its markers. See `Midi::markers`.
+/// 
+/// Each section starts on the measure its marker falls in and lasts until the measure before the
+/// next section, or the end of the piece. When several markers fall in the same measure, the last
+/// of them names the section. Measures before the first marker aren't part of any section.
+pub fn marked_sections(midi: &Midi) -> Vec<NamedSection> {
+    let total = midi.total_measures();
+    let mut sections: Vec<NamedSection> = Vec::new();
+    for marker in midi.markers.iter().filter(|m| !m.text.trim().is_empty()) {
+        let (measure, _) = midi.measure_at(marker.beat);
+        if measure > total {
+            break;
+        }
+        if sections.last().is_some_and(|s| s.start_measure == measure) {
+            sections.pop();
+        }
+        if let Some(previous) = sections.last_mut() {
+            previous.end_measure = measure - 1;
+        }
+        sections.push(NamedSection {
+            name: marker.text.clone(),
+            start_measure: measure,
+            end_measure: total,
+        });
+    }
+    return sections;
+}
+
+/// Splits a piece into phrases of `phrase_length` measures and labels them.
+fn get_form(
+    midi: &Midi,
+    measures: &[HashSet<(usize, u32, u8)>],
+    phrase_length: u32,
+    threshold: f32
+) -> Form {
+    let end = get_end_beat(midi);
+    let starts = measure::measure_starts(&midi.time_signatures, midi.ticks_per_beat, end);
+    // The label of each distinct phrase and the measure it starts on.
+    let mut firsts: Vec<(char, usize)> = Vec::new();
+    let mut sections = Vec::new();
+    let mut start = 0;
+    while start < measures.len() {
+        let stop = (start + phrase_length as usize).min(measures.len());
+        let best = firsts
+            .iter()
+            .map(|(label, first)| {
+                (*label, get_phrase_similarity(measures, *first, start, stop - start))
+            })
+            .filter(|(_, score)| *score >= threshold)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        let (label, similarity) = match best {
+            Some(best) => best,
+            None => {
+                let label = (b'A'..=b'Z').map(char::from).nth(firsts.len()).unwrap_or('?');
+                firsts.push((label, start));
+                (label, 1.0)
+            },
+        };
+        sections.push(Section {
+            label,
+            start_measure: start as u32 + 1,
+            end_measure: stop as u32,
+            start_beat: starts.get(start).copied().unwrap_or(end),
+            end_beat: starts.get(stop).copied().unwrap_or(end).min(end),
+            similarity,
+        });
+        start = stop;
+    }
+    return Form { sections };
+}
+
+/// The average similarity of the `length` measures starting at measure `a` and those starting
+/// at measure `b`, counting from 0.
+fn get_phrase_similarity(
+    measures: &[HashSet<(usize, u32, u8)>],
+    a: usize,
+    b: usize,
+    length: usize
+) -> f32 {
+    let mut total = 0.0;
+    for i in 0..length {
+        let (x, y) = match (measures.get(a + i), measures.get(b + i)) {
+            (Some(x), Some(y)) => (x, y),
+            _ => continue,
+        };
+        let union = x.union(y).count();
+        total += match union {
+            0 => 1.0,
+            _ => x.intersection(y).count() as f32 / union as f32,
+        };
+    }
+    return total / length as f32;
+}
+
+/// Returns the notes of each measure of a piece as `(track, position, value)` triples, where
+/// `position` is the onset of the note within the measure in 48ths of a beat.
+fn get_measure_contents(midi: &Midi) -> Vec<HashSet<(usize, u32, u8)>> {
+    let beat_type = midi.beat_type();
+    let end = get_end_beat(midi);
+    let starts = measure::measure_starts(&midi.time_signatures, midi.ticks_per_beat, end);
+    let count = starts.iter().filter(|s| **s < end - 1e-4).count();
+    let mut measures = vec![HashSet::new(); count];
+    for (t, track) in midi.tracks.iter().enumerate() {
+        if track.kind != TrackKind::Notes {
+            continue;
+        }
+        let mut beat = 0.0;
+        for wrapper in &track.notes {
+            wrapper.for_each_note(beat, beat_type, &mut |note, onset, _| {
+                let Some(value) = note.value() else {
+                    return;
+                };
+                let (measure, position) =
+                    measure::measure_at(&midi.time_signatures, midi.ticks_per_beat, onset);
+                if let Some(contents) = measures.get_mut(measure as usize - 1) {
+                    contents.insert((t, (position * 48.0).round() as u32, value));
+                }
+            });
+            beat += wrapper.beat_count(beat_type);
+        }
+    }
+    return measures;
+}
+
+/// Returns the beat on which the last note track of a piece ends.
+fn get_end_beat(midi: &Midi) -> f32 {
+    let beat_type = midi.beat_type();
+    return midi.tracks
+        .iter()
+        .filter(|t| t.kind == TrackKind::Notes)
+        .map(|t| t.notes.iter().map(|n| n.beat_count(beat_type)).sum::<f32>())
+        .fold(0.0, f32::max);
+}
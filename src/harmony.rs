@@ -0,0 +1,346 @@
+use crate::Midi;
+use crate::parsing::Track;
+use crate::parsing::TrackKind;
+use crate::parsing::measure;
+use crate::parsing::pitch::SpelledPitch;
+use crate::parsing::symbols::KeySignature;
+
+/// The Krumhansl-Kessler profile of how strongly each degree of a major key is felt, starting
+/// from the tonic.
+const MAJOR_PROFILE: [f32; 12] = [
+    6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88
+];
+
+/// The Krumhansl-Kessler profile of how strongly each degree of a minor key is felt, starting
+/// from the tonic.
+const MINOR_PROFILE: [f32; 12] = [
+    6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17
+];
+
+/// The kind of a chord, given by the intervals of its notes above the root.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ChordQuality {
+    Major,
+    Minor,
+    Diminished,
+    Augmented,
+    Dominant7,
+    Major7,
+    Minor7,
+    HalfDiminished7,
+    Diminished7,
+}
+
+impl ChordQuality {
+    /// Every chord quality, with triads before seventh chords.
+    pub const ALL: [ChordQuality; 9] = [
+        ChordQuality::Major, ChordQuality::Minor, ChordQuality::Diminished,
+        ChordQuality::Augmented, ChordQuality::Dominant7, ChordQuality::Major7,
+        ChordQuality::Minor7, ChordQuality::HalfDiminished7, ChordQuality::Diminished7,
+    ];
+
+    /// Converts the enum to a string.
+    pub fn to_string(&self) -> &str {
+        match self {
+            ChordQuality::Major => return "major",
+            ChordQuality::Minor => return "minor",
+            ChordQuality::Diminished => return "diminished",
+            ChordQuality::Augmented => return "augmented",
+            ChordQuality::Dominant7 => return "dominant seventh",
+            ChordQuality::Major7 => return "major seventh",
+            ChordQuality::Minor7 => return "minor seventh",
+            ChordQuality::HalfDiminished7 => return "half-diminished seventh",
+            ChordQuality::Diminished7 => return "diminished seventh",
+        }
+    }
+
+    /// The number of semitones each note of the chord lies above its root.
+    pub fn intervals(&self) -> &[u8] {
+        match self {
+            ChordQuality::Major => return &[0, 4, 7],
+            ChordQuality::Minor => return &[0, 3, 7],
+            ChordQuality::Diminished => return &[0, 3, 6],
+            ChordQuality::Augmented => return &[0, 4, 8],
+            ChordQuality::Dominant7 => return &[0, 4, 7, 10],
+            ChordQuality::Major7 => return &[0, 4, 7, 11],
+            ChordQuality::Minor7 => return &[0, 3, 7, 10],
+            ChordQuality::HalfDiminished7 => return &[0, 3, 6, 10],
+            ChordQuality::Diminished7 => return &[0, 3, 6, 9],
+        }
+    }
+
+    /// The symbol written after the root in a chord name, e.g. "m7".
+    fn symbol(&self) -> &str {
+        match self {
+            ChordQuality::Major => return "",
+            ChordQuality::Minor => return "m",
+            ChordQuality::Diminished => return "dim",
+            ChordQuality::Augmented => return "aug",
+            ChordQuality::Dominant7 => return "7",
+            ChordQuality::Major7 => return "maj7",
+            ChordQuality::Minor7 => return "m7",
+            ChordQuality::HalfDiminished7 => return "m7b5",
+            ChordQuality::Diminished7 => return "dim7",
+        }
+    }
+
+    /// Whether the chord has a major or augmented third, so its Roman numeral is upper case.
+    fn is_major(&self) -> bool {
+        return self.intervals()[1] == 4;
+    }
+
+    /// The symbol written after a Roman numeral, e.g. "ø7".
+    fn numeral_symbol(&self) -> &str {
+        match self {
+            ChordQuality::Major | ChordQuality::Minor => return "",
+            ChordQuality::Diminished => return "°",
+            ChordQuality::Augmented => return "+",
+            ChordQuality::Dominant7 | ChordQuality::Minor7 => return "7",
+            ChordQuality::Major7 => return "maj7",
+            ChordQuality::HalfDiminished7 => return "ø7",
+            ChordQuality::Diminished7 => return "°7",
+        }
+    }
+}
+
+/// A chord, given by the pitch class of its root and its quality.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Chord {
+    /// The pitch class of the root of the chord, where C is 0.
+    pub root: u8,
+    /// The kind of chord built on the root.
+    pub quality: ChordQuality,
+}
+
+impl Chord {
+    /// Identifies the chord that best explains a set of pitch classes, where `weights` holds how
+    /// long each pitch class from C to B sounds. Returns `None` if nothing sounds.
+    /// 
+    /// Each chord is scored by the weight of its notes minus the weight of the notes outside of
+    /// it, less half of the heaviest weight for each of its notes that doesn't sound. A chord's
+    /// root must sound, and triads win ties with seventh chords.
+    pub fn identify(weights: &[f32; 12]) -> Option<Chord> {
+        let heaviest = weights.iter().copied().fold(0.0, f32::max);
+        let total: f32 = weights.iter().sum();
+        let mut best: Option<(f32, Chord)> = None;
+        for root in 0..12 {
+            if weights[root as usize] <= 0.0 {
+                continue;
+            }
+            for quality in ChordQuality::ALL {
+                let mut score = -total;
+                for interval in quality.intervals() {
+                    let weight = weights[((root + interval) % 12) as usize];
+                    score += match weight > 0.0 {
+                        true => 2.0 * weight,
+                        false => -0.5 * heaviest,
+                    };
+                }
+                if best.is_none() || score > best.unwrap().0 + 1e-4 {
+                    best = Some((score, Chord { root, quality }));
+                }
+            }
+        }
+        return best.map(|(_, chord)| chord);
+    }
+
+    /// Identifies the chord formed by a set of midi note values. See `Chord::identify`.
+    pub fn from_values(values: &[u8]) -> Option<Chord> {
+        let mut weights = [0.0; 12];
+        for value in values {
+            weights[*value as usize % 12] += 1.0;
+        }
+        return Chord::identify(&weights);
+    }
+
+    /// Formats the chord as a string, e.g. "F#m7", spelling the root in `key` (or C major).
+    pub fn name(&self, key: Option<KeySignature>) -> String {
+        let root = SpelledPitch::spell(self.root, key);
+        return format!(
+            "{}{}{}",
+            root.letter.to_string(),
+            root.accidental.to_string(),
+            self.quality.symbol()
+        );
+    }
+
+    /// Writes the chord as a Roman numeral relative to `key`, e.g. "V7" or "ii°".
+    /// 
+    /// Roots outside of the key's scale take an accidental, e.g. "bVII" in a major key.
+    pub fn roman_numeral(&self, key: &KeySignature) -> String {
+        const MAJOR: [&str; 12] = [
+            "I", "bII", "II", "bIII", "III", "IV", "#IV", "V", "bVI", "VI", "bVII", "VII"
+        ];
+        const MINOR: [&str; 12] = [
+            "I", "bII", "II", "III", "#III", "IV", "#IV", "V", "VI", "#VI", "VII", "#VII"
+        ];
+        let degree = ((self.root + 12 - key.tonic()) % 12) as usize;
+        let numeral = if key.minor { MINOR[degree] } else { MAJOR[degree] };
+        let numeral = match self.quality.is_major() {
+            true => numeral.to_string(),
+            false => numeral.to_lowercase(),
+        };
+        return format!("{}{}", numeral, self.quality.numeral_symbol());
+    }
+}
+
+/// The span of time each chord of a harmonic analysis covers.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HarmonicRhythm {
+    /// One chord per measure.
+    Measure,
+    /// One chord per beat.
+    Beat,
+}
+
+/// A chord in a harmonic analysis.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HarmonySegment {
+    /// The measure the segment is in, counting from 1.
+    pub measure: u32,
+    /// The beat on which the segment starts, counted from the start of the piece.
+    pub beat: f32,
+    /// The number of beats the segment lasts.
+    pub length: f32,
+    /// The chord that sounds, or `None` if nothing sounds.
+    pub chord: Option<Chord>,
+    /// The chord written as a Roman numeral relative to the key of the piece.
+    pub numeral: Option<String>,
+}
+
+/// The chord progression of a piece relative to its key.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HarmonyAnalysis {
+    /// The key of the piece, found with `detect_key`.
+    pub key: KeySignature,
+    /// The chords of the piece in order.
+    pub segments: Vec<HarmonySegment>,
+}
+
+/// Estimates the key of a piece from how long each pitch class sounds in its pitched tracks,
+/// using the Krumhansl-Schmuckler key-finding algorithm. Returns `None` if nothing sounds.
+pub fn detect_key(midi: &Midi) -> Option<KeySignature> {
+    let beat_type = midi.beat_type();
+    let mut weights = [0.0; 12];
+    for track in get_pitched_tracks(midi) {
+        for_each_sounding(track, beat_type, &mut |value, _, length| {
+            weights[value as usize % 12] += length;
+        });
+    }
+    return get_key(&weights);
+}
+
+/// Finds the key whose profile correlates best with `weights`, which hold how long each pitch
+/// class from C to B sounds.
+fn get_key(weights: &[f32; 12]) -> Option<KeySignature> {
+    if weights.iter().all(|w| *w <= 0.0) {
+        return None;
+    }
+    let mut best: Option<(f32, KeySignature)> = None;
+    for tonic in 0..12 {
+        for (minor, profile) in [(false, &MAJOR_PROFILE), (true, &MINOR_PROFILE)] {
+            let rotated: Vec<f32> = (0..12).map(|i| profile[(i + 12 - tonic) % 12]).collect();
+            let score = correlation(weights, &rotated);
+            if best.is_none() || score > best.unwrap().0 {
+                best = Some((score, KeySignature::from_tonic(tonic as u8, minor)));
+            }
+        }
+    }
+    return best.map(|(_, key)| key);
+}
+
+/// Labels the chords of a piece with Roman numerals relative to its detected key, with one chord
+/// for each measure or beat.
+/// 
+/// Every pitched track is heard together, and each chord is identified from how long each pitch
+/// class sounds within its span. See `Chord::identify`.
+pub fn analyze_harmony(midi: &Midi, rhythm: HarmonicRhythm) -> HarmonyAnalysis {
+    let beat_type = midi.beat_type();
+    let key = detect_key(midi).unwrap_or(KeySignature::from_tonic(0, false));
+    let tracks = get_pitched_tracks(midi);
+    let end = tracks
+        .iter()
+        .map(|t| t.notes.iter().map(|n| n.beat_count(beat_type)).sum::<f32>())
+        .fold(0.0, f32::max);
+    let starts = measure::measure_starts(&midi.time_signatures, midi.ticks_per_beat, end);
+    let mut spans: Vec<(u32, f32, f32)> = Vec::new();
+    for (i, start) in starts.iter().enumerate() {
+        if *start >= end - 1e-4 {
+            break;
+        }
+        let measure_end = starts.get(i + 1).copied().unwrap_or(end);
+        match rhythm {
+            HarmonicRhythm::Measure => spans.push((i as u32 + 1, *start, measure_end - start)),
+            HarmonicRhythm::Beat => {
+                let mut beat = *start;
+                while beat < measure_end - 1e-4 {
+                    spans.push((i as u32 + 1, beat, (measure_end - beat).min(1.0)));
+                    beat += 1.0;
+                }
+            },
+        }
+    }
+
+    let mut weights = vec![[0.0; 12]; spans.len()];
+    for track in &tracks {
+        for_each_sounding(track, beat_type, &mut |value, onset, length| {
+            for (span, weights) in spans.iter().zip(weights.iter_mut()) {
+                let overlap = (onset + length).min(span.1 + span.2) - onset.max(span.1);
+                if overlap > 1e-4 {
+                    weights[value as usize % 12] += overlap;
+                }
+            }
+        });
+    }
+    let segments = spans
+        .into_iter()
+        .zip(weights)
+        .map(|((measure, beat, length), weights)| {
+            let chord = Chord::identify(&weights);
+            let numeral = chord.map(|c| c.roman_numeral(&key));
+            HarmonySegment { measure, beat, length, chord, numeral }
+        })
+        .collect();
+    return HarmonyAnalysis { key, segments };
+}
+
+/// Returns the note tracks of a piece that aren't percussion tracks.
+fn get_pitched_tracks(midi: &Midi) -> Vec<&Track> {
+    return midi.tracks
+        .iter()
+        .filter(|t| t.kind == TrackKind::Notes && !t.percussion)
+        .collect();
+}
+
+/// Calls `f` with the value, onset, and length in beats of every note that sounds in a track.
+fn for_each_sounding<F>(track: &Track, beat_type: u8, f: &mut F)
+where
+    F: FnMut(u8, f32, f32)
+{
+    let mut beat = 0.0;
+    for wrapper in &track.notes {
+        wrapper.for_each_note(beat, beat_type, &mut |note, onset, length| {
+            if let Some(value) = note.value() {
+                f(value, onset, length);
+            }
+        });
+        beat += wrapper.beat_count(beat_type);
+    }
+}
+
+/// The Pearson correlation of two lists of numbers.
+fn correlation(a: &[f32], b: &[f32]) -> f32 {
+    let n = a.len() as f32;
+    let (mean_a, mean_b) = (a.iter().sum::<f32>() / n, b.iter().sum::<f32>() / n);
+    let mut covariance = 0.0;
+    let (mut variance_a, mut variance_b) = (0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        covariance += (x - mean_a) * (y - mean_b);
+        variance_a += (x - mean_a) * (x - mean_a);
+        variance_b += (y - mean_b) * (y - mean_b);
+    }
+    if variance_a <= 0.0 || variance_b <= 0.0 {
+        return 0.0;
+    }
+    return covariance / (variance_a * variance_b).sqrt();
+}
@@ -1,28 +1,108 @@
+#![allow(clippy::needless_return)]
+
+pub mod analysis;
+pub mod builder;
+pub mod cache;
+pub mod error;
+pub mod export;
+pub mod flat;
+pub mod form;
+pub mod harmony;
+pub mod model;
+pub mod parser;
 pub mod parsing;
+pub mod playability;
+pub mod playback;
+pub mod score;
+#[cfg(feature = "serve")]
+pub mod serve;
+pub mod stream;
+pub mod transform;
+pub mod view;
+#[cfg(feature = "watch")]
+pub mod watch;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
+use analysis::TrackStats;
+use error::ParseError;
+use export::netsblox::ListValue;
+use form::Form;
+use form::NamedSection;
+use harmony::HarmonicRhythm;
+use harmony::HarmonyAnalysis;
+use playability::BeatbloxConstraints;
+use playability::PlayabilityReport;
+#[cfg(feature = "play")]
+use playback::Playback;
+#[cfg(feature = "play")]
+use playback::PlayError;
+use score::Score;
 use midly::Smf;
 use parsing::duration;
 use std::fs;
+use std::ops::RangeInclusive;
+use std::time::Instant;
 
+use crate::parsing::Scratch;
+use crate::parsing::metadata::MidiMetadata;
 use crate::parsing::Track;
 use crate::parsing::duration::DurationType;
+use crate::parsing::dynamics::AccentOptions;
+use crate::parsing::dynamics::DynamicThresholds;
+use crate::parsing::events::EventFile;
+use crate::parsing::measure::MetricPosition;
+use crate::parsing::options::Limit;
+use crate::parsing::options::ParseOptions;
+use crate::parsing::options::ResourceLimits;
+use crate::parsing::options::RetainedOptions;
+use crate::parsing::symbols::KeySignature;
+use crate::parsing::symbols::Marker;
+use crate::parsing::symbols::SysexEvent;
 use crate::parsing::symbols::TimeSignature;
+use crate::transform::FermataOptions;
+use crate::parsing::tempo::TempoMap;
 
 /// The Midi structure is a netsblox-friendly representation of the parsed midi file.
 #[derive(Clone)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
 pub struct Midi {
     /// The initial tempo of the piece.
     bmp: u32,
+    /// The initial tempo of the piece, in microseconds per beat.
+    microseconds_per_beat: u32,
     /// A list of time signatures that occur in the piece.
     time_signatures: Vec<TimeSignature>,
+    /// A list of key signatures that occur in the piece.
+    key_signatures: Vec<KeySignature>,
+    /// A list of markers and cue points in the piece.
+    markers: Vec<Marker>,
+    /// A list of system exclusive messages in the piece.
+    sysex_events: Vec<SysexEvent>,
+    /// File-level information about the midi file.
+    metadata: MidiMetadata,
     /// Number of ticks in each beat.
     ticks_per_beat: f32,
     /// A list of tracks in the midi file.
     tracks: Vec<Track>,
+    /// A single track holding the notes of every note track, if it was requested.
+    merged_track: Option<Track>,
+    /// The original events of the file, if it was parsed in lossless mode.
+    raw_events: Option<EventFile>,
+    /// The index and note fingerprint of each track as it was parsed in lossless mode.
+    raw_fingerprints: Vec<(usize, u64)>,
+    /// The independent sequences of a format 2 file.
+    sequences: Vec<Midi>,
+    /// The long to short ratio of swung eighth notes when the piece is played back, if it swings.
+    swing: Option<f32>,
+    /// The original events of the file and the options needed to parse them again, if they
+    /// were retained for requantizing.
+    #[cfg_attr(feature = "msgpack", serde(skip))]
+    retained_timing: Option<(EventFile, RetainedOptions)>,
 }
 impl Midi {
     /// Parses through a midi file found at `dir` and returns a `Midi` object.
-    pub fn parse(dir: String) -> Midi {
+    pub fn parse(dir: String) -> Result<Midi, ParseError> {
         let precision = duration::DEFAULT_DURATION_PRECISION;
         return Midi::parse_with_precision(dir, precision, false);
     }
@@ -35,12 +115,86 @@ impl Midi {
     /// 
     /// The `triplet` parameter indicated if the user wants to scan for triplets. Scanning for
     /// triplets requires extra resources.
-    pub fn parse_with_precision(dir: String, precision: DurationType, triplet: bool) -> Midi {
-        let contents = fs::read(dir).unwrap();
-        let smf = Smf::parse(&contents).unwrap();
-        let mut midi = Midi::new(&smf);
-        parsing::load_tracks(&mut midi, &smf, &precision, triplet);
-        return midi;
+    pub fn parse_with_precision(
+        dir: String,
+        precision: DurationType,
+        triplet: bool
+    ) -> Result<Midi, ParseError> {
+        let options = ParseOptions::new().precision(precision).triplet(triplet);
+        return Midi::parse_with_options(dir, &options);
+    }
+
+    /// Parses through a midi file found at `dir` using the given `ParseOptions` and returns a
+    /// `Midi` object.
+    pub fn parse_with_options(dir: String, options: &ParseOptions) -> Result<Midi, ParseError> {
+        let size = fs::metadata(&dir)?.len() as usize;
+        ResourceLimits::check(Limit::FileSize, size, options.limits.max_file_size)?;
+        let contents = fs::read(dir)?;
+        return Midi::parse_bytes(&contents, options);
+    }
+
+    /// Parses through the contents of a midi file using the given `ParseOptions` and returns a
+    /// `Midi` object.
+    pub fn parse_bytes(bytes: &[u8], options: &ParseOptions) -> Result<Midi, ParseError> {
+        return Midi::parse_bytes_with_scratch(bytes, options, &mut Scratch::default());
+    }
+
+    /// Parses through the contents of a midi file, reusing the buffers in `scratch`.
+    pub(crate) fn parse_bytes_with_scratch(
+        bytes: &[u8],
+        options: &ParseOptions,
+        scratch: &mut Scratch
+    ) -> Result<Midi, ParseError> {
+        ResourceLimits::check(Limit::FileSize, bytes.len(), options.limits.max_file_size)?;
+        let smf = Smf::parse(bytes)?;
+        return Midi::parse_smf(&smf, options, options.limits.deadline(), scratch);
+    }
+
+    /// Parses through the events of an `EventFile` using the given `ParseOptions` and returns a
+    /// `Midi` object.
+    pub fn parse_events(events: &EventFile, options: &ParseOptions) -> Result<Midi, ParseError> {
+        let deadline = options.limits.deadline();
+        return Midi::parse_smf(&events.to_smf(), options, deadline, &mut Scratch::default());
+    }
+
+    /// Parses through a file in the midicsv text format using the given `ParseOptions` and
+    /// returns a `Midi` object.
+    pub fn parse_midicsv(text: &str, options: &ParseOptions) -> Result<Midi, ParseError> {
+        return Midi::parse_events(&EventFile::from_midicsv(text)?, options);
+    }
+
+    /// Parses through a `midly::Smf`, reusing the buffers in `scratch`. Parsing must finish by
+    /// `deadline`, if there is one.
+    fn parse_smf(
+        smf: &Smf,
+        options: &ParseOptions,
+        deadline: Option<Instant>,
+        scratch: &mut Scratch
+    ) -> Result<Midi, ParseError> {
+        let events = smf.tracks.iter().map(|t| t.len()).sum();
+        ResourceLimits::check(Limit::Events, events, options.limits.max_events)?;
+        parsing::check_track_ticks(&smf.tracks)?;
+        let mut midi = Midi::new(smf)?;
+        if options.retain_timing {
+            let retained = RetainedOptions::new(options);
+            midi.retained_timing = Some((EventFile::from_smf(smf)?, retained));
+        }
+        if smf.header.format == midly::Format::Sequential {
+            for track in &smf.tracks {
+                let header = midly::Header::new(midly::Format::SingleTrack, smf.header.timing);
+                let sequence = Smf { header, tracks: vec![track.clone()] };
+                midi.sequences.push(Midi::parse_smf(&sequence, options, deadline, scratch)?);
+            }
+            return Ok(midi);
+        }
+        parsing::load_tracks_with_scratch(&mut midi, smf, options, deadline, scratch)?;
+        if options.check_barlines {
+            midi.check_barlines()?;
+        }
+        if options.split_at_barlines {
+            midi.split_at_barlines();
+        }
+        return Ok(midi);
     }
 
     /// Pretty prints the contents of the `Midi` object.
@@ -54,16 +208,493 @@ impl Midi {
         }
     }
 
+    /// Returns the initial tempo of the piece in beats per minute.
+    pub fn bpm(&self) -> u32 {
+        return self.bmp;
+    }
+
+    /// Returns all the tracks in the piece.
+    /// 
+    /// This is empty for format 2 files, whose tracks are found in `Midi::sequences` instead.
+    pub fn tracks(&self) -> &[Track] {
+        return &self.tracks;
+    }
+
+    /// Returns the independent sequences of a format 2 file, such as the patterns of a drum
+    /// pattern library.
+    /// 
+    /// Each track of a format 2 file is parsed on its own as if it were a format 0 file, with
+    /// its own tempo, time signatures, and key signatures. This is empty for other formats.
+    pub fn sequences(&self) -> &[Midi] {
+        return &self.sequences;
+    }
+
+    /// Returns all the tracks in the piece so they can be edited.
+    pub fn tracks_mut(&mut self) -> &mut [Track] {
+        return &mut self.tracks;
+    }
+
+    /// Returns a track holding the notes of every note track in the piece.
+    /// 
+    /// Notes that start within `ParseOptions::simultaneity_window` of each other are grouped as a
+    /// chord. This is only available if `ParseOptions::merge_tracks` was set.
+    pub fn merged_track(&self) -> Option<&Track> {
+        return self.merged_track.as_ref();
+    }
+
+    /// Checks that every note track agrees on where the bar lines are.
+    /// 
+    /// Returns `ParseError::BarlineDivergence` describing the first point at which a track drifts
+    /// off the score's bar lines, which usually means the file needs a different precision.
+    pub fn check_barlines(&self) -> Result<(), ParseError> {
+        return parsing::validate::check_barlines(self);
+    }
+
+    /// Returns the length of the piece in quarter note beats, which is the length of its longest
+    /// track. See `Track::length`.
+    pub fn total_beats(&self) -> f32 {
+        return self.tracks.iter().map(|t| t.length).fold(0.0, f32::max);
+    }
+
+    /// Returns the number of measures in the piece, counting a final measure that is only
+    /// partly filled. A piece that ends less than `measure::barline_tolerance` past a bar line
+    /// ends on that bar line.
+    pub fn total_measures(&self) -> u32 {
+        let end = self.total_beats();
+        let starts =
+            parsing::measure::measure_starts(&self.time_signatures, self.ticks_per_beat, end);
+        let tolerance = parsing::measure::barline_tolerance(self.beat_type());
+        return starts.iter().filter(|s| **s < end - tolerance).count() as u32;
+    }
+
+    /// Returns the length of the piece in seconds, following its tempo changes. See
+    /// `Midi::tempo_map`.
+    pub fn total_duration_seconds(&self) -> f64 {
+        return self.tempo_map().seconds_at(self.total_beats() as f64);
+    }
+
+    /// Pads the final measure of every note track with rests up to the bar line, and returns the
+    /// number of tracks padded. See `transform::pad_final_measure`.
+    pub fn pad_final_measure(&mut self) -> usize {
+        return transform::pad_final_measure(self);
+    }
+
+    /// Splits every note that crosses a bar line into notes tied across it, as they're written in
+    /// a score, and returns the number of notes and rests split. See
+    /// `transform::split_at_barlines`.
+    pub fn split_at_barlines(&mut self) -> usize {
+        return transform::split_at_barlines(self);
+    }
+
+    /// Returns the measure (starting from 1) and the beat within that measure (starting from 0) in
+    /// which the given beat falls. Beats are quarter notes counted from the start of the piece.
+    pub fn measure_at(&self, beat: f32) -> (u32, f32) {
+        return parsing::measure::measure_at(&self.time_signatures, self.ticks_per_beat, beat);
+    }
+
+    /// Returns the measure, beat of the time signature, and subdivision of that beat in which the
+    /// given beat falls. Beats are quarter notes counted from the start of the piece. See
+    /// `measure::metric_position`.
+    pub fn metric_position(&self, beat: f32) -> MetricPosition {
+        return parsing::measure::metric_position(&self.time_signatures, self.ticks_per_beat, beat);
+    }
+
+    /// Checks the piece against the BeatBlox runtime constraints and lists the simplifications
+    /// it needs to be played back faithfully.
+    pub fn playability(&self, constraints: &BeatbloxConstraints) -> PlayabilityReport {
+        return playability::check(self, constraints);
+    }
+
+    /// Applies every simplification the piece needs to satisfy the BeatBlox runtime
+    /// constraints, returning a report of what was changed.
+    /// 
+    /// Chords lose their lowest notes, notes that are too short are merged with the notes after
+    /// them, and velocities are snapped to the nearest supported dynamics level.
+    pub fn make_playable(&mut self, constraints: &BeatbloxConstraints) -> PlayabilityReport {
+        return playability::simplify(self, constraints);
+    }
+
+    /// Parses the piece again from its original timing with a new precision and triplet setting,
+    /// keeping the rest of the options it was parsed with. Any changes made to the piece are
+    /// lost.
+    /// 
+    /// This requires the piece to have been parsed with `ParseOptions::retain_timing`, and
+    /// returns `ParseError::TimingNotRetained` otherwise.
+    pub fn requantize(&mut self, precision: DurationType, triplet: bool) -> Result<(), ParseError> {
+        let (events, options) = match &self.retained_timing {
+            Some(retained) => retained,
+            None => return Err(ParseError::TimingNotRetained),
+        };
+        *self = Midi::parse_events(events, &options.to_options(precision, triplet))?;
+        return Ok(());
+    }
+
+    /// Swings the eighth notes of the piece when it's played back, so the first eighth note of
+    /// each beat is `ratio` times as long as the second, e.g. 2.0 for a triplet feel. A ratio of
+    /// 1.0 plays the piece straight.
+    /// 
+    /// The notes are still written as straight eighth notes, and only the events from
+    /// `Midi::to_events` are moved onto the swung positions.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if `ratio` is not positive.
+    pub fn apply_swing(&mut self, ratio: f32) {
+        assert!(ratio > 0.0, "swing ratios must be positive");
+        self.swing = if ratio == 1.0 { None } else { Some(ratio) };
+        self.raw_fingerprints.clear();
+        for sequence in &mut self.sequences {
+            sequence.apply_swing(ratio);
+        }
+    }
+
+    /// Returns the swing ratio set by `Midi::apply_swing`, if the piece swings.
+    pub fn swing(&self) -> Option<f32> {
+        return self.swing;
+    }
+
+    /// Multiplies the tempo of the piece by `factor`, e.g. 0.5 for half speed. The notes are left
+    /// as they are.
+    pub fn scale_tempo(&mut self, factor: f32) {
+        transform::scale_tempo(self, factor);
+    }
+
+    /// Multiplies the length of every note by `factor`, e.g. 2 to turn quarter notes into half
+    /// notes, updating the time signatures to match. See `transform::stretch_durations`.
+    pub fn stretch_durations(&mut self, factor: f32) {
+        transform::stretch_durations(self, factor);
+    }
+
+    /// Layers the tracks of `other` on top of the piece, starting `offset` beats in, e.g. to add
+    /// a drum track to a melody. See `transform::overlay`.
+    pub fn overlay(&mut self, other: &Midi, offset: f32) {
+        transform::overlay(self, other, offset);
+    }
+
+    /// Returns a new piece holding only the music between beats `start` and `end`, where beats
+    /// are quarter notes counted from the start of the piece. Notes crossing the boundaries are
+    /// cut short. See `transform::slice_beats`.
+    pub fn slice_beats(&self, start: f32, end: f32) -> Midi {
+        return transform::slice_beats(self, start, end);
+    }
+
+    /// Returns a new piece holding only measures `start` through `end`, counting from 1, e.g.
+    /// `slice_measures(9, 16)` for bars 9 to 16.
+    pub fn slice_measures(&self, start: u32, end: u32) -> Midi {
+        return transform::slice_measures(self, start, end);
+    }
+
+    /// Returns a new piece with the measures in `measures` played `times` times in a row, e.g.
+    /// `repeat_measures(5..=8, 4)` to loop bars 5 to 8 four times. Measures count from 1.
+    pub fn repeat_measures(&self, measures: RangeInclusive<u32>, times: u32) -> Midi {
+        return transform::repeat_measures(self, measures, times);
+    }
+
+    /// Absorbs the rests shorter than `absorb_threshold` into the notes before them and merges
+    /// the remaining rests into as few rests as possible. See `transform::normalize_rests`.
+    pub fn normalize_rests(&mut self, absorb_threshold: Option<DurationType>) {
+        transform::normalize_rests(self, absorb_threshold);
+    }
+
+    /// Removes every track without any notes, such as conductor tracks and tracks left silent by
+    /// `Midi::slice_beats`, and returns the number of tracks removed.
+    /// 
+    /// The tempo, time signatures, key signatures, and markers of the piece are kept. To leave
+    /// these tracks out while parsing instead, see `ParseOptions::skip_empty_tracks`.
+    pub fn prune_empty_tracks(&mut self) -> usize {
+        let count = self.tracks.len();
+        self.tracks.retain(|t| t.notes.iter().flat_map(|n| n.notes()).any(|n| !n.is_rest()));
+        return count - self.tracks.len();
+    }
+
+    /// Returns the statistics of every note track in the piece together. See
+    /// `analysis::piece_stats`.
+    pub fn stats(&self) -> TrackStats {
+        return analysis::piece_stats(self);
+    }
+
+    /// Estimates the key of the piece from its notes. See `harmony::detect_key`.
+    pub fn detect_key(&self) -> Option<KeySignature> {
+        return harmony::detect_key(self);
+    }
+
+    /// Returns the chord of each measure with its Roman numeral relative to the detected key of
+    /// the piece, e.g. for a I-IV-V-I progression. See `harmony::analyze_harmony`.
+    pub fn analyze_harmony(&self) -> HarmonyAnalysis {
+        return harmony::analyze_harmony(self, HarmonicRhythm::Measure);
+    }
+
+    /// Returns the chord of each beat with its Roman numeral relative to the detected key of the
+    /// piece. See `harmony::analyze_harmony`.
+    pub fn analyze_harmony_by_beat(&self) -> HarmonyAnalysis {
+        return harmony::analyze_harmony(self, HarmonicRhythm::Beat);
+    }
+
+    /// Finds the sections of the piece that repeat and labels its structure, e.g. AABA. See
+    /// `form::detect_form`.
+    pub fn detect_form(&self) -> Form {
+        return form::detect_form(self);
+    }
+
+    /// Lays out the note tracks of the piece as parts, staves, voices, and measures, for
+    /// notation exporters. See `score::build_score`.
+    pub fn score(&self) -> Score {
+        return score::build_score(self);
+    }
+
+    /// Returns the sections of the piece named by its markers, e.g. "Intro" or "Chorus 2", with
+    /// the measures each one covers. See `form::marked_sections`.
+    pub fn sections(&self) -> Vec<NamedSection> {
+        return form::marked_sections(self);
+    }
+
+    /// Scores how alike this piece and `other` are, from 0 (nothing alike) to 1 (the same). See
+    /// `analysis::piece_similarity`.
+    pub fn similarity(&self, other: &Midi) -> f32 {
+        return analysis::piece_similarity(self, other);
+    }
+
+    /// Returns the tempo of the piece over time, for converting beats to seconds.
+    /// 
+    /// Every tempo change is included if the piece was parsed with `ParseOptions::retain_timing`
+    /// or `ParseOptions::lossless`. Otherwise, the piece stays at its initial tempo.
+    pub fn tempo_map(&self) -> TempoMap {
+        match (&self.retained_timing, &self.raw_events) {
+            (Some((events, _)), _) | (None, Some(events)) => return TempoMap::from_events(events),
+            (None, None) => return TempoMap::from_events(&self.to_events()),
+        }
+    }
+
+    /// Estimates the tempo the piece was actually played at over time, as `(seconds, bpm)`
+    /// pairs, for files recorded from live playing without a click. See
+    /// `analysis::performed_tempo_curve`.
+    /// 
+    /// This requires the piece to have been parsed with `ParseOptions::retain_timing` or
+    /// `ParseOptions::lossless`, and returns `ParseError::TimingNotRetained` otherwise.
+    pub fn performed_tempo_curve(&self) -> Result<Vec<(f32, f32)>, ParseError> {
+        let events = match (&self.retained_timing, &self.raw_events) {
+            (Some((events, _)), _) | (None, Some(events)) => events,
+            (None, None) => return Err(ParseError::TimingNotRetained),
+        };
+        return Ok(analysis::performed_tempo_curve(events));
+    }
+
+    /// Plays the piece on the first midi output device whose name contains `device`, returning
+    /// a `Playback` that can pause or stop it. See `playback::schedule` for how the notes are
+    /// timed and `playback::output_devices` for the names of the devices.
+    #[cfg(feature = "play")]
+    pub fn play(&self, device: &str) -> Result<Playback, PlayError> {
+        return playback::play(playback::schedule(self), device);
+    }
+
+    /// Marks every note of the piece with the dynamic its velocity falls in. See
+    /// `Track::mark_dynamics`.
+    pub fn mark_dynamics(&mut self, thresholds: &DynamicThresholds) {
+        for track in &mut self.tracks {
+            track.mark_dynamics(thresholds);
+        }
+    }
+
+    /// Accents the notes of every track that are played noticeably louder than the notes around
+    /// them, and returns the number of notes accented. See `dynamics::mark_accents`.
+    pub fn mark_accents(&mut self, options: &AccentOptions) -> usize {
+        let (time_signatures, ticks_per_beat) = (&self.time_signatures, self.ticks_per_beat);
+        return self.tracks
+            .iter_mut()
+            .map(|track| track.mark_accents(time_signatures, ticks_per_beat, options))
+            .sum();
+    }
+
+    /// Beams the eighth and shorter notes of every track into groups following the time
+    /// signature, and returns the number of groups. See `beaming::mark_beams`.
+    pub fn mark_beams(&mut self) -> usize {
+        let (time_signatures, ticks_per_beat) = (&self.time_signatures, self.ticks_per_beat);
+        return self.tracks
+            .iter_mut()
+            .map(|track| track.mark_beams(time_signatures, ticks_per_beat))
+            .sum();
+    }
+
+    /// Gives a fermata to the notes of every track that are held far longer than the rest of
+    /// their track, and returns the number of notes or chords marked. Percussion tracks are left
+    /// as they are. See `transform::mark_fermatas`.
+    pub fn mark_fermatas(&mut self, options: &FermataOptions) -> usize {
+        return self.tracks
+            .iter_mut()
+            .filter(|track| !track.percussion)
+            .map(|track| track.mark_fermatas(options))
+            .sum();
+    }
+
+    /// Returns the tracks whose notes extend past their `EndOfTrack` event.
+    pub fn truncated_tracks(&self) -> Vec<&Track> {
+        return self.tracks.iter().filter(|t| t.truncated).collect();
+    }
+
+    /// Returns file-level information about the midi file, such as its copyright notice.
+    pub fn metadata(&self) -> &MidiMetadata {
+        return &self.metadata;
+    }
+
+    /// Returns all the markers and cue points in the piece, sorted by the tick they occur on.
+    pub fn markers(&self) -> &[Marker] {
+        return &self.markers;
+    }
+
+    /// Returns every system exclusive message in the piece, sorted by the tick it occurs on.
+    /// 
+    /// The messages are not interpreted, so tools that understand specific manufacturers (such
+    /// as GS or XG resets) can act on them.
+    pub fn sysex_events(&self) -> &[SysexEvent] {
+        return &self.sysex_events;
+    }
+
+    /// Returns all the key signatures in the piece, sorted by their time of occurance.
+    pub fn key_signatures(&self) -> &[KeySignature] {
+        return &self.key_signatures;
+    }
+
+    /// Returns the key signature in effect at `tick`, if the piece has one.
+    pub fn key_signature_at(&self, tick: u32) -> Option<KeySignature> {
+        return self.key_signatures.iter().rev().find(|k| k.time_of_occurance <= tick).copied();
+    }
+
+    /// Converts the piece to JSON.
+    /// 
+    /// The output follows a versioned schema that is documented in `export::json`, so its field
+    /// names stay stable even if the crate's types change.
+    pub fn to_json(&self) -> String {
+        return export::json::midi_to_json(self);
+    }
+
+    /// Converts the piece into the nested lists NetsBlox blocks natively consume.
+    /// 
+    /// The result is a list of tracks, each of which is a list of measures, each of which is a
+    /// list of `[pitch, duration, velocity]` entries. Pitches are midi values, a list of midi
+    /// values for chords, or "Rest" for rests. Durations are in beats, with tied notes combined
+    /// into a single entry and the notes of a triplet given their actual (two thirds) length.
+    /// A note belongs to the measure it starts in.
+    pub fn to_netsblox_lists(&self) -> ListValue {
+        return export::netsblox::midi_to_lists(self);
+    }
+
+    /// Converts the piece into BeatBlox block definitions, as JSON ready to drop in a BeatBlox
+    /// project.
+    /// 
+    /// Each track becomes a script and each `NoteWrapper` becomes one block. The format is
+    /// documented in `export::beatblox`.
+    pub fn to_beatblox_blocks(&self) -> String {
+        return export::beatblox::midi_to_blocks(self);
+    }
+
+    /// Converts the piece to CSV with one row per note, so it can be loaded straight into a
+    /// data frame.
+    /// 
+    /// The columns are given by `export::csv::CSV_HEADER`. `measure` starts from 1, `beat` is the
+    /// beat within the measure, `onset` is the beat counted from the start of the piece, and
+    /// `duration` is in beats. Notes inside of the same chord, tie, or triplet share a `group`.
+    pub fn to_csv(&self) -> String {
+        return export::csv::midi_to_csv(self);
+    }
+
+    /// Converts the piece to a MusicXML document.
+    /// 
+    /// The piece is laid out as in `Midi::score`, with one part for each note track. See
+    /// `export::musicxml` for how the parts are written.
+    pub fn to_musicxml(&self) -> String {
+        return export::musicxml::midi_to_musicxml(self);
+    }
+
+    /// Encodes every note of the piece as a bundle of `/note pitch velocity duration beat` OSC
+    /// messages. See `export::osc` for the layout of the messages, and `export::osc::stream_osc`
+    /// for sending them as the piece plays.
+    pub fn to_osc_bundle(&self) -> Vec<u8> {
+        return export::osc::midi_to_osc_bundle(self);
+    }
+
+    /// Returns all the time signatures in the piece, sorted by their time of occurance.
+    pub fn time_signatures(&self) -> &[TimeSignature] {
+        return &self.time_signatures;
+    }
+
+    /// Returns the beat type of the first time signature, which the lengths of the tracks are
+    /// counted in, e.g. 3 for eighth note beats in 6/8 time.
+    pub fn beat_type(&self) -> u8 {
+        return self.time_signatures.first().copied().unwrap_or_default().beat_type;
+    }
+
+    /// Returns the number of ticks in each beat.
+    pub fn ticks_per_beat(&self) -> f32 {
+        return self.ticks_per_beat;
+    }
+
+    /// Serializes the parsed piece as MessagePack, so it can be cached or sent to a client
+    /// without parsing the midi file again.
+    /// 
+    /// The encoding follows the layout of the crate's types, so it should only be read back by
+    /// the same version of the crate.
+    #[cfg(feature = "msgpack")]
+    pub fn to_msgpack(&self) -> Vec<u8> {
+        // Every type in a `Midi` has a MessagePack representation, so encoding cannot fail.
+        return rmp_serde::to_vec(self).expect("failed to encode midi object");
+    }
+
+    /// Reads a piece serialized with `Midi::to_msgpack`.
+    #[cfg(feature = "msgpack")]
+    pub fn from_msgpack(bytes: &[u8]) -> Result<Midi, ParseError> {
+        return Ok(rmp_serde::from_slice(bytes)?);
+    }
+
+    /// Converts the piece back into midi events.
+    /// 
+    /// Chords, ties, and triplets are written as the notes they represent, with tied notes
+    /// joined into a single note. Only the initial tempo of the piece is kept.
+    /// 
+    /// Pieces parsed with `ParseOptions::lossless` are written back out from their original
+    /// events instead, so tracks that haven't been edited come out exactly as they were read. The
+    /// notes and lyrics of edited tracks are rewritten, keeping the rest of their events. Format 0
+    /// files are written back out split by channel, as they were when parsed.
+    pub fn to_events(&self) -> EventFile {
+        return export::smf::midi_to_events(self);
+    }
+
+    /// Converts the piece into the bytes of a standard midi file.
+    pub fn to_smf_bytes(&self) -> Vec<u8> {
+        return self.to_events().to_smf_bytes();
+    }
+
+    /// Saves the piece as a playable standard midi file at `dir`. See `Midi::to_events` for how
+    /// the piece is converted.
+    pub fn write_smf(&self, dir: String) -> Result<(), std::io::Error> {
+        return fs::write(dir, self.to_smf_bytes());
+    }
+
     /// Private constructor for a midi object.
     /// 
     /// Initially, the `tracks` field is empty and tracks must manually be loaded in with
     /// the funtion `parssing::load_tracks(...)`
-    fn new(smf: &midly::Smf) -> Midi {
-        Midi {
-            bmp: parsing::get_bpm(&smf.tracks[0]),
-            time_signatures: parsing::get_time_signature(&smf.tracks[0]),
-            ticks_per_beat: parsing::get_ticks_per_beat(&smf.header),
+    fn new(smf: &midly::Smf) -> Result<Midi, ParseError> {
+        let conductor: &[midly::TrackEvent] = match smf.tracks.first() {
+            Some(track) => track,
+            None => &[],
+        };
+        let ticks_per_beat = parsing::get_ticks_per_beat(&smf.header)?;
+        Ok(Midi {
+            bmp: parsing::get_bpm(conductor),
+            microseconds_per_beat: parsing::merge::get_microseconds_per_beat(conductor),
+            time_signatures: parsing::get_time_signature(conductor),
+            key_signatures: parsing::get_key_signatures(&smf.tracks),
+            markers: parsing::get_markers(&smf.tracks, ticks_per_beat),
+            sysex_events: parsing::get_sysex_events(&smf.tracks),
+            metadata: parsing::metadata::get_metadata(smf),
+            ticks_per_beat,
             tracks: Vec::new(),
-        }
+            merged_track: None,
+            raw_events: None,
+            raw_fingerprints: Vec::new(),
+            sequences: Vec::new(),
+            swing: None,
+            retained_timing: None,
+        })
     }
 }
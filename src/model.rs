@@ -0,0 +1,19 @@
+//! The types a parsed piece is made of, gathered in one place.
+//! 
+//! These are the same types the parser produces and the rest of the crate works with, so code
+//! that only reads or builds pieces can import everything it needs from here.
+
+pub use crate::parsing::duration::DurationType;
+pub use crate::parsing::duration::NoteDuration;
+pub use crate::parsing::duration::NoteDurationModifier;
+pub use crate::parsing::duration::Rational;
+pub use crate::parsing::pitch::Pitch;
+pub use crate::parsing::pitch::SpelledPitch;
+pub use crate::parsing::symbols::KeySignature;
+pub use crate::parsing::symbols::Marker;
+pub use crate::parsing::symbols::Note;
+pub use crate::parsing::symbols::NoteModifier;
+pub use crate::parsing::symbols::NoteWrapper;
+pub use crate::parsing::symbols::TimeSignature;
+pub use crate::parsing::Track;
+pub use crate::parsing::TrackKind;
@@ -0,0 +1,61 @@
+use crate::Midi;
+use crate::error::ParseError;
+use crate::parsing::Scratch;
+use crate::parsing::options::ParseOptions;
+use std::fs::File;
+use std::io::Read;
+
+/// A reusable midi parser for long-running services.
+/// 
+/// Every call to `Midi::parse` allocates fresh buffers for the file contents, the quantization
+/// grid, and the raw note data. A `Parser` keeps these buffers between parses, so a service that
+/// parses many files only grows them to the size of the largest file it has seen.
+/// 
+/// ```no_run
+/// use beatblox_midi::parser::Parser;
+/// use beatblox_midi::parsing::options::ParseOptions;
+/// 
+/// let mut parser = Parser::new(ParseOptions::new());
+/// for dir in ["a.mid", "b.mid"] {
+///     let midi = parser.parse(String::from(dir)).unwrap();
+///     midi.print();
+/// }
+/// ```
+pub struct Parser {
+    /// The options used for every parse.
+    options: ParseOptions,
+    /// The contents of the last file that was read.
+    contents: Vec<u8>,
+    /// The buffers used while parsing tracks.
+    scratch: Scratch,
+}
+
+impl Parser {
+    /// Creates a parser that parses every file with `options`.
+    pub fn new(options: ParseOptions) -> Parser {
+        Parser {
+            options,
+            contents: Vec::new(),
+            scratch: Scratch::default(),
+        }
+    }
+
+    /// Returns the options used for every parse.
+    pub fn options(&self) -> &ParseOptions {
+        return &self.options;
+    }
+
+    /// Parses through a midi file found at `dir` and returns a `Midi` object.
+    pub fn parse(&mut self, dir: String) -> Result<Midi, ParseError> {
+        self.contents.clear();
+        // Reading one byte past the size limit is enough to tell that the file is too large.
+        let limit = self.options.limits.max_file_size.map_or(u64::MAX, |max| max as u64 + 1);
+        File::open(dir)?.take(limit).read_to_end(&mut self.contents)?;
+        return Midi::parse_bytes_with_scratch(&self.contents, &self.options, &mut self.scratch);
+    }
+
+    /// Parses through the contents of a midi file and returns a `Midi` object.
+    pub fn parse_bytes(&mut self, bytes: &[u8]) -> Result<Midi, ParseError> {
+        return Midi::parse_bytes_with_scratch(bytes, &self.options, &mut self.scratch);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::parsing::Track;
+use crate::parsing::duration::NoteDuration;
+use crate::parsing::measure;
+use crate::parsing::symbols::Note;
+use crate::parsing::symbols::TimeSignature;
+
+/// The place of a note within a group of beamed notes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
+pub enum Beam {
+    /// The note starts a beam.
+    Begin,
+    /// The note is inside of a beam.
+    Continue,
+    /// The note ends a beam.
+    End,
+}
+
+impl Beam {
+    /// Converts the enum to the name MusicXML gives it, e.g. "begin".
+    pub fn to_string(&self) -> &str {
+        match self {
+            Beam::Begin => return "begin",
+            Beam::Continue => return "continue",
+            Beam::End => return "end",
+        }
+    }
+}
+
+/// Sets `Note::beam` on the eighth and shorter notes of a track, and returns the number of beam
+/// groups.
+/// 
+/// Notes are beamed together within each beat of a simple meter, e.g. pairs of eighth notes in
+/// 4/4, and within each dotted beat of a compound meter, e.g. groups of three eighth notes in
+/// 6/8. Simple meters with beats shorter than a quarter note, like 2/8, are beamed by the
+/// quarter note instead. A beam never crosses a bar line, and rests and notes of a quarter note
+/// or longer break it. The notes of a chord share a stem, so they're given the same beam.
+/// `time_signatures` and `ticks_per_beat` can be taken from `Midi::time_signatures` and
+/// `Midi::ticks_per_beat`.
+pub fn mark_beams(
+    track: &mut Track,
+    time_signatures: &[TimeSignature],
+    ticks_per_beat: f32
+) -> usize {
+    let beat_type = time_signatures.first().map_or(2, |t| t.beat_type);
+    // Each stem is the onset shared by a note or chord, whether it can be beamed, and the
+    // measure and beam group it falls in.
+    let mut stems: Vec<(f32, bool, (u32, u32))> = Vec::new();
+    let mut stem_of_note: Vec<usize> = Vec::new();
+    let mut beat = 0.0;
+    for wrapper in &track.notes {
+        wrapper.for_each_note(beat, beat_type, &mut |note, onset, _| {
+            let beamable = is_beamable(note);
+            match stems.last_mut() {
+                Some((last, shared, _)) if (*last - onset).abs() < 1e-4 => *shared &= beamable,
+                _ => {
+                    let group = get_beam_group(time_signatures, ticks_per_beat, onset);
+                    stems.push((onset, beamable, group));
+                },
+            }
+            stem_of_note.push(stems.len() - 1);
+        });
+        beat += wrapper.beat_count(beat_type);
+    }
+
+    let mut beams: Vec<Option<Beam>> = vec![None; stems.len()];
+    let mut groups = 0;
+    let mut start = 0;
+    while start < stems.len() {
+        let mut end = start + 1;
+        if stems[start].1 {
+            while end < stems.len() && stems[end].1 && stems[end].2 == stems[start].2 {
+                end += 1;
+            }
+        }
+        if end - start > 1 {
+            beams[start] = Some(Beam::Begin);
+            for beam in &mut beams[start + 1..end - 1] {
+                *beam = Some(Beam::Continue);
+            }
+            beams[end - 1] = Some(Beam::End);
+            groups += 1;
+        }
+        start = end;
+    }
+
+    let mut i = 0;
+    let mut beat = 0.0;
+    for wrapper in &mut track.notes {
+        let length = wrapper.beat_count(beat_type);
+        wrapper.for_each_note_mut(beat, beat_type, &mut |note, _, _| {
+            note.beam = beams[stem_of_note[i]];
+            i += 1;
+        });
+        beat += length;
+    }
+    return groups;
+}
+
+/// Determines if a note is written with a flag, and so can be beamed.
+fn is_beamable(note: &Note) -> bool {
+    return !note.is_rest() && matches!(
+        note.duration.duration,
+        NoteDuration::EIGHTH
+            | NoteDuration::SIXTEENTH
+            | NoteDuration::THIRTYSECOND
+            | NoteDuration::SIXTYFOURTH
+            | NoteDuration::HUNDREDTWENTYEIGHTH
+    );
+}
+
+/// Returns the measure a beat falls in and the index of the beam group within that measure.
+fn get_beam_group(
+    time_signatures: &[TimeSignature],
+    ticks_per_beat: f32,
+    beat: f32
+) -> (u32, u32) {
+    let (measure, beat_in_measure) = measure::measure_at(time_signatures, ticks_per_beat, beat);
+    let tick = (beat * ticks_per_beat).round() as u32;
+    let time_signature = time_signatures.iter().rev().find(|t| t.time_of_occurance <= tick);
+    let span = match time_signature {
+        Some(t) => {
+            if t.beat_type >= 3 && t.beat_count % 3 == 0 {
+                3.0
+            } else {
+                // The number of beats in a quarter note.
+                2f32.powi(t.beat_type as i32 - 2).max(1.0)
+            }
+        },
+        None => 1.0,
+    };
+    return (measure, ((beat_in_measure + 1e-4) / span).floor() as u32);
+}
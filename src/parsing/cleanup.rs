@@ -0,0 +1,183 @@
+use crate::parsing::add_delta;
+
+/// What happens to notes shorter than `ParseOptions::min_note_length`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ShortNotePolicy {
+    /// Short notes are left out.
+    Discard,
+    /// Short notes are joined onto a note of the same key that ends or starts within the
+    /// minimum length of them, as happens when a key bounces. Short notes without such a
+    /// neighbor are left out.
+    Merge,
+}
+
+/// The midi channels whose notes and other channel messages are parsed.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ChannelFilter {
+    /// Every channel is parsed.
+    All,
+    /// Only the listed channels are parsed.
+    Only(Vec<u8>),
+    /// Every channel but the listed ones is parsed.
+    Except(Vec<u8>),
+}
+
+impl ChannelFilter {
+    /// Whether the filter lets the messages on `channel` through.
+    pub fn includes(&self, channel: u8) -> bool {
+        match self {
+            ChannelFilter::All => return true,
+            ChannelFilter::Only(channels) => return channels.contains(&channel),
+            ChannelFilter::Except(channels) => return !channels.contains(&channel),
+        }
+    }
+}
+
+/// A note in a midi track, given by the positions of its events and the ticks they occur on.
+#[derive(Clone, Copy)]
+struct NoteSpan {
+    on: usize,
+    off: usize,
+    start: u32,
+    end: u32,
+}
+
+/// Removes or merges the notes shorter than `min_ticks` in every track of a midi file.
+pub(crate) fn filter_short_notes<'a>(
+    smf: &midly::Smf<'a>,
+    min_ticks: u32,
+    policy: ShortNotePolicy
+) -> midly::Smf<'a> {
+    let tracks = smf.tracks
+        .iter()
+        .map(|track| filter_track(track, min_ticks, policy))
+        .collect();
+    return midly::Smf { header: smf.header, tracks };
+}
+
+/// Removes or merges the notes shorter than `min_ticks` in a midi track.
+fn filter_track<'a>(
+    track: &[midly::TrackEvent<'a>],
+    min_ticks: u32,
+    policy: ShortNotePolicy
+) -> Vec<midly::TrackEvent<'a>> {
+    let mut ticks = Vec::with_capacity(track.len());
+    let mut tick = 0;
+    for event in track {
+        tick = add_delta(tick, event.delta);
+        ticks.push(tick);
+    }
+
+    // Pairs every note on with the first unmatched note off of the same key and channel.
+    let mut open: Vec<((u8, u8), usize)> = Vec::new();
+    let mut notes: Vec<((u8, u8), NoteSpan)> = Vec::new();
+    for (i, event) in track.iter().enumerate() {
+        let (channel, key, on) = match event.kind {
+            midly::TrackEventKind::Midi { channel, message } => match message {
+                midly::MidiMessage::NoteOn { key, vel } => (channel, key, vel > 0),
+                midly::MidiMessage::NoteOff { key, .. } => (channel, key, false),
+                _ => continue,
+            },
+            _ => continue,
+        };
+        let id = (channel.as_int(), key.as_int());
+        if on {
+            open.push((id, i));
+        } else if let Some(position) = open.iter().position(|(o, _)| *o == id) {
+            let (_, start) = open.remove(position);
+            notes.push((id, NoteSpan { on: start, off: i, start: ticks[start], end: ticks[i] }));
+        }
+    }
+    notes.sort_by_key(|(id, note)| (*id, note.start));
+
+    let mut removed = vec![false; track.len()];
+    match policy {
+        ShortNotePolicy::Discard => {
+            for (_, note) in &notes {
+                if note.end - note.start < min_ticks {
+                    removed[note.on] = true;
+                    removed[note.off] = true;
+                }
+            }
+        },
+        ShortNotePolicy::Merge => {
+            let mut kept: Vec<((u8, u8), NoteSpan)> = Vec::new();
+            let mut pending: Option<((u8, u8), NoteSpan)> = None;
+            for (id, mut note) in notes {
+                if let Some((pending_id, short)) = pending.take() {
+                    if pending_id == id && note.start <= short.end + min_ticks {
+                        removed[short.off] = true;
+                        removed[note.on] = true;
+                        note.on = short.on;
+                        note.start = short.start;
+                    } else {
+                        removed[short.on] = true;
+                        removed[short.off] = true;
+                    }
+                }
+                if note.end - note.start >= min_ticks {
+                    kept.push((id, note));
+                    continue;
+                }
+                match kept.last_mut() {
+                    Some((kept_id, previous))
+                        if *kept_id == id && note.start <= previous.end + min_ticks => {
+                        removed[previous.off] = true;
+                        removed[note.on] = true;
+                        previous.off = note.off;
+                        previous.end = note.end;
+                    },
+                    _ => pending = Some((id, note)),
+                }
+            }
+            if let Some((_, short)) = pending {
+                removed[short.on] = true;
+                removed[short.off] = true;
+            }
+        },
+    }
+
+    return remove_events(track, &removed);
+}
+
+/// Removes the channel messages on channels that `filter` leaves out from every track of a midi
+/// file.
+pub(crate) fn filter_channels<'a>(smf: &midly::Smf<'a>, filter: &ChannelFilter) -> midly::Smf<'a> {
+    let tracks = smf.tracks
+        .iter()
+        .map(|track| {
+            let removed: Vec<bool> = track
+                .iter()
+                .map(|event| match event.kind {
+                    midly::TrackEventKind::Midi { channel, .. } => {
+                        !filter.includes(channel.as_int())
+                    },
+                    _ => false,
+                })
+                .collect();
+            return remove_events(track, &removed);
+        })
+        .collect();
+    return midly::Smf { header: smf.header, tracks };
+}
+
+/// Returns a copy of a midi track without the events marked in `removed`, keeping the rest of
+/// the events on the same ticks.
+fn remove_events<'a>(
+    track: &[midly::TrackEvent<'a>],
+    removed: &[bool]
+) -> Vec<midly::TrackEvent<'a>> {
+    let mut filtered = Vec::with_capacity(track.len());
+    let mut tick = 0;
+    let mut previous = 0;
+    for (event, removed) in track.iter().zip(removed) {
+        tick = add_delta(tick, event.delta);
+        if *removed {
+            continue;
+        }
+        let delta = midly::num::u28::new(tick - previous);
+        previous = tick;
+        filtered.push(midly::TrackEvent { delta, kind: event.kind });
+    }
+    return filtered;
+}
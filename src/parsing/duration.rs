@@ -1,5 +1,10 @@
+use std::ops::Add;
+use std::ops::Mul;
+use std::ops::Sub;
+
 /// An array containing the beat lengths for all possible note durations.
-pub const POSSIBLE_NOTE_LENGTHS: [f32; 18] = [
+pub const POSSIBLE_NOTE_LENGTHS: [f32; 24] = [
+    0.03125, 0.046875, 0.0546875, 0.0625, 0.09375, 0.109375,
     0.125, 0.1875, 0.21875, 0.25, 0.375, 0.4375, 
     0.5, 0.75, 0.875, 1.0, 1.5, 1.75, 2.0, 3.0, 
     3.5, 4.0, 6.0, 7.0
@@ -13,6 +18,7 @@ pub const DEFAULT_DURATION_PRECISION: DurationType = DurationType {
 
 /// Represents a note duration.
 #[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
 pub enum NoteDuration { 
     WHOLE, 
     HALF, 
@@ -20,6 +26,8 @@ pub enum NoteDuration {
     EIGHTH, 
     SIXTEENTH, 
     THIRTYSECOND, 
+    SIXTYFOURTH,
+    HUNDREDTWENTYEIGHTH,
     NaN,
 }
 
@@ -33,69 +41,52 @@ impl NoteDuration {
             NoteDuration::EIGHTH => return "eighth note", 
             NoteDuration::SIXTEENTH => return "sixteenth note", 
             NoteDuration::THIRTYSECOND => return "thirtysecond note", 
+            NoteDuration::SIXTYFOURTH => return "sixtyfourth note",
+            NoteDuration::HUNDREDTWENTYEIGHTH => return "hundredtwentyeighth note",
             NoteDuration::NaN => return "unknown note",
         }
     }
 
-    fn shift(&self, shift: u8) -> Self {
-        let mut temp = self.clone();
-        if shift > 2 {
-            for _ in 2..shift {
-                temp = temp.shift_down();
-            }
-        } else if shift < 2 {
-            for _ in shift..2 {
-                temp = temp.shift_up();
-            }
-        }
-        return temp;
-    }
-
-    fn reverse_shift(&self, shift: u8) -> Self {
-        let mut temp = self.clone();
-        if shift > 2 {
-            for _ in 2..shift {
-                temp = temp.shift_up();
-            }
-        } else if shift < 2 {
-            for _ in shift..2 {
-                temp = temp.shift_down();
-            }
-        }
-        return temp;
-    }
-
-    fn shift_up(&self) -> Self {
+    /// The power of two a whole note is divided by to get the duration, e.g. 2 for a quarter
+    /// note.
+    fn exponent(&self) -> Option<u32> {
         match self {
-            NoteDuration::WHOLE => return NoteDuration::NaN,
-            NoteDuration::HALF => return NoteDuration::WHOLE, 
-            NoteDuration::QUARTER => return NoteDuration::HALF, 
-            NoteDuration::EIGHTH => return NoteDuration::QUARTER, 
-            NoteDuration::SIXTEENTH => return NoteDuration::EIGHTH, 
-            NoteDuration::THIRTYSECOND => return NoteDuration::SIXTEENTH, 
-            NoteDuration::NaN => return NoteDuration::NaN,
+            NoteDuration::WHOLE => return Some(0),
+            NoteDuration::HALF => return Some(1),
+            NoteDuration::QUARTER => return Some(2),
+            NoteDuration::EIGHTH => return Some(3),
+            NoteDuration::SIXTEENTH => return Some(4),
+            NoteDuration::THIRTYSECOND => return Some(5),
+            NoteDuration::SIXTYFOURTH => return Some(6),
+            NoteDuration::HUNDREDTWENTYEIGHTH => return Some(7),
+            NoteDuration::NaN => return None,
         }
     }
 
-    fn shift_down(&self) -> Self{
-        match self {
-            NoteDuration::WHOLE => return NoteDuration::HALF,
-            NoteDuration::HALF => return NoteDuration::QUARTER, 
-            NoteDuration::QUARTER => return NoteDuration::EIGHTH, 
-            NoteDuration::EIGHTH => return NoteDuration::SIXTEENTH, 
-            NoteDuration::SIXTEENTH => return NoteDuration::THIRTYSECOND, 
-            NoteDuration::THIRTYSECOND => return NoteDuration::NaN, 
-            NoteDuration::NaN => return NoteDuration::NaN,
+    /// The duration that is a whole note divided by two to the power of `exponent`.
+    fn from_exponent(exponent: u32) -> Self {
+        match exponent {
+            0 => return NoteDuration::WHOLE,
+            1 => return NoteDuration::HALF,
+            2 => return NoteDuration::QUARTER,
+            3 => return NoteDuration::EIGHTH,
+            4 => return NoteDuration::SIXTEENTH,
+            5 => return NoteDuration::THIRTYSECOND,
+            6 => return NoteDuration::SIXTYFOURTH,
+            7 => return NoteDuration::HUNDREDTWENTYEIGHTH,
+            _ => return NoteDuration::NaN,
         }
     }
 }
 
 /// Modifiers that may be added onto a note duration.
 #[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
 pub enum NoteDurationModifier {
     None,
     Dotted,
     DoubleDotted,
+    TripleDotted,
 }
 
 impl NoteDurationModifier {
@@ -105,18 +96,38 @@ impl NoteDurationModifier {
             NoteDurationModifier::None => return "",
             NoteDurationModifier::Dotted => return "dotted",
             NoteDurationModifier::DoubleDotted => return "double dotted",
+            NoteDurationModifier::TripleDotted => return "triple dotted",
         }
     }
 }
 
 /// A struct to help with readability.
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
 pub struct  DurationType {
     pub duration: NoteDuration,
     pub modifier: NoteDurationModifier,
 }
 
 impl DurationType {
+    /// Returns the dotted version of the duration, e.g. `quarter().dotted()`.
+    pub fn dotted(mut self) -> Self {
+        self.modifier = NoteDurationModifier::Dotted;
+        return self;
+    }
+
+    /// Returns the double dotted version of the duration.
+    pub fn double_dotted(mut self) -> Self {
+        self.modifier = NoteDurationModifier::DoubleDotted;
+        return self;
+    }
+
+    /// Returns the triple dotted version of the duration.
+    pub fn triple_dotted(mut self) -> Self {
+        self.modifier = NoteDurationModifier::TripleDotted;
+        return self;
+    }
+
     pub fn quantize(&self, beat_type: u8, precision_beats: f32) -> Self {
         let beats = self.get_beat_count(beat_type);
         if beats < precision_beats {
@@ -126,105 +137,367 @@ impl DurationType {
         return Self::beat_type_map(qualtized_beats, beat_type);
     }
 
-    /// Maps a number of beats to a `DurationType`.
+    /// Maps a number of beats to a `DurationType`, where a beat is a whole note divided by two
+    /// to the power of `beat_type`.
+    /// 
+    /// The beats are read as the nearest `Rational` length, so lengths that are a rounding error
+    /// away from a written duration, as happens with odd numbers of ticks per beat, still map to
+    /// it. Lengths that can't be written as a single note map to `NoteDuration::NaN`.
     pub fn beat_type_map(beats: f32, beat_type: u8) -> DurationType {
-        match beats {
-            7.0 => DurationType {
-                duration: NoteDuration::WHOLE.shift(beat_type),
-                modifier: NoteDurationModifier::DoubleDotted,
-            },
-            6.0 => DurationType {
-                duration: NoteDuration::WHOLE.shift(beat_type),
-                modifier: NoteDurationModifier::Dotted,
-            },
-            4.0 => DurationType {
-                duration: NoteDuration::WHOLE.shift(beat_type),
-                modifier: NoteDurationModifier::None,
-            },
-            3.5 => DurationType {
-                duration: NoteDuration::HALF.shift(beat_type),
-                modifier: NoteDurationModifier::DoubleDotted,
-            },
-            3.0 => DurationType {
-                duration: NoteDuration::HALF.shift(beat_type),
-                modifier: NoteDurationModifier::Dotted,
-            },
-            2.0 => DurationType {
-                duration: NoteDuration::HALF.shift(beat_type),
-                modifier: NoteDurationModifier::None,
-            },
-            1.75 => DurationType {
-                duration: NoteDuration::QUARTER.shift(beat_type),
-                modifier: NoteDurationModifier::DoubleDotted,
-            },
-            1.5 => DurationType {
-                duration: NoteDuration::QUARTER.shift(beat_type),
-                modifier: NoteDurationModifier::Dotted,
-            },
-            1.0 => DurationType {
-                duration: NoteDuration::QUARTER.shift(beat_type),
-                modifier: NoteDurationModifier::None,
-            },
-            0.875 => DurationType {
-                duration: NoteDuration::EIGHTH.shift(beat_type),
-                modifier: NoteDurationModifier::DoubleDotted,
-            },
-            0.75 => DurationType {
-                duration: NoteDuration::EIGHTH.shift(beat_type),
-                modifier: NoteDurationModifier::Dotted,
-            },
-            0.5 => DurationType {
-                duration: NoteDuration::EIGHTH.shift(beat_type),
-                modifier: NoteDurationModifier::None,
-            },
-            0.4375 => DurationType {
-                duration: NoteDuration::SIXTEENTH.shift(beat_type),
-                modifier: NoteDurationModifier::DoubleDotted,
-            },
-            0.375 => DurationType {
-                duration: NoteDuration::SIXTEENTH.shift(beat_type),
-                modifier: NoteDurationModifier::Dotted,
-            },
-            0.25 => DurationType {
-                duration: NoteDuration::SIXTEENTH.shift(beat_type),
-                modifier: NoteDurationModifier::None,
-            },
-            0.21875 => DurationType {
-                duration: NoteDuration::THIRTYSECOND.shift(beat_type),
-                modifier: NoteDurationModifier::DoubleDotted,
-            },
-            0.1875 => DurationType {
-                duration: NoteDuration::THIRTYSECOND.shift(beat_type),
-                modifier: NoteDurationModifier::Dotted,
-            },
-            0.125 => DurationType {
-                duration: NoteDuration::THIRTYSECOND.shift(beat_type),
-                modifier: NoteDurationModifier::None,
-            },
-            _ => DurationType {
-                duration: NoteDuration::NaN,
-                modifier: NoteDurationModifier::None,
-            },
+        return Rational::from_beats(beats, beat_type).to_duration_type();
+    }
+
+    /// Maps a number of beats to a `DurationType` like `beat_type_map`, but snaps lengths to the
+    /// nearest written duration when it's at most `epsilon` beats away.
+    /// 
+    /// Only lengths that are further than `epsilon` from every written duration, such as the
+    /// composite length of a whole note tied to a quarter note, map to `NoteDuration::NaN`.
+    pub fn beat_type_map_with_tolerance(beats: f32, beat_type: u8, epsilon: f32) -> DurationType {
+        let exact = Self::beat_type_map(beats, beat_type);
+        if exact.duration != NoteDuration::NaN {
+            return exact;
         }
+        let nearest = get_written_lengths()
+            .into_iter()
+            .map(|(_, duration)| {
+                let distance = (duration.get_beat_count(beat_type) - beats).abs();
+                return (duration, distance);
+            })
+            .filter(|(_, distance)| *distance <= epsilon)
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        return nearest.map_or(exact, |(duration, _)| duration);
+    }
+
+    /// Returns the exact length of the duration as a fraction of a whole note, or `None` for
+    /// `NoteDuration::NaN`.
+    pub fn to_rational(&self) -> Option<Rational> {
+        let exponent = self.duration.exponent()?;
+        let (numerator, extra) = match self.modifier {
+            NoteDurationModifier::None => (1, 0),
+            NoteDurationModifier::Dotted => (3, 1),
+            NoteDurationModifier::DoubleDotted => (7, 2),
+            NoteDurationModifier::TripleDotted => (15, 3),
+        };
+        return Some(Rational::new(numerator, 1 << (exponent + extra)));
     }
 
     /// A helper function that returns the number of beats in this Duration type.
     pub fn get_beat_count(&self, beat_type: u8) -> f32 {
-        let duration = self.duration.reverse_shift(beat_type);
-        let mod_factor: f32;
-        match self.modifier {
-            NoteDurationModifier::DoubleDotted => mod_factor = 1.75,
-            NoteDurationModifier::Dotted => mod_factor = 1.5,
-            NoteDurationModifier::None => mod_factor = 1.0,
+        return self.to_rational().map_or(0.0, |r| r.to_beats(beat_type));
+    }
+
+    /// Returns the number of seconds the duration lasts at a steady `bpm` beats per minute, where
+    /// a beat is a whole note divided by two to the power of `beat_type`. See
+    /// `Note::duration_seconds` for notes played under tempo changes.
+    pub fn to_seconds(&self, bpm: u32, beat_type: u8) -> f64 {
+        return self.get_beat_count(beat_type) as f64 * 60.0 / bpm.max(1) as f64;
+    }
+}
+
+/// The finest length a `Rational` is rounded to when it's read from a number of beats, as the
+/// number of its parts in a whole note. This is a triple dotted hundred twenty-eighth note's
+/// smallest part, so every written duration can be represented.
+pub const RATIONAL_RESOLUTION: u32 = 1024;
+
+/// How far a number of beats may be from a written duration, as a share of the duration, and
+/// still be read as it by `Rational::from_beats`. This covers the rounding errors of odd numbers
+/// of ticks per beat, which can be more than a part of `RATIONAL_RESOLUTION`.
+pub const RATIONAL_SNAP_TOLERANCE: f64 = 1.0 / 256.0;
+
+/// An exact note length, as a fraction of a whole note, e.g. 3/8 for a dotted quarter note.
+/// 
+/// Fractions are always kept in lowest terms, so lengths can be compared with `==`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rational {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+impl Rational {
+    /// Creates the fraction `numerator / denominator` of a whole note, in lowest terms.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if `denominator` is 0.
+    pub fn new(numerator: u32, denominator: u32) -> Self {
+        assert!(denominator != 0, "rational lengths can't have a denominator of 0");
+        let (mut a, mut b) = (numerator, denominator);
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        return Rational { numerator: numerator / a, denominator: denominator / a };
+    }
+
+    /// Reads a number of beats, where a beat is a whole note divided by two to the power of
+    /// `beat_type`, as the nearest multiple of `1 / RATIONAL_RESOLUTION` of a whole note.
+    /// 
+    /// A length that can't be written as a single note but is within `RATIONAL_SNAP_TOLERANCE`
+    /// of a written duration is read as that duration instead.
+    pub fn from_beats(beats: f32, beat_type: u8) -> Self {
+        let wholes = beats.max(0.0) as f64 / 2f64.powi(beat_type as i32);
+        let parts = (wholes * RATIONAL_RESOLUTION as f64).round() as u32;
+        let rounded = Rational::new(parts, RATIONAL_RESOLUTION);
+        if rounded.to_duration_type().duration != NoteDuration::NaN {
+            return rounded;
+        }
+        let written = get_written_lengths()
+            .into_iter()
+            .map(|(length, _)| length)
+            .find(|length| {
+                let exact = length.numerator as f64 / length.denominator as f64;
+                return (exact - wholes).abs() <= exact * RATIONAL_SNAP_TOLERANCE;
+            });
+        return written.unwrap_or(rounded);
+    }
+
+    /// Returns the number of beats the length lasts, where a beat is a whole note divided by two
+    /// to the power of `beat_type`.
+    pub fn to_beats(&self, beat_type: u8) -> f32 {
+        let beats = self.numerator as f64 * 2f64.powi(beat_type as i32) / self.denominator as f64;
+        return beats as f32;
+    }
+
+    /// Returns the written duration of the length, which is `NoteDuration::NaN` for lengths
+    /// that can't be written as a single note, possibly dotted.
+    pub fn to_duration_type(&self) -> DurationType {
+        // A whole note divided by 2^k is 1/2^k, and dotting it makes it 3/2^(k+1), 7/2^(k+2), or
+        // 15/2^(k+3).
+        let exponent = self.denominator.trailing_zeros();
+        let modifier = match self.numerator {
+            _ if !self.denominator.is_power_of_two() => None,
+            1 => Some((NoteDurationModifier::None, exponent)),
+            3 if exponent >= 1 => Some((NoteDurationModifier::Dotted, exponent - 1)),
+            7 if exponent >= 2 => Some((NoteDurationModifier::DoubleDotted, exponent - 2)),
+            15 if exponent >= 3 => Some((NoteDurationModifier::TripleDotted, exponent - 3)),
+            _ => None,
+        };
+        match modifier {
+            Some((modifier, exponent)) => {
+                let duration = NoteDuration::from_exponent(exponent);
+                if duration == NoteDuration::NaN {
+                    return DurationType { duration, modifier: NoteDurationModifier::None };
+                }
+                return DurationType { duration, modifier };
+            },
+            None => {
+                return DurationType {
+                    duration: NoteDuration::NaN,
+                    modifier: NoteDurationModifier::None,
+                };
+            },
+        }
+    }
+}
+
+impl Rational {
+    /// The length of nothing.
+    pub const ZERO: Rational = Rational { numerator: 0, denominator: 1 };
+
+    /// Multiplies the length by `factor`, rounding to the nearest multiple of
+    /// `1 / RATIONAL_RESOLUTION` of a whole note. Negative factors give `Rational::ZERO`.
+    pub fn scale(&self, factor: f32) -> Rational {
+        let wholes = self.numerator as f64 / self.denominator as f64 * factor.max(0.0) as f64;
+        let parts = (wholes * RATIONAL_RESOLUTION as f64).round() as u32;
+        return Rational::new(parts, RATIONAL_RESOLUTION);
+    }
+
+    /// Splits the length into the durations of a tie, longest first, taking the longest
+    /// duration that fits each time. Any part shorter than a hundred twenty-eighth note is
+    /// dropped.
+    pub fn to_tie(&self) -> Vec<DurationType> {
+        let written = get_written_lengths();
+        let mut tie = Vec::new();
+        let mut remaining = *self;
+        while let Some((length, duration)) = written.iter().find(|(r, _)| *r <= remaining) {
+            tie.push(duration.clone());
+            remaining = remaining - *length;
         }
-        match duration {
-            NoteDuration::WHOLE => 4.0 * mod_factor,
-            NoteDuration::HALF => 2.0 * mod_factor, 
-            NoteDuration::QUARTER => 1.0 * mod_factor, 
-            NoteDuration::EIGHTH => 0.5 * mod_factor, 
-            NoteDuration::SIXTEENTH => 0.25 * mod_factor, 
-            NoteDuration::THIRTYSECOND => 0.125 * mod_factor, 
-            NoteDuration::NaN => 0.0,
+        return tie;
+    }
+}
+
+/// Returns every duration that can be written as a single note, possibly dotted, along with its
+/// length, longest first.
+fn get_written_lengths() -> Vec<(Rational, DurationType)> {
+    let modifiers = [
+        NoteDurationModifier::None,
+        NoteDurationModifier::Dotted,
+        NoteDurationModifier::DoubleDotted,
+        NoteDurationModifier::TripleDotted,
+    ];
+    let mut written: Vec<(Rational, DurationType)> = (0..8)
+        .flat_map(|exponent| {
+            let duration = NoteDuration::from_exponent(exponent);
+            return modifiers.clone().map(|modifier| {
+                return DurationType { duration: duration.clone(), modifier };
+            });
+        })
+        .filter_map(|d| d.to_rational().map(|r| (r, d)))
+        .collect();
+    written.sort_by_key(|(length, _)| std::cmp::Reverse(*length));
+    return written;
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        let left = self.numerator as u64 * other.denominator as u64;
+        let right = other.numerator as u64 * self.denominator as u64;
+        return left.cmp(&right);
+    }
+}
+
+impl Add for Rational {
+    type Output = Rational;
+
+    fn add(self, other: Rational) -> Rational {
+        let numerator = self.numerator as u64 * other.denominator as u64
+            + other.numerator as u64 * self.denominator as u64;
+        return reduce(numerator, self.denominator as u64 * other.denominator as u64);
+    }
+}
+
+impl Sub for Rational {
+    type Output = Rational;
+
+    /// Subtracts one length from another. Taking a longer length away gives `Rational::ZERO`.
+    fn sub(self, other: Rational) -> Rational {
+        let numerator = (self.numerator as u64 * other.denominator as u64)
+            .saturating_sub(other.numerator as u64 * self.denominator as u64);
+        return reduce(numerator, self.denominator as u64 * other.denominator as u64);
+    }
+}
+
+/// Builds a `Rational` from a fraction that may not fit in 32 bits until it's reduced.
+fn reduce(numerator: u64, denominator: u64) -> Rational {
+    let (mut a, mut b) = (numerator, denominator);
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    return Rational { numerator: (numerator / a) as u32, denominator: (denominator / a) as u32 };
+}
+
+/// The result of adding, subtracting, or scaling durations: a single duration, or the
+/// durations of a tie when the result can't be written as one note. See `Rational::to_tie`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum DurationResult {
+    Single(DurationType),
+    Tied(Vec<DurationType>),
+}
+
+impl DurationResult {
+    /// Writes a length as a single duration if it can be, or as a tie otherwise.
+    pub fn from_rational(length: Rational) -> Self {
+        let duration = length.to_duration_type();
+        if duration.duration != NoteDuration::NaN {
+            return DurationResult::Single(duration);
         }
+        return DurationResult::Tied(length.to_tie());
     }
-}
\ No newline at end of file
+
+    /// Returns the durations of the result, one for a single duration.
+    pub fn durations(&self) -> Vec<DurationType> {
+        match self {
+            DurationResult::Single(duration) => return vec![duration.clone()],
+            DurationResult::Tied(durations) => return durations.clone(),
+        }
+    }
+
+    /// Returns the total length of the result as a fraction of a whole note.
+    pub fn to_rational(&self) -> Rational {
+        return self.durations()
+            .iter()
+            .filter_map(DurationType::to_rational)
+            .fold(Rational::ZERO, |total, length| total + length);
+    }
+}
+
+impl Add for DurationType {
+    type Output = DurationResult;
+
+    /// Adds two durations. `NoteDuration::NaN` durations count as no length.
+    fn add(self, other: DurationType) -> DurationResult {
+        let length = self.to_rational().unwrap_or(Rational::ZERO)
+            + other.to_rational().unwrap_or(Rational::ZERO);
+        return DurationResult::from_rational(length);
+    }
+}
+
+impl Sub for DurationType {
+    type Output = DurationResult;
+
+    /// Takes one duration away from another. Taking a longer duration away leaves an empty tie.
+    fn sub(self, other: DurationType) -> DurationResult {
+        let length = self.to_rational().unwrap_or(Rational::ZERO)
+            - other.to_rational().unwrap_or(Rational::ZERO);
+        return DurationResult::from_rational(length);
+    }
+}
+
+impl Mul<f32> for DurationType {
+    type Output = DurationResult;
+
+    /// Scales a duration by `factor`, e.g. by 2/3 for a triplet. See `Rational::scale`.
+    fn mul(self, factor: f32) -> DurationResult {
+        let length = self.to_rational().unwrap_or(Rational::ZERO).scale(factor);
+        return DurationResult::from_rational(length);
+    }
+}
+
+/// Returns a whole note duration.
+pub fn whole() -> DurationType {
+    return DurationType { duration: NoteDuration::WHOLE, modifier: NoteDurationModifier::None };
+}
+
+/// Returns a half note duration.
+pub fn half() -> DurationType {
+    return DurationType { duration: NoteDuration::HALF, modifier: NoteDurationModifier::None };
+}
+
+/// Returns a quarter note duration.
+pub fn quarter() -> DurationType {
+    return DurationType { duration: NoteDuration::QUARTER, modifier: NoteDurationModifier::None };
+}
+
+/// Returns an eighth note duration.
+pub fn eighth() -> DurationType {
+    return DurationType { duration: NoteDuration::EIGHTH, modifier: NoteDurationModifier::None };
+}
+
+/// Returns a sixteenth note duration.
+pub fn sixteenth() -> DurationType {
+    return DurationType {
+        duration: NoteDuration::SIXTEENTH,
+        modifier: NoteDurationModifier::None,
+    };
+}
+
+/// Returns a thirty-second note duration.
+pub fn thirty_second() -> DurationType {
+    return DurationType {
+        duration: NoteDuration::THIRTYSECOND,
+        modifier: NoteDurationModifier::None,
+    };
+}
+
+/// Returns a sixty-fourth note duration.
+pub fn sixty_fourth() -> DurationType {
+    return DurationType {
+        duration: NoteDuration::SIXTYFOURTH,
+        modifier: NoteDurationModifier::None,
+    };
+}
+
+/// Returns a hundred twenty-eighth note duration.
+pub fn hundred_twenty_eighth() -> DurationType {
+    return DurationType {
+        duration: NoteDuration::HUNDREDTWENTYEIGHTH,
+        modifier: NoteDurationModifier::None,
+    };
+}
@@ -0,0 +1,188 @@
+use crate::flat::FlatTrack;
+use crate::parsing::Track;
+use crate::parsing::measure;
+use crate::parsing::symbols::TimeSignature;
+
+/// A dynamic marking, from softest to loudest.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
+pub enum Dynamic {
+    Pianissimo,
+    Piano,
+    MezzoPiano,
+    MezzoForte,
+    Forte,
+    Fortissimo,
+}
+
+impl Dynamic {
+    /// Converts the enum to the marking written in a score, e.g. "mf".
+    pub fn to_string(&self) -> &str {
+        match self {
+            Dynamic::Pianissimo => return "pp",
+            Dynamic::Piano => return "p",
+            Dynamic::MezzoPiano => return "mp",
+            Dynamic::MezzoForte => return "mf",
+            Dynamic::Forte => return "f",
+            Dynamic::Fortissimo => return "ff",
+        }
+    }
+}
+
+/// The velocities at which each dynamic starts. Velocities below `piano` are pianissimo.
+/// 
+/// The defaults fall halfway between the velocities notation programs commonly play each
+/// dynamic at (pp 33, p 49, mp 64, mf 80, f 96, ff 112).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DynamicThresholds {
+    /// The lowest velocity marked piano.
+    pub piano: u8,
+    /// The lowest velocity marked mezzo piano.
+    pub mezzo_piano: u8,
+    /// The lowest velocity marked mezzo forte.
+    pub mezzo_forte: u8,
+    /// The lowest velocity marked forte.
+    pub forte: u8,
+    /// The lowest velocity marked fortissimo.
+    pub fortissimo: u8,
+}
+
+impl DynamicThresholds {
+    /// Creates the default set of thresholds.
+    pub fn new() -> Self {
+        DynamicThresholds {
+            piano: 41,
+            mezzo_piano: 57,
+            mezzo_forte: 72,
+            forte: 88,
+            fortissimo: 104,
+        }
+    }
+
+    /// Returns the dynamic a note played at `velocity` is marked with.
+    pub fn dynamic(&self, velocity: u8) -> Dynamic {
+        if velocity >= self.fortissimo {
+            return Dynamic::Fortissimo;
+        } else if velocity >= self.forte {
+            return Dynamic::Forte;
+        } else if velocity >= self.mezzo_forte {
+            return Dynamic::MezzoForte;
+        } else if velocity >= self.mezzo_piano {
+            return Dynamic::MezzoPiano;
+        } else if velocity >= self.piano {
+            return Dynamic::Piano;
+        }
+        return Dynamic::Pianissimo;
+    }
+}
+
+impl Default for DynamicThresholds {
+    fn default() -> Self {
+        return DynamicThresholds::new();
+    }
+}
+
+/// Options that control which notes `mark_accents` marks.
+/// 
+/// ```
+/// use beatblox_midi::parsing::dynamics::AccentOptions;
+/// 
+/// let options = AccentOptions::new().threshold(30).weak_beat_velocity(Some(110));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccentOptions {
+    /// The number of onsets on each side of a note that make up its neighborhood.
+    pub neighborhood: usize,
+    /// How far a note's velocity must be above the average of its neighborhood to be accented.
+    pub threshold: u8,
+    /// The velocity at or above which notes off the downbeat are accented regardless of their
+    /// neighborhood, if there is one.
+    pub weak_beat_velocity: Option<u8>,
+}
+
+impl AccentOptions {
+    /// Creates options that accent notes at least 20 above the average of the two onsets on
+    /// each side of them.
+    pub fn new() -> Self {
+        return AccentOptions { neighborhood: 2, threshold: 20, weak_beat_velocity: None };
+    }
+
+    /// Sets the number of onsets on each side of a note that make up its neighborhood.
+    pub fn neighborhood(mut self, onsets: usize) -> Self {
+        self.neighborhood = onsets;
+        return self;
+    }
+
+    /// Sets how far a note's velocity must be above its neighborhood to be accented.
+    pub fn threshold(mut self, threshold: u8) -> Self {
+        self.threshold = threshold;
+        return self;
+    }
+
+    /// Sets the velocity at or above which notes off the downbeat are always accented.
+    pub fn weak_beat_velocity(mut self, velocity: Option<u8>) -> Self {
+        self.weak_beat_velocity = velocity;
+        return self;
+    }
+}
+
+impl Default for AccentOptions {
+    fn default() -> Self {
+        return AccentOptions::new();
+    }
+}
+
+/// Sets `Note::accent` on the notes of a track that are played noticeably louder than the
+/// notes around them, and returns the number of notes accented.
+/// 
+/// A note's neighborhood is the onsets on either side of it, where the notes of a chord are one
+/// onset at the velocity of the loudest. The later pieces of tied notes are never accented.
+/// `time_signatures` and `ticks_per_beat` place the notes in measures for
+/// `AccentOptions::weak_beat_velocity`, and can be taken from `Midi::time_signatures` and
+/// `Midi::ticks_per_beat`.
+pub fn mark_accents(
+    track: &mut Track,
+    time_signatures: &[TimeSignature],
+    ticks_per_beat: f32,
+    options: &AccentOptions
+) -> usize {
+    let flat = FlatTrack::new(track, 2);
+    let mut onsets: Vec<(f32, u8)> = Vec::new();
+    for i in (0..flat.len()).filter(|i| flat.is_struck(*i)) {
+        match onsets.last_mut() {
+            Some((onset, velocity)) if *onset == flat.onsets[i] => {
+                *velocity = (*velocity).max(flat.velocities[i]);
+            },
+            _ => onsets.push((flat.onsets[i], flat.velocities[i])),
+        }
+    }
+    let is_accented = |i: usize| {
+        if !flat.is_struck(i) {
+            return false;
+        }
+        let velocity = flat.velocities[i];
+        let (_, beat) = measure::measure_at(time_signatures, ticks_per_beat, flat.onsets[i]);
+        let weak = beat > 1e-3;
+        if options.weak_beat_velocity.is_some_and(|v| weak && velocity >= v) {
+            return true;
+        }
+        let k = onsets.iter().position(|(onset, _)| *onset == flat.onsets[i]).unwrap_or(0);
+        let start = k.saturating_sub(options.neighborhood);
+        let end = (k + options.neighborhood + 1).min(onsets.len());
+        let neighbors: Vec<f32> = (start..end)
+            .filter(|j| *j != k)
+            .map(|j| onsets[j].1 as f32)
+            .collect();
+        if neighbors.is_empty() {
+            return false;
+        }
+        let mean = neighbors.iter().sum::<f32>() / neighbors.len() as f32;
+        return velocity as f32 >= mean + options.threshold as f32;
+    };
+    let accented: Vec<bool> = (0..flat.len()).map(is_accented).collect();
+    let notes = track.notes.iter_mut().flat_map(|n| n.notes_mut());
+    for (note, accent) in notes.zip(&accented) {
+        note.accent = *accent;
+    }
+    return accented.iter().filter(|a| **a).count();
+}
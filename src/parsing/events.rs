@@ -0,0 +1,347 @@
+use crate::error::ParseError;
+use crate::parsing::checked_add_delta;
+use crate::parsing::metadata::SmfFormat;
+use crate::parsing::midicsv;
+
+/// An event-level representation of a midi file.
+///
+/// This sits between the raw file and the parsed `Midi`, so files read from different sources
+/// (standard midi files and midicsv text) are fed through the same quantizer. Unlike
+/// `midly::Smf`, it owns all of its data and stores events by absolute tick.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
+pub struct EventFile {
+    /// The format of the file.
+    pub format: SmfFormat,
+    /// Number of ticks in each beat.
+    pub ticks_per_beat: u16,
+    /// The events of each track, sorted by the tick they occur on.
+    pub tracks: Vec<Vec<Event>>,
+}
+
+/// A single event in an `EventFile`.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
+pub struct Event {
+    /// The tick on which the event occurs, counted from the start of the track.
+    pub tick: u32,
+    /// The content of the event.
+    pub kind: EventKind,
+}
+
+/// The kinds of text a meta event can hold.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
+pub enum TextKind {
+    Text,
+    Copyright,
+    TrackName,
+    InstrumentName,
+    Lyric,
+    Marker,
+    CuePoint,
+}
+
+/// The content of an `Event`.
+///
+/// Channel messages hold their channel from 0 to 15. Pitch bends hold their raw 14 bit value,
+/// where 8192 is no bend.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
+pub enum EventKind {
+    NoteOff { channel: u8, key: u8, velocity: u8 },
+    NoteOn { channel: u8, key: u8, velocity: u8 },
+    PolyPressure { channel: u8, key: u8, value: u8 },
+    Controller { channel: u8, controller: u8, value: u8 },
+    ProgramChange { channel: u8, program: u8 },
+    ChannelPressure { channel: u8, value: u8 },
+    PitchBend { channel: u8, value: u16 },
+    /// A system exclusive message, excluding the leading 0xF0 status byte.
+    SysEx(Vec<u8>),
+    /// An escape sequence holding raw bytes.
+    Escape(Vec<u8>),
+    SequenceNumber(Option<u16>),
+    Text(TextKind, Vec<u8>),
+    ChannelPrefix(u8),
+    Port(u8),
+    EndOfTrack,
+    /// The number of microseconds per beat.
+    Tempo(u32),
+    /// The raw bytes of an SMPTE offset, where the first byte holds both the hour and the frame
+    /// rate.
+    SmpteOffset([u8; 5]),
+    /// The numerator, denominator exponent, clocks per click, and 32nd notes per beat.
+    TimeSignature(u8, u8, u8, u8),
+    /// The number of sharps (positive) or flats (negative), and whether the key is minor.
+    KeySignature(i8, bool),
+    SequencerSpecific(Vec<u8>),
+    /// Any other meta event, with its type byte and data.
+    UnknownMeta(u8, Vec<u8>),
+}
+
+impl EventFile {
+    /// Reads the events of a standard midi file.
+    pub fn from_smf_bytes(bytes: &[u8]) -> Result<EventFile, ParseError> {
+        let smf = midly::Smf::parse(bytes)?;
+        return EventFile::from_smf(&smf);
+    }
+
+    /// Reads the events of a file in the midicsv text format.
+    pub fn from_midicsv(text: &str) -> Result<EventFile, ParseError> {
+        return midicsv::read_midicsv(text);
+    }
+
+    /// Writes the events in the midicsv text format, so they can be inspected and edited by hand.
+    pub fn to_midicsv(&self) -> String {
+        return midicsv::write_midicsv(self);
+    }
+
+    /// Converts a `midly::Smf` into an `EventFile`.
+    pub fn from_smf(smf: &midly::Smf) -> Result<EventFile, ParseError> {
+        let ticks_per_beat = match smf.header.timing {
+            midly::Timing::Metrical(t) => t.as_int(),
+            midly::Timing::Timecode(_, _) => return Err(ParseError::UnsupportedTiming),
+        };
+        let format = match smf.header.format {
+            midly::Format::SingleTrack => SmfFormat::SingleTrack,
+            midly::Format::Parallel => SmfFormat::Parallel,
+            midly::Format::Sequential => SmfFormat::Sequential,
+        };
+        let mut tracks = Vec::new();
+        for track in &smf.tracks {
+            let mut events = Vec::new();
+            let mut cur_time: u32 = 0;
+            for event in track {
+                cur_time = checked_add_delta(cur_time, event.delta)?;
+                events.push(Event { tick: cur_time, kind: EventKind::from_midly(&event.kind) });
+            }
+            tracks.push(events);
+        }
+        return Ok(EventFile { format, ticks_per_beat, tracks });
+    }
+
+    /// Converts the `EventFile` into a `midly::Smf` that borrows its data.
+    pub fn to_smf(&self) -> midly::Smf<'_> {
+        let format = match self.format {
+            SmfFormat::SingleTrack => midly::Format::SingleTrack,
+            SmfFormat::Parallel => midly::Format::Parallel,
+            SmfFormat::Sequential => midly::Format::Sequential,
+        };
+        let timing = midly::Timing::Metrical(self.ticks_per_beat.into());
+        let mut smf = midly::Smf::new(midly::Header::new(format, timing));
+        for track in &self.tracks {
+            let mut events = Vec::new();
+            let mut cur_time: u32 = 0;
+            for event in track {
+                let delta = event.tick.saturating_sub(cur_time);
+                cur_time = cur_time.max(event.tick);
+                events.push(midly::TrackEvent { delta: delta.into(), kind: event.kind.to_midly() });
+            }
+            smf.tracks.push(events);
+        }
+        return smf;
+    }
+
+    /// Writes the `EventFile` as a standard midi file.
+    pub fn to_smf_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        // Writing to a vector can only fail on values midly cannot encode, which the
+        // conversion from `EventKind` already masks into range.
+        self.to_smf().write_std(&mut bytes).expect("failed to encode midi events");
+        return bytes;
+    }
+}
+
+impl EventKind {
+    /// Converts a `midly::TrackEventKind` into an `EventKind`.
+    pub fn from_midly(kind: &midly::TrackEventKind) -> EventKind {
+        match *kind {
+            midly::TrackEventKind::Midi { channel, message } => {
+                let channel = channel.as_int();
+                match message {
+                    midly::MidiMessage::NoteOff { key, vel } => {
+                        let (key, velocity) = (key.into(), vel.into());
+                        return EventKind::NoteOff { channel, key, velocity };
+                    },
+                    midly::MidiMessage::NoteOn { key, vel } => {
+                        let (key, velocity) = (key.into(), vel.into());
+                        return EventKind::NoteOn { channel, key, velocity };
+                    },
+                    midly::MidiMessage::Aftertouch { key, vel } => {
+                        let (key, value) = (key.into(), vel.into());
+                        return EventKind::PolyPressure { channel, key, value };
+                    },
+                    midly::MidiMessage::Controller { controller, value } => {
+                        return EventKind::Controller {
+                            channel,
+                            controller: controller.into(),
+                            value: value.into(),
+                        };
+                    },
+                    midly::MidiMessage::ProgramChange { program } => {
+                        return EventKind::ProgramChange { channel, program: program.into() };
+                    },
+                    midly::MidiMessage::ChannelAftertouch { vel } => {
+                        return EventKind::ChannelPressure { channel, value: vel.into() };
+                    },
+                    midly::MidiMessage::PitchBend { bend } => {
+                        return EventKind::PitchBend { channel, value: bend.0.as_int() };
+                    },
+                }
+            },
+            midly::TrackEventKind::SysEx(data) => return EventKind::SysEx(data.to_vec()),
+            midly::TrackEventKind::Escape(data) => return EventKind::Escape(data.to_vec()),
+            midly::TrackEventKind::Meta(message) => return EventKind::from_meta(message),
+        }
+    }
+
+    /// A helper function for converting a `midly::MetaMessage` into an `EventKind`.
+    fn from_meta(message: midly::MetaMessage) -> EventKind {
+        match message {
+            midly::MetaMessage::TrackNumber(n) => return EventKind::SequenceNumber(n),
+            midly::MetaMessage::Text(s) => return EventKind::Text(TextKind::Text, s.to_vec()),
+            midly::MetaMessage::Copyright(s) => {
+                return EventKind::Text(TextKind::Copyright, s.to_vec());
+            },
+            midly::MetaMessage::TrackName(s) => {
+                return EventKind::Text(TextKind::TrackName, s.to_vec());
+            },
+            midly::MetaMessage::InstrumentName(s) => {
+                return EventKind::Text(TextKind::InstrumentName, s.to_vec());
+            },
+            midly::MetaMessage::Lyric(s) => return EventKind::Text(TextKind::Lyric, s.to_vec()),
+            midly::MetaMessage::Marker(s) => return EventKind::Text(TextKind::Marker, s.to_vec()),
+            midly::MetaMessage::CuePoint(s) => {
+                return EventKind::Text(TextKind::CuePoint, s.to_vec());
+            },
+            midly::MetaMessage::ProgramName(s) => return EventKind::UnknownMeta(0x08, s.to_vec()),
+            midly::MetaMessage::DeviceName(s) => return EventKind::UnknownMeta(0x09, s.to_vec()),
+            midly::MetaMessage::MidiChannel(c) => return EventKind::ChannelPrefix(c.into()),
+            midly::MetaMessage::MidiPort(p) => return EventKind::Port(p.into()),
+            midly::MetaMessage::EndOfTrack => return EventKind::EndOfTrack,
+            midly::MetaMessage::Tempo(t) => return EventKind::Tempo(t.into()),
+            midly::MetaMessage::SmpteOffset(t) => {
+                let fps = match t.fps() {
+                    midly::Fps::Fps24 => 0,
+                    midly::Fps::Fps25 => 1,
+                    midly::Fps::Fps29 => 2,
+                    midly::Fps::Fps30 => 3,
+                };
+                let hour = t.hour() | fps << 5;
+                let (minute, second, frame) = (t.minute(), t.second(), t.frame());
+                return EventKind::SmpteOffset([hour, minute, second, frame, t.subframe()]);
+            },
+            midly::MetaMessage::TimeSignature(n, d, c, b) => {
+                return EventKind::TimeSignature(n, d, c, b);
+            },
+            midly::MetaMessage::KeySignature(s, m) => return EventKind::KeySignature(s, m),
+            midly::MetaMessage::SequencerSpecific(data) => {
+                return EventKind::SequencerSpecific(data.to_vec());
+            },
+            midly::MetaMessage::Unknown(kind, data) => {
+                return EventKind::UnknownMeta(kind, data.to_vec());
+            },
+        }
+    }
+
+    /// Converts the `EventKind` into a `midly::TrackEventKind` that borrows its data.
+    ///
+    /// Values outside of the range midi allows are masked into range.
+    pub fn to_midly(&self) -> midly::TrackEventKind<'_> {
+        let midi = |channel: &u8, message| {
+            return midly::TrackEventKind::Midi { channel: (*channel & 0x0F).into(), message };
+        };
+        let meta = midly::TrackEventKind::Meta;
+        match self {
+            EventKind::NoteOff { channel, key, velocity } => {
+                return midi(channel, midly::MidiMessage::NoteOff {
+                    key: (*key).into(),
+                    vel: (*velocity).into(),
+                });
+            },
+            EventKind::NoteOn { channel, key, velocity } => {
+                return midi(channel, midly::MidiMessage::NoteOn {
+                    key: (*key).into(),
+                    vel: (*velocity).into(),
+                });
+            },
+            EventKind::PolyPressure { channel, key, value } => {
+                return midi(channel, midly::MidiMessage::Aftertouch {
+                    key: (*key).into(),
+                    vel: (*value).into(),
+                });
+            },
+            EventKind::Controller { channel, controller, value } => {
+                return midi(channel, midly::MidiMessage::Controller {
+                    controller: (*controller).into(),
+                    value: (*value).into(),
+                });
+            },
+            EventKind::ProgramChange { channel, program } => {
+                return midi(channel, midly::MidiMessage::ProgramChange {
+                    program: (*program).into(),
+                });
+            },
+            EventKind::ChannelPressure { channel, value } => {
+                return midi(channel, midly::MidiMessage::ChannelAftertouch {
+                    vel: (*value).into(),
+                });
+            },
+            EventKind::PitchBend { channel, value } => {
+                return midi(channel, midly::MidiMessage::PitchBend {
+                    bend: midly::PitchBend((*value).into()),
+                });
+            },
+            EventKind::SysEx(data) => return midly::TrackEventKind::SysEx(data),
+            EventKind::Escape(data) => return midly::TrackEventKind::Escape(data),
+            EventKind::SequenceNumber(n) => return meta(midly::MetaMessage::TrackNumber(*n)),
+            EventKind::Text(kind, s) => {
+                let message = match kind {
+                    TextKind::Text => midly::MetaMessage::Text(s),
+                    TextKind::Copyright => midly::MetaMessage::Copyright(s),
+                    TextKind::TrackName => midly::MetaMessage::TrackName(s),
+                    TextKind::InstrumentName => midly::MetaMessage::InstrumentName(s),
+                    TextKind::Lyric => midly::MetaMessage::Lyric(s),
+                    TextKind::Marker => midly::MetaMessage::Marker(s),
+                    TextKind::CuePoint => midly::MetaMessage::CuePoint(s),
+                };
+                return meta(message);
+            },
+            EventKind::ChannelPrefix(c) => {
+                return meta(midly::MetaMessage::MidiChannel((*c & 0x0F).into()));
+            },
+            EventKind::Port(p) => return meta(midly::MetaMessage::MidiPort((*p).into())),
+            EventKind::EndOfTrack => return meta(midly::MetaMessage::EndOfTrack),
+            EventKind::Tempo(t) => return meta(midly::MetaMessage::Tempo((*t).into())),
+            EventKind::SmpteOffset(bytes) => {
+                let fps = match bytes[0] >> 5 & 0x03 {
+                    0 => midly::Fps::Fps24,
+                    1 => midly::Fps::Fps25,
+                    2 => midly::Fps::Fps29,
+                    _ => midly::Fps::Fps30,
+                };
+                let time = midly::SmpteTime::new(
+                    bytes[0] & 0x1F, bytes[1], bytes[2], bytes[3], bytes[4], fps
+                );
+                match time {
+                    Some(t) => return meta(midly::MetaMessage::SmpteOffset(t)),
+                    None => return meta(midly::MetaMessage::Unknown(0x54, bytes)),
+                }
+            },
+            EventKind::TimeSignature(n, d, c, b) => {
+                return meta(midly::MetaMessage::TimeSignature(*n, *d, *c, *b));
+            },
+            EventKind::KeySignature(s, m) => {
+                return meta(midly::MetaMessage::KeySignature(*s, *m));
+            },
+            EventKind::SequencerSpecific(data) => {
+                return meta(midly::MetaMessage::SequencerSpecific(data));
+            },
+            EventKind::UnknownMeta(0x08, s) => return meta(midly::MetaMessage::ProgramName(s)),
+            EventKind::UnknownMeta(0x09, s) => return meta(midly::MetaMessage::DeviceName(s)),
+            EventKind::UnknownMeta(kind, data) => {
+                return meta(midly::MetaMessage::Unknown(*kind, data));
+            },
+        }
+    }
+}
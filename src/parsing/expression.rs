@@ -0,0 +1,450 @@
+use crate::parsing::add_delta;
+use crate::parsing::symbols::NoteWrapper;
+
+/// The controllers whose values are captured as curves: volume, pan, and expression.
+pub const CAPTURED_CONTROLLERS: [u8; 3] = [7, 10, 11];
+
+/// The number of samples per beat in a resampled controller curve.
+pub const CONTROLLER_SAMPLES_PER_BEAT: u32 = 4;
+
+/// A change in the value of a continuous controller.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
+pub struct ControllerEvent {
+    /// The controller number, e.g. 7 for volume.
+    pub controller: u8,
+    /// The beat on which the change occurs, counted from the start of the piece.
+    pub beat: f32,
+    /// The new value of the controller.
+    pub value: u8,
+}
+
+/// The fewest pitch bends in a row that make up a glissando.
+pub const GLISSANDO_MIN_BENDS: usize = 3;
+
+/// A slide between two pitches, read from a sweep of pitch bends. See `Note::glissando`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
+pub struct Glissando {
+    /// The midi value of the pitch the slide starts from.
+    pub start: u8,
+    /// The midi value of the pitch the slide ends on.
+    pub target: u8,
+    /// The beat on which the slide starts, counted from the start of the note.
+    pub start_beat: f32,
+    /// The beat on which the slide ends, counted from the start of the note.
+    pub end_beat: f32,
+}
+
+/// The controller number of the modulation wheel.
+pub const MODULATION_CONTROLLER: u8 = 1;
+
+/// The widest pitch bend oscillation, in cents from peak to peak, that is read as vibrato.
+pub const VIBRATO_MAX_WIDTH: f32 = 200.0;
+
+/// The fewest times a pitch bend curve must cross its average to be read as vibrato, which is
+/// two full cycles.
+pub const VIBRATO_MIN_CROSSINGS: usize = 4;
+
+/// Where a note's vibrato comes from. See `Vibrato`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
+pub enum VibratoSource {
+    /// The pitch bend oscillates while the note sounds.
+    PitchBend,
+    /// The modulation wheel is raised while the note sounds.
+    Modulation,
+}
+
+impl VibratoSource {
+    /// Converts the enum to a string.
+    pub fn to_string(&self) -> &str {
+        match self {
+            VibratoSource::PitchBend => return "pitch bend",
+            VibratoSource::Modulation => return "modulation",
+        }
+    }
+}
+
+/// A vibrato played on a note. See `Note::vibrato`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vibrato {
+    /// Where the vibrato comes from.
+    pub source: VibratoSource,
+    /// The number of cycles of the vibrato per beat. This is `None` for modulation wheel
+    /// vibrato, whose speed is set by the instrument.
+    pub rate: Option<f32>,
+    /// How far the pitch moves, in cents either side of its center for pitch bend vibrato, or
+    /// the highest modulation wheel value (0 to 127) for modulation wheel vibrato.
+    pub depth: f32,
+}
+
+/// A change in key pressure (aftertouch).
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
+pub struct PressureEvent {
+    /// The key the pressure applies to, or `None` for channel pressure, which applies to every
+    /// sounding note.
+    pub key: Option<u8>,
+    /// The beat on which the change occurs, counted from the start of the piece.
+    pub beat: f32,
+    /// The new pressure.
+    pub value: u8,
+}
+
+/// Returns the General MIDI default value of a controller.
+pub fn default_controller_value(controller: u8) -> u8 {
+    match controller {
+        7 => return 100,
+        10 => return 64,
+        11 => return 127,
+        _ => return 0,
+    }
+}
+
+/// Returns every change to the controllers in `CAPTURED_CONTROLLERS` in a midi track.
+pub fn get_controller_events(
+    track: &[midly::TrackEvent],
+    ticks_per_beat: f32
+) -> Vec<ControllerEvent> {
+    let mut events = Vec::new();
+    let mut cur_time: u32 = 0;
+    for event in track {
+        cur_time = add_delta(cur_time, event.delta);
+        if let midly::TrackEventKind::Midi {
+            channel: _,
+            message: midly::MidiMessage::Controller { controller, value }
+        } = event.kind {
+            if CAPTURED_CONTROLLERS.contains(&controller.as_int()) {
+                events.push(ControllerEvent {
+                    controller: controller.into(),
+                    beat: cur_time as f32 / ticks_per_beat,
+                    value: value.into(),
+                });
+            }
+        }
+    }
+    return events;
+}
+
+/// Resamples the changes to `controller` onto a grid of `samples_per_beat` samples per beat,
+/// from the start of the piece up to `length` beats.
+/// 
+/// Each sample holds the value of the controller at that beat as `(beat, value)`. Before the
+/// first change, the controller has its General MIDI default value.
+pub fn resample_controller(
+    events: &[ControllerEvent],
+    controller: u8,
+    length: f32,
+    samples_per_beat: u32
+) -> Vec<(f32, u8)> {
+    let mut curve = Vec::new();
+    let mut value = default_controller_value(controller);
+    let mut events = events.iter().filter(|e| e.controller == controller).peekable();
+    let samples = (length * samples_per_beat as f32).ceil() as u32;
+    for i in 0..samples {
+        let beat = i as f32 / samples_per_beat as f32;
+        while let Some(event) = events.next_if(|e| e.beat <= beat + 1e-4) {
+            value = event.value;
+        }
+        curve.push((beat, value));
+    }
+    return curve;
+}
+
+/// The pitch bend range assumed when a track does not set one, in semitones.
+pub const DEFAULT_BEND_RANGE: f32 = 2.0;
+
+/// How pitch bend messages are captured on notes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PitchBendMode {
+    /// Pitch bends are ignored.
+    Ignore,
+    /// Every pitch bend during a note is stored in `Note::bend`.
+    Curve,
+    /// The average pitch bend during a note is stored in `Note::detune`.
+    Detune,
+}
+
+/// Returns every pitch bend in a midi track as `(tick, cents)` pairs.
+/// 
+/// The bend range is read from pitch bend sensitivity messages (registered parameter 0) and
+/// defaults to `DEFAULT_BEND_RANGE` semitones.
+pub fn get_pitch_bends(track: &[midly::TrackEvent]) -> Vec<(u32, f32)> {
+    let mut bends = Vec::new();
+    let mut cur_time: u32 = 0;
+    let mut bend_range = DEFAULT_BEND_RANGE;
+    let mut parameter: (Option<u8>, Option<u8>) = (None, None);
+    for event in track {
+        cur_time = add_delta(cur_time, event.delta);
+        if let midly::TrackEventKind::Midi { channel: _, message } = event.kind {
+            match message {
+                midly::MidiMessage::PitchBend { bend } => {
+                    let cents = bend.as_f32() * bend_range * 100.0;
+                    bends.push((cur_time, cents));
+                },
+                midly::MidiMessage::Controller { controller, value } => {
+                    let value: u8 = value.into();
+                    match controller.as_int() {
+                        101 => parameter.0 = Some(value),
+                        100 => parameter.1 = Some(value),
+                        6 if parameter == (Some(0), Some(0)) => bend_range = value as f32,
+                        _ => (),
+                    }
+                },
+                _ => (),
+            }
+        }
+    }
+    return bends;
+}
+
+/// Attaches pitch bends to the notes they are applied to.
+/// 
+/// `bends` must be sorted by tick. A bend that is still in effect when a note starts is
+/// included at the start of the note's curve.
+pub fn attach_pitch_bends(
+    notes: &mut [NoteWrapper],
+    bends: &[(u32, f32)],
+    mode: PitchBendMode,
+    beat_type: u8,
+    ticks_per_beat: f32
+) {
+    if mode == PitchBendMode::Ignore || bends.is_empty() {
+        return;
+    }
+    let mut beat = 0.0;
+    for wrapper in notes {
+        let length = wrapper.beat_count(beat_type);
+        wrapper.for_each_note_mut(beat, beat_type, &mut |note, onset, length| {
+            let Some(value) = note.value() else {
+                return;
+            };
+            let start = (onset * ticks_per_beat).round() as u32;
+            let end = ((onset + length) * ticks_per_beat).round() as u32;
+            let mut curve = Vec::new();
+            if let Some((_, cents)) = bends.iter().rev().find(|(tick, _)| *tick <= start) {
+                if *cents != 0.0 {
+                    curve.push((0.0, *cents));
+                }
+            }
+            for (tick, cents) in bends.iter().filter(|(tick, _)| *tick > start && *tick < end) {
+                curve.push((*tick as f32 / ticks_per_beat - onset, *cents));
+            }
+            // A curve that doesn't start with a bend starts unbent, which a sweep can start from.
+            let glissando = match curve.first() {
+                Some((beat, _)) if *beat > 0.0 => {
+                    let sweep: Vec<(f32, f32)> = std::iter::once((0.0, 0.0))
+                        .chain(curve.iter().copied())
+                        .collect();
+                    find_glissando(value, &sweep)
+                },
+                _ => find_glissando(value, &curve),
+            };
+            note.glissando = glissando;
+            note.vibrato = find_vibrato(&curve);
+            match mode {
+                PitchBendMode::Curve => note.bend = curve,
+                PitchBendMode::Detune if !curve.is_empty() => {
+                    let total: f32 = curve.iter().map(|(_, cents)| cents).sum();
+                    note.detune = Some(total / curve.len() as f32);
+                },
+                _ => (),
+            }
+        });
+        beat += length;
+    }
+}
+
+/// Finds the longest sweep of a pitch bend curve, given as `(beat, cents)` pairs, that moves in
+/// one direction over at least `GLISSANDO_MIN_BENDS` bends and spans more than a semitone.
+/// `value` is the midi value of the bent note, which the start and target pitches are counted
+/// from.
+pub fn find_glissando(value: u8, bend: &[(f32, f32)]) -> Option<Glissando> {
+    let mut best: Option<(usize, usize)> = None;
+    let mut start = 0;
+    for end in 1..=bend.len() {
+        let direction = |i: usize| (bend[i].1 - bend[i - 1].1).signum();
+        let continues = end < bend.len()
+            && bend[end].1 != bend[end - 1].1
+            && (end - start < 2 || direction(end) == direction(end - 1));
+        if continues {
+            continue;
+        }
+        let span = (bend[end - 1].1 - bend[start].1).abs();
+        let longest = best.is_none_or(|(s, e)| span > (bend[e].1 - bend[s].1).abs());
+        if end - start >= GLISSANDO_MIN_BENDS && span > 100.0 && longest {
+            best = Some((start, end - 1));
+        }
+        // A turn starts the next sweep from the bend it turns on.
+        start = end - 1;
+    }
+    let pitch = |cents: f32| (value as f32 + (cents / 100.0).round()).clamp(0.0, 127.0) as u8;
+    return best.map(|(s, e)| Glissando {
+        start: pitch(bend[s].1),
+        target: pitch(bend[e].1),
+        start_beat: bend[s].0,
+        end_beat: bend[e].0,
+    });
+}
+
+/// Estimates the vibrato in a pitch bend curve, given as `(beat, cents)` pairs, if it
+/// oscillates around its average at least `VIBRATO_MIN_CROSSINGS` times and no wider than
+/// `VIBRATO_MAX_WIDTH` cents.
+pub fn find_vibrato(bend: &[(f32, f32)]) -> Option<Vibrato> {
+    if bend.len() < 2 {
+        return None;
+    }
+    let mean = bend.iter().map(|(_, cents)| cents).sum::<f32>() / bend.len() as f32;
+    let (min, max) = bend
+        .iter()
+        .fold((f32::MAX, f32::MIN), |(min, max), (_, c)| (min.min(*c), max.max(*c)));
+    let signs: Vec<f32> = bend
+        .iter()
+        .map(|(_, cents)| cents - mean)
+        .filter(|offset| *offset != 0.0)
+        .map(f32::signum)
+        .collect();
+    let crossings = signs.windows(2).filter(|pair| pair[0] != pair[1]).count();
+    let span = bend[bend.len() - 1].0 - bend[0].0;
+    if crossings < VIBRATO_MIN_CROSSINGS || max - min > VIBRATO_MAX_WIDTH || span <= 0.0 {
+        return None;
+    }
+    return Some(Vibrato {
+        source: VibratoSource::PitchBend,
+        rate: Some(crossings as f32 / 2.0 / span),
+        depth: (max - min) / 2.0,
+    });
+}
+
+/// Returns every change to the modulation wheel in a midi track.
+pub fn get_modulation_events(
+    track: &[midly::TrackEvent],
+    ticks_per_beat: f32
+) -> Vec<ControllerEvent> {
+    let mut events = Vec::new();
+    let mut cur_time: u32 = 0;
+    for event in track {
+        cur_time = add_delta(cur_time, event.delta);
+        if let midly::TrackEventKind::Midi {
+            channel: _,
+            message: midly::MidiMessage::Controller { controller, value }
+        } = event.kind {
+            if controller.as_int() == MODULATION_CONTROLLER {
+                events.push(ControllerEvent {
+                    controller: controller.into(),
+                    beat: cur_time as f32 / ticks_per_beat,
+                    value: value.into(),
+                });
+            }
+        }
+    }
+    return events;
+}
+
+/// Gives a modulation wheel vibrato to the notes that sound while the modulation wheel is
+/// raised. Notes that already have a pitch bend vibrato keep it.
+/// 
+/// `events` must be sorted by beat. A change that is still in effect when a note starts counts
+/// towards the note.
+pub fn attach_modulation(notes: &mut [NoteWrapper], events: &[ControllerEvent], beat_type: u8) {
+    if events.is_empty() {
+        return;
+    }
+    let mut beat = 0.0;
+    for wrapper in notes {
+        let length = wrapper.beat_count(beat_type);
+        wrapper.for_each_note_mut(beat, beat_type, &mut |note, onset, length| {
+            if note.is_rest() || note.vibrato.is_some() {
+                return;
+            }
+            let held = events.iter().rev().find(|e| e.beat <= onset + 1e-4).map(|e| e.value);
+            let depth = events
+                .iter()
+                .filter(|e| e.beat > onset + 1e-4 && e.beat < onset + length)
+                .map(|e| e.value)
+                .chain(held)
+                .max()
+                .unwrap_or(0);
+            if depth > 0 {
+                let source = VibratoSource::Modulation;
+                note.vibrato = Some(Vibrato { source, rate: None, depth: depth as f32 });
+            }
+        });
+        beat += length;
+    }
+}
+
+/// Returns every channel pressure and polyphonic key pressure change in a midi track.
+pub fn get_pressure_events(
+    track: &[midly::TrackEvent],
+    ticks_per_beat: f32
+) -> Vec<PressureEvent> {
+    let mut events = Vec::new();
+    let mut cur_time: u32 = 0;
+    for event in track {
+        cur_time = add_delta(cur_time, event.delta);
+        if let midly::TrackEventKind::Midi { channel: _, message } = event.kind {
+            let (key, value) = match message {
+                midly::MidiMessage::ChannelAftertouch { vel } => (None, vel),
+                midly::MidiMessage::Aftertouch { key, vel } => (Some(key.into()), vel),
+                _ => continue,
+            };
+            events.push(PressureEvent {
+                key,
+                beat: cur_time as f32 / ticks_per_beat,
+                value: value.into(),
+            });
+        }
+    }
+    return events;
+}
+
+/// Attaches pressure changes to the notes they apply to.
+/// 
+/// A note receives every polyphonic pressure change for its key and every channel pressure
+/// change that occurs while it sounds, as `(beat, value)` pairs where `beat` is counted from the
+/// start of the note.
+pub fn attach_pressure(notes: &mut [NoteWrapper], events: &[PressureEvent], beat_type: u8) {
+    if events.is_empty() {
+        return;
+    }
+    let mut beat = 0.0;
+    for wrapper in notes {
+        let length = wrapper.beat_count(beat_type);
+        wrapper.for_each_note_mut(beat, beat_type, &mut |note, onset, length| {
+            let Some(value) = note.value() else {
+                return;
+            };
+            note.pressure = events
+                .iter()
+                .filter(|e| e.key.is_none_or(|key| key == value))
+                .filter(|e| e.beat >= onset - 1e-4 && e.beat < onset + length)
+                .map(|e| (e.beat - onset, e.value))
+                .collect();
+        });
+        beat += length;
+    }
+}
+
+/// Resamples the channel pressure changes onto a grid of `samples_per_beat` samples per beat,
+/// from the start of the piece up to `length` beats.
+pub fn resample_pressure(
+    events: &[PressureEvent],
+    length: f32,
+    samples_per_beat: u32
+) -> Vec<(f32, u8)> {
+    let mut curve = Vec::new();
+    let mut value = 0;
+    let mut events = events.iter().filter(|e| e.key.is_none()).peekable();
+    let samples = (length * samples_per_beat as f32).ceil() as u32;
+    for i in 0..samples {
+        let beat = i as f32 / samples_per_beat as f32;
+        while let Some(event) = events.next_if(|e| e.beat <= beat + 1e-4) {
+            value = event.value;
+        }
+        curve.push((beat, value));
+    }
+    return curve;
+}
@@ -0,0 +1,105 @@
+/// The names of the 128 General MIDI instruments, indexed by program number.
+pub const GM_INSTRUMENT_NAMES: [&str; 128] = [
+    "Acoustic Grand Piano", "Bright Acoustic Piano", "Electric Grand Piano", "Honky-tonk Piano",
+    "Electric Piano 1", "Electric Piano 2", "Harpsichord", "Clavinet",
+    "Celesta", "Glockenspiel", "Music Box", "Vibraphone",
+    "Marimba", "Xylophone", "Tubular Bells", "Dulcimer",
+    "Drawbar Organ", "Percussive Organ", "Rock Organ", "Church Organ",
+    "Reed Organ", "Accordion", "Harmonica", "Tango Accordion",
+    "Acoustic Guitar (nylon)", "Acoustic Guitar (steel)", "Electric Guitar (jazz)",
+    "Electric Guitar (clean)", "Electric Guitar (muted)", "Overdriven Guitar",
+    "Distortion Guitar", "Guitar Harmonics",
+    "Acoustic Bass", "Electric Bass (finger)", "Electric Bass (pick)", "Fretless Bass",
+    "Slap Bass 1", "Slap Bass 2", "Synth Bass 1", "Synth Bass 2",
+    "Violin", "Viola", "Cello", "Contrabass",
+    "Tremolo Strings", "Pizzicato Strings", "Orchestral Harp", "Timpani",
+    "String Ensemble 1", "String Ensemble 2", "Synth Strings 1", "Synth Strings 2",
+    "Choir Aahs", "Voice Oohs", "Synth Voice", "Orchestra Hit",
+    "Trumpet", "Trombone", "Tuba", "Muted Trumpet",
+    "French Horn", "Brass Section", "Synth Brass 1", "Synth Brass 2",
+    "Soprano Sax", "Alto Sax", "Tenor Sax", "Baritone Sax",
+    "Oboe", "English Horn", "Bassoon", "Clarinet",
+    "Piccolo", "Flute", "Recorder", "Pan Flute",
+    "Blown Bottle", "Shakuhachi", "Whistle", "Ocarina",
+    "Lead 1 (square)", "Lead 2 (sawtooth)", "Lead 3 (calliope)", "Lead 4 (chiff)",
+    "Lead 5 (charang)", "Lead 6 (voice)", "Lead 7 (fifths)", "Lead 8 (bass + lead)",
+    "Pad 1 (new age)", "Pad 2 (warm)", "Pad 3 (polysynth)", "Pad 4 (choir)",
+    "Pad 5 (bowed)", "Pad 6 (metallic)", "Pad 7 (halo)", "Pad 8 (sweep)",
+    "FX 1 (rain)", "FX 2 (soundtrack)", "FX 3 (crystal)", "FX 4 (atmosphere)",
+    "FX 5 (brightness)", "FX 6 (goblins)", "FX 7 (echoes)", "FX 8 (sci-fi)",
+    "Sitar", "Banjo", "Shamisen", "Koto",
+    "Kalimba", "Bagpipe", "Fiddle", "Shanai",
+    "Tinkle Bell", "Agogo", "Steel Drums", "Woodblock",
+    "Taiko Drum", "Melodic Tom", "Synth Drum", "Reverse Cymbal",
+    "Guitar Fret Noise", "Breath Noise", "Seashore", "Bird Tweet",
+    "Telephone Ring", "Helicopter", "Applause", "Gunshot",
+];
+
+/// The midi channel General MIDI reserves for percussion (channel 10, counting from one).
+pub const PERCUSSION_CHANNEL: u8 = 9;
+
+/// The names of the General MIDI percussion sounds, starting from key 35.
+const GM_DRUM_NAMES: [&str; 47] = [
+    "Acoustic Bass Drum", "Bass Drum 1", "Side Stick", "Acoustic Snare",
+    "Hand Clap", "Electric Snare", "Low Floor Tom", "Closed Hi-Hat",
+    "High Floor Tom", "Pedal Hi-Hat", "Low Tom", "Open Hi-Hat",
+    "Low-Mid Tom", "Hi-Mid Tom", "Crash Cymbal 1", "High Tom",
+    "Ride Cymbal 1", "Chinese Cymbal", "Ride Bell", "Tambourine",
+    "Splash Cymbal", "Cowbell", "Crash Cymbal 2", "Vibraslap",
+    "Ride Cymbal 2", "Hi Bongo", "Low Bongo", "Mute Hi Conga",
+    "Open Hi Conga", "Low Conga", "High Timbale", "Low Timbale",
+    "High Agogo", "Low Agogo", "Cabasa", "Maracas",
+    "Short Whistle", "Long Whistle", "Short Guiro", "Long Guiro",
+    "Claves", "Hi Wood Block", "Low Wood Block", "Mute Cuica",
+    "Open Cuica", "Mute Triangle", "Open Triangle",
+];
+
+/// The key of the first General MIDI percussion sound.
+const FIRST_DRUM_KEY: u8 = 35;
+
+/// Returns the General MIDI percussion sound for a key on the percussion channel.
+pub fn drum_name(key: u8) -> Option<&'static str> {
+    if key < FIRST_DRUM_KEY {
+        return None;
+    }
+    return GM_DRUM_NAMES.get((key - FIRST_DRUM_KEY) as usize).copied();
+}
+
+/// Determines if a midi track is a percussion track.
+/// 
+/// A track is a percussion track if it has notes and all of them are on the percussion channel.
+pub fn is_percussion_track(track: &[midly::TrackEvent]) -> bool {
+    let mut has_notes = false;
+    for event in track {
+        if let midly::TrackEventKind::Midi {
+            channel,
+            message: midly::MidiMessage::NoteOn { key: _, vel: _ }
+        } = event.kind {
+            if channel != PERCUSSION_CHANNEL {
+                return false;
+            }
+            has_notes = true;
+        }
+    }
+    return has_notes;
+}
+
+/// Returns the General MIDI instrument name for a program number.
+/// 
+/// Program numbers above 127 are not valid and return `None`.
+pub fn instrument_name(program: u8) -> Option<&'static str> {
+    return GM_INSTRUMENT_NAMES.get(program as usize).copied();
+}
+
+/// Gets the program number from the first `ProgramChange` message in a midi track.
+pub fn get_program(track: &[midly::TrackEvent]) -> Option<u8> {
+    for event in track {
+        if let midly::TrackEventKind::Midi {
+            channel: _,
+            message: midly::MidiMessage::ProgramChange { program }
+        } = event.kind {
+            return Some(program.into());
+        }
+    }
+    return None;
+}
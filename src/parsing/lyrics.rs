@@ -0,0 +1,84 @@
+use crate::parsing::add_delta;
+use crate::parsing::symbols::Lyric;
+use crate::parsing::symbols::NoteModifier;
+use crate::parsing::symbols::NoteWrapper;
+
+/// Returns all the lyric events in a midi track, in the order they occur.
+pub fn get_lyrics(track: &[midly::TrackEvent]) -> Vec<Lyric> {
+    let mut lyrics = Vec::new();
+    let mut cur_time: u32 = 0;
+    for event in track {
+        cur_time = add_delta(cur_time, event.delta);
+        if let midly::TrackEventKind::Meta(midly::MetaMessage::Lyric(s)) = event.kind {
+            let text = String::from_utf8_lossy(s).to_string();
+            if !text.trim().is_empty() {
+                lyrics.push(Lyric { text, tick: cur_time });
+            }
+        }
+    }
+    return lyrics;
+}
+
+/// Attaches every lyric to the note it coincides with.
+/// 
+/// A lyric belongs to the note that is sounding on the lyric's tick. Lyrics that fall on a rest
+/// are attached to the next note, and several lyrics that fall on the same note are joined.
+pub fn attach_lyrics(
+    notes: &mut [NoteWrapper],
+    lyrics: &[Lyric],
+    beat_type: u8,
+    ticks_per_beat: f32
+) {
+    let mut lyrics = lyrics.iter().peekable();
+    let mut beat = 0.0;
+    let mut pending: Option<String> = None;
+    for wrapper in notes {
+        let length = wrapper.beat_count(beat_type);
+        let end_tick = ((beat + length) * ticks_per_beat).round() as u32;
+        while let Some(lyric) = lyrics.next_if(|l| l.tick < end_tick) {
+            let offset = lyric.tick as f32 / ticks_per_beat - beat;
+            let text = match pending.take() {
+                Some(p) => p + &lyric.text,
+                None => lyric.text.clone(),
+            };
+            if let Some(rest) = attach_lyric(wrapper, offset.max(0.0), length, text) {
+                pending = Some(rest);
+            }
+        }
+        if let Some(text) = pending.take() {
+            if let Some(rest) = attach_lyric(wrapper, 0.0, length, text) {
+                pending = Some(rest);
+            }
+        }
+        beat += length;
+    }
+}
+
+/// Attaches a lyric to the note in `wrapper` that is sounding `offset` beats after the wrapper
+/// starts. Returns the lyric back if `wrapper` is a rest.
+fn attach_lyric(
+    wrapper: &mut NoteWrapper,
+    offset: f32,
+    length: f32,
+    text: String
+) -> Option<String> {
+    let target = match wrapper {
+        NoteWrapper::Rest(_) => return Some(text),
+        NoteWrapper::ModifiedNote(NoteModifier::Triplet(tr)) if !tr.is_empty() => {
+            let index = (offset / (length / tr.len() as f32)) as usize;
+            let len = tr.len();
+            &mut tr[index.min(len - 1)]
+        },
+        _ => wrapper,
+    };
+    match target.notes_mut().into_iter().find(|n| !n.is_rest()) {
+        Some(note) => {
+            note.lyric = match note.lyric.take() {
+                Some(existing) => Some(existing + &text),
+                None => Some(text),
+            };
+            return None;
+        },
+        None => return Some(text),
+    }
+}
@@ -0,0 +1,94 @@
+use crate::parsing::duration::DEFAULT_DURATION_PRECISION;
+use crate::parsing::symbols::TimeSignature;
+
+/// Where a beat falls in the bars and beats of a piece.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct MetricPosition {
+    /// The measure, counting from 1.
+    pub measure: u32,
+    /// The beat of the time signature within the measure, counting from 1, e.g. 1 to 6 in 6/8
+    /// time.
+    pub beat: u32,
+    /// How far into the beat the position falls, from 0 (on the beat) up to 1.
+    pub subdivision: f32,
+}
+
+/// Finds the measure a beat falls in.
+/// 
+/// Returns the measure number (starting from 1) and the beat within that measure (starting from
+/// 0). `time_signatures` must be sorted by their time of occurance. Beats are measured in units of
+/// `ticks_per_beat` ticks.
+pub fn measure_at(time_signatures: &[TimeSignature], ticks_per_beat: f32, beat: f32) -> (u32, f32) {
+    let mut measure: u32 = 1;
+    let mut cur_beat = 0.0;
+    for (i, time_signature) in time_signatures.iter().enumerate() {
+        let beats_per_measure = time_signature.beats_per_measure();
+        let next_change = match time_signatures.get(i + 1) {
+            Some(next) => next.time_of_occurance as f32 / ticks_per_beat,
+            None => f32::INFINITY,
+        };
+        if beat < next_change {
+            let measures = ((beat - cur_beat) / beats_per_measure + 1e-4).floor();
+            let beat_in_measure = beat - cur_beat - measures * beats_per_measure;
+            return (measure + measures as u32, beat_in_measure.max(0.0));
+        }
+        let measures = ((next_change - cur_beat) / beats_per_measure - 1e-4).ceil().max(0.0);
+        measure += measures as u32;
+        cur_beat += measures * beats_per_measure;
+    }
+    return (measure, beat - cur_beat);
+}
+
+/// Finds the measure, beat of the time signature, and subdivision of that beat a beat falls in.
+/// 
+/// `beat` is counted from the start of the piece, as in `measure_at`.
+/// `time_signatures` must be sorted by their time of occurance.
+pub fn metric_position(
+    time_signatures: &[TimeSignature],
+    ticks_per_beat: f32,
+    beat: f32
+) -> MetricPosition {
+    let (measure, beats) = measure_at(time_signatures, ticks_per_beat, beat);
+    // Positions a rounding error short of a beat are on that beat.
+    let whole_beats = (beats + 1e-4).floor();
+    return MetricPosition {
+        measure,
+        beat: whole_beats as u32 + 1,
+        subdivision: (beats - whole_beats).max(0.0),
+    };
+}
+
+/// Returns the beat on which each measure starts, up to and including `end_beat`.
+/// 
+/// `time_signatures` must be sorted by their time of occurance.
+pub fn measure_starts(
+    time_signatures: &[TimeSignature],
+    ticks_per_beat: f32,
+    end_beat: f32
+) -> Vec<f32> {
+    let mut starts = Vec::new();
+    let mut cur_beat = 0.0;
+    let mut i = 0;
+    while cur_beat <= end_beat + 1e-4 {
+        starts.push(cur_beat);
+        while i + 1 < time_signatures.len()
+            && time_signatures[i + 1].time_of_occurance as f32 / ticks_per_beat <= cur_beat + 1e-4 {
+            i += 1;
+        }
+        let beats_per_measure = match time_signatures.get(i) {
+            Some(time_signature) => time_signature.beats_per_measure(),
+            None => 4.0,
+        };
+        cur_beat += beats_per_measure;
+    }
+    return starts;
+}
+
+/// Returns how far, in beats, the end of a track may be from a bar line and still be treated as
+/// on it.
+/// 
+/// This is half of the default parsing precision, so whatever is left over from the timing of the
+/// file, like an end of track event a tick after the last note, isn't mistaken for a measure.
+pub fn barline_tolerance(beat_type: u8) -> f32 {
+    return DEFAULT_DURATION_PRECISION.get_beat_count(beat_type) / 2.0;
+}
@@ -0,0 +1,73 @@
+use crate::parsing::TrackKind;
+use crate::parsing::add_delta;
+use crate::parsing::classify_track;
+
+/// Merges the note events of every note track into a single track.
+/// 
+/// Notes from different tracks rarely start on exactly the same tick in real exports, so any note
+/// that starts within `window` ticks of the first note in a group is moved onto that note's onset.
+/// This lets the quantizer detect chords that are spread across tracks.
+pub fn merge_tracks<'a>(
+    tracks: &[Vec<midly::TrackEvent<'a>>],
+    window: u32
+) -> Vec<midly::TrackEvent<'a>> {
+    let mut events: Vec<(u32, midly::TrackEventKind<'a>)> = Vec::new();
+    for track in tracks {
+        if classify_track(track) != TrackKind::Notes {
+            continue;
+        }
+        let mut cur_time: u32 = 0;
+        for event in track {
+            cur_time = add_delta(cur_time, event.delta);
+            if let midly::TrackEventKind::Midi { channel: _, message: _ } = event.kind {
+                events.push((cur_time, event.kind));
+            }
+        }
+    }
+    events.sort_by_key(|(time, _)| *time);
+
+    let mut group_onset: Option<u32> = None;
+    for (time, kind) in &mut events {
+        if let midly::TrackEventKind::Midi {
+            channel: _,
+            message: midly::MidiMessage::NoteOn { key: _, vel }
+        } = kind {
+            if *vel == 0 {
+                continue;
+            }
+            match group_onset {
+                Some(onset) if *time - onset <= window => *time = onset,
+                _ => group_onset = Some(*time),
+            }
+        }
+    }
+    events.sort_by_key(|(time, _)| *time);
+
+    let mut merged = Vec::new();
+    let mut prev_time: u32 = 0;
+    for (time, kind) in events {
+        merged.push(midly::TrackEvent {
+            delta: (time - prev_time).into(),
+            kind,
+        });
+        prev_time = time;
+    }
+    merged.push(midly::TrackEvent {
+        delta: 0.into(),
+        kind: midly::TrackEventKind::Meta(midly::MetaMessage::EndOfTrack),
+    });
+    return merged;
+}
+
+/// Gets the length of a beat in microseconds from the first tempo event in a track.
+/// 
+/// If the track has no tempo event, 120 beats per minute is assumed as the midi specification
+/// requires.
+pub fn get_microseconds_per_beat(track: &[midly::TrackEvent]) -> u32 {
+    for event in track {
+        if let midly::TrackEventKind::Meta(midly::MetaMessage::Tempo(tempo)) = event.kind {
+            return tempo.into();
+        }
+    }
+    return 500000;
+}
@@ -0,0 +1,85 @@
+/// The layout of the tracks in a standard midi file.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
+pub enum SmfFormat {
+    /// Format 0: a single track holding every channel.
+    SingleTrack,
+    /// Format 1: several tracks that play at the same time.
+    Parallel,
+    /// Format 2: several independent sequences.
+    Sequential,
+}
+
+impl SmfFormat {
+    /// Converts the enum to a string.
+    pub fn to_string(&self) -> &str {
+        match self {
+            SmfFormat::SingleTrack => return "single track",
+            SmfFormat::Parallel => return "parallel",
+            SmfFormat::Sequential => return "sequential",
+        }
+    }
+}
+
+/// File-level information about a midi file.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
+pub struct MidiMetadata {
+    /// The format of the file.
+    pub format: SmfFormat,
+    /// The number of tracks in the file, including tracks without notes.
+    pub track_count: usize,
+    /// The name of the sequence, taken from the `TrackName` event of the first track.
+    pub sequence_name: Option<String>,
+    /// The copyright notice of the file, if there is one.
+    pub copyright: Option<String>,
+    /// Every `Text` event in the file, in the order they occur.
+    pub text: Vec<String>,
+}
+
+/// Gathers the file-level information of a midi file.
+pub fn get_metadata(smf: &midly::Smf) -> MidiMetadata {
+    let format = match smf.header.format {
+        midly::Format::SingleTrack => SmfFormat::SingleTrack,
+        midly::Format::Parallel => SmfFormat::Parallel,
+        midly::Format::Sequential => SmfFormat::Sequential,
+    };
+    let mut metadata = MidiMetadata {
+        format,
+        track_count: smf.tracks.len(),
+        sequence_name: None,
+        copyright: None,
+        text: Vec::new(),
+    };
+    for (index, track) in smf.tracks.iter().enumerate() {
+        for event in track {
+            if let midly::TrackEventKind::Meta(message) = event.kind {
+                match message {
+                    midly::MetaMessage::TrackName(s)
+                        if index == 0 && metadata.sequence_name.is_none() => {
+                        metadata.sequence_name = get_text(s);
+                    },
+                    midly::MetaMessage::Copyright(s) if metadata.copyright.is_none() => {
+                        metadata.copyright = get_text(s);
+                    },
+                    midly::MetaMessage::Text(s) => {
+                        if let Some(text) = get_text(s) {
+                            metadata.text.push(text);
+                        }
+                    },
+                    _ => (),
+                }
+            }
+        }
+    }
+    return metadata;
+}
+
+/// Converts the raw bytes of a text event to a string, ignoring empty text.
+fn get_text(raw: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(raw).trim().to_string();
+    if text.is_empty() {
+        return None;
+    }
+    return Some(text);
+}
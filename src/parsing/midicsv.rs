@@ -0,0 +1,423 @@
+use crate::error::ParseError;
+use crate::parsing::events::Event;
+use crate::parsing::events::EventFile;
+use crate::parsing::events::EventKind;
+use crate::parsing::events::TextKind;
+use crate::parsing::metadata::SmfFormat;
+use std::str::FromStr;
+
+/// Writes an `EventFile` in the midicsv text format.
+///
+/// Every track ends with an `End_track` record, as midicsv requires, even if the track has no
+/// `EndOfTrack` event.
+pub fn write_midicsv(file: &EventFile) -> String {
+    let format = match file.format {
+        SmfFormat::SingleTrack => 0,
+        SmfFormat::Parallel => 1,
+        SmfFormat::Sequential => 2,
+    };
+    let mut csv = format!(
+        "0, 0, Header, {}, {}, {}\n",
+        format,
+        file.tracks.len(),
+        file.ticks_per_beat
+    );
+    for (index, track) in file.tracks.iter().enumerate() {
+        let number = index + 1;
+        csv.push_str(&format!("{}, 0, Start_track\n", number));
+        let mut ended = false;
+        for event in track {
+            if event.kind == EventKind::EndOfTrack {
+                ended = true;
+            }
+            csv.push_str(&format!("{}, {}, {}\n", number, event.tick, get_record(&event.kind)));
+        }
+        if !ended {
+            let tick = track.last().map_or(0, |e| e.tick);
+            csv.push_str(&format!("{}, {}, End_track\n", number, tick));
+        }
+    }
+    csv.push_str("0, 0, End_of_file\n");
+    return csv;
+}
+
+/// Returns the record type and parameters of an event, e.g. "Note_on_c, 0, 60, 100".
+fn get_record(kind: &EventKind) -> String {
+    match kind {
+        EventKind::NoteOff { channel, key, velocity } => {
+            return format!("Note_off_c, {}, {}, {}", channel, key, velocity);
+        },
+        EventKind::NoteOn { channel, key, velocity } => {
+            return format!("Note_on_c, {}, {}, {}", channel, key, velocity);
+        },
+        EventKind::PolyPressure { channel, key, value } => {
+            return format!("Poly_aftertouch_c, {}, {}, {}", channel, key, value);
+        },
+        EventKind::Controller { channel, controller, value } => {
+            return format!("Control_c, {}, {}, {}", channel, controller, value);
+        },
+        EventKind::ProgramChange { channel, program } => {
+            return format!("Program_c, {}, {}", channel, program);
+        },
+        EventKind::ChannelPressure { channel, value } => {
+            return format!("Channel_aftertouch_c, {}, {}", channel, value);
+        },
+        EventKind::PitchBend { channel, value } => {
+            return format!("Pitch_bend_c, {}, {}", channel, value);
+        },
+        EventKind::SysEx(data) => return format!("System_exclusive, {}", get_bytes(data)),
+        EventKind::Escape(data) => return format!("System_exclusive_packet, {}", get_bytes(data)),
+        EventKind::SequenceNumber(Some(n)) => return format!("Sequence_number, {}", n),
+        EventKind::SequenceNumber(None) => return String::from("Unknown_meta_event, 0, 0"),
+        EventKind::Text(kind, s) => {
+            let record = match kind {
+                TextKind::Text => "Text_t",
+                TextKind::Copyright => "Copyright_t",
+                TextKind::TrackName => "Title_t",
+                TextKind::InstrumentName => "Instrument_name_t",
+                TextKind::Lyric => "Lyric_t",
+                TextKind::Marker => "Marker_t",
+                TextKind::CuePoint => "Cue_point_t",
+            };
+            return format!("{}, {}", record, quote(s));
+        },
+        EventKind::ChannelPrefix(c) => return format!("Channel_prefix, {}", c),
+        EventKind::Port(p) => return format!("MIDI_port, {}", p),
+        EventKind::EndOfTrack => return String::from("End_track"),
+        EventKind::Tempo(t) => return format!("Tempo, {}", t),
+        EventKind::SmpteOffset(b) => {
+            return format!("SMPTE_offset, {}, {}, {}, {}, {}", b[0], b[1], b[2], b[3], b[4]);
+        },
+        EventKind::TimeSignature(n, d, c, b) => {
+            return format!("Time_signature, {}, {}, {}, {}", n, d, c, b);
+        },
+        EventKind::KeySignature(s, m) => {
+            return format!("Key_signature, {}, \"{}\"", s, if *m { "minor" } else { "major" });
+        },
+        EventKind::SequencerSpecific(data) => {
+            return format!("Sequencer_specific, {}", get_bytes(data));
+        },
+        EventKind::UnknownMeta(kind, data) => {
+            return format!("Unknown_meta_event, {}, {}", kind, get_bytes(data));
+        },
+    }
+}
+
+/// Formats raw bytes as a length followed by each byte, e.g. "2, 240, 247".
+fn get_bytes(data: &[u8]) -> String {
+    let mut fields = vec![data.len().to_string()];
+    fields.extend(data.iter().map(|b| b.to_string()));
+    return fields.join(", ");
+}
+
+/// Formats text as a quoted midicsv string.
+///
+/// Quotes are doubled, backslashes are doubled, and non-printable bytes are written as a
+/// backslash followed by three octal digits.
+fn quote(s: &[u8]) -> String {
+    let mut quoted = String::from("\"");
+    for &b in s {
+        match b {
+            b'"' => quoted.push_str("\"\""),
+            b'\\' => quoted.push_str("\\\\"),
+            0x20..=0x7E => quoted.push(b as char),
+            _ => quoted.push_str(&format!("\\{:03o}", b)),
+        }
+    }
+    quoted.push('"');
+    return quoted;
+}
+
+/// Reads an `EventFile` from the midicsv text format.
+///
+/// Blank lines and lines starting with `#` or `;` are ignored. Returns `ParseError::Csv` for the
+/// first line that can't be read, or if the Header's track count is more than 65535 or than the
+/// number of records in the file.
+pub fn read_midicsv(text: &str) -> Result<EventFile, ParseError> {
+    let mut file = EventFile {
+        format: SmfFormat::Parallel,
+        ticks_per_beat: 0,
+        tracks: Vec::new(),
+    };
+    let mut has_header = false;
+    let mut track_count: usize = 0;
+    // Every track starts with its own record, so a file can't hold more tracks than records.
+    let records = text.lines().filter(|l| is_record(l)).count();
+    for (index, raw_line) in text.lines().enumerate() {
+        let line = index + 1;
+        let trimmed = raw_line.trim();
+        if !is_record(trimmed) {
+            continue;
+        }
+        let fields = split_fields(trimmed, line)?;
+        if fields.len() < 3 {
+            return Err(csv_error(line, "expected a track, a time, and a record type"));
+        }
+        let track: usize = get_field(&fields, 0, line)?;
+        let tick: u32 = get_field(&fields, 1, line)?;
+        let record = fields[2].as_str();
+        let kind = match record {
+            "Header" => {
+                let format: u8 = get_field(&fields, 3, line)?;
+                file.format = match format {
+                    0 => SmfFormat::SingleTrack,
+                    1 => SmfFormat::Parallel,
+                    2 => SmfFormat::Sequential,
+                    _ => return Err(csv_error(line, "the format must be 0, 1, or 2")),
+                };
+                track_count = get_field(&fields, 4, line)?;
+                if track_count > u16::MAX as usize {
+                    return Err(csv_error(line, "the track count must be at most 65535"));
+                }
+                if track_count > records {
+                    let message = "the track count is more than the number of records";
+                    return Err(csv_error(line, message));
+                }
+                let division: i32 = get_field(&fields, 5, line)?;
+                if division <= 0 {
+                    return Err(ParseError::UnsupportedTiming);
+                }
+                file.ticks_per_beat = division.min(0x7FFF) as u16;
+                has_header = true;
+                continue;
+            },
+            "End_of_file" => continue,
+            "Start_track" => None,
+            _ => Some(get_event_kind(record, &fields, line)?),
+        };
+        if !has_header {
+            return Err(csv_error(line, "the first record must be a Header"));
+        }
+        if track == 0 {
+            return Err(csv_error(line, "tracks are numbered starting from 1"));
+        }
+        if track > track_count {
+            return Err(csv_error(line, "the track number is more than the Header's track count"));
+        }
+        while file.tracks.len() < track {
+            file.tracks.push(Vec::new());
+        }
+        let Some(kind) = kind else {
+            continue;
+        };
+        file.tracks[track - 1].push(Event { tick, kind });
+    }
+    if !has_header {
+        return Err(csv_error(0, "the file has no Header record"));
+    }
+    for track in file.tracks.iter_mut() {
+        track.sort_by_key(|e| e.tick);
+    }
+    return Ok(file);
+}
+
+/// Returns whether a line of a midicsv file holds a record, i.e. it isn't blank or a comment.
+fn is_record(line: &str) -> bool {
+    let trimmed = line.trim();
+    return !trimmed.is_empty() && !trimmed.starts_with('#') && !trimmed.starts_with(';');
+}
+
+/// Reads the event described by a midicsv record.
+fn get_event_kind(record: &str, fields: &[String], line: usize) -> Result<EventKind, ParseError> {
+    let text = |kind| -> Result<EventKind, ParseError> {
+        return Ok(EventKind::Text(kind, unquote(get_raw_field(fields, 3, line)?, line)?));
+    };
+    let kind = match record {
+        "Note_off_c" => EventKind::NoteOff {
+            channel: get_channel(fields, line)?,
+            key: get_data_byte(fields, 4, line)?,
+            velocity: get_data_byte(fields, 5, line)?,
+        },
+        "Note_on_c" => EventKind::NoteOn {
+            channel: get_channel(fields, line)?,
+            key: get_data_byte(fields, 4, line)?,
+            velocity: get_data_byte(fields, 5, line)?,
+        },
+        "Poly_aftertouch_c" => EventKind::PolyPressure {
+            channel: get_channel(fields, line)?,
+            key: get_data_byte(fields, 4, line)?,
+            value: get_data_byte(fields, 5, line)?,
+        },
+        "Control_c" => EventKind::Controller {
+            channel: get_channel(fields, line)?,
+            controller: get_data_byte(fields, 4, line)?,
+            value: get_data_byte(fields, 5, line)?,
+        },
+        "Program_c" => EventKind::ProgramChange {
+            channel: get_channel(fields, line)?,
+            program: get_data_byte(fields, 4, line)?,
+        },
+        "Channel_aftertouch_c" => EventKind::ChannelPressure {
+            channel: get_channel(fields, line)?,
+            value: get_data_byte(fields, 4, line)?,
+        },
+        "Pitch_bend_c" => {
+            let value: u16 = get_field(fields, 4, line)?;
+            if value > 0x3FFF {
+                return Err(csv_error(line, "pitch bends must be between 0 and 16383"));
+            }
+            EventKind::PitchBend { channel: get_channel(fields, line)?, value }
+        },
+        "System_exclusive" => EventKind::SysEx(get_byte_list(fields, 3, line)?),
+        "System_exclusive_packet" => EventKind::Escape(get_byte_list(fields, 3, line)?),
+        "Sequence_number" => EventKind::SequenceNumber(Some(get_field(fields, 3, line)?)),
+        "Text_t" => return text(TextKind::Text),
+        "Copyright_t" => return text(TextKind::Copyright),
+        "Title_t" => return text(TextKind::TrackName),
+        "Instrument_name_t" => return text(TextKind::InstrumentName),
+        "Lyric_t" => return text(TextKind::Lyric),
+        "Marker_t" => return text(TextKind::Marker),
+        "Cue_point_t" => return text(TextKind::CuePoint),
+        "Channel_prefix" => EventKind::ChannelPrefix(get_field(fields, 3, line)?),
+        "MIDI_port" => EventKind::Port(get_field(fields, 3, line)?),
+        "End_track" => EventKind::EndOfTrack,
+        "Tempo" => EventKind::Tempo(get_field(fields, 3, line)?),
+        "SMPTE_offset" => EventKind::SmpteOffset([
+            get_field(fields, 3, line)?,
+            get_field(fields, 4, line)?,
+            get_field(fields, 5, line)?,
+            get_field(fields, 6, line)?,
+            get_field(fields, 7, line)?,
+        ]),
+        "Time_signature" => EventKind::TimeSignature(
+            get_field(fields, 3, line)?,
+            get_field(fields, 4, line)?,
+            get_field(fields, 5, line)?,
+            get_field(fields, 6, line)?,
+        ),
+        "Key_signature" => {
+            let mode = get_raw_field(fields, 4, line)?.trim_matches('"').to_lowercase();
+            let minor = match mode.as_str() {
+                "major" => false,
+                "minor" => true,
+                _ => return Err(csv_error(line, "the mode must be \"major\" or \"minor\"")),
+            };
+            EventKind::KeySignature(get_field(fields, 3, line)?, minor)
+        },
+        "Sequencer_specific" => EventKind::SequencerSpecific(get_byte_list(fields, 3, line)?),
+        "Unknown_meta_event" => {
+            EventKind::UnknownMeta(get_field(fields, 3, line)?, get_byte_list(fields, 4, line)?)
+        },
+        _ => return Err(csv_error(line, &format!("unknown record type \"{}\"", record))),
+    };
+    return Ok(kind);
+}
+
+/// Splits a midicsv line into its fields, keeping quoted strings (including their quotes) whole.
+fn split_fields(line: &str, line_number: usize) -> Result<Vec<String>, ParseError> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push_str("\"\"");
+                chars.next();
+            },
+            '"' => {
+                in_quotes = !in_quotes;
+                field.push(c);
+            },
+            ',' if !in_quotes => {
+                fields.push(field.trim().to_string());
+                field.clear();
+            },
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(csv_error(line_number, "unterminated string"));
+    }
+    fields.push(field.trim().to_string());
+    return Ok(fields);
+}
+
+/// Reverses the escaping done by `quote`.
+fn unquote(field: &str, line: usize) -> Result<Vec<u8>, ParseError> {
+    let inner = match field.strip_prefix('"').and_then(|f| f.strip_suffix('"')) {
+        Some(inner) => inner,
+        None => return Err(csv_error(line, "expected a quoted string")),
+    };
+    let mut bytes = Vec::new();
+    let raw = inner.as_bytes();
+    let mut i = 0;
+    while i < raw.len() {
+        match raw[i] {
+            b'"' => {
+                bytes.push(b'"');
+                i += 2;
+            },
+            b'\\' if raw.get(i + 1) == Some(&b'\\') => {
+                bytes.push(b'\\');
+                i += 2;
+            },
+            b'\\' => {
+                let octal = std::str::from_utf8(raw.get(i + 1..i + 4).unwrap_or(&[]))
+                    .ok()
+                    .and_then(|o| u8::from_str_radix(o, 8).ok());
+                match octal {
+                    Some(b) => bytes.push(b),
+                    None => return Err(csv_error(line, "invalid escape in string")),
+                }
+                i += 4;
+            },
+            b => {
+                bytes.push(b);
+                i += 1;
+            },
+        }
+    }
+    return Ok(bytes);
+}
+
+/// Returns the field at `index`, or an error if the record is too short.
+fn get_raw_field(fields: &[String], index: usize, line: usize) -> Result<&str, ParseError> {
+    match fields.get(index) {
+        Some(field) => return Ok(field),
+        None => return Err(csv_error(line, &format!("expected at least {} fields", index + 1))),
+    }
+}
+
+/// Parses the field at `index` as a number.
+fn get_field<T: FromStr>(fields: &[String], index: usize, line: usize) -> Result<T, ParseError> {
+    let field = get_raw_field(fields, index, line)?;
+    match field.parse() {
+        Ok(value) => return Ok(value),
+        Err(_) => return Err(csv_error(line, &format!("\"{}\" is not a valid number", field))),
+    }
+}
+
+/// Parses the channel of a channel message, which is always the fourth field.
+fn get_channel(fields: &[String], line: usize) -> Result<u8, ParseError> {
+    let channel: u8 = get_field(fields, 3, line)?;
+    if channel > 15 {
+        return Err(csv_error(line, "channels must be between 0 and 15"));
+    }
+    return Ok(channel);
+}
+
+/// Parses a seven bit data byte, such as a key or velocity.
+fn get_data_byte(fields: &[String], index: usize, line: usize) -> Result<u8, ParseError> {
+    let value: u8 = get_field(fields, index, line)?;
+    if value > 127 {
+        return Err(csv_error(line, "data bytes must be between 0 and 127"));
+    }
+    return Ok(value);
+}
+
+/// Parses a length followed by that many bytes, starting at `index`.
+fn get_byte_list(fields: &[String], index: usize, line: usize) -> Result<Vec<u8>, ParseError> {
+    let length: usize = get_field(fields, index, line)?;
+    // The length comes from the file, so it can't reserve more than the line holds.
+    let mut bytes = Vec::with_capacity(length.min(fields.len().saturating_sub(index + 1)));
+    for i in 0..length {
+        bytes.push(get_field(fields, index + 1 + i, line)?);
+    }
+    return Ok(bytes);
+}
+
+/// A helper function for building a `ParseError::Csv`.
+fn csv_error(line: usize, message: &str) -> ParseError {
+    return ParseError::Csv { line, message: String::from(message) };
+}
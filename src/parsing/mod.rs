@@ -1,71 +1,805 @@
+pub mod beaming;
+pub mod cleanup;
 pub mod duration;
+pub mod dynamics;
+pub mod events;
+pub mod expression;
+pub mod general_midi;
+pub mod measure;
+pub mod lyrics;
+pub mod merge;
+pub mod metadata;
+pub mod midicsv;
+pub mod notation;
+pub mod options;
+pub mod pitch;
 pub mod symbols;
+pub mod tempo;
+pub mod validate;
 
 use duration::NoteDuration;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::time::Instant;
 use crate::Midi;
+use crate::analysis::Difficulty;
+use crate::analysis::Hairpin;
+use crate::analysis::HairpinOptions;
+use crate::analysis::Ostinato;
+use crate::analysis::OstinatoOptions;
+use crate::analysis::PatternMatch;
+use crate::analysis::PatternOptions;
+use crate::analysis::PolyphonyProfile;
+use crate::analysis::TrackStats;
+use crate::error::ParseError;
+use crate::flat::FlatTrack;
+use crate::parsing::cleanup::ChannelFilter;
 use crate::parsing::duration::DurationType;
+use crate::parsing::dynamics::AccentOptions;
+use crate::parsing::dynamics::DynamicThresholds;
 use crate::parsing::duration::POSSIBLE_NOTE_LENGTHS;
+use crate::parsing::events::Event;
+use crate::parsing::events::EventFile;
+use crate::parsing::events::EventKind;
+use crate::parsing::events::TextKind;
+use crate::parsing::expression::ControllerEvent;
+use crate::parsing::expression::PressureEvent;
+use crate::parsing::measure::MetricPosition;
+use crate::parsing::metadata::SmfFormat;
+use crate::parsing::options::Limit;
+use crate::parsing::options::OverlapPolicy;
+use crate::parsing::options::ParseOptions;
+use crate::parsing::options::ResourceLimits;
+use crate::parsing::pitch::Scale;
+use crate::parsing::options::SimultaneityWindow;
+use crate::parsing::symbols::KeySignature;
+use crate::parsing::symbols::Lyric;
+use crate::parsing::symbols::Marker;
+use crate::parsing::symbols::Note;
 use crate::parsing::symbols::NoteModifier;
 use crate::parsing::symbols::NoteWrapper;
+use crate::parsing::symbols::SysexEvent;
+use crate::parsing::symbols::TimedNote;
 use crate::parsing::symbols::TimeSignature;
+use crate::score::StaffLayout;
+use crate::transform::FermataOptions;
 use std::collections::VecDeque;
+use std::ops::RangeInclusive;
 
 /// Represents the content of a midi track.
+/// 
+/// Lengths are counted in beats of the piece's time signature, e.g. eighth notes in 6/8. The
+/// methods that read note durations as beats take the `beat_type` of that time signature, which
+/// can be taken from `Midi::beat_type`. Tracks built with `Track::new`,
+/// `Track::from_notes`, or `Track::from_notation` aren't in any piece, so they count quarter note
+/// beats, as in 4/4 time.
 #[derive(Clone)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
 pub struct Track {
     /// The name of the track.
+    /// 
+    /// This is the first of `track_name`, `instrument_name`, and `instrument` that the track has.
+    /// Tracks with none of these are named after their position in the file, e.g. "Track 2", so
+    /// names stay the same even when other tracks are skipped.
     pub name: String,
+    /// The position of the track in the midi file.
+    /// 
+    /// Format 0 files are split into a track for each channel before they're parsed, following
+    /// a first track of everything else, and `index` is the position in the split file.
+    pub index: usize,
+    /// The title of the track from its `TrackName` event, if there is one.
+    pub track_name: Option<String>,
+    /// The instrument of the track from its `InstrumentName` event, if there is one.
+    pub instrument_name: Option<String>,
+    /// The program number from the first `ProgramChange` message in the track, if there is one.
+    pub program: Option<u8>,
+    /// The General MIDI instrument name for `program`, if there is one.
+    pub instrument: Option<String>,
+    /// Whether the track holds drum hits on the General MIDI percussion channel.
+    /// 
+    /// The values of notes in percussion tracks are drum sounds rather than pitches. See
+    /// `Note::drum_name`.
+    pub percussion: bool,
+    /// What kind of content the track holds.
+    pub kind: TrackKind,
+    /// The tick on which the track ends, taken from its `EndOfTrack` event.
+    pub end_of_track: u32,
+    /// The length of the track in beats.
+    /// 
+    /// This is the time of its `EndOfTrack` event, or the length of the score if the track was
+    /// padded.
+    pub length: f32,
+    /// The length of the track in beats before its final measure was padded with rests, if it
+    /// was. See `ParseOptions::pad_final_measure`.
+    pub unpadded_length: Option<f32>,
+    /// Whether any notes in the track extend past its `EndOfTrack` event.
+    pub truncated: bool,
     /// A vector of all the notes played in the track.
-    pub notes: Vec<NoteWrapper>
+    pub notes: Vec<NoteWrapper>,
+    /// All the lyric events in the track.
+    pub lyric_events: Vec<Lyric>,
+    /// All the changes to the volume, pan, and expression controllers in the track.
+    pub controller_events: Vec<ControllerEvent>,
+    /// All the channel pressure and polyphonic key pressure changes in the track.
+    pub pressure_events: Vec<PressureEvent>,
+}
+
+impl Track {
+    /// Creates a track named `name` that holds `notes` and nothing else.
+    pub fn new(name: String, notes: Vec<NoteWrapper>) -> Track {
+        let length = notes.iter().map(|n| n.beat_count(2)).sum();
+        let has_notes = notes.iter().flat_map(|n| n.notes()).any(|n| !n.is_rest());
+        return Track {
+            name,
+            index: 0,
+            track_name: None,
+            instrument_name: None,
+            program: None,
+            instrument: None,
+            percussion: false,
+            kind: if has_notes { TrackKind::Notes } else { TrackKind::Empty },
+            end_of_track: 0,
+            length,
+            unpadded_length: None,
+            truncated: false,
+            notes,
+            lyric_events: Vec::new(),
+            controller_events: Vec::new(),
+            pressure_events: Vec::new(),
+        };
+    }
+
+    /// Creates a track named `name` from notes that are played one after another.
+    /// 
+    /// Rests are kept as gaps between the notes, and the notes go through the same quantizer as
+    /// a parsed midi file.
+    pub fn from_notes(name: String, notes: Vec<Note>) -> Result<Track, ParseError> {
+        let mut onset = 0.0;
+        let mut timed_notes = Vec::new();
+        for note in notes {
+            let length = note.duration.get_beat_count(2);
+            if let Some(value) = note.value() {
+                let velocity = note.velocity;
+                timed_notes.push(TimedNote { value, velocity, onset, length });
+            }
+            onset += length;
+        }
+        return Track::from_timed_notes(name, &timed_notes, onset, &ParseOptions::new());
+    }
+
+    /// Creates a track named `name` that lasts `length` beats from notes with explicit start
+    /// times, such as those computed by a generative algorithm.
+    /// 
+    /// The notes go through the same quantizer as a parsed midi file using `options`, so notes
+    /// that start together are grouped as chords and lengths that can't be written as a single
+    /// duration are split into tied notes. The track is read in 4/4 time. The track selection,
+    /// channel filters, resource limits, and cancel token of `options` are ignored.
+    pub fn from_timed_notes(
+        name: String,
+        notes: &[TimedNote],
+        length: f32,
+        options: &ParseOptions
+    ) -> Result<Track, ParseError> {
+        let ticks_per_beat = 480.0;
+        let to_tick = |beat: f32| (beat.max(0.0) * ticks_per_beat).round() as u32;
+        let mut note_events = Vec::new();
+        for note in notes {
+            let (channel, key) = (0, note.value.min(127));
+            let on = EventKind::NoteOn { channel, key, velocity: note.velocity.clamp(1, 127) };
+            let off = EventKind::NoteOff { channel, key, velocity: 0 };
+            note_events.push((to_tick(note.onset), 1, on));
+            note_events.push((to_tick(note.onset + note.length), 0, off));
+        }
+        // Note offs are ordered before note ons on the same tick so repeated notes aren't cut off.
+        note_events.sort_by_key(|(tick, order, _)| (*tick, *order));
+        let end = note_events.last().map_or(0, |(tick, _, _)| *tick).max(to_tick(length));
+
+        let mut events = vec![Event {
+            tick: 0,
+            kind: EventKind::Text(TextKind::TrackName, name.clone().into_bytes()),
+        }];
+        events.extend(note_events.into_iter().map(|(tick, _, kind)| Event { tick, kind }));
+        events.push(Event { tick: end, kind: EventKind::EndOfTrack });
+        let file = EventFile {
+            format: SmfFormat::Parallel,
+            ticks_per_beat: ticks_per_beat as u16,
+            tracks: vec![events],
+        };
+
+        // Options that pick which tracks, channels, or how much of a file is parsed don't apply
+        // to the single track built here, and the track is always padded out to `length`.
+        let mut options = options
+            .clone()
+            .check_barlines(false)
+            .skip_empty_tracks(false)
+            .pad_to_score_length(true);
+        options.tracks = None;
+        options.track_name_filter = None;
+        options.channels = ChannelFilter::All;
+        options.cancel_token = None;
+        options.limits = ResourceLimits::new();
+        let midi = Midi::parse_events(&file, &options)?;
+        let mut track = match midi.tracks.into_iter().next() {
+            Some(track) => track,
+            None => Track::new(name.clone(), Vec::new()),
+        };
+        track.name = name;
+        return Ok(track);
+    }
+
+    /// Creates a track named `name` from music written in the text notation, e.g.
+    /// `"C4:q D4:e E4:e | F4:h r:q"`. See `parsing::notation` for the full notation.
+    pub fn from_notation(name: String, text: &str) -> Result<Track, ParseError> {
+        return Ok(Track::new(name, notation::parse_notation(text)?));
+    }
+
+    /// Saves the track on its own as a playable standard midi file at `dir`, played at `bpm`
+    /// beats per minute in `time_signature`.
+    /// 
+    /// Note durations are read in `time_signature`, so it should be the time signature of the
+    /// piece the track came from, e.g. the first of `Midi::time_signatures`.
+    pub fn write_smf(
+        &self,
+        dir: String,
+        bpm: u32,
+        time_signature: &TimeSignature
+    ) -> Result<(), std::io::Error> {
+        let events = crate::export::smf::track_to_events(self, bpm, time_signature);
+        return std::fs::write(dir, events.to_smf_bytes());
+    }
+
+    /// Converts the track to CSV with one row per note. See `Midi::to_csv` for the columns.
+    /// 
+    /// `time_signatures` and `ticks_per_beat` are used to place each note in its measure, and
+    /// can be taken from `Midi::time_signatures` and `Midi::ticks_per_beat`.
+    pub fn to_csv(&self, time_signatures: &[TimeSignature], ticks_per_beat: f32) -> String {
+        return crate::export::csv::track_to_csv(self, time_signatures, ticks_per_beat);
+    }
+
+    /// Shifts every note up by `semitones`, or down if it's negative. Notes that would leave the
+    /// midi range are moved by octaves until they're back in it.
+    /// 
+    /// Percussion tracks are left as they are, since their notes are drum sounds.
+    pub fn transpose(&mut self, semitones: i8) {
+        if self.percussion {
+            return;
+        }
+        for note in self.notes.iter_mut().flat_map(|n| n.notes_mut()) {
+            let Some(pitch) = &mut note.pitch else {
+                continue;
+            };
+            let mut value = pitch.value as i16 + semitones as i16;
+            while value > 127 {
+                value -= 12;
+            }
+            while value < 0 {
+                value += 12;
+            }
+            let shift = value - pitch.value as i16;
+            pitch.set_value(value as u8);
+            if let Some(glissando) = &mut note.glissando {
+                let shifted = |pitch: u8| (pitch as i16 + shift).clamp(0, 127) as u8;
+                glissando.start = shifted(glissando.start);
+                glissando.target = shifted(glissando.target);
+            }
+        }
+    }
+
+    /// Moves every pitch outside of `scale` to the nearest pitch inside of it, where `tonic` is the
+    /// pitch class of the scale's first degree (C is 0). Returns the number of notes moved.
+    /// 
+    /// Percussion tracks are left as they are. See `Scale::snap` for how ties are broken.
+    pub fn snap_to_scale(&mut self, tonic: u8, scale: Scale) -> usize {
+        return self.snap_notes(tonic, scale, 0.0);
+    }
+
+    /// Moves pitches outside of `scale` to the nearest pitch inside of it like
+    /// `Track::snap_to_scale`, but leaves notes written shorter than `threshold` alone so
+    /// chromatic passing and neighbor tones are kept.
+    pub fn snap_to_scale_with_threshold(
+        &mut self,
+        tonic: u8,
+        scale: Scale,
+        threshold: DurationType
+    ) -> usize {
+        return self.snap_notes(tonic, scale, threshold.get_beat_count(2));
+    }
+
+    /// A helper function for snapping the notes at least `threshold` beats long to a scale.
+    fn snap_notes(&mut self, tonic: u8, scale: Scale, threshold: f32) -> usize {
+        if self.percussion {
+            return 0;
+        }
+        let mut moved = 0;
+        for note in self.notes.iter_mut().flat_map(|n| n.notes_mut()) {
+            if note.duration.get_beat_count(2) < threshold {
+                continue;
+            }
+            let Some(pitch) = &mut note.pitch else {
+                continue;
+            };
+            let value = scale.snap(pitch.value, tonic);
+            if value != pitch.value {
+                pitch.set_value(value);
+                moved += 1;
+            }
+        }
+        return moved;
+    }
+
+    /// Gives a fermata to the notes held far longer than the rest of the track, such as the
+    /// final note of a piece, and returns the number of notes or chords marked. Tied notes are
+    /// rewritten as a single note. See `transform::mark_fermatas`.
+    pub fn mark_fermatas(&mut self, options: &FermataOptions) -> usize {
+        return crate::transform::mark_fermatas(self, options);
+    }
+
+    /// Extends every note to the onset of the note after it, removing the rests left between
+    /// notes by detached playing. Rests before the first note and after the last are kept.
+    pub fn legato(&mut self) {
+        let last = self.notes.iter().rposition(|n| !crate::transform::is_rest(n, 2));
+        let end = last.map(|i| i + 1).unwrap_or(0);
+        let trailing = self.notes.split_off(end);
+        crate::transform::absorb_rests(self, f32::INFINITY, 2);
+        self.notes.extend(trailing);
+    }
+
+    /// Rescales the velocities of the notes so the softest note is played at the start of
+    /// `target_range` and the loudest at its end, e.g. `normalize_velocities(40..=110)`. If every
+    /// note has the same velocity, they're all moved to the middle of the range.
+    pub fn normalize_velocities(&mut self, target_range: RangeInclusive<u8>) {
+        let notes = || self.notes.iter().flat_map(|n| n.notes()).filter(|n| !n.is_rest());
+        let min = notes().map(|n| n.velocity).min().unwrap_or(0) as f32;
+        let max = notes().map(|n| n.velocity).max().unwrap_or(0) as f32;
+        let (start, end) = (*target_range.start() as f32, *target_range.end() as f32);
+        for note in self.notes.iter_mut().flat_map(|n| n.notes_mut()) {
+            if note.is_rest() {
+                continue;
+            }
+            let velocity = match max > min {
+                true => start + (note.velocity as f32 - min) / (max - min) * (end - start),
+                false => (start + end) / 2.0,
+            };
+            note.velocity = velocity.round() as u8;
+        }
+    }
+
+    /// Snaps the velocity of every note to the nearest of `levels` evenly spaced loudness levels,
+    /// the loudest of which is 127, e.g. 4 levels are 32, 64, 95, and 127.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if `levels` is 0.
+    pub fn quantize_velocities(&mut self, levels: u8) {
+        assert!(levels > 0, "there must be at least one velocity level");
+        let step = 127.0 / levels as f32;
+        for note in self.notes.iter_mut().flat_map(|n| n.notes_mut()) {
+            if note.is_rest() {
+                continue;
+            }
+            let level = (note.velocity as f32 / step).round().clamp(1.0, levels as f32);
+            note.velocity = (level * step).round() as u8;
+        }
+    }
+
+    /// Marks every note with the dynamic its velocity falls in, e.g. `Dynamic::MezzoForte` for
+    /// a velocity of 80 with the default thresholds. See `Note::dynamic`.
+    pub fn mark_dynamics(&mut self, thresholds: &DynamicThresholds) {
+        for note in self.notes.iter_mut().flat_map(|n| n.notes_mut()) {
+            if !note.is_rest() {
+                note.dynamic = Some(thresholds.dynamic(note.velocity));
+            }
+        }
+    }
+
+    /// Accents the notes played noticeably louder than the notes around them, and returns the
+    /// number of notes accented. See `dynamics::mark_accents`.
+    pub fn mark_accents(
+        &mut self,
+        time_signatures: &[TimeSignature],
+        ticks_per_beat: f32,
+        options: &AccentOptions
+    ) -> usize {
+        return dynamics::mark_accents(self, time_signatures, ticks_per_beat, options);
+    }
+
+    /// Beams the eighth and shorter notes of the track into groups, and returns the number of
+    /// groups. See `beaming::mark_beams`.
+    pub fn mark_beams(&mut self, time_signatures: &[TimeSignature], ticks_per_beat: f32) -> usize {
+        return beaming::mark_beams(self, time_signatures, ticks_per_beat);
+    }
+
+    /// Returns the note count, pitch range, durations, average velocity, and share of rests of
+    /// the track.
+    pub fn stats(&self, beat_type: u8) -> TrackStats {
+        return crate::analysis::track_stats(self, beat_type);
+    }
+
+    /// Returns the number of notes played on each pitch class, from C to B, e.g. for estimating
+    /// the key of the track. See `analysis::pitch_class_histogram`.
+    pub fn pitch_class_histogram(&self) -> [usize; 12] {
+        return crate::analysis::pitch_class_histogram(self);
+    }
+
+    /// Returns the number of written notes of each duration, from whole notes to hundred
+    /// twenty-eighth notes. See `analysis::duration_histogram`.
+    pub fn duration_histogram(&self) -> [usize; 8] {
+        return crate::analysis::duration_histogram(self);
+    }
+
+    /// Returns the number of onsets in each measure of the track, counting every note of a chord
+    /// if `count_chord_notes` is set. See `analysis::density_per_measure`.
+    /// 
+    /// `time_signatures` and `ticks_per_beat` are used to find the measures, and can be taken
+    /// from `Midi::time_signatures` and `Midi::ticks_per_beat`.
+    pub fn density_per_measure(
+        &self,
+        time_signatures: &[TimeSignature],
+        ticks_per_beat: f32,
+        count_chord_notes: bool
+    ) -> Vec<usize> {
+        return crate::analysis::density_per_measure(
+            self,
+            time_signatures,
+            ticks_per_beat,
+            count_chord_notes
+        );
+    }
+
+    /// Returns the measure, beat, and subdivision on which each note starts, including rests and
+    /// the pieces of tied notes, in the order `NoteWrapper::for_each_note` visits them. See
+    /// `measure::metric_position`.
+    /// 
+    /// `time_signatures` and `ticks_per_beat` are used to find the measures, and can be taken
+    /// from `Midi::time_signatures` and `Midi::ticks_per_beat`.
+    pub fn metric_positions(
+        &self,
+        time_signatures: &[TimeSignature],
+        ticks_per_beat: f32
+    ) -> Vec<MetricPosition> {
+        let beat_type = time_signatures.first().map(|t| t.beat_type).unwrap_or(2);
+        return self.flatten(beat_type).metric_positions(time_signatures, ticks_per_beat);
+    }
+
+    /// Returns the number of notes sounding at once over the course of the track, which tells
+    /// whether a monophonic instrument could play it.
+    pub fn polyphony_profile(&self, beat_type: u8) -> PolyphonyProfile {
+        return crate::analysis::polyphony_profile(self, beat_type);
+    }
+
+    /// Finds the crescendos and diminuendos of the track from the velocities of its notes. See
+    /// `analysis::hairpins`.
+    pub fn hairpins(&self, beat_type: u8, options: &HairpinOptions) -> Vec<Hairpin> {
+        return crate::analysis::hairpins(self, beat_type, options);
+    }
+
+    /// Finds the cells of notes, such as riffs and grooves, that the track repeats back to back,
+    /// with the number of times each is repeated. See `analysis::ostinatos`.
+    pub fn ostinatos(&self, beat_type: u8, options: &OstinatoOptions) -> Vec<Ostinato> {
+        return crate::analysis::ostinatos(self, beat_type, options);
+    }
+
+    /// Finds every occurrence of a short melodic pattern in the track, such as a theme, optionally
+    /// in other keys or rhythms. See `analysis::find_pattern`.
+    pub fn find_pattern(
+        &self,
+        pattern: &[Note],
+        options: &PatternOptions,
+        beat_type: u8
+    ) -> Vec<PatternMatch> {
+        return crate::analysis::find_pattern(self, pattern, options, beat_type);
+    }
+
+    /// Scores how alike the melodies of this track and `other` are, from 0 (nothing alike) to 1
+    /// (the same), e.g. to grade a transcription against a reference. See
+    /// `analysis::track_similarity`.
+    pub fn similarity(&self, other: &Track, beat_type: u8) -> f32 {
+        return crate::analysis::track_similarity(self, other, beat_type);
+    }
+
+    /// Rates how hard the track is to play at `bpm` beats per minute as a beginner,
+    /// intermediate, or advanced piece. See `analysis::difficulty`.
+    pub fn difficulty(&self, bpm: u32, beat_type: u8) -> Difficulty {
+        return crate::analysis::difficulty(self, bpm, beat_type);
+    }
+
+    /// Picks the clef, or the grand staff, the track is best written in from the range of its
+    /// pitches. See `score::infer_staff_layout`.
+    pub fn staff_layout(&self) -> StaffLayout {
+        return crate::score::infer_staff_layout(self);
+    }
+
+    /// Flattens the notes of the track into parallel vectors for analysis.
+    pub fn flatten(&self, beat_type: u8) -> FlatTrack {
+        return FlatTrack::new(self, beat_type);
+    }
+
+    /// Returns all the lyric events in the track, in the order they occur.
+    /// 
+    /// Each lyric is also attached to the note it coincides with. See `Note::lyric`.
+    pub fn lyrics(&self) -> &[Lyric] {
+        return &self.lyric_events;
+    }
+
+    /// Returns the values of a continuous controller (7 for volume, 10 for pan, or 11 for
+    /// expression) resampled onto the beat grid, as `(beat, value)` pairs.
+    /// 
+    /// There are `CONTROLLER_SAMPLES_PER_BEAT` samples per beat.
+    pub fn controller_curve(&self, controller: u8) -> Vec<(f32, u8)> {
+        return expression::resample_controller(
+            &self.controller_events,
+            controller,
+            self.length,
+            expression::CONTROLLER_SAMPLES_PER_BEAT
+        );
+    }
+
+    /// Returns the channel pressure (aftertouch) of the track resampled onto the beat grid, as
+    /// `(beat, value)` pairs.
+    /// 
+    /// Polyphonic key pressure is attached to the individual notes instead. See `Note::pressure`.
+    pub fn pressure_curve(&self) -> Vec<(f32, u8)> {
+        return expression::resample_pressure(
+            &self.pressure_events,
+            self.length,
+            expression::CONTROLLER_SAMPLES_PER_BEAT
+        );
+    }
+}
+
+/// Classifies a midi track by its content.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
+pub enum TrackKind {
+    /// The track contains notes.
+    Notes,
+    /// The track only contains tempo, time signature, or key signature events.
+    Conductor,
+    /// The track only contains channel events other than notes, such as control changes.
+    AutomationOnly,
+    /// The track contains nothing of musical significance.
+    Empty,
+}
+
+/// Reusable buffers for parsing tracks.
+/// 
+/// Parsing a track fills a grid of subdivisions and a queue of raw note data. Keeping these
+/// buffers between tracks (and between files, see `Parser`) avoids reallocating them every time.
+#[derive(Default)]
+pub(crate) struct Scratch {
+    /// The raw note data of the track being parsed.
+    raw_note_data: VecDeque<RawNoteData>,
+    /// The subdivisions of every beat in the track being parsed, one beat after another.
+    beat_grid: Vec<Vec<(Option<u8>, u8)>>,
+    /// The number of unique onsets in each beat of `beat_grid`.
+    note_counts: Vec<usize>,
+    /// The beats on which the strummed chords of the track being parsed start. See
+    /// `ParseOptions::arpeggios`.
+    arpeggios: Vec<f32>,
+}
+
+/// The number of beats of a track that are parsed between progress reports and checks for
+/// cancellation. See `ParseOptions::on_progress` and `ParseOptions::cancel_token`.
+const PROGRESS_BEATS: u32 = 64;
+
+/// The piece-wide information needed to parse the tracks of a midi file.
+/// 
+/// Tracks are parsed against this instead of the `Midi` being built, so the piece can be filled
+/// in while its tracks are parsed.
+#[derive(Clone, Copy)]
+pub(crate) struct ParseContext<'a> {
+    /// Number of ticks in each beat.
+    pub(crate) ticks_per_beat: f32,
+    /// The tempo changes of the piece as `(tick, microseconds per beat)` pairs, in order.
+    pub(crate) tempo_map: &'a [(u32, u32)],
+    /// The time signatures of the piece.
+    pub(crate) time_signatures: &'a [TimeSignature],
+    /// The key signatures of the piece.
+    pub(crate) key_signatures: &'a [KeySignature],
+    /// The range of completion fractions that parsing the current track covers, for reporting
+    /// progress.
+    pub(crate) progress: (f32, f32),
+    /// The time by which parsing must finish, if it's limited.
+    pub(crate) deadline: Option<Instant>,
+    /// The number of notes parsed before the current track, which count towards
+    /// `ResourceLimits::max_notes`.
+    pub(crate) note_count: usize,
+}
+
+impl ParseContext<'_> {
+    /// The beat type of the first time signature, which durations are read in.
+    pub(crate) fn beat_type(&self) -> u8 {
+        return self.time_signatures.first().copied().unwrap_or_default().beat_type;
+    }
+
+    /// The length of a beat in microseconds at the first tempo of the piece, or 120 beats per
+    /// minute if it has no tempo events.
+    pub(crate) fn microseconds_per_beat(&self) -> u32 {
+        return self.tempo_map.first().map_or(500000, |(_, tempo)| *tempo);
+    }
+
+    /// Returns an error if the parse has been cancelled or has run out of time.
+    pub(crate) fn check_interrupted(&self, options: &ParseOptions) -> Result<(), ParseError> {
+        options.check_cancelled()?;
+        if self.deadline.is_some_and(|deadline| Instant::now() > deadline) {
+            return Err(ParseError::LimitExceeded(Limit::ParseTime));
+        }
+        return Ok(());
+    }
+}
+
+/// Gets the tempo changes in the tracks of a midi file as `(tick, microseconds per beat)` pairs,
+/// in the order they occur.
+pub(crate) fn get_tempo_map(tracks: &[Vec<midly::TrackEvent>]) -> Vec<(u32, u32)> {
+    let mut tempo_map = Vec::new();
+    for track in tracks {
+        let mut tick = 0;
+        for event in track {
+            tick = add_delta(tick, event.delta);
+            if let midly::TrackEventKind::Meta(midly::MetaMessage::Tempo(tempo)) = event.kind {
+                tempo_map.push((tick, tempo.as_int()));
+            }
+        }
+    }
+    tempo_map.sort_by_key(|(tick, _)| *tick);
+    return tempo_map;
 }
 
 /// Represents a raw note data taken from the midi file.
 #[derive(Clone, Copy)]
 struct RawNoteData {
-    key: u8,
+    /// The key of the note, or `None` for a rest.
+    key: Option<u8>,
     onset: u32,
     vel: u8,
+    /// The tick on which the note is released, which is its onset for rests.
+    release: u32,
+}
+
+/// The most ticks a track may span. The note walk counts ticks twelve times finer for files
+/// whose ticks per beat can't be split into triplets, so longer tracks would overflow it; they're
+/// rejected with `ParseError::LimitExceeded(Limit::Beats)` whatever the resource limits are.
+pub const MAX_TRACK_TICKS: u32 = u32::MAX / 16;
+
+/// Adds a delta time to the tick count of a track, returning
+/// `ParseError::LimitExceeded(Limit::Beats)` if the track would span more than
+/// `MAX_TRACK_TICKS`.
+pub(crate) fn checked_add_delta(tick: u32, delta: midly::num::u28) -> Result<u32, ParseError> {
+    match tick.checked_add(delta.as_int()) {
+        Some(tick) if tick <= MAX_TRACK_TICKS => return Ok(tick),
+        _ => return Err(ParseError::LimitExceeded(Limit::Beats)),
+    }
+}
+
+/// Adds a delta time to the tick count of a track, stopping at `MAX_TRACK_TICKS`.
+/// 
+/// Every file is run through `check_track_ticks` before it's parsed, so this only stops short
+/// for tracks handed straight to one of the public readers, which then can't overflow.
+pub(crate) fn add_delta(tick: u32, delta: midly::num::u28) -> u32 {
+    return checked_add_delta(tick, delta).unwrap_or(MAX_TRACK_TICKS);
+}
+
+/// Returns `ParseError::LimitExceeded(Limit::Beats)` if any of the tracks spans more than
+/// `MAX_TRACK_TICKS`, as a crafted file with absurd delta times can.
+pub(crate) fn check_track_ticks(tracks: &[Vec<midly::TrackEvent>]) -> Result<(), ParseError> {
+    for track in tracks {
+        let mut tick = 0;
+        for event in track {
+            tick = checked_add_delta(tick, event.delta)?;
+        }
+    }
+    return Ok(());
 }
 
 /// Gets the number of ticks in each beat.
-pub fn get_ticks_per_beat(header: &midly::Header) -> f32 {
+/// 
+/// Returns `ParseError::UnsupportedTiming` if the file uses SMPTE timecode timing.
+pub fn get_ticks_per_beat(header: &midly::Header) -> Result<f32, ParseError> {
     let midly::Header { format: _, timing } = header;
     if let midly::Timing::Metrical(x) = timing {
         let ticks_per_beat: u16 = (*x).into();
-        return ticks_per_beat as f32;
+        return Ok(ticks_per_beat as f32);
     }
-    panic!("Timing format not supported");
+    return Err(ParseError::UnsupportedTiming);
 }
 
-/// Gets the tempo of a midi file.
-pub fn get_bpm(track: &Vec<midly::TrackEvent>) -> u32 {
-    for event in track {
-        if let midly::TrackEventKind::Meta(midly::MetaMessage::Tempo(tempo)) = event.kind {
-            let microseconds_per_beat: u32 = tempo.into();
-            return microseconds_per_beat / 1000000 * 60;
-        }
-    }
-    return 0;
+/// Gets the initial tempo of a midi file in beats per minute.
+/// 
+/// If the track has no tempo event, 120 beats per minute is assumed as the midi specification
+/// requires.
+pub fn get_bpm(track: &[midly::TrackEvent]) -> u32 {
+    let microseconds_per_beat = merge::get_microseconds_per_beat(track);
+    return (60_000_000.0 / microseconds_per_beat.max(1) as f32).round() as u32;
 }
 
 /// Returns all time signatures in the midi file.
-pub fn get_time_signature(track: &Vec<midly::TrackEvent>) -> Vec<TimeSignature> {
+/// 
+/// If the file has no time signature, 4/4 is assumed as the midi specification requires.
+pub fn get_time_signature(track: &[midly::TrackEvent]) -> Vec<TimeSignature> {
     let mut time_signatures: Vec<TimeSignature> = Vec::new();
     let mut cur_time: u32 = 0;
     for event in track {
-        let delta_t: u32 = event.delta.into();
-        cur_time += delta_t;
-        if let midly::TrackEventKind::Meta(message) = event.kind {
-            if let midly::MetaMessage::TimeSignature(numerator, denominator, _, _) = message {
-                time_signatures.push(TimeSignature {
-                    beat_count: numerator,
-                    beat_type: denominator.into(),
+        cur_time = add_delta(cur_time, event.delta);
+        let kind = event.kind;
+        if let midly::TrackEventKind::Meta(midly::MetaMessage::TimeSignature(n, d, _, _)) = kind {
+            time_signatures.push(TimeSignature {
+                beat_count: n,
+                beat_type: d,
+                time_of_occurance: cur_time,
+            });
+        }
+    }
+    if time_signatures.is_empty() {
+        time_signatures.push(TimeSignature::default());
+    }
+    return time_signatures;
+}
+
+/// Returns all key signatures in the midi file, sorted by their time of occurance.
+/// 
+/// Key signatures are collected from every track since many files store them alongside the notes
+/// instead of in the first track. Duplicate events at the same time are only kept once.
+pub fn get_key_signatures(tracks: &[Vec<midly::TrackEvent>]) -> Vec<KeySignature> {
+    let mut key_signatures: Vec<KeySignature> = Vec::new();
+    for track in tracks {
+        let mut cur_time: u32 = 0;
+        for event in track {
+            cur_time = add_delta(cur_time, event.delta);
+            let kind = event.kind;
+            if let midly::TrackEventKind::Meta(midly::MetaMessage::KeySignature(s, m)) = kind {
+                key_signatures.push(KeySignature {
+                    sharps: s,
+                    minor: m,
                     time_of_occurance: cur_time,
                 });
             }
         }
     }
-    return time_signatures;
+    key_signatures.sort_by_key(|k| k.time_of_occurance);
+    key_signatures.dedup();
+    return key_signatures;
+}
+
+/// Returns all `Marker` and `CuePoint` events in the midi file, sorted by the tick they occur on.
+pub fn get_markers(tracks: &[Vec<midly::TrackEvent>], ticks_per_beat: f32) -> Vec<Marker> {
+    let mut markers: Vec<Marker> = Vec::new();
+    for track in tracks {
+        let mut cur_time: u32 = 0;
+        for event in track {
+            cur_time = add_delta(cur_time, event.delta);
+            if let midly::TrackEventKind::Meta(
+                midly::MetaMessage::Marker(s) | midly::MetaMessage::CuePoint(s)
+            ) = event.kind {
+                markers.push(Marker {
+                    text: String::from_utf8_lossy(s).trim().to_string(),
+                    tick: cur_time,
+                    beat: cur_time as f32 / ticks_per_beat,
+                });
+            }
+        }
+    }
+    markers.sort_by_key(|m| m.tick);
+    return markers;
+}
+
+/// Returns every system exclusive message in a midi file, sorted by the tick it occurs on.
+pub fn get_sysex_events(tracks: &[Vec<midly::TrackEvent>]) -> Vec<SysexEvent> {
+    let mut events: Vec<SysexEvent> = Vec::new();
+    for (index, track) in tracks.iter().enumerate() {
+        let mut cur_time: u32 = 0;
+        for event in track {
+            cur_time = add_delta(cur_time, event.delta);
+            let (data, escape) = match event.kind {
+                midly::TrackEventKind::SysEx(data) => (data, false),
+                midly::TrackEventKind::Escape(data) => (data, true),
+                _ => continue,
+            };
+            events.push(SysexEvent { track: index, tick: cur_time, data: data.to_vec(), escape });
+        }
+    }
+    events.sort_by_key(|e| e.tick);
+    return events;
 }
 
 /// Loads all the tracks in a midi file.
@@ -74,94 +808,512 @@ pub fn get_time_signature(track: &Vec<midly::TrackEvent>) -> Vec<TimeSignature>
 /// 
 /// `smf` holds the `midly::Smf` object being used to parse through the midi file.
 /// 
-/// The `precision` parameter allows the user to set the degree of precision they would like
-/// when parsing. Any notes shorter than the value specified in the `precision` parameter
-/// will be grouped as a chord.
+/// `options` holds the `ParseOptions` used to parse each track.
 /// 
-/// The `triplet` parameter indicated if the user wants to scan for triplets. Scanning for
-/// triplets requires extra resources.
-pub fn load_tracks(midi: &mut Midi, smf: &midly::Smf, precision: &DurationType, triplet: bool) {
-    let tmp = midi.clone();
-    for track in &smf.tracks {
-        midi.tracks.push(parse_track(&tmp, track, precision, triplet));
+/// Returns `ParseError::Cancelled` if the parse is cancelled through `options`.
+pub fn load_tracks(
+    midi: &mut Midi,
+    smf: &midly::Smf,
+    options: &ParseOptions
+) -> Result<(), ParseError> {
+    check_track_ticks(&smf.tracks)?;
+    let deadline = options.limits.deadline();
+    return load_tracks_with_scratch(midi, smf, options, deadline, &mut Scratch::default());
+}
+
+/// Loads all the tracks in a midi file, reusing the buffers in `scratch`. Parsing must finish by
+/// `deadline`, if there is one.
+pub(crate) fn load_tracks_with_scratch(
+    midi: &mut Midi,
+    smf: &midly::Smf,
+    options: &ParseOptions,
+    deadline: Option<Instant>,
+    scratch: &mut Scratch
+) -> Result<(), ParseError> {
+    let tempo_map = get_tempo_map(&smf.tracks);
+    let context = ParseContext {
+        ticks_per_beat: midi.ticks_per_beat,
+        tempo_map: &tempo_map,
+        time_signatures: &midi.time_signatures,
+        key_signatures: &midi.key_signatures,
+        progress: (0.0, 1.0),
+        deadline,
+        note_count: 0,
+    };
+    // The lossless events are those of the file as stored, before it's split or filtered.
+    let original = smf;
+    let split = split_single_track(smf);
+    let smf = split.as_ref().unwrap_or(smf);
+    let filtered = filter_events(&context, smf, options);
+    let smf = filtered.as_ref().unwrap_or(smf);
+    let included: Vec<bool> = smf.tracks
+        .iter()
+        .enumerate()
+        .map(|(index, track)| is_track_included(options, track, index))
+        .collect();
+    let selected = match included.contains(&false) {
+        true => {
+            let tracks = smf.tracks
+                .iter()
+                .zip(&included)
+                .map(|(track, included)| if *included { track.clone() } else { Vec::new() })
+                .collect();
+            Some(midly::Smf { header: smf.header, tracks })
+        },
+        false => None,
+    };
+    let smf = selected.as_ref().unwrap_or(smf);
+    let total = included.iter().filter(|i| **i).count() + options.merge_tracks as usize;
+    let mut done = 0;
+    let mut note_count = 0;
+    let mut next_context = |note_count| {
+        let progress = (done as f32 / total as f32, (done + 1) as f32 / total as f32);
+        done += 1;
+        return ParseContext { progress, note_count, ..context };
+    };
+    for (index, track) in smf.tracks.iter().enumerate() {
+        if !included[index] {
+            continue;
+        }
+        let context = next_context(note_count);
+        if let Some(parsed_track) = load_track(&context, track, index, options, scratch)? {
+            note_count += parsed_track.notes.iter().map(|n| n.notes().len()).sum::<usize>();
+            ResourceLimits::check(Limit::Notes, note_count, options.limits.max_notes)?;
+            midi.tracks.push(parsed_track);
+        }
+        options.report_progress(context.progress.1);
+    }
+    if options.merge_tracks {
+        let context = next_context(note_count);
+        midi.merged_track = Some(build_merged_track(&context, smf, options, scratch)?);
+        options.report_progress(context.progress.1);
+    }
+    if total == 0 {
+        options.report_progress(1.0);
     }
+    if options.pad_to_score_length {
+        pad_tracks(midi);
+    }
+    if options.pad_final_measure {
+        crate::transform::pad_final_measure(midi);
+    }
+    if options.lossless {
+        let beat_type = midi.beat_type();
+        midi.raw_events = events::EventFile::from_smf(original).ok();
+        midi.raw_fingerprints = midi.tracks
+            .iter()
+            .map(|t| (t.index, get_note_fingerprint(t, beat_type)))
+            .collect();
+    }
+    return Ok(());
+}
+
+/// Applies the channel and short note filters of `options` to a midi file. Returns `None` if
+/// neither filter changes it.
+pub(crate) fn filter_events<'a>(
+    context: &ParseContext,
+    smf: &midly::Smf<'a>,
+    options: &ParseOptions
+) -> Option<midly::Smf<'a>> {
+    let channels = match options.channels {
+        cleanup::ChannelFilter::All => None,
+        _ => Some(cleanup::filter_channels(smf, &options.channels)),
+    };
+    let smf = channels.as_ref().unwrap_or(smf);
+    let filtered = options.min_note_length.as_ref().map(|duration| {
+        let beats = duration.get_beat_count(context.beat_type());
+        let min_ticks = (beats * context.ticks_per_beat).round() as u32;
+        cleanup::filter_short_notes(smf, min_ticks, options.short_note_policy)
+    });
+    return filtered.or(channels);
+}
+
+/// Parses a single midi track of a piece, along with its expression, lyrics, and spelling.
+/// Returns `None` if the track holds no notes and `options` skips empty tracks.
+pub(crate) fn load_track(
+    context: &ParseContext,
+    track: &[midly::TrackEvent],
+    index: usize,
+    options: &ParseOptions,
+    scratch: &mut Scratch
+) -> Result<Option<Track>, ParseError> {
+    let kind = classify_track(track);
+    if options.skip_empty_tracks && kind != TrackKind::Notes {
+        return Ok(None);
+    }
+    let mut parsed_track = parse_track(context, track, index, kind, options, scratch)?;
+    expression::attach_pitch_bends(
+        &mut parsed_track.notes,
+        &expression::get_pitch_bends(track),
+        options.pitch_bend,
+        context.beat_type(),
+        context.ticks_per_beat
+    );
+    expression::attach_modulation(
+        &mut parsed_track.notes,
+        &expression::get_modulation_events(track, context.ticks_per_beat),
+        context.beat_type()
+    );
+    expression::attach_pressure(
+        &mut parsed_track.notes,
+        &parsed_track.pressure_events,
+        context.beat_type()
+    );
+    lyrics::attach_lyrics(
+        &mut parsed_track.notes,
+        &parsed_track.lyric_events,
+        context.beat_type(),
+        context.ticks_per_beat
+    );
+    if !parsed_track.percussion {
+        pitch::spell_notes(
+            &mut parsed_track.notes,
+            context.key_signatures,
+            context.beat_type(),
+            context.ticks_per_beat
+        );
+    }
+    return Ok(Some(parsed_track));
+}
+
+/// Splits the only track of a format 0 file by channel, so each instrument gets a track of its
+/// own. Returns `None` if `smf` isn't a format 0 file.
+/// 
+/// The first track of the split file holds every event that isn't a channel message, and is
+/// followed by a track for each channel in the order the channels are first used.
+pub(crate) fn split_single_track<'a>(smf: &midly::Smf<'a>) -> Option<midly::Smf<'a>> {
+    if smf.header.format != midly::Format::SingleTrack || smf.tracks.len() != 1 {
+        return None;
+    }
+    let mut conductor = Vec::new();
+    let mut channels: Vec<(u8, Vec<(u32, midly::TrackEventKind<'a>)>)> = Vec::new();
+    let mut tick = 0;
+    for event in &smf.tracks[0] {
+        tick = add_delta(tick, event.delta);
+        match event.kind {
+            midly::TrackEventKind::Midi { channel, .. } => {
+                let channel = channel.as_int();
+                match channels.iter_mut().find(|(c, _)| *c == channel) {
+                    Some((_, events)) => events.push((tick, event.kind)),
+                    None => channels.push((channel, vec![(tick, event.kind)])),
+                }
+            },
+            midly::TrackEventKind::Meta(midly::MetaMessage::EndOfTrack) => {},
+            kind => conductor.push((tick, kind)),
+        }
+    }
+    let mut tracks = vec![conductor];
+    tracks.extend(channels.into_iter().map(|(_, events)| events));
+    let tracks = tracks
+        .into_iter()
+        .map(|mut events| {
+            events.push((tick, midly::TrackEventKind::Meta(midly::MetaMessage::EndOfTrack)));
+            let mut previous = 0;
+            return events
+                .into_iter()
+                .map(|(tick, kind)| {
+                    let delta = midly::num::u28::new(tick - previous);
+                    previous = tick;
+                    return midly::TrackEvent { delta, kind };
+                })
+                .collect();
+        })
+        .collect();
+    let header = midly::Header::new(midly::Format::Parallel, smf.header.timing);
+    return Some(midly::Smf { header, tracks });
+}
+
+/// Hashes the notes of a track, so tracks edited after being parsed can be told apart from the
+/// ones that weren't.
+pub(crate) fn get_note_fingerprint(track: &Track, beat_type: u8) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    let mut beat = 0.0;
+    for wrapper in &track.notes {
+        wrapper.for_each_note(beat, beat_type, &mut |note, onset, length| {
+            if note.is_rest() {
+                return;
+            }
+            (note.value(), note.velocity, onset.to_bits(), length.to_bits()).hash(&mut hasher);
+            note.lyric.hash(&mut hasher);
+        });
+        beat += wrapper.beat_count(beat_type);
+    }
+    return hasher.finish();
+}
+
+/// Builds a single track containing the notes of every note track in the file.
+fn build_merged_track(
+    context: &ParseContext,
+    smf: &midly::Smf,
+    options: &ParseOptions,
+    scratch: &mut Scratch
+) -> Result<Track, ParseError> {
+    let window = match options.simultaneity_window {
+        SimultaneityWindow::Ticks(ticks) => ticks,
+        SimultaneityWindow::Milliseconds(ms) => {
+            let microseconds_per_beat = context.microseconds_per_beat() as f32;
+            (ms * 1000.0 / microseconds_per_beat * context.ticks_per_beat).round() as u32
+        },
+    };
+    let merged = merge::merge_tracks(&smf.tracks, window);
+    let index = smf.tracks.len();
+    let mut track = parse_track(context, &merged, index, TrackKind::Notes, options, scratch)?;
+    track.name = String::from("Merged");
+    pitch::spell_notes(
+        &mut track.notes,
+        context.key_signatures,
+        context.beat_type(),
+        context.ticks_per_beat
+    );
+    return Ok(track);
+}
+
+/// Pads every note track with rests so that all tracks end on the same beat.
+/// 
+/// The length of the score is taken from the latest `EndOfTrack` event in the file.
+fn pad_tracks(midi: &mut Midi) {
+    let beat_type = midi.beat_type();
+    let score_length = midi.tracks.iter().map(|t| t.end_of_track).max().unwrap_or(0);
+    let score_beats = score_length as f32 / midi.ticks_per_beat;
+    for track in midi.tracks.iter_mut().chain(midi.merged_track.iter_mut()) {
+        if track.kind != TrackKind::Notes {
+            continue;
+        }
+        track.length = score_beats;
+        let track_beats: f32 = track.notes.iter().map(|n| n.beat_count(beat_type)).sum();
+        let mut remaining_beats = score_beats - track_beats;
+        while remaining_beats >= POSSIBLE_NOTE_LENGTHS[0] {
+            let nested_beat_value = get_nested_beat_value(remaining_beats);
+            let duration = DurationType::beat_type_map(nested_beat_value, beat_type);
+            track.notes.push(NoteWrapper::build_note_wrapper(None, duration, 0));
+            remaining_beats -= nested_beat_value;
+        }
+    }
+}
+
+/// Finds the tick on which a track ends and whether any of its notes extend past that tick.
+/// 
+/// If the track has no `EndOfTrack` event, the time of its last event is used instead. A note is
+/// considered to extend past the end of the track if it is still sounding when the track ends.
+pub fn get_end_of_track(track: &[midly::TrackEvent]) -> (u32, bool) {
+    let mut cur_time: u32 = 0;
+    let mut end_of_track: Option<u32> = None;
+    let mut sounding: Vec<u8> = Vec::new();
+    let mut truncated = false;
+    for event in track {
+        cur_time = add_delta(cur_time, event.delta);
+        match event.kind {
+            midly::TrackEventKind::Meta(midly::MetaMessage::EndOfTrack) => {
+                end_of_track = Some(cur_time);
+            },
+            midly::TrackEventKind::Midi { channel: _, message } => match message {
+                midly::MidiMessage::NoteOn { key, vel } if vel > 0 => {
+                    if end_of_track.is_some() {
+                        truncated = true;
+                    }
+                    sounding.push(key.into());
+                },
+                midly::MidiMessage::NoteOn { key, vel: _ }
+                | midly::MidiMessage::NoteOff { key, vel: _ } => {
+                    let key: u8 = key.into();
+                    if end_of_track.is_some_and(|end| cur_time > end) {
+                        truncated = true;
+                    }
+                    if let Some(position) = sounding.iter().position(|k| *k == key) {
+                        sounding.remove(position);
+                    }
+                },
+                _ => (),
+            },
+            _ => (),
+        }
+    }
+    if !sounding.is_empty() {
+        truncated = true;
+    }
+    return (end_of_track.unwrap_or(cur_time), truncated);
+}
+
+/// Classifies a midi track by its content.
+pub fn classify_track(track: &[midly::TrackEvent]) -> TrackKind {
+    let mut kind = TrackKind::Empty;
+    for event in track {
+        match event.kind {
+            midly::TrackEventKind::Midi { channel: _, message } => match message {
+                midly::MidiMessage::NoteOn { key: _, vel } if vel > 0 => return TrackKind::Notes,
+                _ => kind = TrackKind::AutomationOnly,
+            },
+            midly::TrackEventKind::Meta(
+                midly::MetaMessage::Tempo(_)
+                | midly::MetaMessage::TimeSignature(..)
+                | midly::MetaMessage::KeySignature(..)
+            ) if kind == TrackKind::Empty => kind = TrackKind::Conductor,
+            _ => (),
+        }
+    }
+    return kind;
 }
 
 /// A helper function to build the `Track Object`.
 fn parse_track(
-    midi: &Midi, 
-    track: &Vec<midly::TrackEvent>, 
-    precision: &DurationType,
-    triplet: bool
-) -> Track {
-    Track { 
-        name: get_name(track), 
-        notes: get_notes(midi, track, precision, triplet),
+    context: &ParseContext,
+    track: &[midly::TrackEvent], 
+    index: usize,
+    kind: TrackKind,
+    options: &ParseOptions,
+    scratch: &mut Scratch
+) -> Result<Track, ParseError> {
+    let notes = if kind == TrackKind::Notes {
+        get_notes(context, track, options, scratch)?
+    } else {
+        Vec::new()
+    };
+    let (end_of_track, truncated) = get_end_of_track(track);
+    let percussion = general_midi::is_percussion_track(track);
+    let program = general_midi::get_program(track);
+    let (name, track_name, instrument_name, instrument) = get_track_names(track, index);
+    let mut parsed = Track { 
+        name, 
+        index,
+        track_name,
+        instrument_name,
+        program,
+        instrument,
+        percussion,
+        kind,
+        end_of_track,
+        truncated,
+        length: end_of_track as f32 / context.ticks_per_beat,
+        unpadded_length: None,
+        notes,
+        lyric_events: lyrics::get_lyrics(track),
+        controller_events: expression::get_controller_events(track, context.ticks_per_beat),
+        pressure_events: expression::get_pressure_events(track, context.ticks_per_beat),
+    };
+    if let Some(max_overlap) = &options.slurs {
+        let max_overlap = max_overlap.get_beat_count(2);
+        mark_slurs(&mut parsed, track, context.ticks_per_beat, max_overlap);
     }
+    return Ok(parsed);
 }
 
-/// Gets the name of a midi track.
-fn get_name(track: &Vec<midly::TrackEvent>) -> String {
+/// Gets the name of a midi track, along with its track name, instrument name, and General MIDI
+/// instrument. See `Track::name` for how tracks are named.
+fn get_track_names(
+    track: &[midly::TrackEvent],
+    index: usize
+) -> (String, Option<String>, Option<String>, Option<String>) {
+    let instrument = if general_midi::is_percussion_track(track) {
+        Some(String::from("Percussion"))
+    } else {
+        general_midi::get_program(track).and_then(general_midi::instrument_name).map(String::from)
+    };
+    let track_name = get_meta_text(track, |m| match m {
+        midly::MetaMessage::TrackName(s) => Some(s),
+        _ => None,
+    });
+    let instrument_name = get_meta_text(track, |m| match m {
+        midly::MetaMessage::InstrumentName(s) => Some(s),
+        _ => None,
+    });
+    let name = track_name
+        .clone()
+        .or(instrument_name.clone())
+        .or(instrument.clone())
+        .unwrap_or(format!("Track {}", index));
+    return (name, track_name, instrument_name, instrument);
+}
+
+/// Whether the track at `index` in a midi file passes the track filters of `options`.
+pub(crate) fn is_track_included(
+    options: &ParseOptions,
+    track: &[midly::TrackEvent],
+    index: usize
+) -> bool {
+    if let Some(indices) = &options.tracks {
+        if !indices.contains(&index) {
+            return false;
+        }
+    }
+    if let Some(pattern) = &options.track_name_filter {
+        let (name, _, _, _) = get_track_names(track, index);
+        return name.to_lowercase().contains(&pattern.to_lowercase());
+    }
+    return true;
+}
+
+/// Gets the text of the first non-empty meta event in a midi track that `select` accepts.
+fn get_meta_text<'a, F>(track: &[midly::TrackEvent<'a>], select: F) -> Option<String>
+where
+    F: Fn(midly::MetaMessage<'a>) -> Option<&'a [u8]>
+{
     for event in track {
-        if let midly::TrackEventKind::Meta(midly::MetaMessage::InstrumentName(s)) = event.kind {
-            let raw_string: Vec<u8> = s.to_vec();
-            return String::from_utf8(raw_string).unwrap();
+        if let midly::TrackEventKind::Meta(message) = event.kind {
+            if let Some(s) = select(message) {
+                let text = String::from_utf8_lossy(s).trim().to_string();
+                if !text.is_empty() {
+                    return Some(text);
+                }
+            }
         }
     }
-    return String::from("");
+    return None;
 }
 
 /// Gets all the notes in a midi track. 
 /// 
 /// Does this by formatting the raw midi data.
 fn get_notes(
-    midi: &Midi, 
-    track: &Vec<midly::TrackEvent>, 
-    precision: &DurationType,
-    triplet: bool
-) -> Vec<NoteWrapper> {
-    let beat_type = midi.time_signatures[0].beat_type;
+    context: &ParseContext,
+    track: &[midly::TrackEvent], 
+    options: &ParseOptions,
+    scratch: &mut Scratch
+) -> Result<Vec<NoteWrapper>, ParseError> {
+    let (precision, triplet) = (&options.precision, options.triplet);
+    let beat_type = context.beat_type();
     let precision_beat = precision.get_beat_count(beat_type);
     let divisions = if triplet { 
         4.0 / precision_beat / 2.0 * 1.5 
     } else { 
         1.0 / precision_beat
     };
-    let quantized_note_data = quantize(midi, track, divisions);
+    let beats = quantize(context, track, divisions, options, scratch)?;
 
     let mut possible_triplets = VecDeque::new();
     if triplet {
-        possible_triplets = get_triplets(&quantized_note_data);
-    }
-
-    let mut complete_beat_grid = Vec::new();
-    for (mut beat_grid, _) in quantized_note_data {
-        complete_beat_grid.append(&mut beat_grid);
+        possible_triplets = get_triplets(context, scratch, beats, divisions as usize, options)?;
     }
 
+    let complete_beat_grid = &scratch.beat_grid[..beats * divisions as usize];
     let mut notes = Vec::new();
+    let mut note_count = context.note_count;
     let mut beat_count = 0;
     let mut i = 0;
     let mut length = 0;
-    let mut cur_note: &Vec<(u8, u8)> = &Vec::new();
+    let mut cur_note: &[(Option<u8>, u8)] = &[];
     while i < complete_beat_grid.len() {
         if i % divisions as usize == 0 {
             beat_count += 1;
-            if possible_triplets.len() != 0 && possible_triplets[0] == beat_count {
+            if beat_count % PROGRESS_BEATS == 0 {
+                context.check_interrupted(options)?;
+                let (start, end) = context.progress;
+                options.report_progress(start + (end - start) * beat_count as f32 / beats as f32);
+            }
+            if !possible_triplets.is_empty() && possible_triplets[0] == beat_count {
                 let x = i + divisions as usize;
-                let beat_data = &Vec::from(&complete_beat_grid[i..x]);
-                notes.push(gen_triplet(beat_data, beat_type));
+                let wrapper = gen_triplet(&complete_beat_grid[i..x], beat_type);
+                push_note(&mut notes, wrapper, &mut note_count, options)?;
                 possible_triplets.pop_front();
                 i += divisions as usize;
                 length = 0;
                 continue;
             }
         }
-        if complete_beat_grid[i].len() != 0 {
+        if !complete_beat_grid[i].is_empty() {
             if length != 0 {
                 let beat_length = length as f32 / divisions;
-                println!("{} / {} = {}", length, divisions, beat_length);
-                notes.push(gen_wrapper(cur_note, beat_length, beat_type));
+                let wrapper = gen_wrapper(cur_note, beat_length, beat_type);
+                push_note(&mut notes, wrapper, &mut note_count, options)?;
             }
             length = 0;
             cur_note = &complete_beat_grid[i];
@@ -169,41 +1321,73 @@ fn get_notes(
         length += 1;
         i += 1;
     }
+    if length != 0 && cur_note.iter().any(|(key, _)| key.is_some()) {
+        let beat_length = length as f32 / divisions;
+        let wrapper = gen_wrapper(cur_note, beat_length, beat_type);
+        push_note(&mut notes, wrapper, &mut note_count, options)?;
+    }
+    if !scratch.arpeggios.is_empty() {
+        mark_arpeggios(&mut notes, &scratch.arpeggios, beat_type);
+    }
 
-    return notes;
+    return Ok(notes);
+}
+
+/// Adds a note to the notes of a track, counting it towards `ResourceLimits::max_notes` in
+/// `note_count`.
+fn push_note(
+    notes: &mut Vec<NoteWrapper>,
+    wrapper: NoteWrapper,
+    note_count: &mut usize,
+    options: &ParseOptions
+) -> Result<(), ParseError> {
+    *note_count += wrapper.notes().len();
+    ResourceLimits::check(Limit::Notes, *note_count, options.limits.max_notes)?;
+    notes.push(wrapper);
+    return Ok(());
 }
 
 /// This function finds all the triplets in a piece of music and returns a vector containing what
 /// beats they are on.
 /// 
-/// Precondition: the note data must have already been quantized.
-fn get_triplets(quantized_note_data: &Vec<(Vec<Vec<(u8, u8)>>, u8)>) -> VecDeque<u32> {
+/// Precondition: the note data must have already been quantized into `scratch`.
+fn get_triplets(
+    context: &ParseContext,
+    scratch: &Scratch,
+    beats: usize,
+    divisions: usize,
+    options: &ParseOptions
+) -> Result<VecDeque<u32>, ParseError> {
     let mut triplets = VecDeque::new();
-    for i in 0..quantized_note_data.len() {
-        if is_possible_triplet(&quantized_note_data[i]) {
+    for i in 0..beats {
+        if (i as u32).is_multiple_of(PROGRESS_BEATS) {
+            context.check_interrupted(options)?;
+        }
+        let beat_grid = &scratch.beat_grid[i * divisions..(i + 1) * divisions];
+        if is_possible_triplet(beat_grid, scratch.note_counts[i]) {
             triplets.push_back(i as u32 + 1);
         }
     }
-    return triplets;
+    return Ok(triplets);
 }
 
 /// Determines if a group of notes can be a triplet.
 /// 
-/// `beat_data` is a vector of all the subdivisions of the current beat. Each element in the vector
-/// is another vector containing the key and velocity of the notes that start on that subdivision.
-fn is_possible_triplet(beat_data: &(Vec<Vec<(u8, u8)>>, u8)) -> bool {
-    let (beat_grid, note_count) = beat_data;
-    if *note_count != 3 {
+/// `beat_grid` is a slice of all the subdivisions of the current beat. Each element in the slice
+/// is a vector containing the key and velocity of the notes that start on that subdivision.
+/// `note_count` is the number of unique onsets in the beat.
+fn is_possible_triplet(beat_grid: &[Vec<(Option<u8>, u8)>], note_count: usize) -> bool {
+    if note_count != 3 {
         return false;
     }
 
     let mut beat_length: [u8; 3]= [0, 0, 0];
     let mut i = 0;
-    for data_point_index in 0..3 {
-        beat_length[data_point_index] += 1;
+    for length in beat_length.iter_mut() {
+        *length += 1;
         i +=1;
-        while i < beat_grid.len() && beat_grid[i].len() == 0 {
-            beat_length[data_point_index] += 1;
+        while i < beat_grid.len() && beat_grid[i].is_empty() {
+            *length += 1;
             i += 1;
         }
     }
@@ -215,10 +1399,10 @@ fn is_possible_triplet(beat_data: &(Vec<Vec<(u8, u8)>>, u8)) -> bool {
 /// This function generates a note wrapper for a triplet. The `duration` for the note will be
 /// the appropriate dupal counterpart. For example, eight note triplets will be stored as eigth 
 /// notes in a triplet wrapper.
-fn gen_triplet(beat_data: &Vec<Vec<(u8, u8)>>, beat_type: u8) -> NoteWrapper {
+fn gen_triplet(beat_data: &[Vec<(Option<u8>, u8)>], beat_type: u8) -> NoteWrapper {
     let mut triplet = Vec::new();
     for div in beat_data {
-        if div.len() > 0 {
+        if !div.is_empty() {
             triplet.push(gen_wrapper(div, 0.5, beat_type));
         }
     }
@@ -231,26 +1415,31 @@ fn gen_triplet(beat_data: &Vec<Vec<(u8, u8)>>, beat_type: u8) -> NoteWrapper {
 /// generated made up of all the entries in `cur_note`.
 /// 
 /// `cur_note.len()` must be greater than 0.
-fn gen_wrapper(cur_note: &Vec<(u8, u8)>, beat_length: f32, beat_type: u8) -> NoteWrapper {
+fn gen_wrapper(cur_note: &[(Option<u8>, u8)], beat_length: f32, beat_type: u8) -> NoteWrapper {
     let mut chord = Vec::new();
     for note_data in cur_note {
         let value = note_data.0;
         let velocity = note_data.1;
-        if value != 255 { 
+        if value.is_some() { 
             chord.push(parse_note_data((value, velocity), beat_length, beat_type));
         }
     }
-    if chord.len() == 0 {
-        let duration = DurationType::beat_type_map(beat_length, beat_type);
-        return NoteWrapper::build_note_wrapper(255, duration, 0);
+    if chord.is_empty() {
+        return parse_note_data((None, 0), beat_length, beat_type);
     } else if chord.len() == 1 {
         return chord[0].clone();
     }
     return NoteWrapper::ModifiedNote(NoteModifier::Chord(chord));
 } 
 
-/// A helper function for building a `NoteWrapper`.
-fn parse_note_data((value, velocity): (u8, u8), beat_length: f32, beat_type: u8) -> NoteWrapper {
+/// A helper function for building a `NoteWrapper`, which is a rest if `value` is `None`.
+/// 
+/// Lengths that can't be represented by a single duration are split into tied notes.
+pub(crate) fn parse_note_data(
+    (value, velocity): (Option<u8>, u8),
+    beat_length: f32,
+    beat_type: u8
+) -> NoteWrapper {
     let duration = DurationType::beat_type_map(beat_length, beat_type);
     if duration.duration == NoteDuration::NaN {
         return NoteWrapper::ModifiedNote(get_tied_note((value, beat_length, velocity), beat_type));
@@ -261,38 +1450,70 @@ fn parse_note_data((value, velocity): (u8, u8), beat_length: f32, beat_type: u8)
 
 /// This snaps all of the notes found in `track` to a grid. 
 /// 
-/// The function returns a vector of tuplets (representing beats) made up of a vector and a number. 
-/// The vector in the tuplet represents the grid of subdivisions for each beat and the number shows
-/// how many unique onsets are in that beat.
+/// The grid is written to `scratch.beat_grid`, one beat of `divisions` subdivisions after another,
+/// and the number of unique onsets in each beat is written to `scratch.note_counts`. The function
+/// returns the number of beats in the grid.
 fn quantize(
-    midi: &Midi, 
-    track: &Vec<midly::TrackEvent>, 
-    divisions: f32
-) -> Vec<(Vec<Vec<(u8, u8)>>, u8)> {
-    let mut notes = Vec::new();
-
-    let mut ticks_per_beat = midi.ticks_per_beat;
+    context: &ParseContext,
+    track: &[midly::TrackEvent], 
+    divisions: f32,
+    options: &ParseOptions,
+    scratch: &mut Scratch
+) -> Result<usize, ParseError> {
+    let mut ticks_per_beat = context.ticks_per_beat;
     let mut scalar = 1;
-    if midi.ticks_per_beat % 12.0 != 0.0 {
+    if context.ticks_per_beat % 12.0 != 0.0 {
         scalar = 12;
         ticks_per_beat *= 12.0;
     }
 
-    let mut flag = true;
-    let mut raw_note_data = get_raw_note_data(track, ticks_per_beat, scalar);
-    if raw_note_data.len() == 0 {
-        return Vec::new();
+    let Scratch { raw_note_data, beat_grid, note_counts, arpeggios } = scratch;
+    note_counts.clear();
+    get_raw_note_data(track, ticks_per_beat, scalar, options.overlap_policy, raw_note_data);
+    arpeggios.clear();
+    if let Some(max_spread) = &options.arpeggios {
+        let window = (max_spread.get_beat_count(2) * ticks_per_beat).round() as u32;
+        for onset in collapse_arpeggios(raw_note_data, window) {
+            arpeggios.push(onset as f32 / ticks_per_beat);
+        }
+    }
+    if raw_note_data.is_empty() {
+        return Ok(0);
+    }
+    if let Some(last) = raw_note_data.back() {
+        let beats = (last.onset as f32 / ticks_per_beat) as u32;
+        ResourceLimits::check(Limit::Beats, beats, options.limits.max_beats)?;
     }
 
+    let divisions = divisions as usize;
+    let mut flag = true;
+    let mut beats = 0;
     let mut cur_beat = ticks_per_beat as u32;
     let mut note = raw_note_data.pop_front().unwrap();
     while flag {
-        let mut beat_container = vec![Vec::new(); divisions as usize];
+        if (beats as u32).is_multiple_of(PROGRESS_BEATS) {
+            context.check_interrupted(options)?;
+        }
+        let beat_start = beats * divisions;
+        while beat_grid.len() < beat_start + divisions {
+            beat_grid.push(Vec::new());
+        }
+        for div in &mut beat_grid[beat_start..beat_start + divisions] {
+            div.clear();
+        }
         let mut note_count = 0;
         while note.onset < cur_beat {
             let onset = note.onset - (cur_beat - ticks_per_beat as u32);
-            let position = (onset as f32 * (1.0 / ticks_per_beat) * divisions).floor() as usize;
-            beat_container[position].push((note.key, note.vel));
+            let position = (onset as f32 * (1.0 / ticks_per_beat) * divisions as f32).floor();
+            let div = &mut beat_grid[beat_start + position as usize];
+            let doubled = match options.dedupe_notes && note.key.is_some() {
+                true => div.iter_mut().find(|(key, _)| *key == note.key),
+                false => None,
+            };
+            match doubled {
+                Some((_, vel)) => *vel = (*vel).max(note.vel),
+                None => div.push((note.key, note.vel)),
+            }
             note_count += 1;
             if raw_note_data.is_empty() {
                 flag = false;
@@ -301,60 +1522,221 @@ fn quantize(
             note = raw_note_data.pop_front().unwrap();
         }
         cur_beat += ticks_per_beat as u32;
-        notes.push((beat_container, note_count));
+        note_counts.push(note_count);
+        beats += 1;
     }
 
-    if notes[0].0[0].len() == 0 {
-        notes[0].0[0].push((255, 0));
-        notes[0].1 += 1;
+    if beat_grid[0].is_empty() {
+        beat_grid[0].push((None, 0));
+        note_counts[0] += 1;
     }
 
-    return notes;
+    return Ok(beats);
 }
 
-/// Gets the raw note data in a midi track.
+/// Gets the raw note data in a midi track and stores it in `data`.
 fn get_raw_note_data(
-    track: &Vec<midly::TrackEvent>, 
+    track: &[midly::TrackEvent], 
     ticks_per_beat: f32, 
-    scalar: u32
-) -> VecDeque<RawNoteData> {
-    let mut cur_time: u32 = 0;
-    let mut cur_velocity: u8 = 0;
-    let mut note_on_time: u32 = 0;
+    scalar: u32,
+    overlap: OverlapPolicy,
+    data: &mut VecDeque<RawNoteData>
+) {
+    let mut tick: u32 = 0;
     let mut note_off_time: u32 = 0;
-    let mut data: VecDeque<RawNoteData> = VecDeque::new();
+    // The notes that are sounding, with their channel, the number of times they've been struck
+    // without being released (see `OverlapPolicy::Merge`), and their note data.
+    let mut sounding: Vec<((u8, u8), u32, RawNoteData)> = Vec::new();
+    let mut notes: Vec<RawNoteData> = Vec::new();
+    data.clear();
 
     for event in track {
-        let delta_t: u32 = event.delta.into();
-        cur_time += delta_t * scalar;
-
-        if let midly::TrackEventKind::Midi { channel: _, message } = event.kind {
-            if let midly::MidiMessage::NoteOn {key: _, vel } = message {
-                cur_velocity = vel.into();
-                note_on_time = cur_time;
-                if note_on_time - note_off_time >= (ticks_per_beat *  0.125).ceil() as u32 {
-                    data.push_back(RawNoteData {
-                        key: 255,
-                        onset: note_off_time,
-                        vel: 0,
-                    });
-                }
+        tick = add_delta(tick, event.delta);
+        let cur_time = tick * scalar;
+
+        let (channel, message) = match event.kind {
+            midly::TrackEventKind::Midi { channel, message } => (channel.as_int(), message),
+            _ => continue,
+        };
+        let (key, vel) = match message {
+            midly::MidiMessage::NoteOn { key, vel } => (key.as_int(), vel.as_int()),
+            midly::MidiMessage::NoteOff { key, .. } => (key.as_int(), 0),
+            _ => continue,
+        };
+        let id = (channel, key);
+        if vel > 0 {
+            let held = sounding.iter().position(|(i, _, _)| *i == id);
+            match (held, overlap) {
+                (Some(position), OverlapPolicy::Merge) => {
+                    sounding[position].1 += 1;
+                    continue;
+                },
+                (Some(position), OverlapPolicy::TruncatePrevious) => {
+                    let (_, _, note) = sounding.remove(position);
+                    notes.push(RawNoteData { release: cur_time, ..note });
+                    note_off_time = cur_time;
+                },
+                _ => {},
             }
-            else if let midly::MidiMessage::NoteOff { key , vel: _ } = message {
-                data.push_back(RawNoteData {
-                    key: key.into(),
-                    onset: note_on_time,
-                    vel: cur_velocity,
-                });
+            if sounding.is_empty()
+                && cur_time - note_off_time >= (ticks_per_beat * 0.125).ceil() as u32 {
+                let onset = note_off_time;
+                notes.push(RawNoteData { key: None, onset, vel: 0, release: onset });
+            }
+            let note = RawNoteData { key: Some(key), onset: cur_time, vel, release: cur_time };
+            sounding.push((id, 1, note));
+        } else if let Some(position) = sounding.iter().position(|(i, _, _)| *i == id) {
+            sounding[position].1 -= 1;
+            if sounding[position].1 == 0 {
+                let (_, _, note) = sounding.remove(position);
+                notes.push(RawNoteData { release: cur_time, ..note });
                 note_off_time = cur_time;
             }
         }
     }
+    // Notes that are never released are left out.
+    if !notes.is_empty() {
+        notes.sort_by_key(|n| n.onset);
+        data.extend(notes);
+        // Marks the end of the final note so its length can be measured.
+        data.push_back(RawNoteData {
+            key: None,
+            onset: note_off_time,
+            vel: 0,
+            release: note_off_time,
+        });
+    }
+}
+
+/// Marks the notes that start on the beats in `onsets`, where strummed chords were collapsed, as
+/// arpeggiated.
+fn mark_arpeggios(notes: &mut [NoteWrapper], onsets: &[f32], beat_type: u8) {
+    // The chords were snapped to the grid along with every other note, so each is marked on the
+    // last onset at or before where it was played.
+    let mut beat = 0.0;
+    let mut starts = Vec::new();
+    for wrapper in notes.iter() {
+        starts.push(beat);
+        beat += wrapper.beat_count(beat_type);
+    }
+    for onset in onsets {
+        let position = starts.iter().rposition(|start| *start <= onset + 1e-3);
+        if let Some(wrapper) = position.map(|i| &mut notes[i]) {
+            for note in wrapper.notes_mut().into_iter().filter(|n| !n.is_rest()) {
+                note.arpeggiated = true;
+            }
+        }
+    }
+}
+
+/// Moves the notes of every strummed chord in `data` onto the onset of the chord's first note,
+/// and returns the onsets of the chords moved.
+/// 
+/// A strummed chord is three or more notes of different keys that start within `window` ticks
+/// of its first note, one after another, and are all still sounding when its last note starts.
+fn collapse_arpeggios(data: &mut VecDeque<RawNoteData>, window: u32) -> Vec<u32> {
+    let mut onsets = Vec::new();
+    let mut i = 0;
+    while i < data.len() {
+        let first = data[i];
+        let mut end = i + 1;
+        while end < data.len() {
+            let note = data[end];
+            let repeated = data.range(i..end).any(|n| n.key == note.key);
+            if note.key.is_none() || repeated || note.onset - first.onset > window {
+                break;
+            }
+            end += 1;
+        }
+        let last_onset = data[end - 1].onset;
+        let held = data.range(i..end).all(|n| n.release > last_onset);
+        if first.key.is_some() && end - i >= 3 && last_onset > first.onset && held {
+            for note in data.range_mut(i..end) {
+                note.onset = first.onset;
+            }
+            onsets.push(first.onset);
+            i = end;
+        } else {
+            i += 1;
+        }
+    }
+    return onsets;
+}
 
-    return data;
+/// Marks the notes of a parsed track that are held slightly into the next note of a different
+/// key as slurred into it. `max_overlap` is the longest overlap, in beats, that counts.
+/// 
+/// The notes are found in the midi track's events, since the parsed notes only last until the
+/// next onset, and are matched to the parsed note of the same key that starts closest before
+/// them.
+fn mark_slurs(
+    parsed: &mut Track,
+    track: &[midly::TrackEvent],
+    ticks_per_beat: f32,
+    max_overlap: f32
+) {
+    // The key, onset, and release of every note, in beats.
+    let mut notes: Vec<(u8, f32, f32)> = Vec::new();
+    let mut sounding: Vec<((u8, u8), f32)> = Vec::new();
+    let mut ticks: u32 = 0;
+    for event in track {
+        ticks = add_delta(ticks, event.delta);
+        let (channel, message) = match event.kind {
+            midly::TrackEventKind::Midi { channel, message } => (channel.as_int(), message),
+            _ => continue,
+        };
+        let beat = ticks as f32 / ticks_per_beat;
+        match message {
+            midly::MidiMessage::NoteOn { key, vel } if vel > 0 => {
+                sounding.push(((channel, key.as_int()), beat));
+            },
+            midly::MidiMessage::NoteOn { key, .. } | midly::MidiMessage::NoteOff { key, .. } => {
+                let id = (channel, key.as_int());
+                if let Some(position) = sounding.iter().position(|(i, _)| *i == id) {
+                    let (_, onset) = sounding.remove(position);
+                    notes.push((id.1, onset, beat));
+                }
+            },
+            _ => {},
+        }
+    }
+    notes.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    let mut slurred: Vec<(u8, f32)> = Vec::new();
+    for (i, (key, onset, release)) in notes.iter().enumerate() {
+        let next = match notes[i + 1..].iter().find(|n| n.1 > *onset) {
+            Some(next) => next,
+            None => continue,
+        };
+        let overlap = release - next.1;
+        if next.0 != *key && overlap > 0.0 && overlap <= max_overlap + 1e-4 && *release < next.2 {
+            slurred.push((*key, *onset));
+        }
+    }
+    if slurred.is_empty() {
+        return;
+    }
+
+    let flat = FlatTrack::new(parsed, 2);
+    let mut marks = vec![false; flat.len()];
+    for (key, onset) in slurred {
+        let struck = (0..flat.len())
+            .filter(|i| flat.is_struck(*i) && flat.pitches[*i] == Some(key))
+            .rfind(|i| flat.onsets[*i] <= onset + 1e-3);
+        if let Some(i) = struck {
+            marks[i] = true;
+        }
+    }
+    let notes = parsed.notes.iter_mut().flat_map(|n| n.notes_mut());
+    for (note, slur) in notes.zip(marks) {
+        note.slur = slur;
+    }
 }
 
-fn get_tied_note((value, duration, velocity): (u8, f32, u8), beat_type: u8) -> NoteModifier {
+fn get_tied_note(
+    (value, duration, velocity): (Option<u8>, f32, u8),
+    beat_type: u8
+) -> NoteModifier {
     let mut notes: Vec<NoteWrapper> = Vec::new();
     let mut remaining_beats: f32 = duration;
     while remaining_beats > 0.0 {
@@ -0,0 +1,156 @@
+//! A small text notation for writing music inline.
+//! 
+//! A piece is a list of whitespace-separated notes, such as `"C4:q D4:e E4:e | F4:h r:q"`. Each
+//! note is a pitch and a duration separated by a colon, optionally followed by another colon and
+//! a velocity (e.g. `C4:q:80`).
+//! 
+//! * Pitches are a letter, any number of `#` or `b` accidentals, and an octave, where middle C is
+//!   `C4`. `r` is a rest. Pitches joined with `+` form a chord, e.g. `C4+E4+G4:h`.
+//! * Durations are `w` (whole), `h` (half), `q` (quarter), `e` (eighth), `s` (sixteenth), or `t`
+//!   (thirty-second), followed by a `.` for each dot. Durations joined with `~` are tied, e.g.
+//!   `C4:h~e`.
+//! * `|` marks a bar line. Bar lines are only there for readability and are ignored.
+
+use crate::error::ParseError;
+use crate::parsing::duration::DurationType;
+use crate::parsing::duration::NoteDuration;
+use crate::parsing::duration::NoteDurationModifier;
+use crate::parsing::pitch::Accidental;
+use crate::parsing::pitch::Letter;
+use crate::parsing::pitch::Pitch;
+use crate::parsing::pitch::SpelledPitch;
+use crate::parsing::symbols::Note;
+use crate::parsing::symbols::NoteModifier;
+use crate::parsing::symbols::NoteWrapper;
+
+/// The velocity of notes that don't give one.
+pub const DEFAULT_NOTATION_VELOCITY: u8 = 100;
+
+/// Parses a piece written in the text notation into a list of `NoteWrapper` objects.
+/// 
+/// Returns `ParseError::Notation` for the first note that can't be read.
+pub fn parse_notation(text: &str) -> Result<Vec<NoteWrapper>, ParseError> {
+    let mut notes = Vec::new();
+    for token in text.split_whitespace() {
+        if token == "|" {
+            continue;
+        }
+        notes.push(parse_token(token)?);
+    }
+    return Ok(notes);
+}
+
+/// Parses a single note of the text notation.
+fn parse_token(token: &str) -> Result<NoteWrapper, ParseError> {
+    let parts: Vec<&str> = token.split(':').collect();
+    if parts.len() < 2 || parts.len() > 3 {
+        return Err(notation_error(token, "expected a pitch and a duration, e.g. C4:q"));
+    }
+    let velocity = match parts.get(2) {
+        Some(v) => match v.parse::<u8>() {
+            Ok(v) if v <= 127 => v,
+            _ => return Err(notation_error(token, "velocities must be between 0 and 127")),
+        },
+        None => DEFAULT_NOTATION_VELOCITY,
+    };
+    let mut pitches = Vec::new();
+    for pitch in parts[0].split('+') {
+        pitches.push(parse_pitch(pitch, token)?);
+    }
+    let mut durations = Vec::new();
+    for duration in parts[1].split('~') {
+        durations.push(parse_duration(duration, token)?);
+    }
+
+    let mut segments: Vec<NoteWrapper> = durations.into_iter().map(|duration| {
+        let mut chord: Vec<NoteWrapper> = pitches.iter().map(|pitch| {
+            return build_wrapper(*pitch, duration.clone(), velocity);
+        }).collect();
+        if chord.len() == 1 {
+            return chord.remove(0);
+        }
+        return NoteWrapper::ModifiedNote(NoteModifier::Chord(chord));
+    }).collect();
+    if segments.len() == 1 {
+        return Ok(segments.remove(0));
+    }
+    return Ok(NoteWrapper::ModifiedNote(NoteModifier::TiedNote(segments)));
+}
+
+/// Builds a note (or rest, if `pitch` is `None`) that keeps the spelling it was written with.
+fn build_wrapper(pitch: Option<SpelledPitch>, duration: DurationType, velocity: u8) -> NoteWrapper {
+    match pitch {
+        Some(spelling) => {
+            let mut note = Note::new(spelling.to_value() as u8, duration, velocity);
+            note.pitch = Some(Pitch::from(spelling));
+            return NoteWrapper::PlainNote(note);
+        },
+        None => return NoteWrapper::Rest(Note::rest(duration)),
+    }
+}
+
+/// Parses a pitch such as `F#4`, or `r` for a rest.
+fn parse_pitch(pitch: &str, token: &str) -> Result<Option<SpelledPitch>, ParseError> {
+    if pitch == "r" || pitch == "R" {
+        return Ok(None);
+    }
+    let mut chars = pitch.chars();
+    let letter = match chars.next().map(|c| c.to_ascii_uppercase()) {
+        Some('C') => Letter::C,
+        Some('D') => Letter::D,
+        Some('E') => Letter::E,
+        Some('F') => Letter::F,
+        Some('G') => Letter::G,
+        Some('A') => Letter::A,
+        Some('B') => Letter::B,
+        _ => return Err(notation_error(token, "pitches must start with a letter from A to G")),
+    };
+    let rest = chars.as_str();
+    let octave_start = rest.find(|c: char| c.is_ascii_digit() || c == '-').unwrap_or(rest.len());
+    let (accidentals, octave) = rest.split_at(octave_start);
+    let semitones = accidentals.chars().map(|c| match c {
+        '#' => Some(1),
+        'b' => Some(-1),
+        _ => None,
+    }).sum::<Option<i8>>();
+    let accidental = match semitones.and_then(Accidental::from_semitones) {
+        Some(accidental) => accidental,
+        None => return Err(notation_error(token, "accidentals must be up to two # or b")),
+    };
+    let octave = match octave.parse::<i8>() {
+        Ok(octave) => octave,
+        Err(_) => return Err(notation_error(token, "pitches must end with an octave, e.g. C4")),
+    };
+    let spelling = SpelledPitch { letter, accidental, octave };
+    if !(0..=127).contains(&spelling.to_value()) {
+        return Err(notation_error(token, "the pitch is outside of the midi range"));
+    }
+    return Ok(Some(spelling));
+}
+
+/// Parses a duration such as `q.`.
+fn parse_duration(duration: &str, token: &str) -> Result<DurationType, ParseError> {
+    let dots = duration.chars().rev().take_while(|c| *c == '.').count();
+    let base = match &duration[..duration.len() - dots] {
+        "w" => NoteDuration::WHOLE,
+        "h" => NoteDuration::HALF,
+        "q" => NoteDuration::QUARTER,
+        "e" => NoteDuration::EIGHTH,
+        "s" => NoteDuration::SIXTEENTH,
+        "t" => NoteDuration::THIRTYSECOND,
+        _ => return Err(notation_error(token, "durations must be one of w, h, q, e, s, or t")),
+    };
+    let modifier = match dots {
+        0 => NoteDurationModifier::None,
+        1 => NoteDurationModifier::Dotted,
+        2 => NoteDurationModifier::DoubleDotted,
+        3 => NoteDurationModifier::TripleDotted,
+        _ => return Err(notation_error(token, "durations can have at most three dots")),
+    };
+    return Ok(DurationType { duration: base, modifier });
+}
+
+/// A helper function for building a `ParseError::Notation`.
+fn notation_error(token: &str, message: &str) -> ParseError {
+    return ParseError::Notation { token: String::from(token), message: String::from(message) };
+}
@@ -0,0 +1,586 @@
+use crate::parsing::duration::DurationType;
+use crate::parsing::duration::DEFAULT_DURATION_PRECISION;
+use crate::parsing::cleanup::ChannelFilter;
+use crate::parsing::cleanup::ShortNotePolicy;
+use crate::parsing::expression::PitchBendMode;
+use crate::error::ParseError;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+
+/// A function called with the completion fraction of a parse, from 0 to 1. See
+/// `ParseOptions::on_progress`.
+pub type ProgressCallback = Arc<dyn Fn(f32) + Send + Sync>;
+
+/// The window within which notes from different tracks are considered simultaneous.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SimultaneityWindow {
+    /// A window measured in midi ticks.
+    Ticks(u32),
+    /// A window measured in milliseconds at the initial tempo of the piece.
+    Milliseconds(f32),
+}
+
+/// What happens when a note starts on a key that is already sounding on the same channel.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OverlapPolicy {
+    /// The sounding note ends where the new note starts.
+    TruncatePrevious,
+    /// The new note is joined onto the sounding note, which lasts until both have been
+    /// released.
+    Merge,
+    /// The key is struck again. A track holds a single line of notes, so the sounding note still
+    /// ends where the new note starts, but the new note lasts until the key has been released as
+    /// many times as it was struck. Each note off releases the earliest strike still sounding.
+    Restrike,
+}
+
+/// A resource that a parse can be limited in. See `ResourceLimits`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Limit {
+    /// The size of the file in bytes.
+    FileSize,
+    /// The number of events in the file.
+    Events,
+    /// The number of notes parsed, including rests and the pieces of tied notes.
+    Notes,
+    /// The length of a track in beats.
+    Beats,
+    /// The time spent parsing the tracks.
+    ParseTime,
+}
+
+impl Limit {
+    /// Converts the enum to a string.
+    pub fn to_string(&self) -> &str {
+        match self {
+            Limit::FileSize => return "file size",
+            Limit::Events => return "event count",
+            Limit::Notes => return "note count",
+            Limit::Beats => return "track length",
+            Limit::ParseTime => return "parse time",
+        }
+    }
+}
+
+/// Limits on the resources a parse may use, for services that parse files from untrusted
+/// sources. A parse that goes over a limit stops with `ParseError::LimitExceeded`.
+/// 
+/// Every limit is off by default. A crafted file with absurd delta times can make the notes of a
+/// track span millions of beats, so `max_beats` is worth setting on servers. Tracks longer than
+/// `parsing::MAX_TRACK_TICKS` are refused whatever the limits are.
+/// 
+/// ```
+/// use beatblox_midi::parsing::options::ParseOptions;
+/// use beatblox_midi::parsing::options::ResourceLimits;
+/// use std::time::Duration;
+/// 
+/// let limits = ResourceLimits::new()
+///     .max_file_size(1 << 20)
+///     .max_beats(10_000)
+///     .max_parse_time(Duration::from_secs(5));
+/// let options = ParseOptions::new().limits(limits);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ResourceLimits {
+    /// The largest file, in bytes, that is parsed.
+    pub max_file_size: Option<usize>,
+    /// The most events the file may hold, across all of its tracks.
+    pub max_events: Option<usize>,
+    /// The most notes the parsed piece may hold, including rests and the pieces of tied notes.
+    pub max_notes: Option<usize>,
+    /// The most beats the notes of a track may span.
+    pub max_beats: Option<u32>,
+    /// The longest time that may be spent parsing the tracks.
+    pub max_parse_time: Option<Duration>,
+}
+
+impl ResourceLimits {
+    /// Creates a set of limits with every limit off.
+    pub fn new() -> Self {
+        return ResourceLimits::default();
+    }
+
+    /// Sets the largest file, in bytes, that is parsed.
+    pub fn max_file_size(mut self, bytes: usize) -> Self {
+        self.max_file_size = Some(bytes);
+        return self;
+    }
+
+    /// Sets the most events the file may hold, across all of its tracks.
+    pub fn max_events(mut self, events: usize) -> Self {
+        self.max_events = Some(events);
+        return self;
+    }
+
+    /// Sets the most notes the parsed piece may hold.
+    pub fn max_notes(mut self, notes: usize) -> Self {
+        self.max_notes = Some(notes);
+        return self;
+    }
+
+    /// Sets the most beats the notes of a track may span.
+    pub fn max_beats(mut self, beats: u32) -> Self {
+        self.max_beats = Some(beats);
+        return self;
+    }
+
+    /// Sets the longest time that may be spent parsing the tracks.
+    pub fn max_parse_time(mut self, time: Duration) -> Self {
+        self.max_parse_time = Some(time);
+        return self;
+    }
+
+    /// Returns the time by which a parse that starts now must finish, if it's limited.
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        return self.max_parse_time.map(|time| Instant::now() + time);
+    }
+
+    /// Returns `ParseError::LimitExceeded` if `amount` is over the limit `max`, if there is one.
+    pub(crate) fn check<T: PartialOrd>(
+        limit: Limit,
+        amount: T,
+        max: Option<T>
+    ) -> Result<(), ParseError> {
+        match max {
+            Some(max) if amount > max => return Err(ParseError::LimitExceeded(limit)),
+            _ => return Ok(()),
+        }
+    }
+}
+
+/// Options that control how a midi file is parsed.
+/// 
+/// Options are set with builder-style methods:
+/// 
+/// ```
+/// use beatblox_midi::parsing::options::ParseOptions;
+/// 
+/// let options = ParseOptions::new().triplet(true).skip_empty_tracks(true);
+/// ```
+#[derive(Clone)]
+pub struct ParseOptions {
+    /// Any notes shorter than this duration will be grouped as a chord.
+    pub precision: DurationType,
+    /// Whether or not to scan for triplets. Scanning for triplets requires extra resources.
+    pub triplet: bool,
+    /// Whether or not tracks without any notes are left out of `Midi::tracks`.
+    pub skip_empty_tracks: bool,
+    /// Whether or not note tracks are padded with rests so they all end on the same beat. This is
+    /// off by default, so each track ends with its last note.
+    pub pad_to_score_length: bool,
+    /// Whether or not the final measure of each note track is padded with rests up to the bar
+    /// line. See `Midi::pad_final_measure`.
+    pub pad_final_measure: bool,
+    /// Whether or not a merged view of all note tracks is built. See `Midi::merged_track`.
+    pub merge_tracks: bool,
+    /// The window within which notes from different tracks are grouped as a chord in the merged
+    /// view.
+    pub simultaneity_window: SimultaneityWindow,
+    /// Whether or not parsing fails if the tracks disagree on where the bar lines are. See
+    /// `Midi::check_barlines`.
+    pub check_barlines: bool,
+    /// Whether or not notes that cross a bar line are split into notes tied across it. See
+    /// `Midi::split_at_barlines`.
+    pub split_at_barlines: bool,
+    /// How pitch bend messages are captured on notes.
+    pub pitch_bend: PitchBendMode,
+    /// Whether or not the original events of the file are kept, so writing the piece back out
+    /// reproduces them exactly. See `Midi::to_events`.
+    pub lossless: bool,
+    /// The positions of the tracks in the file that are parsed, or `None` to parse every track.
+    pub tracks: Option<Vec<usize>>,
+    /// Only tracks whose names contain this text, ignoring case, are parsed. See `Track::name`
+    /// for how tracks are named.
+    pub track_name_filter: Option<String>,
+    /// The midi channels that are parsed. Messages on other channels are dropped before the notes
+    /// are snapped to the grid.
+    pub channels: ChannelFilter,
+    /// Notes shorter than this duration are handled by `short_note_policy` before they're
+    /// snapped to the grid, rather than becoming chord members. This is `None` by default.
+    pub min_note_length: Option<DurationType>,
+    /// What happens to notes shorter than `min_note_length`.
+    pub short_note_policy: ShortNotePolicy,
+    /// Whether or not the original timing of the notes is kept, so the piece can be
+    /// requantized later. See `Midi::requantize`.
+    pub retain_timing: bool,
+    /// What happens when a note starts on a key that is already sounding.
+    pub overlap_policy: OverlapPolicy,
+    /// Whether or not a note that is doubled on the same key and onset, as happens with layered
+    /// instruments, is kept once with the louder of the two velocities.
+    pub dedupe_notes: bool,
+    /// The longest overlap between a note and the next note of a different key that is read as
+    /// legato playing, marking the first note as slurred into the second (see `Note::slur`).
+    /// Slurs aren't detected if this is `None`, which it is by default.
+    pub slurs: Option<DurationType>,
+    /// The widest spread of onsets at which the notes of a strummed chord are collapsed into a
+    /// chord, which is marked as arpeggiated (see `Note::arpeggiated`). Strums are left as they
+    /// were played if this is `None`, which it is by default.
+    pub arpeggios: Option<DurationType>,
+    /// The function called as parsing progresses, if there is one. See
+    /// `ParseOptions::on_progress`.
+    pub on_progress: Option<ProgressCallback>,
+    /// A flag that cancels the parse when it's set, if there is one. See
+    /// `ParseOptions::cancel_token`.
+    pub cancel_token: Option<Arc<AtomicBool>>,
+    /// The limits on the resources the parse may use.
+    pub limits: ResourceLimits,
+}
+
+impl ParseOptions {
+    /// Creates the default set of parse options.
+    pub fn new() -> Self {
+        ParseOptions {
+            precision: DEFAULT_DURATION_PRECISION,
+            triplet: false,
+            skip_empty_tracks: false,
+            pad_to_score_length: false,
+            pad_final_measure: false,
+            merge_tracks: false,
+            simultaneity_window: SimultaneityWindow::Ticks(0),
+            check_barlines: false,
+            split_at_barlines: false,
+            pitch_bend: PitchBendMode::Curve,
+            lossless: false,
+            tracks: None,
+            track_name_filter: None,
+            channels: ChannelFilter::All,
+            min_note_length: None,
+            short_note_policy: ShortNotePolicy::Discard,
+            retain_timing: false,
+            overlap_policy: OverlapPolicy::TruncatePrevious,
+            dedupe_notes: false,
+            slurs: None,
+            arpeggios: None,
+            on_progress: None,
+            cancel_token: None,
+            limits: ResourceLimits::new(),
+        }
+    }
+
+    /// Sets the parsing precision.
+    pub fn precision(mut self, precision: DurationType) -> Self {
+        self.precision = precision;
+        return self;
+    }
+
+    /// Sets whether or not to scan for triplets.
+    pub fn triplet(mut self, triplet: bool) -> Self {
+        self.triplet = triplet;
+        return self;
+    }
+
+    /// Sets whether or not tracks without any notes are left out of `Midi::tracks`.
+    pub fn skip_empty_tracks(mut self, skip: bool) -> Self {
+        self.skip_empty_tracks = skip;
+        return self;
+    }
+
+    /// Sets whether or not note tracks are padded with rests so they all end on the same beat.
+    pub fn pad_to_score_length(mut self, pad: bool) -> Self {
+        self.pad_to_score_length = pad;
+        return self;
+    }
+
+    /// Sets whether or not the final measure of each note track is padded with rests up to the
+    /// bar line.
+    pub fn pad_final_measure(mut self, pad: bool) -> Self {
+        self.pad_final_measure = pad;
+        return self;
+    }
+
+    /// Sets whether or not a merged view of all note tracks is built.
+    pub fn merge_tracks(mut self, merge: bool) -> Self {
+        self.merge_tracks = merge;
+        return self;
+    }
+
+    /// Sets the window within which notes from different tracks are grouped as a chord in the
+    /// merged view.
+    pub fn simultaneity_window(mut self, window: SimultaneityWindow) -> Self {
+        self.simultaneity_window = window;
+        return self;
+    }
+
+    /// Sets whether or not parsing fails if the tracks disagree on where the bar lines are.
+    pub fn check_barlines(mut self, check: bool) -> Self {
+        self.check_barlines = check;
+        return self;
+    }
+
+    /// Sets whether or not notes that cross a bar line are split into notes tied across it.
+    pub fn split_at_barlines(mut self, split: bool) -> Self {
+        self.split_at_barlines = split;
+        return self;
+    }
+
+    /// Sets how pitch bend messages are captured on notes.
+    pub fn pitch_bend(mut self, mode: PitchBendMode) -> Self {
+        self.pitch_bend = mode;
+        return self;
+    }
+
+    /// Sets whether or not the original events of the file are kept.
+    pub fn lossless(mut self, lossless: bool) -> Self {
+        self.lossless = lossless;
+        return self;
+    }
+
+    /// Sets the positions of the tracks in the file to parse, counting from 0. Other tracks are
+    /// left out of `Midi::tracks` without being parsed.
+    pub fn tracks(mut self, indices: &[usize]) -> Self {
+        self.tracks = Some(indices.to_vec());
+        return self;
+    }
+
+    /// Only parses tracks whose names contain `pattern`, ignoring case, e.g. "bass".
+    pub fn track_name_filter(mut self, pattern: &str) -> Self {
+        self.track_name_filter = Some(pattern.to_string());
+        return self;
+    }
+
+    /// Sets the midi channels to parse, counting from 0, e.g. `channels(&[9])` for just the
+    /// percussion channel.
+    pub fn channels(mut self, channels: &[u8]) -> Self {
+        self.channels = ChannelFilter::Only(channels.to_vec());
+        return self;
+    }
+
+    /// Sets the midi channels to leave out, counting from 0.
+    pub fn exclude_channels(mut self, channels: &[u8]) -> Self {
+        self.channels = ChannelFilter::Except(channels.to_vec());
+        return self;
+    }
+
+    /// Sets the shortest note that is kept as it is, and what happens to notes shorter than it.
+    /// Key bounces on cheap keyboards, for example, can be merged into the notes they belong to
+    /// with `ShortNotePolicy::Merge`.
+    pub fn min_note_length(mut self, duration: DurationType, policy: ShortNotePolicy) -> Self {
+        self.min_note_length = Some(duration);
+        self.short_note_policy = policy;
+        return self;
+    }
+
+    /// Sets whether or not the original timing of the notes is kept.
+    pub fn retain_timing(mut self, retain: bool) -> Self {
+        self.retain_timing = retain;
+        return self;
+    }
+
+    /// Sets what happens when a note starts on a key that is already sounding, as happens when
+    /// a sustained note is struck again or two layered parts double a pitch.
+    pub fn overlap_policy(mut self, policy: OverlapPolicy) -> Self {
+        self.overlap_policy = policy;
+        return self;
+    }
+
+    /// Sets whether or not doubled notes within a chord are kept once, with the louder velocity.
+    pub fn dedupe_notes(mut self, dedupe: bool) -> Self {
+        self.dedupe_notes = dedupe;
+        return self;
+    }
+
+    /// Sets the longest overlap between consecutive notes of different keys that is read as a
+    /// slur, e.g. `slurs(sixteenth())`. Notes that are held longer than this into the next note
+    /// aren't slurred.
+    pub fn slurs(mut self, max_overlap: DurationType) -> Self {
+        self.slurs = Some(max_overlap);
+        return self;
+    }
+
+    /// Sets the widest spread of onsets at which three or more notes that ring together are read
+    /// as a strummed chord, e.g. `arpeggios(eighth())` for strummed guitar parts. Each strum is
+    /// collapsed onto the onset of its first note before the notes are snapped to the grid.
+    pub fn arpeggios(mut self, max_spread: DurationType) -> Self {
+        self.arpeggios = Some(max_spread);
+        return self;
+    }
+
+    /// Sets a function to call with the completion fraction of the parse, from 0 to 1, e.g. for
+    /// showing a progress bar while a long file is parsed.
+    /// 
+    /// The function is called after each track is parsed, and every few beats while a long track
+    /// is parsed.
+    pub fn on_progress<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(f32) + Send + Sync + 'static
+    {
+        self.on_progress = Some(Arc::new(callback));
+        return self;
+    }
+
+    /// Sets a flag that cancels the parse when it's set from another thread, e.g. to abort a
+    /// request that is taking too long. A cancelled parse returns `ParseError::Cancelled`.
+    /// 
+    /// The flag is checked every few beats while notes are snapped to the grid and triplets are
+    /// found.
+    pub fn cancel_token(mut self, token: Arc<AtomicBool>) -> Self {
+        self.cancel_token = Some(token);
+        return self;
+    }
+
+    /// Sets the limits on the resources the parse may use.
+    pub fn limits(mut self, limits: ResourceLimits) -> Self {
+        self.limits = limits;
+        return self;
+    }
+
+    /// Returns `ParseError::Cancelled` if the parse has been cancelled.
+    pub(crate) fn check_cancelled(&self) -> Result<(), ParseError> {
+        match &self.cancel_token {
+            Some(token) if token.load(Ordering::Relaxed) => return Err(ParseError::Cancelled),
+            _ => return Ok(()),
+        }
+    }
+
+    /// Hashes the options that change what a parse returns, so parses of the same file with
+    /// options that only differ in their progress function, cancel token, or time limit share
+    /// the same key.
+    pub(crate) fn fingerprint<H: Hasher>(&self, hasher: &mut H) {
+        self.precision.get_beat_count(2).to_bits().hash(hasher);
+        match self.simultaneity_window {
+            SimultaneityWindow::Ticks(ticks) => (0, ticks).hash(hasher),
+            SimultaneityWindow::Milliseconds(ms) => (1, ms.to_bits()).hash(hasher),
+        }
+        (self.triplet, self.skip_empty_tracks, self.pad_to_score_length).hash(hasher);
+        (self.merge_tracks, self.check_barlines, self.lossless).hash(hasher);
+        (self.retain_timing, self.dedupe_notes, self.split_at_barlines).hash(hasher);
+        self.pad_final_measure.hash(hasher);
+        format!("{:?} {:?}", self.pitch_bend, self.overlap_policy).hash(hasher);
+        (&self.tracks, &self.track_name_filter).hash(hasher);
+        format!("{:?}", self.channels).hash(hasher);
+        self.min_note_length.as_ref().map(|d| d.get_beat_count(2).to_bits()).hash(hasher);
+        self.slurs.as_ref().map(|d| d.get_beat_count(2).to_bits()).hash(hasher);
+        self.arpeggios.as_ref().map(|d| d.get_beat_count(2).to_bits()).hash(hasher);
+        format!("{:?}", self.short_note_policy).hash(hasher);
+        let limits = &self.limits;
+        (limits.max_file_size, limits.max_events, limits.max_notes).hash(hasher);
+        limits.max_beats.hash(hasher);
+    }
+
+    /// Calls the progress function, if there is one, with the completion fraction `fraction`.
+    pub(crate) fn report_progress(&self, fraction: f32) {
+        if let Some(callback) = &self.on_progress {
+            callback(fraction.clamp(0.0, 1.0));
+        }
+    }
+}
+
+/// The options a piece parsed with `ParseOptions::retain_timing` is requantized with, which are
+/// the options that shape its notes other than the precision and triplet setting. The progress
+/// function, cancel token, and resource limits only apply to the first parse, so they're left
+/// out. See `Midi::requantize`.
+#[derive(Clone)]
+pub(crate) struct RetainedOptions {
+    skip_empty_tracks: bool,
+    pad_to_score_length: bool,
+    pad_final_measure: bool,
+    merge_tracks: bool,
+    simultaneity_window: SimultaneityWindow,
+    check_barlines: bool,
+    split_at_barlines: bool,
+    pitch_bend: PitchBendMode,
+    lossless: bool,
+    tracks: Option<Vec<usize>>,
+    track_name_filter: Option<String>,
+    channels: ChannelFilter,
+    min_note_length: Option<DurationType>,
+    short_note_policy: ShortNotePolicy,
+    overlap_policy: OverlapPolicy,
+    dedupe_notes: bool,
+    slurs: Option<DurationType>,
+    arpeggios: Option<DurationType>,
+}
+
+impl RetainedOptions {
+    /// Keeps the options of `options` that a requantized parse needs.
+    pub(crate) fn new(options: &ParseOptions) -> Self {
+        let ParseOptions {
+            precision: _,
+            triplet: _,
+            skip_empty_tracks,
+            pad_to_score_length,
+            pad_final_measure,
+            merge_tracks,
+            simultaneity_window,
+            check_barlines,
+            split_at_barlines,
+            pitch_bend,
+            lossless,
+            tracks,
+            track_name_filter,
+            channels,
+            min_note_length,
+            short_note_policy,
+            retain_timing: _,
+            overlap_policy,
+            dedupe_notes,
+            slurs,
+            arpeggios,
+            on_progress: _,
+            cancel_token: _,
+            limits: _,
+        } = options.clone();
+        return RetainedOptions {
+            skip_empty_tracks,
+            pad_to_score_length,
+            pad_final_measure,
+            merge_tracks,
+            simultaneity_window,
+            check_barlines,
+            split_at_barlines,
+            pitch_bend,
+            lossless,
+            tracks,
+            track_name_filter,
+            channels,
+            min_note_length,
+            short_note_policy,
+            overlap_policy,
+            dedupe_notes,
+            slurs,
+            arpeggios,
+        };
+    }
+
+    /// Builds the options of a requantized parse with the given precision and triplet setting.
+    pub(crate) fn to_options(&self, precision: DurationType, triplet: bool) -> ParseOptions {
+        let retained = self.clone();
+        return ParseOptions {
+            precision,
+            triplet,
+            skip_empty_tracks: retained.skip_empty_tracks,
+            pad_to_score_length: retained.pad_to_score_length,
+            pad_final_measure: retained.pad_final_measure,
+            merge_tracks: retained.merge_tracks,
+            simultaneity_window: retained.simultaneity_window,
+            check_barlines: retained.check_barlines,
+            split_at_barlines: retained.split_at_barlines,
+            pitch_bend: retained.pitch_bend,
+            lossless: retained.lossless,
+            tracks: retained.tracks,
+            track_name_filter: retained.track_name_filter,
+            channels: retained.channels,
+            min_note_length: retained.min_note_length,
+            short_note_policy: retained.short_note_policy,
+            retain_timing: true,
+            overlap_policy: retained.overlap_policy,
+            dedupe_notes: retained.dedupe_notes,
+            slurs: retained.slurs,
+            arpeggios: retained.arpeggios,
+            on_progress: None,
+            cancel_token: None,
+            limits: ResourceLimits::new(),
+        };
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        return ParseOptions::new();
+    }
+}
@@ -0,0 +1,332 @@
+use crate::parsing::symbols::KeySignature;
+use crate::parsing::symbols::NoteModifier;
+use crate::parsing::symbols::NoteWrapper;
+
+/// The octave of middle C in scientific pitch notation.
+pub const DEFAULT_MIDDLE_C_OCTAVE: i8 = 4;
+
+/// The pitch class of each natural letter, starting from C.
+const NATURAL_PITCH_CLASSES: [i16; 7] = [0, 2, 4, 5, 7, 9, 11];
+
+/// The letter name of a pitch.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
+pub enum Letter {
+    C,
+    D,
+    E,
+    F,
+    G,
+    A,
+    B,
+}
+
+impl Letter {
+    /// All the letters in order, starting from C.
+    pub const ALL: [Letter; 7] = [
+        Letter::C, Letter::D, Letter::E, Letter::F, Letter::G, Letter::A, Letter::B
+    ];
+
+    /// Converts the enum to a string.
+    pub fn to_string(&self) -> &str {
+        match self {
+            Letter::C => return "C",
+            Letter::D => return "D",
+            Letter::E => return "E",
+            Letter::F => return "F",
+            Letter::G => return "G",
+            Letter::A => return "A",
+            Letter::B => return "B",
+        }
+    }
+
+    /// The pitch class of the natural letter, where C is 0.
+    pub fn pitch_class(&self) -> u8 {
+        return NATURAL_PITCH_CLASSES[*self as usize] as u8;
+    }
+}
+
+/// An accidental applied to a letter name.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
+pub enum Accidental {
+    DoubleFlat,
+    Flat,
+    Natural,
+    Sharp,
+    DoubleSharp,
+}
+
+impl Accidental {
+    /// Converts the enum to a string.
+    ///
+    /// Naturals are represented by an empty string since they are rarely written out.
+    pub fn to_string(&self) -> &str {
+        match self {
+            Accidental::DoubleFlat => return "bb",
+            Accidental::Flat => return "b",
+            Accidental::Natural => return "",
+            Accidental::Sharp => return "#",
+            Accidental::DoubleSharp => return "##",
+        }
+    }
+
+    /// The number of semitones the accidental raises (positive) or lowers (negative) a letter.
+    pub fn semitones(&self) -> i8 {
+        match self {
+            Accidental::DoubleFlat => return -2,
+            Accidental::Flat => return -1,
+            Accidental::Natural => return 0,
+            Accidental::Sharp => return 1,
+            Accidental::DoubleSharp => return 2,
+        }
+    }
+
+    /// Maps a number of semitones to an `Accidental`, if one exists.
+    pub fn from_semitones(semitones: i8) -> Option<Accidental> {
+        match semitones {
+            -2 => return Some(Accidental::DoubleFlat),
+            -1 => return Some(Accidental::Flat),
+            0 => return Some(Accidental::Natural),
+            1 => return Some(Accidental::Sharp),
+            2 => return Some(Accidental::DoubleSharp),
+            _ => return None,
+        }
+    }
+}
+
+/// A pitch spelled with a letter name, accidental, and octave.
+///
+/// Octaves follow scientific pitch notation, so middle C (midi value 60) is C4. The octave
+/// belongs to the letter, so midi value 71 spelled as Cb is Cb5.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpelledPitch {
+    pub letter: Letter,
+    pub accidental: Accidental,
+    pub octave: i8,
+}
+
+impl SpelledPitch {
+    /// Spells a midi value according to the given key signature.
+    ///
+    /// Notes that belong to the key are spelled as they appear in its scale. Chromatic notes
+    /// are spelled with the accidental closest to the key, favoring sharps in sharp keys (and C
+    /// major) and flats in flat keys. If `key` is `None`, C major is assumed.
+    pub fn spell(value: u8, key: Option<KeySignature>) -> SpelledPitch {
+        let sharps = match key {
+            Some(k) => k.sharps as i16,
+            None => 0,
+        };
+        let key_accidentals = get_key_accidentals(sharps);
+        let pitch_class = value as i16 % 12;
+
+        let mut best: Option<(i16, usize, i16)> = None;
+        for letter in 0..7 {
+            let semitones = (pitch_class - NATURAL_PITCH_CLASSES[letter] + 18).rem_euclid(12) - 6;
+            if semitones.abs() > 2 {
+                continue;
+            }
+            let mut cost = (semitones - key_accidentals[letter]).abs() * 10 + semitones.abs() * 2;
+            if (sharps >= 0 && semitones < 0) || (sharps < 0 && semitones > 0) {
+                cost += 1;
+            }
+            if best.is_none() || cost < best.unwrap().0 {
+                best = Some((cost, letter, semitones));
+            }
+        }
+
+        let (_, letter, semitones) = best.unwrap();
+        let natural_value = value as i16 - semitones;
+        return SpelledPitch {
+            letter: Letter::ALL[letter],
+            accidental: Accidental::from_semitones(semitones as i8).unwrap(),
+            octave: (natural_value.div_euclid(12) - 1) as i8,
+        };
+    }
+
+    /// Converts the spelled pitch back into a midi value.
+    pub fn to_value(&self) -> i16 {
+        let natural = (self.octave as i16 + 1) * 12 + self.letter.pitch_class() as i16;
+        return natural + self.accidental.semitones() as i16;
+    }
+
+    /// Formats the spelled pitch as a string, e.g. "F#4".
+    pub fn name(&self) -> String {
+        return format!(
+            "{}{}{}",
+            self.letter.to_string(),
+            self.accidental.to_string(),
+            self.octave
+        );
+    }
+}
+
+/// The pitch of a note, as a midi value and, once it's been through the spelling pass, how it's
+/// spelled in the key it is played in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pitch {
+    /// The midi value of the pitch, where middle C is 60.
+    pub value: u8,
+    /// The spelling of the pitch, or `None` if it hasn't been spelled.
+    pub spelling: Option<SpelledPitch>,
+}
+
+impl Pitch {
+    /// Creates an unspelled pitch from a midi value.
+    pub fn new(value: u8) -> Pitch {
+        return Pitch { value, spelling: None };
+    }
+
+    /// Creates a pitch from a midi value, spelled according to the given key signature. See
+    /// `SpelledPitch::spell`.
+    pub fn spell(value: u8, key: Option<KeySignature>) -> Pitch {
+        return Pitch { value, spelling: Some(SpelledPitch::spell(value, key)) };
+    }
+
+    /// Returns the spelling of the pitch, or a spelling with sharps if it hasn't been spelled.
+    pub fn spelled(&self) -> SpelledPitch {
+        return self.spelling.unwrap_or_else(|| SpelledPitch::spell(self.value, None));
+    }
+
+    /// Moves the pitch to the midi value `value`. A spelled pitch is spelled again with sharps,
+    /// since the key it was spelled in isn't known.
+    pub fn set_value(&mut self, value: u8) {
+        self.value = value;
+        if self.spelling.is_some() {
+            self.spelling = Some(SpelledPitch::spell(value, None));
+        }
+    }
+}
+
+impl From<u8> for Pitch {
+    fn from(value: u8) -> Pitch {
+        return Pitch::new(value);
+    }
+}
+
+impl From<Pitch> for u8 {
+    fn from(pitch: Pitch) -> u8 {
+        return pitch.value;
+    }
+}
+
+impl From<SpelledPitch> for Pitch {
+    /// Keeps the spelling, clamping its value into the midi range.
+    fn from(spelling: SpelledPitch) -> Pitch {
+        let value = spelling.to_value().clamp(0, 127) as u8;
+        return Pitch { value, spelling: Some(spelling) };
+    }
+}
+
+impl From<Pitch> for SpelledPitch {
+    fn from(pitch: Pitch) -> SpelledPitch {
+        return pitch.spelled();
+    }
+}
+
+/// Returns the accidental (in semitones) the key signature applies to each letter.
+fn get_key_accidentals(sharps: i16) -> [i16; 7] {
+    let mut accidentals = [0; 7];
+    // Sharps are added in the order F C G D A E B and flats in the reverse order.
+    let order: [usize; 7] = [3, 0, 4, 1, 5, 2, 6];
+    for i in 0..sharps.unsigned_abs().min(7) as usize {
+        if sharps > 0 {
+            accidentals[order[i]] = 1;
+        } else {
+            accidentals[order[6 - i]] = -1;
+        }
+    }
+    return accidentals;
+}
+
+/// Spells every note in `notes` according to the key signature in effect when the note starts.
+///
+/// `key_signatures` must be sorted by their time of occurance. `beat_type` and `ticks_per_beat`
+/// are used to find the tick on which each note starts.
+pub fn spell_notes(
+    notes: &mut [NoteWrapper],
+    key_signatures: &[KeySignature],
+    beat_type: u8,
+    ticks_per_beat: f32
+) {
+    let mut beat = 0.0;
+    for wrapper in notes {
+        let tick = (beat * ticks_per_beat) as u32;
+        let key = key_signatures.iter().rev().find(|k| k.time_of_occurance <= tick).copied();
+        beat += wrapper.beat_count(beat_type);
+        spell_wrapper(wrapper, key);
+    }
+}
+
+/// A helper function for spelling every note inside of a `NoteWrapper`.
+fn spell_wrapper(wrapper: &mut NoteWrapper, key: Option<KeySignature>) {
+    match wrapper {
+        NoteWrapper::PlainNote(n) => n.pitch = n.value().map(|v| Pitch::spell(v, key)),
+        NoteWrapper::Rest(_) => (),
+        NoteWrapper::ModifiedNote(m) => {
+            let (NoteModifier::TiedNote(v) | NoteModifier::Chord(v) | NoteModifier::Triplet(v)) = m;
+            for n in v {
+                spell_wrapper(n, key);
+            }
+        },
+    }
+}
+
+/// A scale that pitches can be snapped to. See `Track::snap_to_scale`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Scale {
+    Major,
+    NaturalMinor,
+    HarmonicMinor,
+    MajorPentatonic,
+    MinorPentatonic,
+    Blues,
+}
+
+impl Scale {
+    /// Converts the enum to a string.
+    pub fn to_string(&self) -> &str {
+        match self {
+            Scale::Major => return "major",
+            Scale::NaturalMinor => return "natural minor",
+            Scale::HarmonicMinor => return "harmonic minor",
+            Scale::MajorPentatonic => return "major pentatonic",
+            Scale::MinorPentatonic => return "minor pentatonic",
+            Scale::Blues => return "blues",
+        }
+    }
+
+    /// The number of semitones between the tonic and each degree of the scale.
+    pub fn intervals(&self) -> &[u8] {
+        match self {
+            Scale::Major => return &[0, 2, 4, 5, 7, 9, 11],
+            Scale::NaturalMinor => return &[0, 2, 3, 5, 7, 8, 10],
+            Scale::HarmonicMinor => return &[0, 2, 3, 5, 7, 8, 11],
+            Scale::MajorPentatonic => return &[0, 2, 4, 7, 9],
+            Scale::MinorPentatonic => return &[0, 3, 5, 7, 10],
+            Scale::Blues => return &[0, 3, 5, 6, 7, 10],
+        }
+    }
+
+    /// Whether a midi value belongs to the scale built on `tonic`, a pitch class where C is 0.
+    pub fn contains(&self, value: u8, tonic: u8) -> bool {
+        let degree = (value as i16 - tonic as i16).rem_euclid(12) as u8;
+        return self.intervals().contains(&degree);
+    }
+
+    /// Moves a midi value to the nearest pitch in the scale built on `tonic`, a pitch class
+    /// where C is 0. Values halfway between two pitches of the scale are moved down.
+    pub fn snap(&self, value: u8, tonic: u8) -> u8 {
+        for distance in 0..12 {
+            if value >= distance && self.contains(value - distance, tonic) {
+                return value - distance;
+            }
+            if value as u16 + distance as u16 <= 127 && self.contains(value + distance, tonic) {
+                return value + distance;
+            }
+        }
+        return value;
+    }
+}
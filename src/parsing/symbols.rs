@@ -1,16 +1,21 @@
+use crate::parsing::beaming::Beam;
 use crate::parsing::duration::DurationType;
+use crate::parsing::dynamics::Dynamic;
+use crate::parsing::expression::Glissando;
+use crate::parsing::expression::Vibrato;
+use crate::parsing::general_midi;
+use crate::parsing::pitch::Pitch;
+use crate::parsing::pitch::SpelledPitch;
+use crate::parsing::pitch::DEFAULT_MIDDLE_C_OCTAVE;
+use crate::parsing::tempo::TempoMap;
 
-/// Represents the content of a midi track.
-#[derive(Clone)]
-pub struct Track {
-    /// The name of the track.
-    pub name: String,
-    /// A vector of all the notes played in the track.
-    pub notes: Vec<NoteWrapper>
-}
+/// The content of a midi track.
+#[deprecated(note = "use `parsing::Track`, also found in `model`, instead")]
+pub type Track = crate::parsing::Track;
 
 /// A wrapper for a musical note.
 #[derive(Clone)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
 pub enum NoteWrapper {
     PlainNote(Note),
     ModifiedNote(NoteModifier),
@@ -18,12 +23,128 @@ pub enum NoteWrapper {
 }
 
 impl NoteWrapper {
-    /// A helper function to create a `NoteWrapper` object.
-    pub fn build_note_wrapper(value: u8, duration: DurationType, velocity: u8) -> Self {
-        if value == 255 {
-            return NoteWrapper::Rest(Note {value: value, duration: duration, velocity: velocity});
+    /// A helper function to create a `NoteWrapper` object, which is a rest if `value` is `None`.
+    pub fn build_note_wrapper(value: Option<u8>, duration: DurationType, velocity: u8) -> Self {
+        match value {
+            Some(value) => return NoteWrapper::PlainNote(Note::new(value, duration, velocity)),
+            None => return NoteWrapper::Rest(Note::rest(duration)),
+        }
+    }
+
+    /// Returns the number of beats the `NoteWrapper` takes up.
+    /// 
+    /// Triplets are stored with their dupal counterparts, so their length is scaled by two
+    /// thirds.
+    pub fn beat_count(&self, beat_type: u8) -> f32 {
+        match self {
+            NoteWrapper::PlainNote(n) | NoteWrapper::Rest(n) => {
+                return n.duration.get_beat_count(beat_type);
+            },
+            NoteWrapper::ModifiedNote(NoteModifier::TiedNote(t)) => {
+                return t.iter().map(|n| n.beat_count(beat_type)).sum();
+            },
+            NoteWrapper::ModifiedNote(NoteModifier::Chord(c)) => {
+                return c.iter().map(|n| n.beat_count(beat_type)).fold(0.0, f32::max);
+            },
+            NoteWrapper::ModifiedNote(NoteModifier::Triplet(tr)) => {
+                let beats: f32 = tr.iter().map(|n| n.beat_count(beat_type)).sum();
+                return beats * 2.0 / 3.0;
+            },
+        }
+    }
+
+    /// Returns every `Note` inside of the `NoteWrapper`, including rests.
+    pub fn notes(&self) -> Vec<&Note> {
+        match self {
+            NoteWrapper::PlainNote(n) | NoteWrapper::Rest(n) => return vec![n],
+            NoteWrapper::ModifiedNote(
+                NoteModifier::TiedNote(v) | NoteModifier::Chord(v) | NoteModifier::Triplet(v)
+            ) => {
+                return v.iter().flat_map(|n| n.notes()).collect();
+            },
+        }
+    }
+
+    /// Returns a mutable reference to every `Note` inside of the `NoteWrapper`, including rests.
+    pub fn notes_mut(&mut self) -> Vec<&mut Note> {
+        match self {
+            NoteWrapper::PlainNote(n) | NoteWrapper::Rest(n) => return vec![n],
+            NoteWrapper::ModifiedNote(
+                NoteModifier::TiedNote(v) | NoteModifier::Chord(v) | NoteModifier::Triplet(v)
+            ) => {
+                return v.iter_mut().flat_map(|n| n.notes_mut()).collect();
+            },
+        }
+    }
+
+    /// Calls `f` on every `Note` inside of the `NoteWrapper`, including rests, along with the beat
+    /// the note starts on and the number of beats it lasts.
+    /// 
+    /// `onset` is the beat on which the `NoteWrapper` starts.
+    pub fn for_each_note<F>(&self, onset: f32, beat_type: u8, f: &mut F)
+    where
+        F: FnMut(&Note, f32, f32)
+    {
+        let timings = self.get_note_timings(onset, beat_type);
+        for (note, (onset, length)) in self.notes().into_iter().zip(timings) {
+            f(note, onset, length);
+        }
+    }
+
+    /// Calls `f` on every `Note` inside of the `NoteWrapper`, including rests, along with the beat
+    /// the note starts on and the number of beats it lasts.
+    /// 
+    /// `onset` is the beat on which the `NoteWrapper` starts.
+    pub fn for_each_note_mut<F>(&mut self, onset: f32, beat_type: u8, f: &mut F)
+    where
+        F: FnMut(&mut Note, f32, f32)
+    {
+        let timings = self.get_note_timings(onset, beat_type);
+        for (note, (onset, length)) in self.notes_mut().into_iter().zip(timings) {
+            f(note, onset, length);
+        }
+    }
+
+    /// Returns the beat each `Note` inside of the `NoteWrapper` starts on and the number of beats
+    /// it lasts, in the same order as `NoteWrapper::notes`.
+    fn get_note_timings(&self, onset: f32, beat_type: u8) -> Vec<(f32, f32)> {
+        let mut timings = Vec::new();
+        self.push_note_timings(onset, beat_type, 1.0, &mut timings);
+        return timings;
+    }
+
+    /// A helper function for `get_note_timings` that scales every length by `scale`.
+    fn push_note_timings(
+        &self,
+        onset: f32,
+        beat_type: u8,
+        scale: f32,
+        timings: &mut Vec<(f32, f32)>
+    ) {
+        match self {
+            NoteWrapper::PlainNote(n) | NoteWrapper::Rest(n) => {
+                timings.push((onset, n.duration.get_beat_count(beat_type) * scale));
+            },
+            NoteWrapper::ModifiedNote(NoteModifier::Chord(c)) => {
+                for n in c {
+                    n.push_note_timings(onset, beat_type, scale, timings);
+                }
+            },
+            NoteWrapper::ModifiedNote(NoteModifier::TiedNote(t)) => {
+                let mut beat = onset;
+                for n in t {
+                    n.push_note_timings(beat, beat_type, scale, timings);
+                    beat += n.beat_count(beat_type) * scale;
+                }
+            },
+            NoteWrapper::ModifiedNote(NoteModifier::Triplet(tr)) => {
+                let mut beat = onset;
+                for n in tr {
+                    n.push_note_timings(beat, beat_type, scale * 2.0 / 3.0, timings);
+                    beat += n.beat_count(beat_type) * scale * 2.0 / 3.0;
+                }
+            },
         }
-        return NoteWrapper::PlainNote(Note {value: value, duration: duration, velocity: velocity});
     }
 
     /// Pretty prints a `NoteWrapper` object.
@@ -32,7 +153,7 @@ impl NoteWrapper {
             NoteWrapper::PlainNote(n) => {
                 let duration_str = n.duration.duration.to_string();
                 let mod_str = n.duration.modifier.to_string();
-                print!("Note: {} | ", n.value);
+                print!("Note: {} | ", n.value().unwrap_or_default());
                 print!("Duration: {} {} | ", mod_str, duration_str);
                 println!("Velocity: {}", n.velocity);
             },
@@ -68,6 +189,7 @@ impl NoteWrapper {
 
 /// Simulates a beatblox modifier being placed on a note.
 #[derive(Clone)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
 pub enum NoteModifier {
     TiedNote(Vec<NoteWrapper>),
     Chord(Vec<NoteWrapper>),
@@ -76,14 +198,157 @@ pub enum NoteModifier {
 
 /// The basic representation of a note.
 #[derive(Clone)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
 pub struct Note {
-    pub value: u8,
+    /// The pitch of the note, or `None` for a rest.
+    /// 
+    /// The pitch is only spelled once the note has been through the spelling pass.
+    pub pitch: Option<Pitch>,
     pub duration: DurationType,
     pub velocity: u8,
+    /// The lyric sung on the note, if there is one.
+    pub lyric: Option<String>,
+    /// The pitch bend applied while the note sounds, as `(beat, cents)` pairs where `beat` is
+    /// counted from the start of the note.
+    /// 
+    /// This is only filled in when parsing with `PitchBendMode::Curve`.
+    pub bend: Vec<(f32, f32)>,
+    /// The average pitch bend applied while the note sounds, in cents.
+    /// 
+    /// This is only filled in when parsing with `PitchBendMode::Detune`.
+    pub detune: Option<f32>,
+    /// The key pressure (aftertouch) applied while the note sounds, as `(beat, value)` pairs
+    /// where `beat` is counted from the start of the note.
+    pub pressure: Vec<(f32, u8)>,
+    /// The slide between pitches played with pitch bends while the note sounds, if there is
+    /// one. This is filled in unless parsing with `PitchBendMode::Ignore`.
+    pub glissando: Option<Glissando>,
+    /// The vibrato played on the note, if there is one, read from the pitch bend unless parsing
+    /// with `PitchBendMode::Ignore`, or else from the modulation wheel.
+    pub vibrato: Option<Vibrato>,
+    /// The dynamic marking of the note, taken from its velocity.
+    /// 
+    /// This is `None` for rests and for notes that have not been through `Track::mark_dynamics`.
+    pub dynamic: Option<Dynamic>,
+    /// Whether the note is accented. See `Track::mark_accents`.
+    pub accent: bool,
+    /// Whether the note is slurred into the note that follows it. A run of slurred notes and the
+    /// note after it make up a slur. See `ParseOptions::slurs`.
+    pub slur: bool,
+    /// Whether the note is held beyond its written length. See `Track::mark_fermatas`.
+    pub fermata: bool,
+    /// Whether the note is part of a chord that was strummed. See `ParseOptions::arpeggios`.
+    pub arpeggiated: bool,
+    /// The place of the note within a group of beamed notes, if it's beamed. See
+    /// `Track::mark_beams`.
+    pub beam: Option<Beam>,
+}
+
+impl Note {
+    /// Creates a plain note, without any spelling, lyric, pitch bend, dynamic, or articulation.
+    pub fn new(value: u8, duration: DurationType, velocity: u8) -> Self {
+        Note {
+            pitch: Some(Pitch::new(value)),
+            duration,
+            velocity,
+            lyric: None,
+            bend: Vec::new(),
+            detune: None,
+            pressure: Vec::new(),
+            glissando: None,
+            vibrato: None,
+            dynamic: None,
+            accent: false,
+            slur: false,
+            fermata: false,
+            arpeggiated: false,
+            beam: None,
+        }
+    }
+
+    /// Creates a rest, which has no pitch and a velocity of 0.
+    pub fn rest(duration: DurationType) -> Self {
+        let mut rest = Note::new(0, duration, 0);
+        rest.pitch = None;
+        return rest;
+    }
+
+    /// Whether or not the note is a rest.
+    pub fn is_rest(&self) -> bool {
+        return self.pitch.is_none();
+    }
+
+    /// Returns the midi value of the note, or `None` for a rest.
+    pub fn value(&self) -> Option<u8> {
+        return self.pitch.map(|p| p.value);
+    }
+
+    /// Returns the number of seconds the note lasts when it starts `start` quarter notes into
+    /// the piece, following the tempo changes of `tempo_map`.
+    /// 
+    /// Notes in triplets are given their written length, as the note doesn't know it's in one.
+    pub fn duration_seconds(&self, tempo_map: &TempoMap, start: f32) -> f64 {
+        return tempo_map.seconds_between(start, start + self.duration.get_beat_count(2));
+    }
+
+    /// Returns the pitch class of the note, where C is 0 and B is 11, or `None` for a rest.
+    pub fn pitch_class(&self) -> Option<u8> {
+        return self.value().map(|value| value % 12);
+    }
+
+    /// Returns the octave of the note in scientific pitch notation, where middle C is C4, or
+    /// `None` for a rest.
+    pub fn octave(&self) -> Option<i8> {
+        return self.octave_with_middle_c(DEFAULT_MIDDLE_C_OCTAVE);
+    }
+
+    /// Returns the octave of the note, where middle C is in `middle_c_octave`, or `None` for a
+    /// rest.
+    /// 
+    /// Some manufacturers call middle C "C3" or "C5" instead of "C4".
+    pub fn octave_with_middle_c(&self, middle_c_octave: i8) -> Option<i8> {
+        let spelling = self.get_spelling()?;
+        return Some(spelling.octave + middle_c_octave - DEFAULT_MIDDLE_C_OCTAVE);
+    }
+
+    /// Returns the General MIDI percussion sound of the note, e.g. "Acoustic Snare".
+    /// 
+    /// This is only meaningful for notes in percussion tracks. See `Track::percussion`.
+    pub fn drum_name(&self) -> Option<&'static str> {
+        return general_midi::drum_name(self.value()?);
+    }
+
+    /// Returns the name of the note in scientific pitch notation, e.g. "C4" or "F#3".
+    /// 
+    /// Notes that have been through the spelling pass use their key-aware spelling. Otherwise,
+    /// sharps are used. Rests are named "Rest".
+    pub fn name(&self) -> String {
+        return self.name_with_middle_c(DEFAULT_MIDDLE_C_OCTAVE);
+    }
+
+    /// Returns the name of the note, where middle C is in `middle_c_octave`.
+    pub fn name_with_middle_c(&self, middle_c_octave: i8) -> String {
+        let Some(spelling) = self.get_spelling() else {
+            return String::from("Rest");
+        };
+        return format!(
+            "{}{}{}",
+            spelling.letter.to_string(),
+            spelling.accidental.to_string(),
+            spelling.octave + middle_c_octave - DEFAULT_MIDDLE_C_OCTAVE
+        );
+    }
+
+    /// A helper function that returns the spelling of the note, or a spelling with sharps if the
+    /// note has not been through the spelling pass. Rests have no spelling.
+    fn get_spelling(&self) -> Option<SpelledPitch> {
+        return self.pitch.map(|p| p.spelled());
+    }
 }
 
 /// A musical time signature.
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
 pub struct TimeSignature {
     /// The number of beats in a measure.
     pub beat_count: u8,
@@ -93,4 +358,113 @@ pub struct TimeSignature {
     /// 
     /// This allows for the handling of time signature changes.
     pub time_of_occurance: u32,
-}
\ No newline at end of file
+}
+
+impl TimeSignature {
+    /// Returns the number of beats in a measure.
+    /// 
+    /// A beat is the note value of `beat_type`, as in the durations of a parsed track, so 6/8
+    /// time has 6 eighth note beats in a measure.
+    pub fn beats_per_measure(&self) -> f32 {
+        return self.beat_count as f32;
+    }
+}
+
+impl Default for TimeSignature {
+    /// Returns 4/4 time from the start of the piece, which midi files without a time signature
+    /// are read in.
+    fn default() -> Self {
+        return TimeSignature { beat_count: 4, beat_type: 2, time_of_occurance: 0 };
+    }
+}
+
+/// A lyric event in a midi track.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lyric {
+    /// The text of the lyric, usually a single syllable.
+    pub text: String,
+    /// The tick on which the lyric occurs.
+    pub tick: u32,
+}
+
+/// A marker or cue point in a midi file, such as "Verse" or "Chorus".
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
+pub struct Marker {
+    /// The text of the marker.
+    pub text: String,
+    /// The tick on which the marker occurs.
+    pub tick: u32,
+    /// The beat on which the marker occurs, counted from the start of the piece.
+    pub beat: f32,
+}
+
+/// A system exclusive message in a midi file, kept as opaque bytes.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
+pub struct SysexEvent {
+    /// The index of the track the message appears in.
+    pub track: usize,
+    /// The tick on which the message occurs.
+    pub tick: u32,
+    /// The bytes of the message, excluding the leading 0xF0 status byte. For escape sequences
+    /// these are the raw bytes to send.
+    pub data: Vec<u8>,
+    /// Whether the message was stored as an escape sequence (0xF7) rather than a regular system
+    /// exclusive message (0xF0).
+    pub escape: bool,
+}
+
+/// A note with an explicit start and length, such as one computed by a generative algorithm.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TimedNote {
+    /// The midi value of the note.
+    pub value: u8,
+    /// The velocity of the note.
+    pub velocity: u8,
+    /// The beat on which the note starts, counted in quarter notes from the start of the track.
+    pub onset: f32,
+    /// The number of quarter note beats the note lasts.
+    pub length: f32,
+}
+
+/// A musical key signature.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "msgpack", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeySignature {
+    /// The number of sharps (positive) or flats (negative) in the key signature.
+    pub sharps: i8,
+    /// Whether the key is minor rather than major.
+    pub minor: bool,
+    /// The time at which the key signature first occurs in the piece.
+    pub time_of_occurance: u32,
+}
+
+impl KeySignature {
+    /// Builds the key signature of the key whose first degree is the pitch class `tonic`, where C
+    /// is 0. Keys with six sharps are written with six flats instead.
+    /// 
+    /// `tonic` is read modulo 12, so a note value such as 62 for D can be given as well.
+    pub fn from_tonic(tonic: u8, minor: bool) -> KeySignature {
+        let tonic = tonic % 12;
+        let major_tonic = if minor { (tonic + 3) % 12 } else { tonic };
+        // Each sharp raises the tonic of the major key by a fifth.
+        let sharps = (major_tonic as i8 * 7).rem_euclid(12);
+        let sharps = if sharps > 5 { sharps - 12 } else { sharps };
+        return KeySignature { sharps, minor, time_of_occurance: 0 };
+    }
+
+    /// The pitch class of the first degree of the key, where C is 0.
+    pub fn tonic(&self) -> u8 {
+        let major_tonic = (self.sharps as i16 * 7).rem_euclid(12) as u8;
+        return if self.minor { (major_tonic + 9) % 12 } else { major_tonic };
+    }
+
+    /// Formats the key as a string, e.g. "Eb major" or "F# minor".
+    pub fn name(&self) -> String {
+        let tonic = SpelledPitch::spell(self.tonic(), Some(*self));
+        let mode = if self.minor { "minor" } else { "major" };
+        return format!("{}{} {}", tonic.letter.to_string(), tonic.accidental.to_string(), mode);
+    }
+}
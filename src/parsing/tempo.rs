@@ -0,0 +1,66 @@
+use crate::parsing::events::EventFile;
+use crate::parsing::events::EventKind;
+
+/// The tempo of a piece over time, for converting beats to seconds.
+/// 
+/// Beats are quarter notes counted from the start of the piece, as in the tracks of a `Midi`.
+/// The tempo is 120 beats per minute until the first tempo change, as in a midi file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TempoMap {
+    /// The tempo changes as `(beat, microseconds_per_beat)` pairs, sorted by beat.
+    changes: Vec<(f64, u32)>,
+}
+
+impl TempoMap {
+    /// Creates a tempo map that stays at `bpm` beats per minute.
+    pub fn new(bpm: u32) -> Self {
+        return TempoMap { changes: vec![(0.0, 60_000_000 / bpm.max(1))] };
+    }
+
+    /// Reads the tempo changes of every track of `events`.
+    pub fn from_events(events: &EventFile) -> Self {
+        let ticks_per_beat = events.ticks_per_beat.max(1) as f64;
+        let mut changes: Vec<(f64, u32)> = events.tracks
+            .iter()
+            .flatten()
+            .filter_map(|e| match e.kind {
+                EventKind::Tempo(tempo) => Some((e.tick as f64 / ticks_per_beat, tempo)),
+                _ => None,
+            })
+            .collect();
+        changes.sort_by(|a, b| a.0.total_cmp(&b.0));
+        return TempoMap { changes };
+    }
+
+    /// Changes the tempo to `bpm` beats per minute from `beat` on.
+    pub fn tempo_change(mut self, beat: f32, bpm: u32) -> Self {
+        let beat = beat.max(0.0) as f64;
+        let index = self.changes.partition_point(|(b, _)| *b <= beat);
+        self.changes.insert(index, (beat, 60_000_000 / bpm.max(1)));
+        return self;
+    }
+
+    /// Returns the tempo at `beat` in beats per minute.
+    pub fn bpm_at(&self, beat: f32) -> f32 {
+        let tempo = self.changes
+            .iter()
+            .rfind(|(b, _)| *b <= beat as f64)
+            .map_or(500_000, |(_, t)| *t);
+        return 60_000_000.0 / tempo as f32;
+    }
+
+    /// Returns the number of seconds from the start of the piece to `beat`.
+    pub fn seconds_at(&self, beat: f64) -> f64 {
+        let (mut seconds, mut last_beat, mut tempo) = (0.0, 0.0, 500_000);
+        for (change, new_tempo) in self.changes.iter().take_while(|(b, _)| *b <= beat) {
+            seconds += (change - last_beat) * tempo as f64 / 1e6;
+            (last_beat, tempo) = (*change, *new_tempo);
+        }
+        return seconds + (beat - last_beat) * tempo as f64 / 1e6;
+    }
+
+    /// Returns the number of seconds between `start` and `end`, both in beats.
+    pub fn seconds_between(&self, start: f32, end: f32) -> f64 {
+        return self.seconds_at(end as f64) - self.seconds_at(start as f64);
+    }
+}
@@ -0,0 +1,69 @@
+use crate::Midi;
+use crate::error::ParseError;
+use crate::parsing::TrackKind;
+use crate::parsing::measure;
+
+/// The finest subdivision of a beat the quantizer can produce. This is the grid used when
+/// scanning for triplets.
+const GRID_DIVISIONS: f32 = 24.0;
+
+/// Checks that every note track in `midi` agrees on where the measures are.
+/// 
+/// Every note produced by the quantizer starts on the quantization grid, so a note that starts
+/// off the grid means the track has drifted by a partial beat and every later bar line in that
+/// track is misplaced. Tracks must also all end on the same beat.
+/// 
+/// Returns `ParseError::BarlineDivergence` describing the first point at which a track diverges.
+pub fn check_barlines(midi: &Midi) -> Result<(), ParseError> {
+    let beat_type = midi.beat_type();
+    let mut first_divergence: Option<(f32, &str)> = None;
+    let mut score_length: Option<f32> = None;
+
+    for track in &midi.tracks {
+        if track.kind != TrackKind::Notes {
+            continue;
+        }
+        let mut beat = 0.0;
+        for wrapper in &track.notes {
+            if !is_on_grid(beat) {
+                break;
+            }
+            beat += wrapper.beat_count(beat_type);
+        }
+        if !is_on_grid(beat) {
+            let earlier = first_divergence.is_none_or(|(b, _)| beat < b);
+            if earlier {
+                first_divergence = Some((beat, &track.name));
+            }
+            continue;
+        }
+        match score_length {
+            None => score_length = Some(beat),
+            Some(length) if (length - beat).abs() > 1e-3 => {
+                let end = length.min(beat);
+                if first_divergence.is_none_or(|(b, _)| end < b) {
+                    first_divergence = Some((end, &track.name));
+                }
+            },
+            _ => (),
+        }
+    }
+
+    if let Some((beat, name)) = first_divergence {
+        let (measure, beat_in_measure) =
+            measure::measure_at(&midi.time_signatures, midi.ticks_per_beat, beat);
+        return Err(ParseError::BarlineDivergence {
+            track: String::from(name),
+            measure,
+            beat: beat_in_measure,
+        });
+    }
+    return Ok(());
+}
+
+/// Determines if a beat position lies on the quantization grid.
+fn is_on_grid(beat: f32) -> bool {
+    let divisions = beat * GRID_DIVISIONS;
+    return (divisions - divisions.round()).abs() < 1e-3;
+}
+
@@ -0,0 +1,270 @@
+use crate::Midi;
+use crate::parsing::TrackKind;
+use crate::parsing::duration::DurationType;
+use crate::parsing::duration::NoteDuration;
+use crate::parsing::duration::NoteDurationModifier;
+use crate::parsing::parse_note_data;
+use crate::parsing::symbols::NoteModifier;
+use crate::parsing::symbols::NoteWrapper;
+
+/// The limits of the BeatBlox runtime that a piece must respect to be played back faithfully.
+#[derive(Clone)]
+pub struct BeatbloxConstraints {
+    /// The maximum number of notes a single sprite can play at once.
+    pub max_simultaneous_notes: usize,
+    /// The shortest duration BeatBlox can play.
+    pub min_duration: DurationType,
+    /// The velocities BeatBlox supports, sorted from softest to loudest.
+    pub dynamics_levels: Vec<u8>,
+}
+
+impl Default for BeatbloxConstraints {
+    fn default() -> Self {
+        BeatbloxConstraints {
+            max_simultaneous_notes: 4,
+            min_duration: DurationType {
+                duration: NoteDuration::SIXTEENTH,
+                modifier: NoteDurationModifier::None,
+            },
+            dynamics_levels: vec![32, 64, 96, 127],
+        }
+    }
+}
+
+/// A change that must be made to a piece for it to be playable in BeatBlox.
+/// 
+/// `track` is the name of the track and `position` is the index of the affected entry in
+/// `Track::notes`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Simplification {
+    /// A chord has more notes than a sprite can play at once and must drop its lowest notes.
+    ReduceChord { track: String, position: usize, from: usize, to: usize },
+    /// A note or rest is shorter than BeatBlox can play and must be merged into a neighbouring
+    /// note or rest.
+    MergeShortNote { track: String, position: usize, beats: f32 },
+    /// A velocity is not one of the supported dynamics levels and must be snapped to one.
+    SnapVelocity { track: String, position: usize, from: u8, to: u8 },
+}
+
+/// Lists the simplifications a piece needs to be playable in BeatBlox.
+#[derive(Clone, Debug, Default)]
+pub struct PlayabilityReport {
+    pub simplifications: Vec<Simplification>,
+}
+
+impl PlayabilityReport {
+    /// Returns `true` if the piece can be played in BeatBlox without any simplifications.
+    pub fn is_playable(&self) -> bool {
+        return self.simplifications.is_empty();
+    }
+}
+
+/// Checks a piece against the BeatBlox runtime constraints.
+pub fn check(midi: &Midi, constraints: &BeatbloxConstraints) -> PlayabilityReport {
+    let beat_type = midi.beat_type();
+    let min_beats = constraints.min_duration.get_beat_count(beat_type);
+    let mut report = PlayabilityReport::default();
+    for track in &midi.tracks {
+        if track.kind != TrackKind::Notes {
+            continue;
+        }
+        for (position, wrapper) in track.notes.iter().enumerate() {
+            let chord_size = get_chord_size(wrapper);
+            if chord_size > constraints.max_simultaneous_notes {
+                report.simplifications.push(Simplification::ReduceChord {
+                    track: track.name.clone(),
+                    position,
+                    from: chord_size,
+                    to: constraints.max_simultaneous_notes,
+                });
+            }
+            let beats = wrapper.beat_count(beat_type);
+            let is_triplet = matches!(wrapper, NoteWrapper::ModifiedNote(NoteModifier::Triplet(_)));
+            if beats < min_beats && !is_triplet {
+                report.simplifications.push(Simplification::MergeShortNote {
+                    track: track.name.clone(),
+                    position,
+                    beats,
+                });
+            }
+            for note in wrapper.notes() {
+                if note.is_rest() || constraints.dynamics_levels.is_empty() {
+                    continue;
+                }
+                let level = get_nearest_level(note.velocity, &constraints.dynamics_levels);
+                if level != note.velocity {
+                    report.simplifications.push(Simplification::SnapVelocity {
+                        track: track.name.clone(),
+                        position,
+                        from: note.velocity,
+                        to: level,
+                    });
+                }
+            }
+        }
+    }
+    return report;
+}
+
+/// Applies every simplification needed for a piece to be playable in BeatBlox.
+/// 
+/// Returns the report of what was changed.
+pub fn simplify(midi: &mut Midi, constraints: &BeatbloxConstraints) -> PlayabilityReport {
+    let report = check(midi, constraints);
+    let beat_type = midi.beat_type();
+    let min_beats = constraints.min_duration.get_beat_count(beat_type);
+    for track in &mut midi.tracks {
+        if track.kind != TrackKind::Notes {
+            continue;
+        }
+        for wrapper in &mut track.notes {
+            reduce_chords(wrapper, constraints.max_simultaneous_notes);
+            if constraints.dynamics_levels.is_empty() {
+                continue;
+            }
+            for note in wrapper.notes_mut() {
+                if !note.is_rest() {
+                    note.velocity = get_nearest_level(note.velocity, &constraints.dynamics_levels);
+                }
+            }
+        }
+        track.notes = merge_short_notes(&track.notes, min_beats, beat_type);
+    }
+    return report;
+}
+
+/// Returns the largest number of notes that sound at once inside of a `NoteWrapper`.
+fn get_chord_size(wrapper: &NoteWrapper) -> usize {
+    match wrapper {
+        NoteWrapper::PlainNote(_) => return 1,
+        NoteWrapper::Rest(_) => return 0,
+        NoteWrapper::ModifiedNote(NoteModifier::Chord(c)) => return c.len(),
+        NoteWrapper::ModifiedNote(NoteModifier::TiedNote(v) | NoteModifier::Triplet(v)) => {
+            return v.iter().map(get_chord_size).max().unwrap_or(0);
+        },
+    }
+}
+
+/// Drops the lowest notes of every chord inside of a `NoteWrapper` until it has at most
+/// `max_notes` notes.
+fn reduce_chords(wrapper: &mut NoteWrapper, max_notes: usize) {
+    if let NoteWrapper::ModifiedNote(modifier) = wrapper {
+        match modifier {
+            NoteModifier::Chord(c) if c.len() > max_notes.max(1) => {
+                c.sort_by_key(|n| std::cmp::Reverse(n.notes().first().and_then(|n| n.value())));
+                c.truncate(max_notes.max(1));
+                if c.len() == 1 {
+                    *wrapper = c[0].clone();
+                }
+            },
+            NoteModifier::Chord(_) => (),
+            NoteModifier::TiedNote(v) | NoteModifier::Triplet(v) => {
+                for n in v {
+                    reduce_chords(n, max_notes);
+                }
+            },
+        }
+    }
+}
+
+/// Merges every note or rest shorter than `min_beats` into a neighbouring entry, which keeps
+/// its own pitch and takes on the short entry's beats, so the track keeps its length.
+/// 
+/// A short note goes into the pitched note before it, or else the one after it. A short note
+/// between rests instead takes the beats it needs from the rest after it, or else the one before
+/// it, so a rest never replaces a pitched note. A short rest goes into the entry before it, or
+/// else the one after it. Triplets are never merged.
+fn merge_short_notes(notes: &[NoteWrapper], min_beats: f32, beat_type: u8) -> Vec<NoteWrapper> {
+    // Each entry is a wrapper, its length in beats, and whether that length has changed.
+    let mut entries: Vec<(NoteWrapper, f32, bool)> = notes
+        .iter()
+        .map(|n| (n.clone(), n.beat_count(beat_type), false))
+        .collect();
+    let is_triplet = |w: &NoteWrapper| {
+        return matches!(w, NoteWrapper::ModifiedNote(NoteModifier::Triplet(_)));
+    };
+    let is_rest = |w: &NoteWrapper| w.notes().iter().all(|n| n.is_rest());
+    let mut i = 0;
+    while i < entries.len() {
+        let (wrapper, beats, _) = &entries[i];
+        let beats = *beats;
+        if beats >= min_beats - 1e-4 || is_triplet(wrapper) {
+            i += 1;
+            continue;
+        }
+        let rest = is_rest(wrapper);
+        // The kind of each neighbour that can be merged with, as whether it is a rest.
+        let previous = i.checked_sub(1)
+            .map(|j| &entries[j].0)
+            .filter(|w| !is_triplet(w))
+            .map(is_rest);
+        let next = entries.get(i + 1).map(|e| &e.0).filter(|w| !is_triplet(w)).map(is_rest);
+        let into = match (rest, previous, next) {
+            (true, Some(_), _) | (false, Some(false), _) => Some(i - 1),
+            (true, None, Some(_)) | (false, _, Some(false)) => Some(i + 1),
+            _ => None,
+        };
+        if let Some(j) = into {
+            entries[j].1 += beats;
+            entries[j].2 = true;
+            entries.remove(i);
+            continue;
+        }
+        let lender = match (previous, next) {
+            (_, Some(true)) => i + 1,
+            (Some(true), _) => i - 1,
+            _ => {
+                i += 1;
+                continue;
+            },
+        };
+        let lent = (min_beats - beats).min(entries[lender].1);
+        entries[lender].1 -= lent;
+        entries[lender].2 = true;
+        entries[i].1 += lent;
+        entries[i].2 = true;
+        if entries[lender].1 <= 1e-4 {
+            entries.remove(lender);
+            if lender < i {
+                i -= 1;
+            }
+        }
+    }
+    return entries
+        .into_iter()
+        .map(|(wrapper, beats, changed)| match changed {
+            true => rebuild_with_length(&wrapper, beats, beat_type),
+            false => wrapper,
+        })
+        .collect();
+}
+
+/// Rebuilds a note or chord so that it lasts `beats` beats.
+fn rebuild_with_length(wrapper: &NoteWrapper, beats: f32, beat_type: u8) -> NoteWrapper {
+    let mut chord = Vec::new();
+    for note in wrapper.notes() {
+        let data = (note.value(), note.velocity);
+        if !chord.iter().any(|(value, _)| *value == note.value()) {
+            chord.push(data);
+        }
+    }
+    let mut notes: Vec<NoteWrapper> = chord
+        .iter()
+        .map(|data| parse_note_data(*data, beats, beat_type))
+        .collect();
+    if notes.len() == 1 {
+        return notes.remove(0);
+    }
+    return NoteWrapper::ModifiedNote(NoteModifier::Chord(notes));
+}
+
+/// Finds the supported dynamics level closest to `velocity`.
+fn get_nearest_level(velocity: u8, levels: &[u8]) -> u8 {
+    let mut nearest = levels[0];
+    for level in levels {
+        if level.abs_diff(velocity) < nearest.abs_diff(velocity) {
+            nearest = *level;
+        }
+    }
+    return nearest;
+}
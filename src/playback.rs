@@ -0,0 +1,257 @@
+use crate::Midi;
+use crate::parsing::events::EventKind;
+use crate::parsing::tempo::TempoMap;
+use std::fmt;
+
+/// A channel message to be sent `time` seconds after playback starts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScheduledMessage {
+    /// The number of seconds from the start of playback at which the message is sent.
+    pub time: f64,
+    /// The bytes of the message, starting with its status byte.
+    pub bytes: Vec<u8>,
+}
+
+/// Lays out the channel messages of a piece in the order and at the times they're played.
+/// 
+/// The messages are those of `Midi::to_events`, so chords, ties, triplets, and swing sound as
+/// they're written, and tick times are converted to seconds with the tempo changes of the piece.
+/// Messages that fall on the same tick keep the order of their tracks, except that note offs
+/// are sent before note ons so repeated notes are struck again.
+pub fn schedule(midi: &Midi) -> Vec<ScheduledMessage> {
+    let file = midi.to_events();
+    let tempo_map = TempoMap::from_events(&file);
+    let ticks_per_beat = file.ticks_per_beat.max(1) as f64;
+    let seconds_at = |tick: u32| tempo_map.seconds_at(tick as f64 / ticks_per_beat);
+    let mut events: Vec<(u32, bool, Vec<u8>)> = file.tracks
+        .iter()
+        .flatten()
+        .filter_map(|e| {
+            let is_note_on = matches!(e.kind, EventKind::NoteOn { velocity, .. } if velocity > 0);
+            return get_message(&e.kind).map(|bytes| (e.tick, is_note_on, bytes));
+        })
+        .collect();
+    events.sort_by_key(|(tick, is_note_on, _)| (*tick, *is_note_on));
+    return events
+        .into_iter()
+        .map(|(tick, _, bytes)| ScheduledMessage { time: seconds_at(tick), bytes })
+        .collect();
+}
+
+/// Returns the bytes of a channel message, or `None` for events that aren't channel messages.
+fn get_message(kind: &EventKind) -> Option<Vec<u8>> {
+    match *kind {
+        EventKind::NoteOff { channel, key, velocity } => {
+            return Some(vec![0x80 | channel, key, velocity]);
+        },
+        EventKind::NoteOn { channel, key, velocity } => {
+            return Some(vec![0x90 | channel, key, velocity]);
+        },
+        EventKind::PolyPressure { channel, key, value } => {
+            return Some(vec![0xA0 | channel, key, value]);
+        },
+        EventKind::Controller { channel, controller, value } => {
+            return Some(vec![0xB0 | channel, controller, value]);
+        },
+        EventKind::ProgramChange { channel, program } => return Some(vec![0xC0 | channel, program]),
+        EventKind::ChannelPressure { channel, value } => return Some(vec![0xD0 | channel, value]),
+        EventKind::PitchBend { channel, value } => {
+            return Some(vec![0xE0 | channel, (value & 0x7F) as u8, (value >> 7) as u8]);
+        },
+        _ => return None,
+    }
+}
+
+/// An error raised while connecting to a midi output device.
+#[derive(Debug)]
+pub enum PlayError {
+    /// The system's midi output couldn't be opened.
+    Unavailable(String),
+    /// No output device has a name containing the requested text.
+    NoDevice(String),
+    /// The output device was found but couldn't be connected to.
+    Connect(String),
+}
+
+impl fmt::Display for PlayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlayError::Unavailable(e) => write!(f, "midi output is unavailable: {}", e),
+            PlayError::NoDevice(name) => write!(f, "no midi output device matches \"{}\"", name),
+            PlayError::Connect(e) => write!(f, "could not connect to midi output device: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for PlayError {}
+
+#[cfg(feature = "play")]
+pub use device::*;
+
+/// Playback through the system's midi output, using `midir`.
+#[cfg(feature = "play")]
+mod device {
+    use super::PlayError;
+    use super::ScheduledMessage;
+    use std::sync::Arc;
+    use std::sync::Condvar;
+    use std::sync::Mutex;
+    use std::thread::JoinHandle;
+    use std::time::Duration;
+    use std::time::Instant;
+
+    /// The name the crate's connections are given on the system's midi output.
+    const CLIENT_NAME: &str = "beatblox_midi";
+
+    /// Whether playback is paused or stopped, shared with the playback thread.
+    #[derive(Default)]
+    struct State {
+        paused: bool,
+        stopped: bool,
+    }
+
+    /// A piece being played on a midi output device. See `Midi::play`.
+    /// 
+    /// Playback happens on a thread of its own. Dropping the `Playback` stops it; use
+    /// `Playback::wait` to let the piece play to its end.
+    pub struct Playback {
+        /// Whether playback is paused or stopped, and the signal for changing it.
+        state: Arc<(Mutex<State>, Condvar)>,
+        /// The thread that sends the messages.
+        thread: Option<JoinHandle<()>>,
+    }
+
+    impl Playback {
+        /// Pauses playback. Every note that is sounding is released.
+        pub fn pause(&self) {
+            self.update(|state| state.paused = true);
+        }
+
+        /// Resumes playback from where it was paused.
+        pub fn resume(&self) {
+            self.update(|state| state.paused = false);
+        }
+
+        /// Whether or not playback is paused.
+        pub fn is_paused(&self) -> bool {
+            return self.state.0.lock().map(|state| state.paused).unwrap_or(false);
+        }
+
+        /// Stops playback for good. Every note that is sounding is released.
+        pub fn stop(&mut self) {
+            self.update(|state| state.stopped = true);
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+        }
+
+        /// Whether or not the piece has played to its end or been stopped.
+        pub fn is_finished(&self) -> bool {
+            return self.thread.as_ref().is_none_or(|thread| thread.is_finished());
+        }
+
+        /// Blocks until the piece has played to its end.
+        pub fn wait(mut self) {
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+        }
+
+        /// Changes the shared state and wakes the playback thread to act on it.
+        fn update<F: FnOnce(&mut State)>(&self, f: F) {
+            if let Ok(mut state) = self.state.0.lock() {
+                f(&mut state);
+            }
+            self.state.1.notify_all();
+        }
+    }
+
+    impl Drop for Playback {
+        fn drop(&mut self) {
+            self.stop();
+        }
+    }
+
+    /// Returns the names of the system's midi output devices.
+    pub fn output_devices() -> Result<Vec<String>, PlayError> {
+        let output = midir::MidiOutput::new(CLIENT_NAME)
+            .map_err(|e| PlayError::Unavailable(e.to_string()))?;
+        let names = output.ports().iter().filter_map(|port| output.port_name(port).ok()).collect();
+        return Ok(names);
+    }
+
+    /// Connects to the first output device whose name contains `device` and starts sending
+    /// `messages` to it.
+    pub(crate) fn play(
+        messages: Vec<ScheduledMessage>,
+        device: &str
+    ) -> Result<Playback, PlayError> {
+        let output = midir::MidiOutput::new(CLIENT_NAME)
+            .map_err(|e| PlayError::Unavailable(e.to_string()))?;
+        let port = output.ports().into_iter().find(|port| {
+            return output.port_name(port).is_ok_and(|name| name.contains(device));
+        });
+        let port = port.ok_or(PlayError::NoDevice(String::from(device)))?;
+        let connection = output
+            .connect(&port, CLIENT_NAME)
+            .map_err(|e| PlayError::Connect(e.to_string()))?;
+        let state = Arc::new((Mutex::new(State::default()), Condvar::new()));
+        let shared = Arc::clone(&state);
+        let thread = std::thread::spawn(move || run(connection, messages, shared));
+        return Ok(Playback { state, thread: Some(thread) });
+    }
+
+    /// Sends each message at its time, following the pauses and stops in `state`.
+    fn run(
+        mut connection: midir::MidiOutputConnection,
+        messages: Vec<ScheduledMessage>,
+        state: Arc<(Mutex<State>, Condvar)>
+    ) {
+        let (lock, signal) = &*state;
+        // The time played before the last resume, and when it was resumed.
+        let (mut played, mut resumed) = (Duration::ZERO, Instant::now());
+        for message in messages {
+            let target = Duration::from_secs_f64(message.time);
+            let mut guard = match lock.lock() {
+                Ok(guard) => guard,
+                Err(_) => return,
+            };
+            loop {
+                if guard.stopped {
+                    release_notes(&mut connection);
+                    return;
+                }
+                if guard.paused {
+                    played += resumed.elapsed();
+                    release_notes(&mut connection);
+                    while guard.paused && !guard.stopped {
+                        guard = match signal.wait(guard) {
+                            Ok(guard) => guard,
+                            Err(_) => return,
+                        };
+                    }
+                    resumed = Instant::now();
+                    continue;
+                }
+                let now = played + resumed.elapsed();
+                if now >= target {
+                    break;
+                }
+                guard = match signal.wait_timeout(guard, target - now) {
+                    Ok((guard, _)) => guard,
+                    Err(_) => return,
+                };
+            }
+            drop(guard);
+            let _ = connection.send(&message.bytes);
+        }
+        release_notes(&mut connection);
+    }
+
+    /// Sends an all notes off message on every channel.
+    fn release_notes(connection: &mut midir::MidiOutputConnection) {
+        for channel in 0..16 {
+            let _ = connection.send(&[0xB0 | channel, 123, 0]);
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use crate::Midi;
+use crate::parsing::Track;
+use crate::parsing::TrackKind;
+use crate::parsing::measure;
+use crate::parsing::symbols::KeySignature;
+use crate::parsing::symbols::Note;
+use crate::parsing::symbols::NoteModifier;
+use crate::parsing::symbols::NoteWrapper;
+use crate::parsing::symbols::TimeSignature;
+use std::ops::RangeInclusive;
+
+/// The midi value at which a grand staff is split, middle C. Notes at or above it go on the
+/// treble staff.
+pub const GRAND_STAFF_SPLIT: u8 = 60;
+
+/// A piece laid out the way it's written on the page, as parts made of staves, staves made of
+/// voices, and voices made of measures.
+/// 
+/// Every voice has the same measures, so the measures of different parts line up with each other
+/// even where a part has nothing to play.
+#[derive(Clone)]
+pub struct Score {
+    /// The parts of the score, one for each note track of the piece.
+    pub parts: Vec<Part>,
+}
+
+/// The music played by one instrument.
+#[derive(Clone)]
+pub struct Part {
+    /// The name of the track the part was made from.
+    pub name: String,
+    /// The position in the midi file of the track the part was made from. See `Track::index`.
+    pub track: usize,
+    /// Whether the part holds drum hits rather than pitches.
+    pub percussion: bool,
+    /// How the part is laid out on staves. See `infer_staff_layout`.
+    pub layout: StaffLayout,
+    /// The staves of the part, from top to bottom.
+    pub staves: Vec<Staff>,
+}
+
+/// A clef a staff is written in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Clef {
+    Treble,
+    Bass,
+    /// The neutral clef of drum parts, whose lines stand for drums rather than pitches.
+    Percussion,
+}
+
+impl Clef {
+    /// Converts the enum to a string.
+    pub fn to_string(&self) -> &str {
+        match self {
+            Clef::Treble => return "treble",
+            Clef::Bass => return "bass",
+            Clef::Percussion => return "percussion",
+        }
+    }
+}
+
+/// How the notes of a part are laid out on staves.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StaffLayout {
+    /// Every note is written on a single staff in the given clef.
+    Single(Clef),
+    /// The notes are written on a treble staff and a bass staff, as for piano. Notes at or above
+    /// the midi value `split` go on the treble staff, and the rest on the bass staff.
+    Grand { split: u8 },
+}
+
+/// A single staff of a part.
+#[derive(Clone)]
+pub struct Staff {
+    /// The clef the staff is written in.
+    pub clef: Clef,
+    /// The voices written on the staff.
+    pub voices: Vec<Voice>,
+}
+
+/// A single line of notes and chords within a staff.
+#[derive(Clone)]
+pub struct Voice {
+    /// The measures of the voice, in order.
+    pub measures: Vec<Measure>,
+}
+
+/// The notes of one voice within a single measure.
+#[derive(Clone)]
+pub struct Measure {
+    /// The number of the measure, counting from 1.
+    pub number: u32,
+    /// The beat on which the measure starts, counted from the start of the piece.
+    pub start: f32,
+    /// The number of beats the measure lasts.
+    pub length: f32,
+    /// The time signature in effect during the measure.
+    pub time_signature: TimeSignature,
+    /// The key signature in effect at the start of the measure, if the piece has one.
+    pub key_signature: Option<KeySignature>,
+    /// The name of the section the measure belongs to, if it's in one. See `Midi::sections`.
+    pub section: Option<String>,
+    /// The notes that start in the measure, in order.
+    pub notes: Vec<NoteWrapper>,
+}
+
+impl Score {
+    /// Returns the number of measures in the score.
+    pub fn measure_count(&self) -> usize {
+        return self.parts
+            .iter()
+            .flat_map(|p| p.staves.iter())
+            .flat_map(|s| s.voices.iter())
+            .map(|v| v.measures.len())
+            .max()
+            .unwrap_or(0);
+    }
+}
+
+/// Picks the staves a track is best written on from the range of its pitches.
+/// 
+/// Percussion tracks use the percussion clef. Tracks that stay around or above middle C use the
+/// treble clef, and tracks that stay around or below it use the bass clef, allowing a tenth of
+/// their notes to stray to the other side. Anything wider is written on a grand staff split at
+/// `GRAND_STAFF_SPLIT`.
+pub fn infer_staff_layout(track: &Track) -> StaffLayout {
+    if track.percussion {
+        return StaffLayout::Single(Clef::Percussion);
+    }
+    let values: Vec<u8> = track.notes
+        .iter()
+        .flat_map(|n| n.notes())
+        .filter_map(|n| n.value())
+        .collect();
+    let (Some(low), Some(high)) = (values.iter().min(), values.iter().max()) else {
+        return StaffLayout::Single(Clef::Treble);
+    };
+    let above = values.iter().filter(|v| **v >= GRAND_STAFF_SPLIT).count() as f32;
+    let share_above = above / values.len() as f32;
+    // G3 and E4 are the notes a few ledger lines past each clef.
+    if *low >= 55 || (*low >= 48 && share_above >= 0.9) {
+        return StaffLayout::Single(Clef::Treble);
+    }
+    if *high <= 64 || (*high <= 72 && share_above <= 0.1) {
+        return StaffLayout::Single(Clef::Bass);
+    }
+    return StaffLayout::Grand { split: GRAND_STAFF_SPLIT };
+}
+
+/// Lays out the note tracks of a piece as a `Score`.
+/// 
+/// Each note track becomes a part with a single staff, or a treble and a bass staff if its range
+/// calls for a grand staff (see `infer_staff_layout`). Notes on the other staff of a grand staff
+/// are written as rests. A track is parsed into one line of notes and chords, so each staff holds
+/// a single voice. Notes are placed in the measure they start in, so a note that crosses a bar
+/// line is kept whole in the measure where it starts. Use `Midi::split_at_barlines` first to tie
+/// such notes across the bar line instead.
+pub fn build_score(midi: &Midi) -> Score {
+    let beat_type = midi.beat_type();
+    let tracks: Vec<_> = midi.tracks.iter().filter(|t| t.kind == TrackKind::Notes).collect();
+    let end = tracks
+        .iter()
+        .map(|t| t.notes.iter().map(|n| n.beat_count(beat_type)).sum::<f32>())
+        .fold(0.0, f32::max);
+    let starts = measure::measure_starts(&midi.time_signatures, midi.ticks_per_beat, end);
+    let sections = midi.sections();
+    let mut empty_measures = Vec::new();
+    for (i, start) in starts.iter().enumerate() {
+        if *start >= end - 1e-4 {
+            break;
+        }
+        let tick = (start * midi.ticks_per_beat).round() as u32;
+        let time_signature = midi.time_signatures
+            .iter()
+            .rev()
+            .find(|t| t.time_of_occurance <= tick)
+            .copied()
+            .or_else(|| midi.time_signatures.first().copied())
+            .unwrap_or_default();
+        empty_measures.push(Measure {
+            number: i as u32 + 1,
+            start: *start,
+            length: starts.get(i + 1).map_or(time_signature.beats_per_measure(), |s| s - start),
+            time_signature,
+            key_signature: midi.key_signature_at(tick),
+            section: sections
+                .iter()
+                .find(|s| (s.start_measure..=s.end_measure).contains(&(i as u32 + 1)))
+                .map(|s| s.name.clone()),
+            notes: Vec::new(),
+        });
+    }
+
+    let mut parts = Vec::new();
+    for track in tracks {
+        let layout = infer_staff_layout(track);
+        // Each staff holds the notes whose values fall in its range.
+        let staves = match layout {
+            StaffLayout::Single(clef) => vec![(clef, 0..=127)],
+            StaffLayout::Grand { split } => vec![
+                (Clef::Treble, split..=127),
+                (Clef::Bass, 0..=split.saturating_sub(1)),
+            ],
+        };
+        let staves = staves
+            .iter()
+            .map(|(clef, range)| {
+                let mut measures = empty_measures.clone();
+                let mut beat = 0.0;
+                let mut current = 0;
+                for wrapper in &track.notes {
+                    while current + 1 < measures.len()
+                        && measures[current + 1].start <= beat + 1e-4 {
+                        current += 1;
+                    }
+                    beat += wrapper.beat_count(beat_type);
+                    let Some(measure) = measures.get_mut(current) else {
+                        continue;
+                    };
+                    match keep_staff_notes(wrapper, range) {
+                        // A tie that is entirely on the other staff is written as its rests.
+                        NoteWrapper::ModifiedNote(NoteModifier::TiedNote(t))
+                            if t.iter().flat_map(|n| n.notes()).all(|n| n.is_rest()) => {
+                            measure.notes.extend(t);
+                        },
+                        kept => measure.notes.push(kept),
+                    }
+                }
+                return Staff { clef: *clef, voices: vec![Voice { measures }] };
+            })
+            .collect();
+        parts.push(Part {
+            name: track.name.clone(),
+            track: track.index,
+            percussion: track.percussion,
+            layout,
+            staves,
+        });
+    }
+    return Score { parts };
+}
+
+/// Returns a copy of a `NoteWrapper` with the notes whose values fall outside of `range` turned
+/// into rests, dropping them from chords.
+fn keep_staff_notes(wrapper: &NoteWrapper, range: &RangeInclusive<u8>) -> NoteWrapper {
+    match wrapper {
+        NoteWrapper::PlainNote(n) => {
+            if n.value().is_some_and(|v| range.contains(&v)) {
+                return wrapper.clone();
+            }
+            return NoteWrapper::Rest(Note::rest(n.duration.clone()));
+        },
+        NoteWrapper::Rest(_) => return wrapper.clone(),
+        NoteWrapper::ModifiedNote(NoteModifier::Chord(c)) => {
+            let mut members: Vec<NoteWrapper> = c
+                .iter()
+                .map(|n| keep_staff_notes(n, range))
+                .filter(|n| n.notes().iter().any(|n| !n.is_rest()))
+                .collect();
+            return match members.len() {
+                0 => keep_staff_notes(&c[0], range),
+                1 => members.remove(0),
+                _ => NoteWrapper::ModifiedNote(NoteModifier::Chord(members)),
+            };
+        },
+        NoteWrapper::ModifiedNote(NoteModifier::TiedNote(t)) => {
+            let pieces = t.iter().map(|n| keep_staff_notes(n, range)).collect();
+            return NoteWrapper::ModifiedNote(NoteModifier::TiedNote(pieces));
+        },
+        NoteWrapper::ModifiedNote(NoteModifier::Triplet(tr)) => {
+            let members = tr.iter().map(|n| keep_staff_notes(n, range)).collect();
+            return NoteWrapper::ModifiedNote(NoteModifier::Triplet(members));
+        },
+    }
+}
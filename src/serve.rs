@@ -0,0 +1,214 @@
+use crate::Midi;
+use crate::analysis::TrackStats;
+use crate::error::ParseError;
+use crate::export::json_number;
+use crate::export::json_option;
+use crate::export::json_string;
+use crate::parsing::duration;
+use crate::parsing::duration::DurationType;
+use crate::parsing::midicsv;
+use crate::parsing::options::ParseOptions;
+use std::io::Read;
+
+/// A response to a request made to a `Server`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Response {
+    /// The HTTP status code.
+    pub status: u16,
+    /// The media type of the body.
+    pub content_type: &'static str,
+    /// The body of the response.
+    pub body: Vec<u8>,
+}
+
+/// An HTTP service that parses uploaded midi files, for NetsBlox RPCs.
+/// 
+/// Every endpoint takes a `POST` whose body is the contents of a midi file:
+/// 
+/// - `/parse` returns the piece as JSON (see `Midi::to_json`), or as NetsBlox lists written as
+///   JSON arrays with `format=lists`.
+/// - `/stats` returns the statistics of the piece and each of its tracks as JSON.
+/// - `/export` returns the piece in the `format` given: `json`, `lists`, `csv`, `blocks`,
+///   `midicsv`, or `smf`.
+/// 
+/// The query string may also set the `precision` (`whole` through `hundred-twenty-eighth`),
+/// `triplet` (`true` or `false`), and `tracks` (a comma separated list of track positions) the
+/// file is parsed with. Errors are returned as `{"error": "<message>"}`, with a 400 status for
+/// bad requests, 413 for files over the server's resource limits, and 422 for files that can't
+/// be parsed.
+/// 
+/// ```no_run
+/// use beatblox_midi::parsing::options::ParseOptions;
+/// use beatblox_midi::parsing::options::ResourceLimits;
+/// use beatblox_midi::serve::Server;
+/// 
+/// let limits = ResourceLimits::new().max_file_size(1 << 20);
+/// let server = Server::new(ParseOptions::new().limits(limits));
+/// server.run("0.0.0.0:8080").unwrap();
+/// ```
+pub struct Server {
+    /// The options every file is parsed with, before the query string is applied.
+    options: ParseOptions,
+}
+
+impl Server {
+    /// Creates a server that parses files with `options`, as changed by each request's query
+    /// string. The resource limits of `options` guard every request.
+    pub fn new(options: ParseOptions) -> Server {
+        return Server { options };
+    }
+
+    /// Listens for requests on `addr`, e.g. `"127.0.0.1:8080"`, handling them one at a time
+    /// until the listener fails.
+    pub fn run(&self, addr: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let listener = tiny_http::Server::http(addr)?;
+        for mut request in listener.incoming_requests() {
+            let mut body = Vec::new();
+            // Reading one byte past the size limit is enough to tell that the file is too large.
+            let limit = self.options.limits.max_file_size.map_or(u64::MAX, |max| max as u64 + 1);
+            let response = match request.as_reader().take(limit).read_to_end(&mut body) {
+                Ok(_) => self.handle(&request.method().to_string(), request.url(), &body),
+                Err(e) => error_response(400, &e.to_string()),
+            };
+            let header = tiny_http::Header::from_bytes("Content-Type", response.content_type);
+            let mut reply = tiny_http::Response::from_data(response.body)
+                .with_status_code(response.status);
+            if let Ok(header) = header {
+                reply = reply.with_header(header);
+            }
+            // A client that hung up doesn't stop the server.
+            let _ = request.respond(reply);
+        }
+        return Ok(());
+    }
+
+    /// Handles a request for `url`, a path with an optional query string, whose body is `body`.
+    pub fn handle(&self, method: &str, url: &str, body: &[u8]) -> Response {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        if !["/parse", "/stats", "/export"].contains(&path) {
+            return error_response(404, &format!("no endpoint at {}", path));
+        }
+        if method != "POST" {
+            return error_response(405, "endpoints only accept POST requests");
+        }
+        let mut options = self.options.clone();
+        let mut format = None;
+        for (key, value) in query.split('&').filter_map(|pair| pair.split_once('=')) {
+            match key {
+                "format" => format = Some(value),
+                "precision" => match get_precision(value) {
+                    Some(precision) => options = options.precision(precision),
+                    None => return error_response(400, &format!("unknown precision {}", value)),
+                },
+                "triplet" => options = options.triplet(value == "true"),
+                "tracks" => {
+                    let tracks: Result<Vec<usize>, _> = value.split(',').map(str::parse).collect();
+                    match tracks {
+                        Ok(tracks) => options = options.tracks(&tracks),
+                        Err(_) => return error_response(400, &format!("bad tracks {}", value)),
+                    }
+                },
+                _ => return error_response(400, &format!("unknown parameter {}", key)),
+            }
+        }
+        let midi = match Midi::parse_bytes(body, &options) {
+            Ok(midi) => midi,
+            Err(e) => return parse_error_response(&e),
+        };
+        match (path, format) {
+            ("/parse", None | Some("json")) => return export(&midi, "json"),
+            ("/parse", Some("lists")) => return export(&midi, "lists"),
+            ("/parse", Some(format)) => {
+                return error_response(400, &format!("/parse can't return {}", format));
+            },
+            ("/stats", _) => return json_response(stats_to_json(&midi)),
+            (_, Some(format)) => return export(&midi, format),
+            (_, None) => return error_response(400, "/export needs a format"),
+        }
+    }
+}
+
+/// Converts a piece to `format`.
+fn export(midi: &Midi, format: &str) -> Response {
+    let (content_type, body) = match format {
+        "json" => ("application/json", midi.to_json().into_bytes()),
+        "lists" => ("application/json", midi.to_netsblox_lists().to_json().into_bytes()),
+        "csv" => ("text/csv", midi.to_csv().into_bytes()),
+        "blocks" => ("application/json", midi.to_beatblox_blocks().into_bytes()),
+        "midicsv" => ("text/csv", midicsv::write_midicsv(&midi.to_events()).into_bytes()),
+        "smf" => ("audio/midi", midi.to_smf_bytes()),
+        _ => return error_response(400, &format!("unsupported format {}", format)),
+    };
+    return Response { status: 200, content_type, body };
+}
+
+/// Builds the response for a file that couldn't be parsed.
+fn parse_error_response(e: &ParseError) -> Response {
+    match e {
+        ParseError::LimitExceeded(_) => return error_response(413, &e.to_string()),
+        _ => return error_response(422, &e.to_string()),
+    }
+}
+
+/// Builds an error response holding `message`.
+fn error_response(status: u16, message: &str) -> Response {
+    let body = format!("{{\"error\":{}}}", json_string(message)).into_bytes();
+    return Response { status, content_type: "application/json", body };
+}
+
+/// Builds a successful response holding `json`.
+fn json_response(json: String) -> Response {
+    return Response { status: 200, content_type: "application/json", body: json.into_bytes() };
+}
+
+/// Writes the statistics of a piece and each of its tracks as JSON.
+fn stats_to_json(midi: &Midi) -> String {
+    let beat_type = midi.beat_type();
+    let tracks: Vec<String> = midi.tracks()
+        .iter()
+        .map(|track| {
+            let stats = track_stats_to_json(&track.stats(beat_type));
+            return format!(
+                "{{\"name\":{},\"index\":{},\"stats\":{}}}",
+                json_string(&track.name), track.index, stats
+            );
+        })
+        .collect();
+    return format!(
+        "{{\"stats\":{},\"tracks\":[{}]}}",
+        track_stats_to_json(&midi.stats()), tracks.join(",")
+    );
+}
+
+/// Writes a set of track statistics as JSON.
+fn track_stats_to_json(stats: &TrackStats) -> String {
+    let histogram: Vec<String> = stats.duration_histogram.iter().map(|n| n.to_string()).collect();
+    return format!(
+        "{{\"note_count\":{},\"min_pitch\":{},\"max_pitch\":{},\"mean_pitch\":{},\
+        \"duration_histogram\":[{}],\"mean_velocity\":{},\"total_beats\":{},\
+        \"rest_percentage\":{}}}",
+        stats.note_count,
+        json_option(stats.min_pitch, |p| p.to_string()),
+        json_option(stats.max_pitch, |p| p.to_string()),
+        json_option(stats.mean_pitch, json_number),
+        histogram.join(","),
+        json_option(stats.mean_velocity, json_number),
+        json_number(stats.total_beats),
+        json_number(stats.rest_percentage)
+    );
+}
+
+/// Reads a precision given in a query string.
+fn get_precision(name: &str) -> Option<DurationType> {
+    match name {
+        "whole" => return Some(duration::whole()),
+        "half" => return Some(duration::half()),
+        "quarter" => return Some(duration::quarter()),
+        "eighth" => return Some(duration::eighth()),
+        "sixteenth" => return Some(duration::sixteenth()),
+        "thirty-second" => return Some(duration::thirty_second()),
+        "sixty-fourth" => return Some(duration::sixty_fourth()),
+        "hundred-twenty-eighth" => return Some(duration::hundred_twenty_eighth()),
+        _ => return None,
+    }
+}
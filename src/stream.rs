@@ -0,0 +1,185 @@
+use std::collections::VecDeque;
+use std::time::Instant;
+use crate::Midi;
+use crate::error::ParseError;
+use crate::parsing;
+use crate::parsing::ParseContext;
+use crate::parsing::Scratch;
+use crate::parsing::Track;
+use crate::parsing::options::Limit;
+use crate::parsing::options::ParseOptions;
+use crate::parsing::options::ResourceLimits;
+use crate::parsing::symbols::NoteWrapper;
+
+/// Reads the notes of a midi file one track at a time, for parsing files too large to hold in
+/// memory as a whole `Midi`.
+/// 
+/// Only the piece-wide events, such as tempo and time signatures, are read up front. Each track
+/// is then decoded and parsed as it's reached, and dropped once its notes have been yielded, so
+/// at most one track is held in memory at a time. Format 0 files are the exception, since their
+/// single track must be decoded whole before it can be split by channel.
+/// 
+/// Options that need every track at once (`merge_tracks`, `pad_to_score_length`, `lossless`,
+/// `retain_timing`, and `check_barlines`) are ignored. The resource limits apply to the whole
+/// file, so the time limit starts when the stream is created and the note limit counts the notes
+/// of every track read so far.
+/// 
+/// ```no_run
+/// use beatblox_midi::parsing::options::ParseOptions;
+/// use beatblox_midi::stream::MidiStream;
+/// 
+/// let bytes = std::fs::read("large.mid").unwrap();
+/// for note in MidiStream::new(&bytes, &ParseOptions::new()).unwrap() {
+///     let (track, note) = note.unwrap();
+///     println!("track {}:", track);
+///     note.print();
+/// }
+/// ```
+pub struct MidiStream<'a> {
+    /// The piece-wide information of the file, with no tracks.
+    piece: Midi,
+    /// The tempo changes of the file as `(tick, microseconds per beat)` pairs.
+    tempo_map: Vec<(u32, u32)>,
+    /// The options used to parse each track.
+    options: ParseOptions,
+    /// The buffers used while parsing tracks.
+    scratch: Scratch,
+    /// The header of the file.
+    header: midly::Header,
+    /// The tracks of the file that haven't been decoded yet.
+    tracks: midly::TrackIter<'a>,
+    /// The tracks of a format 0 file after it was split by channel, if it was.
+    split: Option<VecDeque<Vec<midly::TrackEvent<'a>>>>,
+    /// The index of the next track in the file.
+    index: usize,
+    /// The index of the track whose notes are being yielded, and its remaining notes.
+    current: (usize, std::vec::IntoIter<NoteWrapper>),
+    /// The time by which the whole file must be parsed, if it's limited.
+    deadline: Option<Instant>,
+    /// The number of notes parsed so far, which count towards `ResourceLimits::max_notes`.
+    note_count: usize,
+}
+
+impl<'a> MidiStream<'a> {
+    /// Starts reading the contents of a midi file using the given `ParseOptions`.
+    pub fn new(bytes: &'a [u8], options: &ParseOptions) -> Result<MidiStream<'a>, ParseError> {
+        let limits = &options.limits;
+        ResourceLimits::check(Limit::FileSize, bytes.len(), limits.max_file_size)?;
+        let deadline = limits.deadline();
+        let (header, tracks) = midly::parse(bytes)?;
+        // Only the events that aren't channel messages are kept to read the piece-wide
+        // information, with their deltas adjusted for the dropped events.
+        let mut meta_tracks = Vec::new();
+        let mut events = 0;
+        for track in tracks.clone() {
+            let mut kept = Vec::new();
+            let (mut tick, mut last_tick) = (0, 0);
+            for event in track? {
+                let event = event?;
+                events += 1;
+                ResourceLimits::check(Limit::Events, events, limits.max_events)?;
+                tick = parsing::checked_add_delta(tick, event.delta)?;
+                if let midly::TrackEventKind::Midi { .. } = event.kind {
+                    continue;
+                }
+                let delta = (tick - last_tick).into();
+                kept.push(midly::TrackEvent { delta, kind: event.kind });
+                last_tick = tick;
+            }
+            meta_tracks.push(kept);
+        }
+        let tempo_map = parsing::get_tempo_map(&meta_tracks);
+        let piece = Midi::new(&midly::Smf { header, tracks: meta_tracks })?;
+        let split = match header.format {
+            midly::Format::SingleTrack => {
+                let smf = midly::Smf { header, tracks: tracks.clone().collect_tracks()? };
+                parsing::split_single_track(&smf).map(|split| split.tracks.into())
+            },
+            _ => None,
+        };
+        Ok(MidiStream {
+            piece,
+            tempo_map,
+            options: options.clone(),
+            scratch: Scratch::default(),
+            header,
+            tracks,
+            split,
+            index: 0,
+            current: (0, Vec::new().into_iter()),
+            deadline,
+            note_count: 0,
+        })
+    }
+
+    /// Returns the piece-wide information of the file, such as its tempo and time signatures.
+    /// The returned `Midi` has no tracks.
+    pub fn piece(&self) -> &Midi {
+        return &self.piece;
+    }
+
+    /// Decodes and parses the next track of the file that passes the track filters of the
+    /// options, or returns `None` once every track has been read.
+    /// 
+    /// This skips the notes of the current track that haven't been yielded yet.
+    pub fn next_track(&mut self) -> Option<Result<Track, ParseError>> {
+        loop {
+            let events = match self.next_events()? {
+                Ok(events) => events,
+                Err(e) => return Some(Err(e)),
+            };
+            let index = self.index;
+            self.index += 1;
+            if !parsing::is_track_included(&self.options, &events, index) {
+                continue;
+            }
+            let context = ParseContext {
+                ticks_per_beat: self.piece.ticks_per_beat,
+                tempo_map: &self.tempo_map,
+                time_signatures: &self.piece.time_signatures,
+                key_signatures: &self.piece.key_signatures,
+                progress: (0.0, 1.0),
+                deadline: self.deadline,
+                note_count: self.note_count,
+            };
+            let smf = midly::Smf { header: self.header, tracks: vec![events] };
+            let filtered = parsing::filter_events(&context, &smf, &self.options);
+            let track = &filtered.as_ref().unwrap_or(&smf).tracks[0];
+            let options = &self.options;
+            match parsing::load_track(&context, track, index, options, &mut self.scratch) {
+                Ok(Some(track)) => {
+                    self.note_count += track.notes.iter().map(|n| n.notes().len()).sum::<usize>();
+                    return Some(Ok(track));
+                },
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+
+    /// Decodes the events of the next track of the file.
+    fn next_events(&mut self) -> Option<Result<Vec<midly::TrackEvent<'a>>, ParseError>> {
+        if let Some(split) = &mut self.split {
+            return split.pop_front().map(Ok);
+        }
+        let events = self.tracks.next()?.and_then(|events| events.into_vec());
+        return Some(events.map_err(ParseError::from));
+    }
+}
+
+impl Iterator for MidiStream<'_> {
+    /// The index of the track a note is in and the note itself.
+    type Item = Result<(usize, NoteWrapper), ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(note) = self.current.1.next() {
+                return Some(Ok((self.current.0, note)));
+            }
+            match self.next_track()? {
+                Ok(track) => self.current = (track.index, track.notes.into_iter()),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
@@ -0,0 +1,859 @@
+use crate::Midi;
+use crate::parsing::Track;
+use crate::parsing::TrackKind;
+use crate::parsing::duration::DurationType;
+use crate::parsing::duration::POSSIBLE_NOTE_LENGTHS;
+use crate::parsing::events::EventKind;
+use crate::parsing::expression::ControllerEvent;
+use crate::parsing::expression::PressureEvent;
+use crate::parsing::measure::barline_tolerance;
+use crate::parsing::measure::measure_starts;
+use crate::parsing::parse_note_data;
+use crate::parsing::symbols::Note;
+use crate::parsing::symbols::NoteModifier;
+use crate::parsing::symbols::NoteWrapper;
+use std::ops::RangeInclusive;
+
+/// Multiplies the tempo of a piece by `factor`, leaving its notes as they are.
+/// 
+/// # Panics
+/// 
+/// Panics if `factor` is not positive.
+pub fn scale_tempo(midi: &mut Midi, factor: f32) {
+    assert!(factor > 0.0, "tempo scale factors must be positive");
+    midi.bmp = (midi.bmp as f32 * factor).round() as u32;
+    midi.microseconds_per_beat = (midi.microseconds_per_beat as f32 / factor).round() as u32;
+    if let Some(raw) = &mut midi.raw_events {
+        for event in raw.tracks.iter_mut().flatten() {
+            if let EventKind::Tempo(us) = &mut event.kind {
+                *us = (*us as f32 / factor).round() as u32;
+            }
+        }
+    }
+    for sequence in &mut midi.sequences {
+        scale_tempo(sequence, factor);
+    }
+}
+
+/// Multiplies the length of every note in a piece by `factor`, so a factor of 2 turns quarter
+/// notes into half notes.
+/// 
+/// Time signatures keep their denominator when the stretched measure is a whole number of beats
+/// (4/4 becomes 8/4 or 2/4), and take a larger denominator otherwise (3/4 becomes 3/8). Notes that
+/// are too long for a single duration are tied. Lossless pieces lose their original events, since
+/// none of them line up with the stretched notes.
+/// 
+/// # Panics
+/// 
+/// Panics if `factor` is not a positive power of two.
+pub fn stretch_durations(midi: &mut Midi, factor: f32) {
+    assert!(
+        factor > 0.0 && factor.log2().fract() == 0.0,
+        "duration stretch factors must be powers of two"
+    );
+    let old_beat_type = midi.beat_type();
+    let scale_tick = |tick: &mut u32| *tick = (*tick as f32 * factor).round() as u32;
+    for time_signature in &mut midi.time_signatures {
+        let mut beat_count = time_signature.beat_count as f32 * factor;
+        while beat_count.fract() != 0.0 {
+            beat_count *= 2.0;
+            time_signature.beat_type += 1;
+        }
+        time_signature.beat_count = beat_count as u8;
+        scale_tick(&mut time_signature.time_of_occurance);
+    }
+    let new_beat_type = midi.beat_type();
+    for key_signature in &mut midi.key_signatures {
+        scale_tick(&mut key_signature.time_of_occurance);
+    }
+    for marker in &mut midi.markers {
+        scale_tick(&mut marker.tick);
+        marker.beat *= factor;
+    }
+    for sysex in &mut midi.sysex_events {
+        scale_tick(&mut sysex.tick);
+    }
+    for track in midi.tracks.iter_mut().chain(midi.merged_track.as_mut()) {
+        stretch_track(track, factor, old_beat_type, new_beat_type);
+    }
+    midi.raw_events = None;
+    midi.raw_fingerprints.clear();
+    for sequence in &mut midi.sequences {
+        stretch_durations(sequence, factor);
+    }
+}
+
+/// Multiplies the length of every note and the time of every event in a track by `factor`.
+fn stretch_track(track: &mut Track, factor: f32, old_beat_type: u8, new_beat_type: u8) {
+    for wrapper in &mut track.notes {
+        stretch_wrapper(wrapper, factor, old_beat_type, new_beat_type);
+    }
+    track.end_of_track = (track.end_of_track as f32 * factor).round() as u32;
+    track.length *= factor;
+    for lyric in &mut track.lyric_events {
+        lyric.tick = (lyric.tick as f32 * factor).round() as u32;
+    }
+    for event in &mut track.controller_events {
+        event.beat *= factor;
+    }
+    for event in &mut track.pressure_events {
+        event.beat *= factor;
+    }
+}
+
+/// Multiplies the length of every note in a `NoteWrapper` by `factor`, tying notes that no
+/// longer fit in a single duration.
+fn stretch_wrapper(wrapper: &mut NoteWrapper, factor: f32, old_beat_type: u8, new_beat_type: u8) {
+    match wrapper {
+        NoteWrapper::PlainNote(note) | NoteWrapper::Rest(note) => {
+            let beats = note.duration.get_beat_count(old_beat_type) * factor;
+            for (beat, _) in &mut note.bend {
+                *beat *= factor;
+            }
+            for (beat, _) in &mut note.pressure {
+                *beat *= factor;
+            }
+            *wrapper = retime_note(note, beats, new_beat_type);
+        },
+        NoteWrapper::ModifiedNote(
+            NoteModifier::TiedNote(v) | NoteModifier::Chord(v) | NoteModifier::Triplet(v)
+        ) => {
+            for w in v {
+                stretch_wrapper(w, factor, old_beat_type, new_beat_type);
+            }
+        },
+    }
+}
+
+/// Layers the tracks of `other` on top of a piece, starting `offset` beats into it.
+/// 
+/// The tracks are converted to the ticks per beat and note durations of `midi`, and are renamed
+/// with a number, e.g. "Piano (2)", if `midi` already has a track of the same name. Conductor
+/// tracks of `other` are left out. Lossless pieces lose their original events, and the merged
+/// track is left as it was.
+pub fn overlay(midi: &mut Midi, other: &Midi, offset: f32) {
+    let offset = offset.max(0.0);
+    let beat_type = midi.beat_type();
+    let other_beat_type = other.beat_type();
+    let mut index = midi.tracks.iter().map(|t| t.index + 1).max().unwrap_or(0);
+    for track in &other.tracks {
+        if track.kind == TrackKind::Conductor {
+            continue;
+        }
+        let mut track = track.clone();
+        let name = track.name.clone();
+        let mut copy = 1;
+        while midi.tracks.iter().any(|t| t.name == track.name) {
+            copy += 1;
+            track.name = format!("{} ({})", name, copy);
+        }
+        if other_beat_type != beat_type {
+            for wrapper in &mut track.notes {
+                stretch_wrapper(wrapper, 1.0, other_beat_type, beat_type);
+            }
+        }
+        if offset > 0.0 {
+            let rest = parse_note_data((None, 0), offset, beat_type);
+            track.notes.insert(0, rest);
+        }
+        track.index = index;
+        index += 1;
+        track.length += offset;
+        track.end_of_track = (track.length * midi.ticks_per_beat).round() as u32;
+        for lyric in &mut track.lyric_events {
+            let beat = lyric.tick as f32 / other.ticks_per_beat + offset;
+            lyric.tick = (beat * midi.ticks_per_beat).round() as u32;
+        }
+        for event in &mut track.controller_events {
+            event.beat += offset;
+        }
+        for event in &mut track.pressure_events {
+            event.beat += offset;
+        }
+        midi.tracks.push(track);
+    }
+    midi.raw_events = None;
+    midi.raw_fingerprints.clear();
+}
+
+/// Returns a copy of a piece holding only the music between beats `start` and `end`.
+/// 
+/// Notes that cross either boundary are cut short, and are tied if the part that's left doesn't
+/// fit in a single duration. Triplets that cross a boundary are rounded to the nearest durations.
+/// The time signature, key signature, and controller values in effect at `start` carry over to
+/// the beginning of the slice. Lossless pieces lose their original events.
+pub fn slice_beats(midi: &Midi, start: f32, end: f32) -> Midi {
+    let mut sliced = slice_piece(midi, start, end);
+    sliced.sequences = midi.sequences.iter().map(|s| slice_beats(s, start, end)).collect();
+    return sliced;
+}
+
+/// Returns a copy of a piece holding only measures `start` through `end`, counting from 1. See
+/// `slice_beats` for how notes crossing the boundaries are handled.
+pub fn slice_measures(midi: &Midi, start: u32, end: u32) -> Midi {
+    let piece_end = midi.tracks.iter().map(|t| t.length).fold(0.0, f32::max);
+    let starts = measure_starts(&midi.time_signatures, midi.ticks_per_beat, piece_end);
+    let measure_start = |measure: u32| match starts.get(measure.max(1) as usize - 1) {
+        Some(beat) => beat.min(piece_end),
+        None => piece_end,
+    };
+    let mut sliced = slice_piece(midi, measure_start(start), measure_start(end.saturating_add(1)));
+    sliced.sequences = midi.sequences.iter().map(|s| slice_measures(s, start, end)).collect();
+    return sliced;
+}
+
+/// Returns a copy of a piece with the measures in `measures` played `times` times in a row,
+/// counting measures from 1.
+/// 
+/// Everything after the section is moved back to make room for the repeats, and notes crossing
+/// the edges of the section are cut as in `slice_beats`. A `times` of 0 removes the section.
+pub fn repeat_measures(midi: &Midi, measures: RangeInclusive<u32>, times: u32) -> Midi {
+    let piece_end = midi.tracks.iter().map(|t| t.length).fold(0.0, f32::max);
+    let starts = measure_starts(&midi.time_signatures, midi.ticks_per_beat, piece_end);
+    let measure_start = |measure: u32| match starts.get(measure.max(1) as usize - 1) {
+        Some(beat) => beat.min(piece_end),
+        None => piece_end,
+    };
+    let start = measure_start(*measures.start());
+    let end = measure_start(measures.end().saturating_add(1)).max(start);
+
+    let mut repeated = slice_piece(midi, 0.0, start);
+    let section = slice_piece(midi, start, end);
+    let mut beat = start;
+    for _ in 0..times {
+        append_piece(&mut repeated, &section, beat);
+        beat += end - start;
+    }
+    append_piece(&mut repeated, &slice_piece(midi, end, piece_end), beat);
+    repeated.sequences = midi.sequences
+        .iter()
+        .map(|s| repeat_measures(s, measures.clone(), times))
+        .collect();
+    return repeated;
+}
+
+/// Adds the music of `piece` onto the end of the tracks of `midi`, starting on beat `at`. Both
+/// pieces must have the same tracks, as they do when they're slices of the same piece.
+fn append_piece(midi: &mut Midi, piece: &Midi, at: f32) {
+    let ticks_per_beat = midi.ticks_per_beat;
+    let at_tick = (at * ticks_per_beat).round() as u32;
+    for time_signature in &piece.time_signatures {
+        let last = midi.time_signatures.last();
+        if let Some(last) = last {
+            if last.beat_count == time_signature.beat_count
+                && last.beat_type == time_signature.beat_type {
+                continue;
+            }
+        }
+        let mut time_signature = *time_signature;
+        time_signature.time_of_occurance += at_tick;
+        midi.time_signatures.push(time_signature);
+    }
+    for key_signature in &piece.key_signatures {
+        let last = midi.key_signatures.last();
+        if let Some(last) = last {
+            if last.sharps == key_signature.sharps && last.minor == key_signature.minor {
+                continue;
+            }
+        }
+        let mut key_signature = *key_signature;
+        key_signature.time_of_occurance += at_tick;
+        midi.key_signatures.push(key_signature);
+    }
+    for marker in &piece.markers {
+        let mut marker = marker.clone();
+        marker.tick += at_tick;
+        marker.beat += at;
+        midi.markers.push(marker);
+    }
+    for sysex in &piece.sysex_events {
+        let mut sysex = sysex.clone();
+        sysex.tick += at_tick;
+        midi.sysex_events.push(sysex);
+    }
+    let beat_type = midi.beat_type();
+    let piece_beat_type = piece.beat_type();
+    let tracks = midi.tracks.iter_mut().zip(&piece.tracks);
+    let merged_track = midi.merged_track.as_mut().zip(piece.merged_track.as_ref());
+    for (track, part) in tracks.chain(merged_track) {
+        if part.notes.is_empty() && part.length <= 0.0 {
+            continue;
+        }
+        let beats: f32 = track.notes.iter().map(|n| n.beat_count(beat_type)).sum();
+        if at - beats > 1e-4 {
+            track.notes.push(parse_note_data((None, 0), at - beats, beat_type));
+        }
+        for wrapper in &part.notes {
+            let mut wrapper = wrapper.clone();
+            if piece_beat_type != beat_type {
+                stretch_wrapper(&mut wrapper, 1.0, piece_beat_type, beat_type);
+            }
+            track.notes.push(wrapper);
+        }
+        track.length = at + part.length;
+        track.end_of_track = (track.length * ticks_per_beat).round() as u32;
+        track.truncated |= part.truncated;
+        for lyric in &part.lyric_events {
+            let mut lyric = lyric.clone();
+            lyric.tick += at_tick;
+            track.lyric_events.push(lyric);
+        }
+        for event in &part.controller_events {
+            track.controller_events.push(ControllerEvent { beat: event.beat + at, ..*event });
+        }
+        for event in &part.pressure_events {
+            track.pressure_events.push(PressureEvent { beat: event.beat + at, ..*event });
+        }
+    }
+}
+
+/// Slices everything in a piece but its sequences to the beats between `start` and `end`.
+fn slice_piece(midi: &Midi, start: f32, end: f32) -> Midi {
+    let start = start.max(0.0);
+    let end = end.max(start);
+    let ticks_per_beat = midi.ticks_per_beat;
+    let start_tick = (start * ticks_per_beat).round() as u32;
+    let end_tick = (end * ticks_per_beat).round() as u32;
+    let old_beat_type = midi.beat_type();
+
+    let mut sliced = midi.clone();
+    sliced.time_signatures = slice_changes(
+        &midi.time_signatures,
+        |t| &mut t.time_of_occurance,
+        start_tick,
+        end_tick
+    );
+    sliced.key_signatures = slice_changes(
+        &midi.key_signatures,
+        |k| &mut k.time_of_occurance,
+        start_tick,
+        end_tick
+    );
+    sliced.markers.retain(|m| m.tick >= start_tick && m.tick < end_tick);
+    for marker in &mut sliced.markers {
+        marker.tick -= start_tick;
+        marker.beat -= start;
+    }
+    sliced.sysex_events.retain(|e| e.tick >= start_tick && e.tick < end_tick);
+    for sysex in &mut sliced.sysex_events {
+        sysex.tick -= start_tick;
+    }
+    let new_beat_type = sliced.beat_type();
+    for track in sliced.tracks.iter_mut().chain(sliced.merged_track.as_mut()) {
+        slice_track(track, start, end, old_beat_type, ticks_per_beat);
+        if new_beat_type != old_beat_type {
+            for wrapper in &mut track.notes {
+                stretch_wrapper(wrapper, 1.0, old_beat_type, new_beat_type);
+            }
+        }
+    }
+    sliced.raw_events = None;
+    sliced.raw_fingerprints.clear();
+    return sliced;
+}
+
+/// Slices a list of changes that last until the next one, such as time signatures, to the ticks
+/// between `start` and `end`. The change in effect at `start` is moved to the start of the slice.
+fn slice_changes<T: Clone>(
+    changes: &[T],
+    tick: fn(&mut T) -> &mut u32,
+    start: u32,
+    end: u32
+) -> Vec<T> {
+    let mut sliced = Vec::new();
+    for change in changes {
+        let mut change = change.clone();
+        let time = *tick(&mut change);
+        if time >= end && time > start {
+            continue;
+        }
+        if time <= start {
+            sliced.clear();
+        }
+        *tick(&mut change) = time.saturating_sub(start);
+        sliced.push(change);
+    }
+    return sliced;
+}
+
+/// Slices the notes and events of a track to the beats between `start` and `end`.
+fn slice_track(track: &mut Track, start: f32, end: f32, beat_type: u8, ticks_per_beat: f32) {
+    let mut beat = 0.0;
+    let mut notes = Vec::new();
+    for wrapper in &track.notes {
+        notes.append(&mut slice_wrapper(wrapper, beat, start, end, beat_type));
+        beat += wrapper.beat_count(beat_type);
+    }
+    track.notes = notes;
+    track.length = (track.length.min(end) - start).max(0.0);
+    track.end_of_track = (track.length * ticks_per_beat).round() as u32;
+    let beats: f32 = track.notes.iter().map(|n| n.beat_count(beat_type)).sum();
+    track.truncated = beats > track.length + 1e-4;
+
+    let (start_tick, end_tick) = (start * ticks_per_beat, end * ticks_per_beat);
+    track.lyric_events.retain(|l| l.tick as f32 >= start_tick && (l.tick as f32) < end_tick);
+    for lyric in &mut track.lyric_events {
+        lyric.tick -= start_tick.round() as u32;
+    }
+    let mut controller_events = Vec::new();
+    for event in &track.controller_events {
+        if event.beat >= end {
+            break;
+        }
+        let mut event = *event;
+        if event.beat <= start {
+            controller_events.retain(|e: &ControllerEvent| e.controller != event.controller);
+        }
+        event.beat = (event.beat - start).max(0.0);
+        controller_events.push(event);
+    }
+    track.controller_events = controller_events;
+    track.pressure_events.retain(|e| e.beat >= start && e.beat < end);
+    for event in &mut track.pressure_events {
+        event.beat -= start;
+    }
+}
+
+/// Returns the part of a `NoteWrapper` that starts on beat `onset` falling between beats `start`
+/// and `end`.
+fn slice_wrapper(
+    wrapper: &NoteWrapper,
+    onset: f32,
+    start: f32,
+    end: f32,
+    beat_type: u8
+) -> Vec<NoteWrapper> {
+    let length = wrapper.beat_count(beat_type);
+    let (from, to) = (onset.max(start), (onset + length).min(end));
+    if to - from <= 1e-4 {
+        return Vec::new();
+    }
+    if from - onset <= 1e-4 && onset + length - to <= 1e-4 {
+        return vec![wrapper.clone()];
+    }
+    match wrapper {
+        NoteWrapper::PlainNote(note) | NoteWrapper::Rest(note) => {
+            let mut note = note.clone();
+            let offset = from - onset;
+            note.bend.retain(|(beat, _)| *beat >= offset);
+            note.bend.iter_mut().for_each(|(beat, _)| *beat -= offset);
+            note.pressure.retain(|(beat, _)| *beat >= offset);
+            note.pressure.iter_mut().for_each(|(beat, _)| *beat -= offset);
+            if offset > 0.0 {
+                note.lyric = None;
+            }
+            return vec![retime_note(&note, to - from, beat_type)];
+        },
+        NoteWrapper::ModifiedNote(NoteModifier::Chord(c)) => {
+            let members: Vec<NoteWrapper> = c
+                .iter()
+                .filter_map(|n| join_tied(slice_wrapper(n, onset, start, end, beat_type)))
+                .collect();
+            return match members.len() {
+                0 | 1 => members,
+                _ => vec![NoteWrapper::ModifiedNote(NoteModifier::Chord(members))],
+            };
+        },
+        NoteWrapper::ModifiedNote(NoteModifier::TiedNote(t)) => {
+            let mut beat = onset;
+            let mut pieces = Vec::new();
+            for n in t {
+                pieces.append(&mut slice_wrapper(n, beat, start, end, beat_type));
+                beat += n.beat_count(beat_type);
+            }
+            return join_tied(pieces).into_iter().collect();
+        },
+        NoteWrapper::ModifiedNote(NoteModifier::Triplet(_)) => {
+            let mut groups: Vec<(f32, f32, Vec<Note>)> = Vec::new();
+            wrapper.for_each_note(onset, beat_type, &mut |note, onset, length| {
+                match groups.last_mut() {
+                    Some(group) if (group.0 - onset).abs() <= 1e-4 => {
+                        group.1 = group.1.max(length);
+                        group.2.push(note.clone());
+                    },
+                    _ => groups.push((onset, length, vec![note.clone()])),
+                }
+            });
+            let mut pieces = Vec::new();
+            for (onset, length, notes) in groups {
+                let beats = (onset + length).min(end) - onset.max(start);
+                if beats <= 1e-4 {
+                    continue;
+                }
+                let mut members: Vec<NoteWrapper> = notes
+                    .iter()
+                    .map(|n| retime_note(n, beats, beat_type))
+                    .collect();
+                pieces.push(match members.len() {
+                    1 => members.remove(0),
+                    _ => NoteWrapper::ModifiedNote(NoteModifier::Chord(members)),
+                });
+            }
+            return pieces;
+        },
+    }
+}
+
+/// Ties a list of consecutive pieces of a note together, or returns the only piece.
+fn join_tied(mut pieces: Vec<NoteWrapper>) -> Option<NoteWrapper> {
+    return match pieces.len() {
+        0 => None,
+        1 => Some(pieces.remove(0)),
+        _ => Some(NoteWrapper::ModifiedNote(NoteModifier::TiedNote(pieces))),
+    };
+}
+
+/// Returns a copy of `note` lasting `beats` beats, tied if it doesn't fit in a single duration.
+/// 
+/// Every piece of a tied note keeps the spelling of `note`, while its lyric and expression go
+/// on the first piece.
+fn retime_note(note: &Note, beats: f32, beat_type: u8) -> NoteWrapper {
+    let mut wrapper = parse_note_data((note.value(), note.velocity), beats, beat_type);
+    for (i, n) in wrapper.notes_mut().into_iter().enumerate() {
+        n.pitch = note.pitch;
+        if i == 0 {
+            n.lyric = note.lyric.clone();
+            n.bend = note.bend.clone();
+            n.detune = note.detune;
+            n.pressure = note.pressure.clone();
+            n.glissando = note.glissando;
+            n.vibrato = note.vibrato;
+        }
+    }
+    return wrapper;
+}
+
+/// Options that control which notes `mark_fermatas` marks.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FermataOptions {
+    /// How many times longer than the track's median note a note must be held to be given a
+    /// fermata.
+    pub min_ratio: f32,
+    /// Whether or not only notes at the end of a phrase, those followed by a rest or by nothing,
+    /// are given fermatas.
+    pub phrase_ends_only: bool,
+}
+
+impl FermataOptions {
+    /// Creates options that give a fermata to phrase ends held at least three times as long as
+    /// the median note.
+    pub fn new() -> Self {
+        return FermataOptions { min_ratio: 3.0, phrase_ends_only: true };
+    }
+
+    /// Sets how many times longer than the median note a note must be to be given a fermata.
+    pub fn min_ratio(mut self, ratio: f32) -> Self {
+        self.min_ratio = ratio;
+        return self;
+    }
+
+    /// Sets whether or not only notes at the end of a phrase are given fermatas.
+    pub fn phrase_ends_only(mut self, phrase_ends_only: bool) -> Self {
+        self.phrase_ends_only = phrase_ends_only;
+        return self;
+    }
+}
+
+impl Default for FermataOptions {
+    fn default() -> Self {
+        return FermataOptions::new();
+    }
+}
+
+/// Gives a fermata (see `Note::fermata`) to the notes of a track that are held far longer than
+/// its other notes, and returns the number of notes or chords marked.
+/// 
+/// A held note that was written as a chain of tied notes is rewritten as the longest single
+/// note that fits in it, and the rest of its length is left to the fermata, so the notes after
+/// it move earlier and the track gets shorter by as much. Triplets are never given fermatas.
+pub fn mark_fermatas(track: &mut Track, options: &FermataOptions) -> usize {
+    let is_held = |wrapper: &NoteWrapper| {
+        return !is_rest(wrapper, 2)
+            && !matches!(wrapper, NoteWrapper::ModifiedNote(NoteModifier::Triplet(_)));
+    };
+    let mut lengths: Vec<f32> = track.notes
+        .iter()
+        .filter(|n| is_held(n))
+        .map(|n| n.beat_count(2))
+        .collect();
+    if lengths.is_empty() {
+        return 0;
+    }
+    lengths.sort_by(f32::total_cmp);
+    let median = lengths[lengths.len() / 2];
+
+    let mut marked = 0;
+    let mut dropped = 0.0;
+    for i in 0..track.notes.len() {
+        let length = track.notes[i].beat_count(2);
+        let phrase_end = track.notes[i + 1..].first().is_none_or(|n| is_rest(n, 2));
+        if !is_held(&track.notes[i]) || length < median * options.min_ratio {
+            continue;
+        }
+        if options.phrase_ends_only && !phrase_end {
+            continue;
+        }
+        let wrapper = &mut track.notes[i];
+        if let NoteWrapper::ModifiedNote(NoteModifier::TiedNote(pieces)) = wrapper {
+            let first = pieces.first().and_then(|p| p.notes().first().copied()).cloned();
+            let single = first.as_ref().is_some_and(|first| {
+                return pieces.iter().all(|p| {
+                    return matches!(p, NoteWrapper::PlainNote(n) if n.value() == first.value());
+                });
+            });
+            if let (Some(first), true) = (first, single) {
+                let written = POSSIBLE_NOTE_LENGTHS
+                    .iter()
+                    .copied()
+                    .filter(|l| *l <= length + 1e-4)
+                    .fold(0.0, f32::max);
+                *wrapper = retime_note(&first, written, 2);
+                dropped += length - wrapper.beat_count(2);
+            }
+        }
+        for note in wrapper.notes_mut() {
+            note.fermata = true;
+        }
+        marked += 1;
+    }
+    track.length = (track.length - dropped).max(0.0);
+    return marked;
+}
+
+/// Tidies up the rests of a piece. Rests shorter than `absorb_threshold` that follow a note are
+/// absorbed into it, lengthening the note, and then every run of rests within a measure is
+/// rewritten with as few rests as possible, longest first.
+pub fn normalize_rests(midi: &mut Midi, absorb_threshold: Option<DurationType>) {
+    let beat_type = midi.beat_type();
+    let piece_end = midi.tracks.iter().map(|t| t.length).fold(0.0, f32::max);
+    let starts = measure_starts(&midi.time_signatures, midi.ticks_per_beat, piece_end);
+    let threshold = absorb_threshold.as_ref().map(|d| d.get_beat_count(beat_type));
+    for track in midi.tracks.iter_mut().chain(midi.merged_track.as_mut()) {
+        if let Some(threshold) = threshold {
+            absorb_rests(track, threshold, beat_type);
+        }
+        merge_rests(track, &starts, beat_type);
+    }
+    for sequence in &mut midi.sequences {
+        normalize_rests(sequence, absorb_threshold.clone());
+    }
+}
+
+/// Pads the final measure of every note track of a piece with rests up to the bar line, and
+/// returns the number of tracks padded.
+/// 
+/// Tracks that already end on a bar line, or within `measure::barline_tolerance` of one, are
+/// left as they are. Each padded track keeps the length it had before in
+/// `Track::unpadded_length`.
+pub fn pad_final_measure(midi: &mut Midi) -> usize {
+    let beat_type = midi.beat_type();
+    let (time_signatures, ticks_per_beat) = (&midi.time_signatures, midi.ticks_per_beat);
+    let tolerance = barline_tolerance(beat_type);
+    let mut padded = 0;
+    for track in midi.tracks.iter_mut().chain(midi.merged_track.as_mut()) {
+        if track.kind != TrackKind::Notes {
+            continue;
+        }
+        let beats: f32 = track.notes.iter().map(|n| n.beat_count(beat_type)).sum();
+        let end = track.length.max(beats);
+        let starts = measure_starts(time_signatures, ticks_per_beat, end);
+        let last_start = starts.last().copied().unwrap_or(0.0);
+        if end - last_start <= tolerance {
+            continue;
+        }
+        let beats_per_measure = time_signatures
+            .iter()
+            .rev()
+            .find(|t| t.time_of_occurance as f32 / ticks_per_beat <= last_start + 1e-4)
+            .map_or(4.0, |t| t.beats_per_measure());
+        let barline = last_start + beats_per_measure;
+        if barline - end <= tolerance {
+            continue;
+        }
+        let rests = get_rests(beats, barline - beats, &starts, beat_type);
+        track.notes.extend(rests);
+        track.unpadded_length = Some(track.length);
+        track.length = barline;
+        padded += 1;
+    }
+    for sequence in &mut midi.sequences {
+        padded += pad_final_measure(sequence);
+    }
+    return padded;
+}
+
+/// Splits every note of a piece that crosses a bar line into notes tied across it, and every
+/// rest that crosses one into a rest on each side. Returns the number of notes and rests split.
+/// 
+/// The notes of a chord are each tied across the bar line, and triplets are left as they are.
+/// A note's lyric stays on its first piece.
+pub fn split_at_barlines(midi: &mut Midi) -> usize {
+    let beat_type = midi.beat_type();
+    let piece_end = midi.tracks.iter().map(|t| t.length).fold(0.0, f32::max);
+    let starts = measure_starts(&midi.time_signatures, midi.ticks_per_beat, piece_end);
+    let mut split = 0;
+    for track in midi.tracks.iter_mut().chain(midi.merged_track.as_mut()) {
+        let mut notes: Vec<NoteWrapper> = Vec::with_capacity(track.notes.len());
+        let mut beat = 0.0;
+        for wrapper in track.notes.drain(..) {
+            let length = wrapper.beat_count(beat_type);
+            // Ties that already break at the bar line are left as they are.
+            let mut crosses = false;
+            wrapper.for_each_note(beat, beat_type, &mut |_, onset, length| {
+                crosses |= starts.iter().any(|b| *b > onset + 1e-4 && *b < onset + length - 1e-4);
+            });
+            if crosses && !matches!(wrapper, NoteWrapper::ModifiedNote(NoteModifier::Triplet(_))) {
+                notes.append(&mut split_wrapper(&wrapper, beat, &starts, beat_type));
+                split += 1;
+            } else {
+                notes.push(wrapper);
+            }
+            beat += length;
+        }
+        track.notes = notes;
+    }
+    for sequence in &mut midi.sequences {
+        split += split_at_barlines(sequence);
+    }
+    return split;
+}
+
+/// Splits a `NoteWrapper` that starts on beat `onset` at every bar line in `measure_starts` that
+/// it crosses.
+fn split_wrapper(
+    wrapper: &NoteWrapper,
+    onset: f32,
+    measure_starts: &[f32],
+    beat_type: u8
+) -> Vec<NoteWrapper> {
+    let end = onset + wrapper.beat_count(beat_type);
+    if let NoteWrapper::ModifiedNote(NoteModifier::Chord(c)) = wrapper {
+        let members = c
+            .iter()
+            .filter_map(|n| join_tied(split_wrapper(n, onset, measure_starts, beat_type)))
+            .collect();
+        return vec![NoteWrapper::ModifiedNote(NoteModifier::Chord(members))];
+    }
+    let mut bounds = vec![onset];
+    bounds.extend(measure_starts.iter().filter(|b| **b > onset + 1e-4 && **b < end - 1e-4));
+    bounds.push(end);
+    let mut pieces = Vec::new();
+    for segment in bounds.windows(2) {
+        for piece in slice_wrapper(wrapper, onset, segment[0], segment[1], beat_type) {
+            match piece {
+                NoteWrapper::ModifiedNote(NoteModifier::TiedNote(mut t)) => pieces.append(&mut t),
+                piece => pieces.push(piece),
+            }
+        }
+    }
+    if is_rest(wrapper, beat_type) {
+        return pieces;
+    }
+    return join_tied(pieces).into_iter().collect();
+}
+
+/// Lengthens every note in a track that is followed by a rest shorter than `threshold` beats,
+/// removing the rest.
+pub(crate) fn absorb_rests(track: &mut Track, threshold: f32, beat_type: u8) {
+    let mut notes: Vec<NoteWrapper> = Vec::with_capacity(track.notes.len());
+    for wrapper in track.notes.drain(..) {
+        let beats = wrapper.beat_count(beat_type);
+        let previous = match notes.last_mut() {
+            Some(previous) if is_rest(&wrapper, beat_type) && beats < threshold => previous,
+            _ => {
+                notes.push(wrapper);
+                continue;
+            },
+        };
+        match previous {
+            NoteWrapper::PlainNote(_) | NoteWrapper::ModifiedNote(NoteModifier::TiedNote(_))
+                if !is_rest(previous, beat_type) => {
+                lengthen(previous, beats, beat_type);
+            },
+            NoteWrapper::ModifiedNote(NoteModifier::Chord(members)) => {
+                for member in members.iter_mut() {
+                    lengthen(member, beats, beat_type);
+                }
+            },
+            _ => notes.push(wrapper),
+        }
+    }
+    track.notes = notes;
+}
+
+/// Lengthens a note by `beats` beats, tying it if needed.
+fn lengthen(wrapper: &mut NoteWrapper, beats: f32, beat_type: u8) {
+    let length = wrapper.beat_count(beat_type) + beats;
+    let note = match wrapper.notes().first() {
+        Some(note) => (*note).clone(),
+        None => return,
+    };
+    match wrapper {
+        NoteWrapper::PlainNote(_) => *wrapper = retime_note(&note, length, beat_type),
+        NoteWrapper::ModifiedNote(NoteModifier::TiedNote(pieces)) => {
+            // A tie of a single pitch is rewritten whole, so it's held with as few notes as
+            // possible.
+            if pieces.iter().flat_map(|p| p.notes()).all(|n| n.value() == note.value()) {
+                *wrapper = retime_note(&note, length, beat_type);
+            } else if let Some(last) = pieces.last().and_then(|p| p.notes().last().copied()) {
+                let last = last.clone();
+                pieces.push(retime_note(&last, beats, beat_type));
+            }
+        },
+        _ => {},
+    }
+}
+
+/// Whether a `NoteWrapper` holds only rests.
+pub(crate) fn is_rest(wrapper: &NoteWrapper, beat_type: u8) -> bool {
+    return wrapper.beat_count(beat_type) > 0.0 && wrapper.notes().iter().all(|n| n.is_rest());
+}
+
+/// Rewrites every run of rests within a measure with as few rests as possible. `measure_starts`
+/// holds the beat on which each measure starts.
+fn merge_rests(track: &mut Track, measure_starts: &[f32], beat_type: u8) {
+    let mut notes: Vec<NoteWrapper> = Vec::with_capacity(track.notes.len());
+    let mut run: Option<(f32, f32)> = None;
+    let mut beat = 0.0;
+    for wrapper in track.notes.drain(..) {
+        let beats = wrapper.beat_count(beat_type);
+        if is_rest(&wrapper, beat_type) {
+            let (start, length) = run.unwrap_or((beat, 0.0));
+            run = Some((start, length + beats));
+        } else {
+            if let Some((start, length)) = run.take() {
+                notes.append(&mut get_rests(start, length, measure_starts, beat_type));
+            }
+            notes.push(wrapper);
+        }
+        beat += beats;
+    }
+    if let Some((start, length)) = run {
+        notes.append(&mut get_rests(start, length, measure_starts, beat_type));
+    }
+    track.notes = notes;
+}
+
+/// Returns the fewest rests that fill `length` beats from beat `start` without crossing a bar
+/// line, longest first within each measure.
+fn get_rests(start: f32, length: f32, measure_starts: &[f32], beat_type: u8) -> Vec<NoteWrapper> {
+    let end = start + length;
+    let mut bounds = vec![start];
+    bounds.extend(measure_starts.iter().filter(|b| **b > start + 1e-4 && **b < end - 1e-4));
+    bounds.push(end);
+    let mut rests = Vec::new();
+    for segment in bounds.windows(2) {
+        let mut remaining = segment[1] - segment[0];
+        while remaining > 1e-4 {
+            let beats = POSSIBLE_NOTE_LENGTHS
+                .iter()
+                .rev()
+                .find(|b| **b <= remaining + 1e-4)
+                .copied()
+                .unwrap_or(remaining);
+            rests.push(parse_note_data((None, 0), beats, beat_type));
+            remaining -= beats;
+        }
+    }
+    return rests;
+}
@@ -0,0 +1,190 @@
+use std::borrow::Cow;
+use crate::Midi;
+use crate::error::ParseError;
+use crate::parsing;
+use crate::parsing::ParseContext;
+use crate::parsing::Scratch;
+use crate::parsing::options::Limit;
+use crate::parsing::options::ParseOptions;
+use crate::parsing::options::ResourceLimits;
+use crate::parsing::symbols::KeySignature;
+use crate::parsing::symbols::NoteWrapper;
+use crate::parsing::symbols::TimeSignature;
+
+/// A borrowed view of a midi file that reads from the file's bytes instead of copying them.
+/// 
+/// Track names, lyrics, and raw events are borrowed from the bytes passed to `MidiRef::parse`,
+/// and notes are only parsed, one track at a time, when `MidiRef::notes` is called. This keeps
+/// peak memory low for large files whose tracks are only needed one at a time. Use
+/// `MidiRef::to_midi` to parse the whole file into an owned `Midi`.
+/// 
+/// The tracks are the tracks of the file as stored, so the single track of a format 0 file
+/// isn't split by channel.
+/// 
+/// ```no_run
+/// use beatblox_midi::parsing::options::ParseOptions;
+/// use beatblox_midi::view::MidiRef;
+/// 
+/// let bytes = std::fs::read("large.mid").unwrap();
+/// let view = MidiRef::parse(&bytes).unwrap();
+/// for index in 0..view.track_count() {
+///     let notes = view.notes(index, &ParseOptions::new()).unwrap().unwrap_or_default();
+///     println!("{:?}: {} notes", view.track_name(index), notes.len());
+/// }
+/// ```
+pub struct MidiRef<'a> {
+    /// The events of the file, borrowing their data from its bytes.
+    smf: midly::Smf<'a>,
+    /// The size of the file in bytes.
+    size: usize,
+    /// Number of ticks in each beat.
+    ticks_per_beat: f32,
+    /// The tempo changes of the file as `(tick, microseconds per beat)` pairs.
+    tempo_map: Vec<(u32, u32)>,
+    /// A list of time signatures that occur in the piece.
+    time_signatures: Vec<TimeSignature>,
+    /// A list of key signatures that occur in the piece.
+    key_signatures: Vec<KeySignature>,
+}
+
+impl<'a> MidiRef<'a> {
+    /// Reads the contents of a midi file without copying them.
+    pub fn parse(bytes: &'a [u8]) -> Result<MidiRef<'a>, ParseError> {
+        let smf = midly::Smf::parse(bytes)?;
+        parsing::check_track_ticks(&smf.tracks)?;
+        let conductor: &[midly::TrackEvent] = match smf.tracks.first() {
+            Some(track) => track,
+            None => &[],
+        };
+        Ok(MidiRef {
+            ticks_per_beat: parsing::get_ticks_per_beat(&smf.header)?,
+            tempo_map: parsing::get_tempo_map(&smf.tracks),
+            time_signatures: parsing::get_time_signature(conductor),
+            key_signatures: parsing::get_key_signatures(&smf.tracks),
+            smf,
+            size: bytes.len(),
+        })
+    }
+
+    /// Parses the whole file into an owned `Midi` using the given `ParseOptions`.
+    pub fn to_midi(&self, options: &ParseOptions) -> Result<Midi, ParseError> {
+        ResourceLimits::check(Limit::FileSize, self.size, options.limits.max_file_size)?;
+        let deadline = options.limits.deadline();
+        return Midi::parse_smf(&self.smf, options, deadline, &mut Scratch::default());
+    }
+
+    /// Returns the number of tracks in the file.
+    pub fn track_count(&self) -> usize {
+        return self.smf.tracks.len();
+    }
+
+    /// Returns the raw events of the track at `index`.
+    pub fn events(&self, index: usize) -> Option<&[midly::TrackEvent<'a>]> {
+        return self.smf.tracks.get(index).map(|track| track.as_slice());
+    }
+
+    /// Returns the text of the first track name event in the track at `index`, borrowed from
+    /// the file unless it isn't valid UTF-8.
+    pub fn track_name(&self, index: usize) -> Option<Cow<'a, str>> {
+        return self.get_text(index, |message| match message {
+            midly::MetaMessage::TrackName(s) => Some(s),
+            _ => None,
+        });
+    }
+
+    /// Returns the text of the first instrument name event in the track at `index`, borrowed
+    /// from the file unless it isn't valid UTF-8.
+    pub fn instrument_name(&self, index: usize) -> Option<Cow<'a, str>> {
+        return self.get_text(index, |message| match message {
+            midly::MetaMessage::InstrumentName(s) => Some(s),
+            _ => None,
+        });
+    }
+
+    /// Returns the lyrics of the track at `index` as `(tick, text)` pairs, borrowed from the
+    /// file unless they aren't valid UTF-8.
+    pub fn lyrics(&self, index: usize) -> Vec<(u32, Cow<'a, str>)> {
+        let mut lyrics = Vec::new();
+        let mut cur_time: u32 = 0;
+        for event in self.events(index).unwrap_or_default() {
+            cur_time = parsing::add_delta(cur_time, event.delta);
+            if let midly::TrackEventKind::Meta(midly::MetaMessage::Lyric(s)) = event.kind {
+                let text = String::from_utf8_lossy(s);
+                if !text.trim().is_empty() {
+                    lyrics.push((cur_time, text));
+                }
+            }
+        }
+        return lyrics;
+    }
+
+    /// Parses the notes of the track at `index` using the given `ParseOptions`. Returns `None`
+    /// if there is no such track, or if it's left out by the track filters of the options.
+    /// 
+    /// The resource limits of the options apply to the whole file and to the notes of the one
+    /// track.
+    pub fn notes(
+        &self,
+        index: usize,
+        options: &ParseOptions
+    ) -> Result<Option<Vec<NoteWrapper>>, ParseError> {
+        let limits = &options.limits;
+        ResourceLimits::check(Limit::FileSize, self.size, limits.max_file_size)?;
+        let events = self.smf.tracks.iter().map(|t| t.len()).sum();
+        ResourceLimits::check(Limit::Events, events, limits.max_events)?;
+        let track = match self.smf.tracks.get(index) {
+            Some(track) if parsing::is_track_included(options, track, index) => track,
+            _ => return Ok(None),
+        };
+        let context = ParseContext {
+            ticks_per_beat: self.ticks_per_beat,
+            tempo_map: &self.tempo_map,
+            time_signatures: &self.time_signatures,
+            key_signatures: &self.key_signatures,
+            progress: (0.0, 1.0),
+            deadline: limits.deadline(),
+            note_count: 0,
+        };
+        let smf = midly::Smf { header: self.smf.header, tracks: vec![track.clone()] };
+        let filtered = parsing::filter_events(&context, &smf, options);
+        let track = &filtered.as_ref().unwrap_or(&smf).tracks[0];
+        let parsed = parsing::load_track(&context, track, index, options, &mut Scratch::default())?;
+        return Ok(parsed.map(|track| track.notes));
+    }
+
+    /// Returns the number of ticks in each beat.
+    pub fn ticks_per_beat(&self) -> f32 {
+        return self.ticks_per_beat;
+    }
+
+    /// Returns the tempo changes of the file as `(tick, microseconds per beat)` pairs.
+    pub fn tempo_map(&self) -> &[(u32, u32)] {
+        return &self.tempo_map;
+    }
+
+    /// Returns all the time signatures in the piece.
+    pub fn time_signatures(&self) -> &[TimeSignature] {
+        return &self.time_signatures;
+    }
+
+    /// Returns all the key signatures in the piece.
+    pub fn key_signatures(&self) -> &[KeySignature] {
+        return &self.key_signatures;
+    }
+
+    /// Gets the text of the first non-empty meta event in the track at `index` that `select`
+    /// accepts.
+    fn get_text<F>(&self, index: usize, select: F) -> Option<Cow<'a, str>>
+    where
+        F: Fn(midly::MetaMessage<'a>) -> Option<&'a [u8]>
+    {
+        return self.events(index)?
+            .iter()
+            .filter_map(|event| match event.kind {
+                midly::TrackEventKind::Meta(message) => select(message),
+                _ => None,
+            })
+            .map(String::from_utf8_lossy)
+            .find(|text| !text.trim().is_empty());
+    }
+}
@@ -0,0 +1,86 @@
+use crate::Midi;
+use crate::export::netsblox::ListValue;
+use crate::parsing::options::ParseOptions;
+use wasm_bindgen::prelude::*;
+
+/// A parsed midi file, as handed to JavaScript.
+/// 
+/// These bindings only read from bytes, so files dropped into the browser can be parsed without
+/// the file system or a server:
+/// 
+/// ```js
+/// import init, { parseBytes } from "beatblox_midi";
+/// 
+/// await init();
+/// const bytes = new Uint8Array(await file.arrayBuffer());
+/// const midi = parseBytes(bytes, false);
+/// const lists = midi.toNetsbloxLists();
+/// ```
+#[wasm_bindgen(js_name = Midi)]
+pub struct WasmMidi {
+    midi: Midi,
+}
+
+#[wasm_bindgen(js_class = Midi)]
+impl WasmMidi {
+    /// Converts the piece to JSON. See `Midi::to_json`.
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> String {
+        return self.midi.to_json();
+    }
+
+    /// Converts the piece into nested JavaScript arrays of tracks, measures, and
+    /// `[pitch, duration, velocity]` entries. See `Midi::to_netsblox_lists`.
+    #[wasm_bindgen(js_name = toNetsbloxLists)]
+    pub fn to_netsblox_lists(&self) -> JsValue {
+        return to_js(&self.midi.to_netsblox_lists());
+    }
+
+    /// Returns the number of tracks in the piece.
+    #[wasm_bindgen(js_name = trackCount)]
+    pub fn track_count(&self) -> usize {
+        return self.midi.tracks().len();
+    }
+}
+
+impl WasmMidi {
+    /// Returns the parsed piece.
+    pub fn midi(&self) -> &Midi {
+        return &self.midi;
+    }
+}
+
+/// Parses the contents of a midi file, scanning for triplets if `triplet` is set. Throws the
+/// parse error's message if the file can't be parsed.
+#[wasm_bindgen(js_name = parseBytes)]
+pub fn parse_bytes(bytes: &[u8], triplet: bool) -> Result<WasmMidi, JsValue> {
+    let options = ParseOptions::new().triplet(triplet);
+    match Midi::parse_bytes(bytes, &options) {
+        Ok(midi) => return Ok(WasmMidi { midi }),
+        Err(e) => return Err(JsValue::from_str(&e.to_string())),
+    }
+}
+
+/// Converts the contents of a midi file straight to JSON. See `Midi::to_json`.
+#[wasm_bindgen(js_name = toJson)]
+pub fn to_json(bytes: &[u8], triplet: bool) -> Result<String, JsValue> {
+    return parse_bytes(bytes, triplet).map(|midi| midi.to_json());
+}
+
+/// Converts the contents of a midi file straight to NetsBlox lists. See
+/// `Midi::to_netsblox_lists`.
+#[wasm_bindgen(js_name = toNetsbloxLists)]
+pub fn to_netsblox_lists(bytes: &[u8], triplet: bool) -> Result<JsValue, JsValue> {
+    return parse_bytes(bytes, triplet).map(|midi| midi.to_netsblox_lists());
+}
+
+/// Converts a NetsBlox list value into the matching JavaScript number, string, or array.
+fn to_js(value: &ListValue) -> JsValue {
+    match value {
+        ListValue::Number(n) => return JsValue::from_f64(*n as f64),
+        ListValue::Text(s) => return JsValue::from_str(s),
+        ListValue::List(items) => {
+            return items.iter().map(to_js).collect::<js_sys::Array>().into();
+        },
+    }
+}
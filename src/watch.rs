@@ -0,0 +1,102 @@
+use crate::Midi;
+use crate::error::ParseError;
+use crate::parsing::options::ParseOptions;
+use notify::EventKind;
+use notify::RecursiveMode;
+use notify::Watcher;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// How long the file must go without changing before it's parsed, so a save that takes several
+/// writes is only parsed once it's done.
+pub const SETTLE_TIME: Duration = Duration::from_millis(100);
+
+/// Watches a midi file and parses it again every time it's saved, for live coding against a
+/// file that is being edited in a DAW.
+/// 
+/// The directory holding the file is watched rather than the file itself, since many programs
+/// save by writing a new file and renaming it over the old one. A save often shows up as several
+/// changes, so the file is parsed once it has gone `SETTLE_TIME` without changing, and only if
+/// its contents differ from the last time it was parsed. Files that can't be read, such as while
+/// the file is briefly missing during a save, are skipped.
+/// 
+/// The callback is called on a thread of its own. Watching stops when the `MidiWatcher` is
+/// dropped.
+/// 
+/// ```no_run
+/// use beatblox_midi::parsing::options::ParseOptions;
+/// use beatblox_midi::watch::MidiWatcher;
+/// 
+/// let _watcher = MidiWatcher::new("song.mid", ParseOptions::new(), |midi| match midi {
+///     Ok(midi) => midi.print(),
+///     Err(e) => eprintln!("{}", e),
+/// }).unwrap();
+/// std::thread::park();
+/// ```
+pub struct MidiWatcher {
+    /// The file being watched.
+    path: PathBuf,
+    /// The watcher of the directory holding the file, which stops watching when it's dropped.
+    _watcher: notify::RecommendedWatcher,
+}
+
+impl MidiWatcher {
+    /// Starts watching the midi file at `path`, calling `callback` with the piece parsed with
+    /// `options` whenever the file changes.
+    pub fn new<P, F>(
+        path: P,
+        options: ParseOptions,
+        mut callback: F
+    ) -> Result<MidiWatcher, notify::Error>
+    where
+        P: AsRef<Path>,
+        F: FnMut(Result<Midi, ParseError>) + Send + 'static
+    {
+        let path = std::path::absolute(path.as_ref())?;
+        let directory = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let file = path.clone();
+        let (sender, receiver) = mpsc::channel();
+        let handler = move |event: notify::Result<notify::Event>| {
+            let event = match event {
+                Ok(event) => event,
+                Err(_) => return,
+            };
+            let changed = matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_));
+            if changed && event.paths.iter().any(|p| p.file_name() == file.file_name()) {
+                let _ = sender.send(());
+            }
+        };
+        let mut watcher = notify::recommended_watcher(handler)?;
+        let file = path.clone();
+        // The thread ends once the watcher is dropped, which disconnects the channel.
+        std::thread::spawn(move || {
+            let mut last_hash = None;
+            while receiver.recv().is_ok() {
+                while receiver.recv_timeout(SETTLE_TIME).is_ok() {}
+                let bytes = match std::fs::read(&file) {
+                    Ok(bytes) => bytes,
+                    Err(_) => continue,
+                };
+                let mut hasher = DefaultHasher::new();
+                bytes.hash(&mut hasher);
+                let hash = Some(hasher.finish());
+                if hash != last_hash {
+                    last_hash = hash;
+                    callback(Midi::parse_bytes(&bytes, &options));
+                }
+            }
+        });
+        watcher.watch(&directory, RecursiveMode::NonRecursive)?;
+        return Ok(MidiWatcher { path, _watcher: watcher });
+    }
+
+    /// Returns the path of the file being watched.
+    pub fn path(&self) -> &Path {
+        return &self.path;
+    }
+}
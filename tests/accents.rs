@@ -0,0 +1,41 @@
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::eighth;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::dynamics::AccentOptions;
+
+#[test]
+fn accents_1() {
+    let mut midi = MidiBuilder::new()
+        .track("Drums")
+        .note(36, quarter(), 60)
+        .note(38, quarter(), 62)
+        .note(36, quarter(), 110)
+        .note(38, quarter(), 64)
+        .chord(&[36, 42], quarter(), 61)
+        .build();
+    assert_eq!(midi.mark_accents(&AccentOptions::new()), 1);
+    let notes = &midi.tracks()[0].notes;
+    let accents: Vec<bool> = notes.iter().flat_map(|n| n.notes()).map(|n| n.accent).collect();
+    assert_eq!(accents, vec![false, false, true, false, false, false]);
+    let accent = "{\"block\":\"accent\",\"blocks\":[{\"block\":\"note\"";
+    assert!(midi.to_beatblox_blocks().contains(accent));
+    assert_eq!(midi.mark_accents(&AccentOptions::new().threshold(60)), 0);
+    assert!(!midi.to_beatblox_blocks().contains("accent"));
+}
+
+#[test]
+fn accents_2() {
+    let mut midi = MidiBuilder::new()
+        .track("Hats")
+        .note(42, eighth(), 100)
+        .note(42, eighth(), 100)
+        .note(42, eighth(), 100)
+        .note(42, eighth(), 100)
+        .build();
+    assert_eq!(midi.mark_accents(&AccentOptions::new()), 0);
+    let options = AccentOptions::new().weak_beat_velocity(Some(100));
+    assert_eq!(midi.mark_accents(&options), 3);
+    let notes = &midi.tracks()[0].notes;
+    assert!(!notes[0].notes()[0].accent);
+    assert!(notes[1].notes()[0].accent);
+}
@@ -0,0 +1,72 @@
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::duration::eighth;
+use beatblox_midi::parsing::duration::thirty_second;
+use beatblox_midi::parsing::events::Event;
+use beatblox_midi::parsing::events::EventFile;
+use beatblox_midi::parsing::events::EventKind;
+use beatblox_midi::parsing::metadata::SmfFormat;
+use beatblox_midi::parsing::options::ParseOptions;
+
+fn on(tick: u32, key: u8) -> Event {
+    Event { tick, kind: EventKind::NoteOn { channel: 0, key, velocity: 100 } }
+}
+
+fn off(tick: u32, key: u8) -> Event {
+    Event { tick, kind: EventKind::NoteOff { channel: 0, key, velocity: 0 } }
+}
+
+/// Parses a single track of events and returns the pitches and arpeggiation of each entry.
+fn parse(mut events: Vec<Event>, options: &ParseOptions) -> Vec<(Vec<u8>, bool)> {
+    events.sort_by_key(|e| e.tick);
+    events.push(Event { tick: 1920, kind: EventKind::EndOfTrack });
+    let conductor = vec![Event { tick: 0, kind: EventKind::EndOfTrack }];
+    let file = EventFile {
+        format: SmfFormat::Parallel,
+        ticks_per_beat: 480,
+        tracks: vec![conductor, events],
+    };
+    let midi = Midi::parse_events(&file, options).unwrap();
+    midi.tracks()[1].notes
+        .iter()
+        .map(|n| {
+            let notes = n.notes();
+            (notes.iter().filter_map(|n| n.value()).collect(), notes.iter().any(|n| n.arpeggiated))
+        })
+        .collect()
+}
+
+/// A strummed E minor chord spread over a sixteenth note, then a single note.
+fn strum() -> Vec<Event> {
+    let mut events = Vec::new();
+    for (i, key) in [40, 47, 52, 55].into_iter().enumerate() {
+        events.push(on(i as u32 * 40, key));
+        events.push(off(960, key));
+    }
+    events.push(on(960, 64));
+    events.push(off(1440, 64));
+    events
+}
+
+#[test]
+fn arpeggios_1() {
+    let options = ParseOptions::new().precision(thirty_second());
+    let plain = parse(strum(), &options);
+    assert!(plain.len() > 3);
+    assert!(plain.iter().all(|(_, arpeggiated)| !arpeggiated));
+
+    let strummed = parse(strum(), &options.arpeggios(eighth()));
+    assert_eq!((vec![40, 47, 52, 55], true), strummed[0]);
+    assert_eq!((vec![64], false), strummed[1]);
+}
+
+#[test]
+fn arpeggios_2() {
+    // Notes that are released before the last one starts are a melody, not a strum.
+    let events = vec![
+        on(0, 60), off(50, 60), on(60, 64), off(110, 64), on(120, 67), off(960, 67),
+    ];
+    let options = ParseOptions::new().precision(thirty_second()).arpeggios(eighth());
+    let notes = parse(events, &options);
+    assert!(notes.iter().all(|(_, arpeggiated)| !arpeggiated));
+    assert_eq!(vec![60], notes[0].0);
+}
@@ -0,0 +1,39 @@
+use beatblox_midi::parsing::duration::DurationType;
+use beatblox_midi::parsing::lyrics::attach_lyrics;
+use beatblox_midi::parsing::symbols::Lyric;
+use beatblox_midi::parsing::symbols::NoteWrapper;
+
+fn quarter(value: u8) -> NoteWrapper {
+    NoteWrapper::build_note_wrapper(Some(value), DurationType::beat_type_map(1.0, 2), 100)
+}
+
+fn rest() -> NoteWrapper {
+    NoteWrapper::build_note_wrapper(None, DurationType::beat_type_map(1.0, 2), 0)
+}
+
+fn lyric(text: &str, tick: u32) -> Lyric {
+    Lyric { text: String::from(text), tick }
+}
+
+fn lyric_of(wrapper: &NoteWrapper) -> Option<String> {
+    wrapper.notes()[0].lyric.clone()
+}
+
+#[test]
+fn attach_lyrics_1() {
+    let mut notes = vec![quarter(60), quarter(62), quarter(64)];
+    let lyrics = vec![lyric("Twin", 0), lyric("kle", 96)];
+    attach_lyrics(&mut notes, &lyrics, 2, 96.0);
+    assert_eq!(Some(String::from("Twin")), lyric_of(&notes[0]));
+    assert_eq!(Some(String::from("kle")), lyric_of(&notes[1]));
+    assert_eq!(None, lyric_of(&notes[2]));
+}
+
+#[test]
+fn attach_lyrics_2() {
+    let mut notes = vec![rest(), quarter(62)];
+    let lyrics = vec![lyric("la", 10)];
+    attach_lyrics(&mut notes, &lyrics, 2, 96.0);
+    assert_eq!(None, lyric_of(&notes[0]));
+    assert_eq!(Some(String::from("la")), lyric_of(&notes[1]));
+}
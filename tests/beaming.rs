@@ -0,0 +1,70 @@
+use beatblox_midi::Midi;
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::beaming::Beam;
+use beatblox_midi::parsing::duration::eighth;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::duration::sixteenth;
+
+fn beams(midi: &Midi) -> Vec<Option<Beam>> {
+    midi.tracks()[0].notes.iter().flat_map(|n| n.notes()).map(|n| n.beam).collect()
+}
+
+#[test]
+fn beaming_1() {
+    let mut midi = MidiBuilder::new()
+        .track("Lead")
+        .note(60, eighth(), 80)
+        .note(62, eighth(), 80)
+        .note(64, sixteenth(), 80)
+        .note(65, sixteenth(), 80)
+        .note(67, eighth(), 80)
+        .note(69, quarter(), 80)
+        .note(71, eighth(), 80)
+        .rest(eighth())
+        .build();
+    assert_eq!(2, midi.mark_beams());
+    let expected = vec![
+        Some(Beam::Begin), Some(Beam::End),
+        Some(Beam::Begin), Some(Beam::Continue), Some(Beam::End),
+        None, None, None,
+    ];
+    assert_eq!(expected, beams(&midi));
+}
+
+#[test]
+fn beaming_2() {
+    // Compound meters beam a dotted quarter's worth of notes together.
+    let mut midi = MidiBuilder::new()
+        .time_signature(6, 8)
+        .track("Jig")
+        .note(60, eighth(), 80)
+        .note(62, eighth(), 80)
+        .note(64, eighth(), 80)
+        .note(65, eighth(), 80)
+        .note(67, eighth(), 80)
+        .note(69, eighth(), 80)
+        .build();
+    assert_eq!(2, midi.mark_beams());
+    let expected = vec![
+        Some(Beam::Begin), Some(Beam::Continue), Some(Beam::End),
+        Some(Beam::Begin), Some(Beam::Continue), Some(Beam::End),
+    ];
+    assert_eq!(expected, beams(&midi));
+}
+
+#[test]
+fn beaming_3() {
+    // The notes of a chord share a beam, and a beam is never carried across a bar line.
+    let mut midi = MidiBuilder::new()
+        .time_signature(2, 8)
+        .track("Lead")
+        .chord(&[60, 64], eighth(), 80)
+        .note(62, eighth(), 80)
+        .note(64, eighth(), 80)
+        .rest(eighth())
+        .build();
+    assert_eq!(1, midi.mark_beams());
+    let expected = vec![Some(Beam::Begin), Some(Beam::Begin), Some(Beam::End), None, None];
+    assert_eq!(expected, beams(&midi));
+    assert!(midi.to_json().contains("\"beam\":\"begin\""));
+}
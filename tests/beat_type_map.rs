@@ -55,4 +55,24 @@ fn beat_type_map_5() {
     let duration = DurationType::beat_type_map(2.0, 3);
     assert_eq!(control.duration, duration.duration);
     assert_eq!(control.modifier, duration.modifier);
-}
\ No newline at end of file
+}
+
+#[test]
+fn beat_type_map_6() {
+    // A quarter note that's a little too long still maps to a quarter note within the tolerance.
+    let duration = DurationType::beat_type_map_with_tolerance(1.01, 2, 0.02);
+    assert_eq!(NoteDuration::QUARTER, duration.duration);
+    assert_eq!(NoteDurationModifier::None, duration.modifier);
+    let duration = DurationType::beat_type_map_with_tolerance(1.01, 2, 0.005);
+    assert_eq!(NoteDuration::NaN, duration.duration);
+}
+
+#[test]
+fn beat_type_map_7() {
+    // Five quarter notes are a composite length, so they aren't snapped to a dotted whole note.
+    let duration = DurationType::beat_type_map_with_tolerance(5.0, 2, 0.1);
+    assert_eq!(NoteDuration::NaN, duration.duration);
+    let duration = DurationType::beat_type_map_with_tolerance(1.49, 2, 0.1);
+    assert_eq!(NoteDuration::QUARTER, duration.duration);
+    assert_eq!(NoteDurationModifier::Dotted, duration.modifier);
+}
@@ -0,0 +1,37 @@
+use beatblox_midi::Midi;
+use beatblox_midi::export::beatblox::get_block_duration;
+use beatblox_midi::export::beatblox::wrapper_to_block;
+use beatblox_midi::parsing::duration::DurationType;
+use beatblox_midi::parsing::symbols::NoteModifier;
+use beatblox_midi::parsing::symbols::NoteWrapper;
+
+#[test]
+fn beatblox_blocks_1() {
+    assert_eq!("Dotted Quarter", get_block_duration(&DurationType::beat_type_map(1.5, 2)));
+    assert_eq!("Eighth", get_block_duration(&DurationType::beat_type_map(0.5, 2)));
+}
+
+#[test]
+fn beatblox_blocks_2() {
+    let chord = NoteWrapper::ModifiedNote(NoteModifier::Chord(vec![
+        NoteWrapper::build_note_wrapper(Some(60), DurationType::beat_type_map(1.0, 2), 90),
+        NoteWrapper::build_note_wrapper(Some(64), DurationType::beat_type_map(1.0, 2), 90),
+    ]));
+    assert_eq!(
+        "{\"block\":\"chord\",\"blocks\":[\
+        {\"block\":\"note\",\"pitch\":\"C4\",\"duration\":\"Quarter\",\"velocity\":90},\
+        {\"block\":\"note\",\"pitch\":\"E4\",\"duration\":\"Quarter\",\"velocity\":90}]}",
+        wrapper_to_block(&chord)
+    );
+    let rest = NoteWrapper::build_note_wrapper(None, DurationType::beat_type_map(2.0, 2), 0);
+    assert_eq!("{\"block\":\"rest\",\"duration\":\"Half\"}", wrapper_to_block(&rest));
+}
+
+#[test]
+fn beatblox_blocks_3() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let blocks = Midi::parse(dir).unwrap().to_beatblox_blocks();
+    assert!(blocks.starts_with("{\"format\":\"beatblox-blocks\",\"version\":1,"));
+    assert!(blocks.contains("\"time_signature\":[2,4]"));
+    assert!(blocks.contains("\"track\":\"viola\""));
+}
@@ -0,0 +1,62 @@
+use beatblox_midi::Midi;
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::TrackKind;
+use beatblox_midi::parsing::duration::eighth;
+use beatblox_midi::parsing::duration::half;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::options::ParseOptions;
+use beatblox_midi::parsing::symbols::NoteModifier;
+use beatblox_midi::parsing::symbols::NoteWrapper;
+
+#[test]
+fn builder_1() {
+    let midi = MidiBuilder::new()
+        .bpm(90)
+        .time_signature(3, 4)
+        .track("Lead")
+        .program(40)
+        .note(60, quarter(), 90)
+        .rest(eighth())
+        .chord(&[60, 64, 67], half().dotted(), 80)
+        .track("Bass")
+        .note(36, half().dotted(), 100)
+        .build();
+    let tracks = midi.tracks();
+    assert_eq!(2, tracks.len());
+    assert_eq!("Lead", tracks[0].name);
+    assert_eq!(Some("Violin"), tracks[0].instrument.as_deref());
+    assert_eq!(4.5, tracks[0].length);
+    assert!(matches!(tracks[0].notes[2], NoteWrapper::ModifiedNote(NoteModifier::Chord(_))));
+    assert_eq!(TrackKind::Notes, tracks[1].kind);
+    assert_eq!(3, midi.time_signatures()[0].beat_count);
+}
+
+#[test]
+fn builder_2() {
+    let midi = MidiBuilder::new()
+        .track("Lead")
+        .note(60, quarter(), 90)
+        .note(62, quarter(), 90)
+        .note(64, half(), 90)
+        .build();
+    let written = Midi::parse_bytes(&midi.to_smf_bytes(), &ParseOptions::new()).unwrap();
+    assert_eq!(midi.to_csv(), written.to_csv());
+}
+
+#[test]
+fn builder_3() {
+    let midi = MidiBuilder::new()
+        .time_signature(6, 8)
+        .track("Lead")
+        .note(60, quarter(), 90)
+        .note(62, eighth(), 90)
+        .note(64, quarter().dotted(), 90)
+        .build();
+    let durations: Vec<_> = midi.tracks()[0].notes
+        .iter()
+        .flat_map(|n| n.notes())
+        .map(|n| n.duration.clone())
+        .collect();
+    assert_eq!(vec![quarter(), eighth(), quarter().dotted()], durations);
+    assert_eq!(6.0, midi.tracks()[0].length);
+}
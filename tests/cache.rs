@@ -0,0 +1,50 @@
+use beatblox_midi::cache::MidiCache;
+use beatblox_midi::error::ParseError;
+use beatblox_midi::parsing::options::ParseOptions;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+
+#[test]
+fn cache_1() {
+    let bytes = std::fs::read("tests/test_files/test-1.mid").unwrap();
+    let mut cache = MidiCache::new(4);
+    assert!(cache.is_empty());
+    let first = cache.get_or_parse(&bytes, &ParseOptions::new()).unwrap();
+    assert!(cache.contains(&bytes, &ParseOptions::new()));
+    // A progress function doesn't change the key.
+    let options = ParseOptions::new().on_progress(|_| {});
+    let second = cache.get_or_parse(&bytes, &options).unwrap();
+    assert!(Arc::ptr_eq(&first, &second));
+    assert_eq!(cache.len(), 1);
+    // Options that change the result do.
+    let third = cache.get_or_parse(&bytes, &ParseOptions::new().triplet(true)).unwrap();
+    assert!(!Arc::ptr_eq(&first, &third));
+    assert_eq!(cache.len(), 2);
+    assert_eq!(first.tracks().len(), third.tracks().len());
+}
+
+#[test]
+fn cache_2() {
+    let one = std::fs::read("tests/test_files/test-1.mid").unwrap();
+    let two = std::fs::read("tests/test_files/test-2.mid").unwrap();
+    let mut cache = MidiCache::new(1);
+    cache.get_or_parse(&one, &ParseOptions::new()).unwrap();
+    cache.get_or_parse(&two, &ParseOptions::new()).unwrap();
+    assert_eq!(cache.len(), 1);
+    assert!(!cache.contains(&one, &ParseOptions::new()));
+    assert!(cache.contains(&two, &ParseOptions::new()));
+    cache.clear();
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn cache_3() {
+    // Failed parses aren't stored.
+    let bytes = std::fs::read("tests/test_files/test-1.mid").unwrap();
+    let mut cache = MidiCache::new(4);
+    let options = ParseOptions::new().cancel_token(Arc::new(AtomicBool::new(true)));
+    assert!(matches!(cache.get_or_parse(&bytes, &options), Err(ParseError::Cancelled)));
+    assert!(cache.is_empty());
+    assert!(cache.get_or_parse(&[0, 1, 2], &ParseOptions::new()).is_err());
+    assert!(cache.is_empty());
+}
@@ -0,0 +1,31 @@
+use beatblox_midi::Midi;
+use beatblox_midi::error::ParseError;
+use beatblox_midi::parsing::options::ParseOptions;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+fn parse(options: &ParseOptions) -> Result<Midi, ParseError> {
+    Midi::parse_with_options(String::from("tests/test_files/test-1.mid"), options)
+}
+
+#[test]
+fn cancel_1() {
+    let token = Arc::new(AtomicBool::new(false));
+    let options = ParseOptions::new().cancel_token(Arc::clone(&token));
+    assert!(parse(&options).is_ok());
+    token.store(true, Ordering::Relaxed);
+    assert!(matches!(parse(&options), Err(ParseError::Cancelled)));
+}
+
+#[test]
+fn cancel_2() {
+    // Cancelling once the first track is done stops the parse before the next one.
+    let token = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&token);
+    let options = ParseOptions::new()
+        .triplet(true)
+        .cancel_token(Arc::clone(&token))
+        .on_progress(move |_| flag.store(true, Ordering::Relaxed));
+    assert!(matches!(parse(&options), Err(ParseError::Cancelled)));
+}
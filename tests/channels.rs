@@ -0,0 +1,51 @@
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::TrackKind;
+use beatblox_midi::parsing::events::Event;
+use beatblox_midi::parsing::events::EventFile;
+use beatblox_midi::parsing::events::EventKind;
+use beatblox_midi::parsing::metadata::SmfFormat;
+use beatblox_midi::parsing::options::ParseOptions;
+
+/// A file with a piano track on channel 0 and a track mixing a bass on channel 1 with drums on
+/// channel 9.
+fn arrangement() -> EventFile {
+    let mut tracks = vec![vec![Event { tick: 0, kind: EventKind::EndOfTrack }]];
+    for channels in [vec![0], vec![1, 9]] {
+        let mut events = Vec::new();
+        for channel in channels {
+            let key = 40 + channel * 2;
+            events.push(Event { tick: 0, kind: EventKind::NoteOn { channel, key, velocity: 90 } });
+            let off = EventKind::NoteOff { channel, key, velocity: 0 };
+            events.push(Event { tick: 480, kind: off });
+        }
+        events.sort_by_key(|e| e.tick);
+        events.push(Event { tick: 480, kind: EventKind::EndOfTrack });
+        tracks.push(events);
+    }
+    EventFile { format: SmfFormat::Parallel, ticks_per_beat: 480, tracks }
+}
+
+fn values(midi: &Midi) -> Vec<Vec<u8>> {
+    midi.tracks()
+        .iter()
+        .filter(|t| t.kind == TrackKind::Notes)
+        .map(|t| t.notes.iter().flat_map(|n| n.notes()).map(|n| n.value().unwrap()).collect())
+        .collect()
+}
+
+#[test]
+fn channels_1() {
+    let options = ParseOptions::new().channels(&[9]);
+    let midi = Midi::parse_events(&arrangement(), &options).unwrap();
+    assert_eq!(vec![vec![58]], values(&midi));
+    assert!(midi.tracks()[2].percussion);
+}
+
+#[test]
+fn channels_2() {
+    let options = ParseOptions::new().exclude_channels(&[9]);
+    let midi = Midi::parse_events(&arrangement(), &options).unwrap();
+    assert_eq!(vec![vec![40], vec![42]], values(&midi));
+    let everything = Midi::parse_events(&arrangement(), &ParseOptions::new()).unwrap();
+    assert_eq!(2, values(&everything)[1].len());
+}
@@ -0,0 +1,18 @@
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::options::ParseOptions;
+
+#[test]
+fn check_barlines_1() {
+    let dir = String::from("tests/test_files/test-2.mid");
+    let options = ParseOptions::new().check_barlines(true);
+    assert!(Midi::parse_with_options(dir, &options).is_ok());
+}
+
+#[test]
+fn check_barlines_2() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let midi = Midi::parse(dir).unwrap();
+    assert!(midi.check_barlines().is_ok());
+    assert_eq!((1, 0.0), midi.measure_at(0.0));
+    assert_eq!((3, 1.0), midi.measure_at(5.0));
+}
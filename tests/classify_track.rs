@@ -0,0 +1,37 @@
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::TrackKind;
+use beatblox_midi::parsing::options::ParseOptions;
+
+#[test]
+fn classify_track_1() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let midi = Midi::parse(dir).unwrap();
+    let tracks = midi.tracks();
+    assert_eq!(3, tracks.len());
+    assert_eq!(TrackKind::Conductor, tracks[0].kind);
+    assert_eq!("control track", tracks[0].name);
+    assert_eq!(TrackKind::Notes, tracks[1].kind);
+    assert_eq!("viola", tracks[1].name);
+}
+
+#[test]
+fn classify_track_2() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let options = ParseOptions::new().skip_empty_tracks(true);
+    let midi = Midi::parse_with_options(dir, &options).unwrap();
+    let tracks = midi.tracks();
+    assert_eq!(2, tracks.len());
+    assert_eq!(1, tracks[0].index);
+    assert_eq!(2, tracks[1].index);
+}
+
+#[test]
+fn classify_track_3() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let mut midi = Midi::parse(dir).unwrap();
+    assert_eq!(1, midi.prune_empty_tracks());
+    assert_eq!(2, midi.tracks().len());
+    assert_eq!("viola", midi.tracks()[0].name);
+    assert_eq!(2, midi.time_signatures()[0].beat_count);
+    assert_eq!(0, midi.prune_empty_tracks());
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "cli")]
+
+use beatblox_midi::Midi;
+use std::process::Command;
+
+const TEST_FILE: &str = "tests/test_files/test-1.mid";
+
+fn run(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_beatblox-midi")).args(args).output().unwrap()
+}
+
+#[test]
+fn cli_1() {
+    let midi = Midi::parse(String::from(TEST_FILE)).unwrap();
+    let output = run(&["parse", TEST_FILE, "--json"]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), midi.to_json());
+    let output = run(&["inspect", TEST_FILE]);
+    let text = String::from_utf8(output.stdout).unwrap();
+    assert!(text.contains("Time signature: 2/4 at tick 0"));
+    assert!(text.contains(&format!("Tracks: {}", midi.tracks().len())));
+    let output = run(&["stats", TEST_FILE]);
+    assert!(String::from_utf8(output.stdout).unwrap().starts_with("Piece\n"));
+}
+
+#[test]
+fn cli_2() {
+    let midi = Midi::parse(String::from(TEST_FILE)).unwrap();
+    let output = run(&["transpose", TEST_FILE, "-2"]);
+    assert!(output.status.success());
+    let transposed = Midi::parse_bytes(&output.stdout, &Default::default()).unwrap();
+    let (before, after) = (midi.stats(), transposed.stats());
+    assert_eq!(after.min_pitch, before.min_pitch.map(|p| p - 2));
+    assert_eq!(after.max_pitch, before.max_pitch.map(|p| p - 2));
+    let output = run(&["slice", TEST_FILE, "1", "2"]);
+    let sliced = Midi::parse_bytes(&output.stdout, &Default::default()).unwrap();
+    assert!(sliced.stats().total_beats <= 4.0 * sliced.tracks().len() as f32);
+}
+
+#[test]
+fn cli_3() {
+    let output = run(&["export", TEST_FILE, "--format", "abc"]);
+    assert!(!output.status.success());
+    let error = String::from_utf8(output.stderr).unwrap();
+    assert!(error.contains("unsupported format abc"));
+    assert!(!run(&["frobnicate", TEST_FILE]).status.success());
+    assert!(!run(&["parse"]).status.success());
+}
+
+#[test]
+fn cli_4() {
+    let midi = Midi::parse(String::from(TEST_FILE)).unwrap();
+    let output = run(&["export", TEST_FILE, "--format", "musicxml"]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), midi.to_musicxml());
+    let output = run(&["parse", TEST_FILE, "--lists"]);
+    let text = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(text, midi.to_netsblox_lists().to_json());
+    assert!(text.starts_with("[[") && !text.contains("Number("));
+}
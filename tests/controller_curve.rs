@@ -0,0 +1,21 @@
+use beatblox_midi::parsing::expression::ControllerEvent;
+use beatblox_midi::parsing::expression::resample_controller;
+
+#[test]
+fn controller_curve_1() {
+    let events = vec![
+        ControllerEvent { controller: 7, beat: 0.5, value: 80 },
+        ControllerEvent { controller: 10, beat: 0.0, value: 0 },
+        ControllerEvent { controller: 7, beat: 1.1, value: 40 },
+    ];
+    let curve = resample_controller(&events, 7, 1.5, 4);
+    let values: Vec<u8> = curve.iter().map(|(_, v)| *v).collect();
+    assert_eq!(vec![100, 100, 80, 80, 80, 40], values);
+    assert_eq!(0.25, curve[1].0);
+}
+
+#[test]
+fn controller_curve_2() {
+    let curve = resample_controller(&[], 11, 1.0, 2);
+    assert_eq!(vec![(0.0, 127), (0.5, 127)], curve);
+}
@@ -0,0 +1,54 @@
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::events::Event;
+use beatblox_midi::parsing::events::EventFile;
+use beatblox_midi::parsing::events::EventKind;
+use beatblox_midi::parsing::metadata::SmfFormat;
+use beatblox_midi::parsing::options::ParseOptions;
+
+fn note(channel: u8, key: u8, velocity: u8) -> Vec<Event> {
+    vec![
+        Event { tick: 0, kind: EventKind::NoteOn { channel, key, velocity } },
+        Event { tick: 480, kind: EventKind::NoteOff { channel, key, velocity: 0 } },
+    ]
+}
+
+/// A C major third where the C is played by two layered instruments on different channels.
+fn layered_file() -> EventFile {
+    let mut events = Vec::new();
+    events.extend(note(0, 60, 80));
+    events.extend(note(1, 60, 100));
+    events.extend(note(0, 64, 90));
+    events.sort_by_key(|e| e.tick);
+    events.push(Event { tick: 480, kind: EventKind::EndOfTrack });
+    let conductor = vec![Event { tick: 0, kind: EventKind::EndOfTrack }];
+    EventFile { format: SmfFormat::Parallel, ticks_per_beat: 480, tracks: vec![conductor, events] }
+}
+
+fn chord(options: &ParseOptions) -> Vec<(u8, u8)> {
+    let midi = Midi::parse_events(&layered_file(), options).unwrap();
+    midi.tracks()[1].notes[0].notes().iter().map(|n| (n.value().unwrap(), n.velocity)).collect()
+}
+
+#[test]
+fn dedupe_1() {
+    let deduped = chord(&ParseOptions::new().dedupe_notes(true));
+    assert_eq!(vec![(60, 100), (64, 90)], deduped);
+    let doubled = chord(&ParseOptions::new());
+    assert_eq!(3, doubled.len());
+}
+
+#[test]
+fn dedupe_2() {
+    // More onsets than fit in a byte can start on the same beat.
+    let mut events = Vec::new();
+    for i in 0..300 {
+        events.extend(note(i as u8 % 16, 36 + (i / 16) as u8, 90));
+    }
+    events.sort_by_key(|e| e.tick);
+    events.push(Event { tick: 480, kind: EventKind::EndOfTrack });
+    let conductor = vec![Event { tick: 0, kind: EventKind::EndOfTrack }];
+    let tracks = vec![conductor, events];
+    let file = EventFile { format: SmfFormat::Parallel, ticks_per_beat: 480, tracks };
+    let midi = Midi::parse_events(&file, &ParseOptions::new()).unwrap();
+    assert_eq!(300, midi.tracks()[1].notes[0].notes().len());
+}
@@ -0,0 +1,35 @@
+use beatblox_midi::Midi;
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::eighth;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::duration::sixteenth;
+use beatblox_midi::parsing::duration::whole;
+
+#[test]
+fn density_1() {
+    let midi = MidiBuilder::new()
+        .track("Lead")
+        .note(60, quarter(), 80)
+        .chord(&[64, 67], quarter(), 100)
+        .note(72, eighth(), 60)
+        .note(71, eighth(), 60)
+        .rest(quarter())
+        .rest(whole())
+        .chord(&[60, 64, 67], whole(), 90)
+        .build();
+    let track = &midi.tracks()[0];
+    let (time_signatures, ticks_per_beat) = (midi.time_signatures(), midi.ticks_per_beat());
+    assert_eq!(vec![4, 0, 1], track.density_per_measure(time_signatures, ticks_per_beat, false));
+    assert_eq!(vec![5, 0, 3], track.density_per_measure(time_signatures, ticks_per_beat, true));
+}
+
+#[test]
+fn density_2() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let midi = Midi::parse_with_precision(dir, sixteenth(), true).unwrap();
+    let track = &midi.tracks()[1];
+    let density = track.density_per_measure(midi.time_signatures(), midi.ticks_per_beat(), true);
+    assert!(!density.is_empty());
+    let stats = track.stats(midi.time_signatures()[0].beat_type);
+    assert_eq!(stats.note_count, density.iter().sum::<usize>());
+}
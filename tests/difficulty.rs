@@ -0,0 +1,41 @@
+use beatblox_midi::analysis::DifficultyLevel;
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::half;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::duration::sixteenth;
+use beatblox_midi::parsing::duration::thirty_second;
+
+#[test]
+fn difficulty_1() {
+    let midi = MidiBuilder::new()
+        .track("Lead")
+        .note(60, quarter(), 90)
+        .note(62, quarter(), 90)
+        .note(64, half(), 90)
+        .note(62, quarter(), 90)
+        .note(60, quarter(), 90)
+        .note(60, half(), 90)
+        .build();
+    let difficulty = midi.tracks()[0].difficulty(80, midi.beat_type());
+    assert_eq!(DifficultyLevel::Beginner, difficulty.level);
+    assert_eq!(0.0, difficulty.polyphony);
+    assert_eq!(4.0 / 36.0, difficulty.range);
+}
+
+#[test]
+fn difficulty_2() {
+    let mut builder = MidiBuilder::new().track("Lead");
+    for i in 0..16 {
+        let value = if i % 2 == 0 { 40 + i * 3 } else { 84 - i * 2 };
+        builder = builder
+            .note(value, thirty_second(), 90)
+            .note(value + 7, sixteenth().dotted(), 90);
+        builder = builder.chord(&[value, value + 4, value + 7, value + 12], quarter(), 90);
+    }
+    let midi = builder.build();
+    let difficulty = midi.tracks()[0].difficulty(160, midi.beat_type());
+    assert_eq!(DifficultyLevel::Advanced, difficulty.level);
+    assert_eq!(1.0, difficulty.polyphony);
+    assert!(difficulty.score > 65.0 && difficulty.score <= 100.0);
+    assert_eq!("advanced", difficulty.level.to_string());
+}
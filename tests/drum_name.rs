@@ -0,0 +1,32 @@
+use beatblox_midi::parsing::general_midi::drum_name;
+use beatblox_midi::parsing::general_midi::is_percussion_track;
+use midly::MidiMessage;
+use midly::TrackEvent;
+use midly::TrackEventKind;
+
+fn note_on(channel: u8, key: u8) -> TrackEvent<'static> {
+    TrackEvent {
+        delta: 0.into(),
+        kind: TrackEventKind::Midi {
+            channel: channel.into(),
+            message: MidiMessage::NoteOn { key: key.into(), vel: 100.into() },
+        },
+    }
+}
+
+#[test]
+fn drum_name_1() {
+    assert_eq!(Some("Acoustic Bass Drum"), drum_name(35));
+    assert_eq!(Some("Acoustic Snare"), drum_name(38));
+    assert_eq!(Some("Closed Hi-Hat"), drum_name(42));
+    assert_eq!(Some("Open Triangle"), drum_name(81));
+    assert_eq!(None, drum_name(34));
+    assert_eq!(None, drum_name(82));
+}
+
+#[test]
+fn drum_name_2() {
+    assert!(is_percussion_track(&[note_on(9, 36), note_on(9, 42)]));
+    assert!(!is_percussion_track(&[note_on(9, 36), note_on(0, 60)]));
+    assert!(!is_percussion_track(&[]));
+}
@@ -0,0 +1,28 @@
+use beatblox_midi::parsing::duration::DurationResult;
+use beatblox_midi::parsing::duration::Rational;
+use beatblox_midi::parsing::duration::eighth;
+use beatblox_midi::parsing::duration::half;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::duration::sixteenth;
+use beatblox_midi::parsing::duration::whole;
+
+#[test]
+fn duration_arithmetic_1() {
+    assert_eq!(DurationResult::Single(half()), quarter() + quarter());
+    assert_eq!(DurationResult::Single(quarter().dotted()), quarter() + eighth());
+    assert_eq!(DurationResult::Single(eighth()), quarter() - eighth());
+    assert_eq!(DurationResult::Single(half().dotted()), quarter() * 3.0);
+    assert_eq!(DurationResult::Tied(Vec::new()), eighth() - quarter());
+}
+
+#[test]
+fn duration_arithmetic_2() {
+    // Five quarter notes can't be written as one note, so they're tied.
+    let result = whole() + quarter();
+    assert_eq!(DurationResult::Tied(vec![whole(), quarter()]), result);
+    assert_eq!(Rational::new(5, 4), result.to_rational());
+    let result = half() + sixteenth().dotted();
+    assert_eq!(vec![half(), sixteenth().dotted()], result.durations());
+    assert_eq!(Rational::new(3, 8), Rational::new(1, 4) + Rational::new(1, 8));
+    assert!(Rational::new(1, 4) < Rational::new(3, 8));
+}
@@ -0,0 +1,35 @@
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::dynamics::Dynamic;
+use beatblox_midi::parsing::dynamics::DynamicThresholds;
+
+#[test]
+fn dynamics_1() {
+    let thresholds = DynamicThresholds::new();
+    assert_eq!(thresholds.dynamic(20), Dynamic::Pianissimo);
+    assert_eq!(thresholds.dynamic(49), Dynamic::Piano);
+    assert_eq!(thresholds.dynamic(64), Dynamic::MezzoPiano);
+    assert_eq!(thresholds.dynamic(80), Dynamic::MezzoForte);
+    assert_eq!(thresholds.dynamic(96), Dynamic::Forte);
+    assert_eq!(thresholds.dynamic(127), Dynamic::Fortissimo);
+    let custom = DynamicThresholds { forte: 70, ..thresholds };
+    assert_eq!(custom.dynamic(80), Dynamic::Forte);
+    assert_eq!(Dynamic::MezzoForte.to_string(), "mf");
+}
+
+#[test]
+fn dynamics_2() {
+    let mut midi = MidiBuilder::new()
+        .track("Lead")
+        .note(60, quarter(), 40)
+        .rest(quarter())
+        .chord(&[64, 67], quarter(), 110)
+        .build();
+    assert!(midi.to_json().contains("\"dynamic\":null"));
+    midi.mark_dynamics(&DynamicThresholds::default());
+    let notes = &midi.tracks()[0].notes;
+    let dynamics: Vec<_> = notes.iter().flat_map(|n| n.notes()).map(|n| n.dynamic).collect();
+    let (pp, ff) = (Some(Dynamic::Pianissimo), Some(Dynamic::Fortissimo));
+    assert_eq!(dynamics, vec![pp, None, ff, ff]);
+    assert!(midi.to_json().contains("\"velocity\":40,\"dynamic\":\"pp\""));
+}
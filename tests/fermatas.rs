@@ -0,0 +1,48 @@
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::duration::whole;
+use beatblox_midi::parsing::symbols::NoteModifier;
+use beatblox_midi::parsing::symbols::NoteWrapper;
+use beatblox_midi::transform::FermataOptions;
+
+#[test]
+fn fermatas_1() {
+    let mut midi = MidiBuilder::new()
+        .track("Melody")
+        .note(60, quarter(), 90)
+        .note(62, quarter(), 90)
+        .note(64, quarter(), 90)
+        .note(65, whole(), 90)
+        .note(65, quarter(), 90)
+        .build();
+    // Joins the last two notes into a tie held for five beats.
+    let notes = &mut midi.tracks_mut()[0].notes;
+    let last = notes.pop().unwrap();
+    let held = notes.pop().unwrap();
+    notes.push(NoteWrapper::ModifiedNote(NoteModifier::TiedNote(vec![held, last])));
+    let length = midi.tracks()[0].length;
+    assert_eq!(midi.mark_fermatas(&FermataOptions::new()), 1);
+    let track = &midi.tracks()[0];
+    assert!(matches!(track.notes[3], NoteWrapper::PlainNote(_)));
+    assert_eq!(track.notes[3].beat_count(2), 4.0);
+    assert!(track.notes[3].notes()[0].fermata);
+    assert!(track.notes[..3].iter().all(|n| !n.notes()[0].fermata));
+    assert_eq!(track.length, length - 1.0);
+    assert!(midi.to_json().contains("\"fermata\":true"));
+}
+
+#[test]
+fn fermatas_2() {
+    let mut midi = MidiBuilder::new()
+        .track("Melody")
+        .note(60, quarter(), 90)
+        .note(62, whole(), 90)
+        .note(64, quarter(), 90)
+        .note(65, quarter(), 90)
+        .build();
+    assert_eq!(midi.mark_fermatas(&FermataOptions::new()), 0);
+    let options = FermataOptions::new().phrase_ends_only(false);
+    assert_eq!(midi.mark_fermatas(&options.clone().min_ratio(5.0)), 0);
+    assert_eq!(midi.mark_fermatas(&options), 1);
+    assert!(midi.tracks()[0].notes[1].notes()[0].fermata);
+}
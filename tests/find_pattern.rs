@@ -0,0 +1,40 @@
+use beatblox_midi::analysis::PatternOptions;
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::eighth;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::symbols::Note;
+
+fn motif() -> Vec<Note> {
+    vec![Note::new(60, eighth(), 0), Note::new(62, eighth(), 0), Note::new(64, quarter(), 0)]
+}
+
+#[test]
+fn find_pattern_1() {
+    let midi = MidiBuilder::new()
+        .track("Lead")
+        .note(60, eighth(), 90)
+        .note(62, eighth(), 90)
+        .note(64, quarter(), 90)
+        .rest(quarter())
+        .note(67, eighth(), 90)
+        .note(69, eighth(), 90)
+        .chord(&[59, 71], quarter(), 90)
+        .note(60, quarter(), 90)
+        .note(62, quarter(), 90)
+        .note(64, quarter(), 90)
+        .build();
+    let (track, beat_type) = (&midi.tracks()[0], midi.beat_type());
+    let exact = track.find_pattern(&motif(), &PatternOptions::new(), beat_type);
+    assert_eq!(1, exact.len());
+    assert_eq!((0, 0.0, 0), (exact[0].position, exact[0].beat, exact[0].transposition));
+
+    let options = PatternOptions::new().transpose(true);
+    let transposed = track.find_pattern(&motif(), &options, beat_type);
+    assert_eq!(vec![0.0, 3.0], transposed.iter().map(|m| m.beat).collect::<Vec<_>>());
+    assert_eq!(7, transposed[1].transposition);
+    assert_eq!(4, transposed[1].position);
+
+    let options = PatternOptions::new().transpose(true).ignore_rhythm(true);
+    let loose = track.find_pattern(&motif(), &options, beat_type);
+    assert_eq!(vec![0.0, 3.0, 5.0], loose.iter().map(|m| m.beat).collect::<Vec<_>>());
+}
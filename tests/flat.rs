@@ -0,0 +1,78 @@
+use beatblox_midi::Midi;
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::flat::FlatTrack;
+use beatblox_midi::flat::FLAG_CHORD;
+use beatblox_midi::flat::FLAG_START;
+use beatblox_midi::flat::FLAG_TIED;
+use beatblox_midi::flat::FLAG_TRIPLET;
+use beatblox_midi::parsing::Track;
+use beatblox_midi::parsing::duration::DurationType;
+use beatblox_midi::parsing::duration::eighth;
+use beatblox_midi::parsing::duration::half;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::symbols::NoteModifier;
+use beatblox_midi::parsing::symbols::NoteWrapper;
+
+fn note(value: u8, duration: DurationType) -> NoteWrapper {
+    NoteWrapper::build_note_wrapper(Some(value), duration, 80)
+}
+
+fn track(notes: Vec<NoteWrapper>) -> Track {
+    let midi = MidiBuilder::new().track("t").note(60, quarter(), 80).build();
+    let mut track = midi.tracks()[0].clone();
+    track.notes = notes;
+    track
+}
+
+#[test]
+fn flat_1() {
+    let notes = vec![
+        note(60, quarter()),
+        NoteWrapper::ModifiedNote(NoteModifier::Chord(vec![note(64, half()), note(67, half())])),
+        NoteWrapper::ModifiedNote(NoteModifier::TiedNote(vec![
+            note(62, half()),
+            note(62, eighth()),
+        ])),
+        NoteWrapper::ModifiedNote(NoteModifier::Triplet(vec![
+            note(60, eighth()),
+            NoteWrapper::build_note_wrapper(None, eighth(), 0),
+            note(64, eighth()),
+        ])),
+    ];
+    let flat = FlatTrack::new(&track(notes), 2);
+    let pitches = [Some(60), Some(64), Some(67), Some(62), Some(62), Some(60), None, Some(64)];
+    assert_eq!(pitches.to_vec(), flat.pitches);
+    assert_eq!(vec![0.0, 1.0, 1.0, 3.0, 5.0], flat.onsets[..5].to_vec());
+    assert_eq!(FLAG_START, flat.flags[0]);
+    assert_eq!(FLAG_CHORD, flat.flags[2]);
+    assert_eq!(FLAG_TIED, flat.flags[4]);
+    assert_eq!(FLAG_START | FLAG_TRIPLET, flat.flags[5]);
+    assert!(!flat.is_struck(6));
+    assert!((flat.beats - 6.5).abs() < 1e-4);
+
+    let rebuilt = FlatTrack::new(&track(flat.to_notes()), 2);
+    assert_eq!(flat, rebuilt);
+}
+
+#[test]
+fn flat_2() {
+    let midi = Midi::parse(String::from("tests/test_files/test-1.mid")).unwrap();
+    let beat_type = midi.beat_type();
+    for track in midi.tracks() {
+        let flat = track.flatten(beat_type);
+        assert_eq!(track.stats(beat_type), flat.stats());
+        assert_eq!(track.pitch_class_histogram(), flat.pitch_class_histogram());
+        assert_eq!(track.polyphony_profile(beat_type), flat.polyphony_profile());
+        assert_eq!(track.notes.len(), flat.to_notes().len());
+        let beats: f32 = flat.to_notes().iter().map(|n| n.beat_count(beat_type)).sum();
+        assert!((beats - flat.beats).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn flat_3() {
+    let empty = FlatTrack::default();
+    assert!(empty.is_empty());
+    assert!(empty.to_notes().is_empty());
+    assert_eq!(0, empty.stats().note_count);
+}
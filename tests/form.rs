@@ -0,0 +1,39 @@
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::form::detect_form_with_length;
+use beatblox_midi::parsing::duration::half;
+use beatblox_midi::parsing::duration::whole;
+
+#[test]
+fn form_1() {
+    let mut builder = MidiBuilder::new().track("Lead");
+    for value in [60, 62, 60, 62, 64, 65, 60, 62] {
+        builder = builder.note(value, whole(), 90);
+    }
+    let form = builder.build().detect_form();
+    assert_eq!("AABA", form.labels());
+    let b = &form.sections[2];
+    assert_eq!((5, 6), (b.start_measure, b.end_measure));
+    assert_eq!((16.0, 24.0), (b.start_beat, b.end_beat));
+    assert!(form.sections.iter().all(|s| s.similarity == 1.0));
+}
+
+#[test]
+fn form_2() {
+    let phrase = |builder: MidiBuilder, last: u8| {
+        builder
+            .note(60, half(), 90)
+            .note(64, half(), 90)
+            .note(67, half(), 90)
+            .note(last, half(), 90)
+    };
+    let mut builder = MidiBuilder::new().track("Lead");
+    builder = phrase(builder, 72);
+    builder = phrase(builder, 71);
+    builder = builder.note(65, whole(), 90).note(69, whole(), 90);
+    let midi = builder.build();
+    let form = detect_form_with_length(&midi, 2, 0.6);
+    assert_eq!("AAB", form.labels());
+    assert!(form.sections[1].similarity < 1.0);
+    let strict = detect_form_with_length(&midi, 2, 1.0);
+    assert_eq!("ABC", strict.labels());
+}
@@ -0,0 +1,98 @@
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::TrackKind;
+use beatblox_midi::parsing::events::Event;
+use beatblox_midi::parsing::events::EventFile;
+use beatblox_midi::parsing::events::EventKind;
+use beatblox_midi::parsing::metadata::SmfFormat;
+use beatblox_midi::parsing::options::ParseOptions;
+use beatblox_midi::parsing::symbols::NoteWrapper;
+
+fn note(tick: u32, channel: u8, key: u8, on: bool) -> Event {
+    let kind = match on {
+        true => EventKind::NoteOn { channel, key, velocity: 100 },
+        false => EventKind::NoteOff { channel, key, velocity: 0 },
+    };
+    Event { tick, kind }
+}
+
+fn single_track_file() -> EventFile {
+    let mut events = vec![
+        Event { tick: 0, kind: EventKind::Tempo(500_000) },
+        Event { tick: 0, kind: EventKind::ProgramChange { channel: 0, program: 0 } },
+        Event { tick: 0, kind: EventKind::ProgramChange { channel: 1, program: 32 } },
+    ];
+    for i in 0..4 {
+        events.push(note(i * 480, 0, 60 + i as u8, true));
+        events.push(note(i * 480, 1, 36, true));
+        events.push(note(i * 480, 9, 42, true));
+        events.push(note(i * 480 + 480, 0, 60 + i as u8, false));
+        events.push(note(i * 480 + 480, 1, 36, false));
+        events.push(note(i * 480 + 480, 9, 42, false));
+    }
+    events.push(Event { tick: 1920, kind: EventKind::EndOfTrack });
+    EventFile { format: SmfFormat::SingleTrack, ticks_per_beat: 480, tracks: vec![events] }
+}
+
+#[test]
+fn format_0_1() {
+    let midi = Midi::parse_events(&single_track_file(), &ParseOptions::new()).unwrap();
+    let tracks: Vec<_> = midi.tracks().iter().filter(|t| t.kind == TrackKind::Notes).collect();
+    assert_eq!(3, tracks.len());
+    assert_eq!(Some(0), tracks[0].program);
+    assert_eq!(Some(32), tracks[1].program);
+    assert!(tracks[2].percussion);
+    assert_ne!(tracks[0].name, tracks[1].name);
+    let pitches: Vec<u8> = tracks[0].notes
+        .iter()
+        .flat_map(|n| n.notes())
+        .map(|n| n.value().unwrap())
+        .collect();
+    assert_eq!(vec![60, 61, 62, 63], pitches);
+    assert_eq!(SmfFormat::SingleTrack, midi.metadata().format);
+}
+
+#[test]
+fn format_0_2() {
+    let midi = Midi::parse_events(&single_track_file(), &ParseOptions::new()).unwrap();
+    let written = midi.to_events();
+    assert_eq!(SmfFormat::Parallel, written.format);
+    assert_eq!(4, written.tracks.len());
+    let channels: Vec<Vec<u8>> = written.tracks
+        .iter()
+        .map(|t| t.iter().filter_map(|e| match e.kind {
+            EventKind::NoteOn { channel, .. } => Some(channel),
+            _ => None,
+        }).collect())
+        .collect();
+    assert!(channels[0].is_empty());
+    assert_eq!(vec![0; 4], channels[1]);
+    assert_eq!(vec![1; 4], channels[2]);
+    assert_eq!(vec![9; 4], channels[3]);
+}
+
+#[test]
+fn format_0_3() {
+    // Lossless parses keep the file as a single track, even after a track is changed.
+    let file = single_track_file();
+    let options = ParseOptions::new().lossless(true).channels(&[0, 1]);
+    let mut midi = Midi::parse_events(&file, &options).unwrap();
+    assert_eq!(file, midi.to_events());
+    let track = midi.tracks_mut().iter_mut().find(|t| t.program == Some(32)).unwrap();
+    for wrapper in &mut track.notes {
+        if let NoteWrapper::PlainNote(note) = wrapper {
+            note.velocity = 1;
+        }
+    }
+    let written = midi.to_events();
+    assert_eq!(SmfFormat::SingleTrack, written.format);
+    assert_eq!(1, written.tracks.len());
+    let velocities: Vec<(u8, u8)> = written.tracks[0]
+        .iter()
+        .filter_map(|e| match e.kind {
+            EventKind::NoteOn { channel, velocity, .. } => Some((channel, velocity)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(12, velocities.len());
+    assert!(velocities.iter().all(|(c, v)| (*c == 1) == (*v == 1)));
+}
@@ -0,0 +1,49 @@
+use beatblox_midi::parsing::Track;
+use beatblox_midi::parsing::duration::half;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::options::ParseOptions;
+use beatblox_midi::parsing::symbols::Note;
+use beatblox_midi::parsing::symbols::NoteModifier;
+use beatblox_midi::parsing::symbols::NoteWrapper;
+use beatblox_midi::parsing::symbols::TimedNote;
+
+#[test]
+fn from_notes_1() {
+    let notes = vec![
+        Note::new(60, quarter(), 90),
+        Note::rest(quarter()),
+        Note::new(64, half(), 90),
+    ];
+    let track = Track::from_notes(String::from("Melody"), notes).unwrap();
+    assert_eq!("Melody", track.name);
+    assert_eq!(4.0, track.length);
+    let values: Vec<_> = track.notes.iter().flat_map(|n| n.notes()).map(|n| n.value()).collect();
+    assert_eq!(vec![Some(60), None, Some(64)], values);
+}
+
+#[test]
+fn from_notes_2() {
+    let notes = [
+        TimedNote { value: 60, velocity: 90, onset: 0.0, length: 1.0 },
+        TimedNote { value: 64, velocity: 90, onset: 0.0, length: 1.0 },
+        TimedNote { value: 67, velocity: 90, onset: 1.0, length: 5.0 },
+    ];
+    let options = ParseOptions::new();
+    let track = Track::from_timed_notes(String::from("Pad"), &notes, 8.0, &options).unwrap();
+    assert!(matches!(track.notes[0], NoteWrapper::ModifiedNote(NoteModifier::Chord(_))));
+    assert!(matches!(track.notes[1], NoteWrapper::ModifiedNote(NoteModifier::TiedNote(_))));
+    assert_eq!(5.0, track.notes[1].beat_count(2));
+    assert_eq!(8.0, track.notes.iter().map(|n| n.beat_count(2)).sum::<f32>());
+}
+
+#[test]
+fn from_notes_3() {
+    let notes = [
+        TimedNote { value: 60, velocity: 90, onset: 0.0, length: 1.0 },
+        TimedNote { value: 62, velocity: 90, onset: 1.0, length: 1.0 },
+    ];
+    let options = ParseOptions::new().tracks(&[3]).channels(&[9]);
+    let track = Track::from_timed_notes(String::from("Lead"), &notes, 2.0, &options).unwrap();
+    let values: Vec<_> = track.notes.iter().flat_map(|n| n.notes()).map(|n| n.value()).collect();
+    assert_eq!(vec![Some(60), Some(62)], values);
+}
@@ -70,4 +70,19 @@ fn get_beat_count_7() {
     };
     let beats = duration.get_beat_count(2);
     assert_eq!(0.125, beats);
-}
\ No newline at end of file
+}
+
+#[test]
+fn get_beat_count_8() {
+    let duration = DurationType {
+        duration: NoteDuration::SIXTYFOURTH,
+        modifier: NoteDurationModifier::Dotted,
+    };
+    assert_eq!(0.09375, duration.get_beat_count(2));
+    let duration = DurationType::beat_type_map(0.03125, 2);
+    assert_eq!(NoteDuration::HUNDREDTWENTYEIGHTH, duration.duration);
+    // A thirty-second note is a sixty-fourth note when eighth notes get the beat.
+    let duration = DurationType::beat_type_map(0.125, 3);
+    assert_eq!(NoteDuration::SIXTYFOURTH, duration.duration);
+    assert_eq!(0.125, duration.get_beat_count(3));
+}
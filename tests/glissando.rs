@@ -0,0 +1,40 @@
+use beatblox_midi::parsing::duration::DurationType;
+use beatblox_midi::parsing::expression::Glissando;
+use beatblox_midi::parsing::expression::PitchBendMode;
+use beatblox_midi::parsing::expression::attach_pitch_bends;
+use beatblox_midi::parsing::expression::find_glissando;
+use beatblox_midi::parsing::symbols::NoteWrapper;
+
+fn half(value: u8) -> NoteWrapper {
+    NoteWrapper::build_note_wrapper(Some(value), DurationType::beat_type_map(2.0, 2), 100)
+}
+
+#[test]
+fn glissando_1() {
+    // A wobble, then a sweep up a major third, then a return to the starting pitch.
+    let bend = vec![
+        (0.0, 0.0), (0.1, 20.0), (0.2, 0.0),
+        (0.25, 100.0), (0.5, 200.0), (0.75, 300.0), (1.0, 400.0),
+        (1.1, 400.0), (1.5, 0.0),
+    ];
+    let expected = Glissando { start: 60, target: 64, start_beat: 0.2, end_beat: 1.0 };
+    assert_eq!(Some(expected), find_glissando(60, &bend));
+    // Sweeps of a semitone or less, and sweeps of too few bends, aren't glissandos.
+    assert_eq!(None, find_glissando(60, &[(0.0, 0.0), (0.5, 50.0), (1.0, 100.0)]));
+    assert_eq!(None, find_glissando(60, &[(0.0, 0.0), (0.5, -600.0)]));
+    let down = find_glissando(67, &[(0.0, 0.0), (0.5, -300.0), (1.0, -700.0)]).unwrap();
+    assert_eq!((67, 60), (down.start, down.target));
+}
+
+#[test]
+fn glissando_2() {
+    let bends = vec![(0, 0.0), (48, 200.0), (96, 400.0), (144, 500.0)];
+    let mut notes = vec![half(60), half(62)];
+    attach_pitch_bends(&mut notes, &bends, PitchBendMode::Detune, 2, 96.0);
+    let glissando = notes[0].notes()[0].glissando.unwrap();
+    assert_eq!((60, 65), (glissando.start, glissando.target));
+    assert!(notes[1].notes()[0].glissando.is_none());
+    let mut notes = vec![half(60)];
+    attach_pitch_bends(&mut notes, &bends, PitchBendMode::Ignore, 2, 96.0);
+    assert!(notes[0].notes()[0].glissando.is_none());
+}
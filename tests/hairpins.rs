@@ -0,0 +1,43 @@
+use beatblox_midi::analysis::HairpinDirection;
+use beatblox_midi::analysis::HairpinOptions;
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::quarter;
+
+#[test]
+fn hairpins_1() {
+    let mut builder = MidiBuilder::new().track("Lead");
+    for velocity in [40, 50, 60, 70, 80, 70, 60, 50, 40, 40] {
+        builder = builder.note(60, quarter(), velocity);
+    }
+    let midi = builder.build();
+    let hairpins = midi.tracks()[0].hairpins(midi.beat_type(), &HairpinOptions::new());
+    assert_eq!(hairpins.len(), 2);
+    assert_eq!(hairpins[0].direction, HairpinDirection::Crescendo);
+    assert_eq!((hairpins[0].start, hairpins[0].end), (0.0, 4.0));
+    assert_eq!((hairpins[0].start_velocity, hairpins[0].end_velocity), (40, 80));
+    assert_eq!(hairpins[1].direction, HairpinDirection::Diminuendo);
+    assert_eq!((hairpins[1].start, hairpins[1].end), (4.0, 9.0));
+}
+
+#[test]
+fn hairpins_2() {
+    // Short or small changes aren't hairpins, and chords count as one onset.
+    let midi = MidiBuilder::new()
+        .track("Lead")
+        .note(60, quarter(), 60)
+        .chord(&[60, 64], quarter(), 70)
+        .note(60, quarter(), 80)
+        .note(60, quarter(), 60)
+        .note(60, quarter(), 62)
+        .note(60, quarter(), 64)
+        .note(60, quarter(), 66)
+        .build();
+    let track = &midi.tracks()[0];
+    assert!(track.hairpins(midi.beat_type(), &HairpinOptions::new()).is_empty());
+    let options = HairpinOptions::new().min_notes(3).min_change(6);
+    let hairpins = track.hairpins(midi.beat_type(), &options);
+    assert_eq!(hairpins.len(), 2);
+    assert_eq!((hairpins[0].start, hairpins[0].end), (0.0, 2.0));
+    assert_eq!((hairpins[1].start, hairpins[1].end), (3.0, 6.0));
+    assert_eq!(hairpins[1].direction.to_string(), "crescendo");
+}
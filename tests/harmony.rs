@@ -0,0 +1,66 @@
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::harmony::Chord;
+use beatblox_midi::harmony::ChordQuality;
+use beatblox_midi::parsing::duration::half;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::duration::whole;
+
+#[test]
+fn harmony_1() {
+    let midi = MidiBuilder::new()
+        .track("Piano")
+        .chord(&[48, 64, 67], whole(), 80)
+        .chord(&[53, 65, 69, 72], whole(), 80)
+        .chord(&[55, 65, 71, 74], whole(), 80)
+        .chord(&[48, 64, 67, 72], whole(), 80)
+        .track("Melody")
+        .note(72, half(), 90)
+        .note(74, half(), 90)
+        .note(77, whole(), 90)
+        .note(74, whole(), 90)
+        .note(72, whole(), 90)
+        .build();
+    let analysis = midi.analyze_harmony();
+    assert_eq!("C major", analysis.key.name());
+    let numerals: Vec<_> = analysis.segments.iter().map(|s| s.numeral.clone().unwrap()).collect();
+    assert_eq!(vec!["I", "IV", "V7", "I"], numerals);
+    let names: Vec<_> = analysis.segments
+        .iter()
+        .map(|s| s.chord.unwrap().name(Some(analysis.key)))
+        .collect();
+    assert_eq!(vec!["C", "F", "G7", "C"], names);
+    assert_eq!(vec![1, 2, 3, 4], analysis.segments.iter().map(|s| s.measure).collect::<Vec<_>>());
+}
+
+#[test]
+fn harmony_2() {
+    let midi = MidiBuilder::new()
+        .track("Piano")
+        .chord(&[57, 60, 64], whole(), 80)
+        .chord(&[50, 62, 65, 69], whole(), 80)
+        .chord(&[52, 56, 59, 64], half(), 80)
+        .chord(&[52, 56, 59, 62], half(), 80)
+        .chord(&[45, 60, 64, 69], whole(), 80)
+        .build();
+    let key = midi.detect_key().unwrap();
+    assert_eq!("A minor", key.name());
+    let analysis = midi.analyze_harmony_by_beat();
+    assert_eq!(16, analysis.segments.len());
+    assert_eq!(Some(String::from("i")), analysis.segments[0].numeral);
+    assert_eq!(Some(String::from("iv")), analysis.segments[4].numeral);
+    assert_eq!(Some(String::from("V")), analysis.segments[8].numeral);
+    assert_eq!(Some(String::from("V7")), analysis.segments[10].numeral);
+    assert_eq!(3.0, analysis.segments[3].beat);
+}
+
+#[test]
+fn harmony_3() {
+    let diminished = Chord::from_values(&[59, 62, 65]).unwrap();
+    assert_eq!(ChordQuality::Diminished, diminished.quality);
+    assert_eq!(11, diminished.root);
+    let minor_seventh = Chord::from_values(&[62, 65, 69, 72]).unwrap();
+    assert_eq!("Dm7", minor_seventh.name(None));
+    assert_eq!(None, Chord::from_values(&[]));
+    let midi = MidiBuilder::new().track("Lead").rest(quarter()).build();
+    assert!(midi.detect_key().is_none());
+}
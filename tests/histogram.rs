@@ -0,0 +1,44 @@
+use beatblox_midi::Midi;
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::eighth;
+use beatblox_midi::parsing::duration::hundred_twenty_eighth;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::duration::sixty_fourth;
+use beatblox_midi::parsing::duration::whole;
+
+#[test]
+fn histogram_1() {
+    let midi = MidiBuilder::new()
+        .track("Lead")
+        .note(60, quarter(), 80)
+        .chord(&[64, 67], quarter().dotted(), 100)
+        .note(72, eighth(), 60)
+        .rest(whole())
+        .build();
+    let track = &midi.tracks()[0];
+    assert_eq!([2, 0, 0, 0, 1, 0, 0, 1, 0, 0, 0, 0], track.pitch_class_histogram());
+    assert_eq!([0, 0, 3, 1, 0, 0, 0, 0], track.duration_histogram());
+}
+
+#[test]
+fn histogram_2() {
+    let dir = String::from("tests/test_files/test-2.mid");
+    let midi = Midi::parse(dir).unwrap();
+    for track in midi.tracks() {
+        let stats = track.stats(midi.time_signatures()[0].beat_type);
+        assert_eq!(stats.note_count, track.pitch_class_histogram().iter().sum::<usize>());
+        assert_eq!(stats.duration_histogram, track.duration_histogram());
+    }
+}
+
+#[test]
+fn histogram_3() {
+    let midi = MidiBuilder::new()
+        .track("Ornament")
+        .note(60, sixty_fourth(), 80)
+        .note(62, sixty_fourth(), 80)
+        .note(64, hundred_twenty_eighth().dotted(), 80)
+        .note(65, quarter(), 80)
+        .build();
+    assert_eq!([0, 0, 1, 0, 0, 0, 2, 1], midi.tracks()[0].duration_histogram());
+}
@@ -0,0 +1,21 @@
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::general_midi::instrument_name;
+
+#[test]
+fn instrument_name_1() {
+    assert_eq!(Some("Acoustic Grand Piano"), instrument_name(0));
+    assert_eq!(Some("Viola"), instrument_name(41));
+    assert_eq!(Some("Gunshot"), instrument_name(127));
+    assert_eq!(None, instrument_name(128));
+}
+
+#[test]
+fn instrument_name_2() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let midi = Midi::parse(dir).unwrap();
+    let track = &midi.tracks()[2];
+    assert_eq!(Some(42), track.program);
+    assert_eq!(Some(String::from("Cello")), track.instrument);
+    assert_eq!("cello", track.name);
+    assert_eq!(None, midi.tracks()[0].program);
+}
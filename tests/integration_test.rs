@@ -7,7 +7,7 @@ use beatblox_midi::parsing::duration::NoteDurationModifier;
 #[test]
 fn parse() {
     let dir = String::from("tests/test_files/test-2.mid");
-    let midi = Midi::parse(dir);
+    let midi = Midi::parse(dir).unwrap();
     midi.print();
 }
 
@@ -19,7 +19,7 @@ fn parse_precision() {
         duration: NoteDuration::EIGHTH,
         modifier: NoteDurationModifier::None,
     };
-    let midi = Midi::parse_with_precision(dir, precision, false);
+    let midi = Midi::parse_with_precision(dir, precision, false).unwrap();
     midi.print();
 }
 
@@ -31,6 +31,6 @@ fn parse_tuplet() {
         duration: NoteDuration::SIXTEENTH,
         modifier: NoteDurationModifier::None,
     };    
-    let midi = Midi::parse_with_precision(dir, precision, true);
+    let midi = Midi::parse_with_precision(dir, precision, true).unwrap();
     midi.print();
 }
@@ -0,0 +1,33 @@
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::symbols::KeySignature;
+
+#[test]
+fn key_signatures_1() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let midi = Midi::parse(dir).unwrap();
+    let key_signatures = midi.key_signatures();
+    assert_eq!(1, key_signatures.len());
+    assert_eq!(1, key_signatures[0].sharps);
+    assert!(!key_signatures[0].minor);
+    assert_eq!(0, key_signatures[0].time_of_occurance);
+}
+
+#[test]
+fn key_signatures_2() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let midi = Midi::parse(dir).unwrap();
+    let key_signature = midi.key_signature_at(5000).unwrap();
+    assert_eq!(1, key_signature.sharps);
+}
+
+#[test]
+fn key_signatures_3() {
+    // Tonics are read as pitch classes, so note values and out of range bytes can be given.
+    let d_major = KeySignature::from_tonic(2, false);
+    assert_eq!(2, d_major.sharps);
+    assert_eq!(d_major, KeySignature::from_tonic(62, false));
+    for tonic in 0..=255 {
+        let key = KeySignature::from_tonic(tonic, true);
+        assert_eq!(tonic % 12, key.tonic());
+    }
+}
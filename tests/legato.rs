@@ -0,0 +1,29 @@
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::eighth;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::duration::sixteenth;
+
+#[test]
+fn legato_1() {
+    let midi = MidiBuilder::new()
+        .track("Lead")
+        .rest(quarter())
+        .note(60, eighth(), 90)
+        .rest(eighth())
+        .note(62, quarter(), 90)
+        .rest(sixteenth())
+        .rest(sixteenth())
+        .chord(&[64, 67], eighth(), 80)
+        .rest(sixteenth())
+        .note(65, quarter(), 90)
+        .rest(quarter())
+        .build();
+    let mut track = midi.tracks()[0].clone();
+    track.legato();
+    let lengths: Vec<f32> = track.notes.iter().map(|n| n.beat_count(2)).collect();
+    assert_eq!(vec![1.0, 1.0, 1.5, 0.75, 1.0, 1.0], lengths);
+    let values: Vec<_> = track.notes.iter().flat_map(|n| n.notes()).map(|n| n.value()).collect();
+    assert_eq!(vec![None, Some(60), Some(62), Some(64), Some(67), Some(65), None], values);
+    let total: f32 = track.notes.iter().map(|n| n.beat_count(2)).sum();
+    assert_eq!(midi.tracks()[0].length, total);
+}
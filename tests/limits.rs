@@ -0,0 +1,110 @@
+use beatblox_midi::Midi;
+use beatblox_midi::error::ParseError;
+use beatblox_midi::parser::Parser;
+use beatblox_midi::parsing::events::Event;
+use beatblox_midi::parsing::events::EventFile;
+use beatblox_midi::parsing::events::EventKind;
+use beatblox_midi::parsing::metadata::SmfFormat;
+use beatblox_midi::parsing::options::Limit;
+use beatblox_midi::parsing::options::ParseOptions;
+use beatblox_midi::parsing::options::ResourceLimits;
+use beatblox_midi::stream::MidiStream;
+use beatblox_midi::view::MidiRef;
+use std::time::Duration;
+
+const TEST_FILE: &str = "tests/test_files/test-1.mid";
+
+fn limited(limits: ResourceLimits) -> Result<Midi, ParseError> {
+    Midi::parse_with_options(String::from(TEST_FILE), &ParseOptions::new().limits(limits))
+}
+
+#[test]
+fn limits_1() {
+    assert!(limited(ResourceLimits::new()).is_ok());
+    let size = std::fs::metadata(TEST_FILE).unwrap().len() as usize;
+    assert!(limited(ResourceLimits::new().max_file_size(size)).is_ok());
+    let result = limited(ResourceLimits::new().max_file_size(size - 1));
+    assert!(matches!(result, Err(ParseError::LimitExceeded(Limit::FileSize))));
+    let options = ParseOptions::new().limits(ResourceLimits::new().max_file_size(16));
+    let mut parser = Parser::new(options);
+    let result = parser.parse(String::from(TEST_FILE));
+    assert!(matches!(result, Err(ParseError::LimitExceeded(Limit::FileSize))));
+}
+
+#[test]
+fn limits_2() {
+    let result = limited(ResourceLimits::new().max_events(10));
+    assert!(matches!(result, Err(ParseError::LimitExceeded(Limit::Events))));
+    let result = limited(ResourceLimits::new().max_notes(10));
+    assert!(matches!(result, Err(ParseError::LimitExceeded(Limit::Notes))));
+    let result = limited(ResourceLimits::new().max_parse_time(Duration::ZERO));
+    assert!(matches!(result, Err(ParseError::LimitExceeded(Limit::ParseTime))));
+}
+
+#[test]
+fn limits_3() {
+    // A note placed a billion beats into the track is refused before any grid is built.
+    let events = vec![
+        Event { tick: 0, kind: EventKind::NoteOn { channel: 0, key: 60, velocity: 90 } },
+        Event { tick: 480, kind: EventKind::NoteOff { channel: 0, key: 60, velocity: 0 } },
+        Event { tick: 480_000_000, kind: EventKind::NoteOn { channel: 0, key: 62, velocity: 90 } },
+        Event { tick: 480_000_480, kind: EventKind::NoteOff { channel: 0, key: 62, velocity: 0 } },
+        Event { tick: 480_000_480, kind: EventKind::EndOfTrack },
+    ];
+    let file = EventFile { format: SmfFormat::Parallel, ticks_per_beat: 480, tracks: vec![events] };
+    let options = ParseOptions::new().limits(ResourceLimits::new().max_beats(10_000));
+    let result = Midi::parse_events(&file, &options);
+    assert!(matches!(result, Err(ParseError::LimitExceeded(Limit::Beats))));
+}
+
+#[test]
+fn limits_4() {
+    // Streams and views are held to the same limits as a whole parse.
+    let bytes = std::fs::read(TEST_FILE).unwrap();
+    let limits = [
+        (ResourceLimits::new().max_file_size(16), Limit::FileSize),
+        (ResourceLimits::new().max_events(10), Limit::Events),
+        (ResourceLimits::new().max_notes(10), Limit::Notes),
+        (ResourceLimits::new().max_parse_time(Duration::ZERO), Limit::ParseTime),
+    ];
+    for (limits, limit) in limits {
+        let options = ParseOptions::new().limits(limits);
+        let streamed = MidiStream::new(&bytes, &options).and_then(|stream| {
+            stream.collect::<Result<Vec<_>, _>>()
+        });
+        assert!(matches!(streamed, Err(ParseError::LimitExceeded(l)) if l == limit));
+        let view = MidiRef::parse(&bytes).unwrap();
+        let viewed = (0..view.track_count())
+            .map(|index| view.notes(index, &options))
+            .collect::<Result<Vec<_>, _>>();
+        assert!(matches!(viewed, Err(ParseError::LimitExceeded(l)) if l == limit));
+    }
+}
+
+#[test]
+fn limits_5() {
+    // Twenty notes held for the longest delta time a file can store overflow a tick count.
+    for ticks_per_beat in [480u16, 100] {
+        let mut track = Vec::new();
+        for _ in 0..10 {
+            track.extend([0xFF, 0xFF, 0xFF, 0x7F, 0x90, 0x3C, 0x40]);
+            track.extend([0xFF, 0xFF, 0xFF, 0x7F, 0x80, 0x3C, 0x00]);
+        }
+        track.extend([0x00, 0xFF, 0x2F, 0x00]);
+        let mut bytes = b"MThd\x00\x00\x00\x06\x00\x01\x00\x01".to_vec();
+        bytes.extend(ticks_per_beat.to_be_bytes());
+        bytes.extend(b"MTrk");
+        bytes.extend((track.len() as u32).to_be_bytes());
+        bytes.extend(track);
+
+        for limits in [ResourceLimits::new(), ResourceLimits::new().max_beats(10_000)] {
+            let options = ParseOptions::new().limits(limits);
+            let result = Midi::parse_bytes(&bytes, &options);
+            assert!(matches!(result, Err(ParseError::LimitExceeded(Limit::Beats))));
+            let result = MidiStream::new(&bytes, &options);
+            assert!(matches!(result, Err(ParseError::LimitExceeded(Limit::Beats))));
+        }
+        let result = MidiRef::parse(&bytes);
+        assert!(matches!(result, Err(ParseError::LimitExceeded(Limit::Beats))));
+    }
+}
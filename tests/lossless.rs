@@ -0,0 +1,49 @@
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::events::EventFile;
+use beatblox_midi::parsing::events::EventKind;
+use beatblox_midi::parsing::options::ParseOptions;
+use beatblox_midi::parsing::symbols::NoteWrapper;
+
+#[test]
+fn lossless_1() {
+    let bytes = std::fs::read("tests/test_files/test-1.mid").unwrap();
+    let options = ParseOptions::new().lossless(true);
+    let midi = Midi::parse_bytes(&bytes, &options).unwrap();
+    let original = EventFile::from_smf_bytes(&bytes).unwrap();
+    assert_eq!(EventFile::from_smf_bytes(&midi.to_smf_bytes()).unwrap(), original);
+}
+
+#[test]
+fn lossless_2() {
+    let bytes = std::fs::read("tests/test_files/test-1.mid").unwrap();
+    let options = ParseOptions::new().lossless(true);
+    let mut midi = Midi::parse_bytes(&bytes, &options).unwrap();
+    let track = midi.tracks_mut().iter_mut().find(|t| t.name == "viola").unwrap();
+    let index = track.index;
+    for wrapper in &mut track.notes {
+        if let NoteWrapper::PlainNote(note) = wrapper {
+            note.velocity = 1;
+        }
+    }
+    let original = EventFile::from_smf_bytes(&bytes).unwrap();
+    let written = midi.to_events();
+    for (i, track) in written.tracks.iter().enumerate() {
+        if i != index {
+            assert_eq!(track, &original.tracks[i]);
+        }
+    }
+    assert!(written.tracks[index].iter().any(|e| matches!(
+        e.kind,
+        EventKind::NoteOn { velocity: 1, .. }
+    )));
+}
+
+#[test]
+fn lossless_3() {
+    // Tracks and channels that are filtered out are still written back.
+    let bytes = std::fs::read("tests/test_files/test-1.mid").unwrap();
+    let options = ParseOptions::new().lossless(true).tracks(&[1]).exclude_channels(&[0]);
+    let midi = Midi::parse_bytes(&bytes, &options).unwrap();
+    let original = EventFile::from_smf_bytes(&bytes).unwrap();
+    assert_eq!(EventFile::from_smf_bytes(&midi.to_smf_bytes()).unwrap(), original);
+}
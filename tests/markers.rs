@@ -0,0 +1,34 @@
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::options::ParseOptions;
+use midly::Format;
+use midly::Header;
+use midly::MetaMessage;
+use midly::Smf;
+use midly::Timing;
+use midly::TrackEvent;
+use midly::TrackEventKind;
+
+fn meta(delta: u32, message: MetaMessage<'static>) -> TrackEvent<'static> {
+    TrackEvent { delta: delta.into(), kind: TrackEventKind::Meta(message) }
+}
+
+#[test]
+fn markers_1() {
+    let mut smf = Smf::new(Header::new(Format::Parallel, Timing::Metrical(96.into())));
+    smf.tracks.push(vec![
+        meta(0, MetaMessage::Marker(b"Verse")),
+        meta(384, MetaMessage::CuePoint(b"Chorus")),
+        meta(0, MetaMessage::EndOfTrack),
+    ]);
+    let mut bytes = Vec::new();
+    smf.write_std(&mut bytes).unwrap();
+
+    let midi = Midi::parse_bytes(&bytes, &ParseOptions::new()).unwrap();
+    let markers = midi.markers();
+    assert_eq!(2, markers.len());
+    assert_eq!("Verse", markers[0].text);
+    assert_eq!(0, markers[0].tick);
+    assert_eq!("Chorus", markers[1].text);
+    assert_eq!(384, markers[1].tick);
+    assert_eq!(4.0, markers[1].beat);
+}
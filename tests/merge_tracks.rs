@@ -0,0 +1,48 @@
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::merge::merge_tracks;
+use beatblox_midi::parsing::options::ParseOptions;
+use beatblox_midi::parsing::options::SimultaneityWindow;
+use midly::MidiMessage;
+use midly::TrackEvent;
+use midly::TrackEventKind;
+
+fn note_on(delta: u32, key: u8) -> TrackEvent<'static> {
+    TrackEvent {
+        delta: delta.into(),
+        kind: TrackEventKind::Midi {
+            channel: 0.into(),
+            message: MidiMessage::NoteOn { key: key.into(), vel: 100.into() },
+        },
+    }
+}
+
+fn onsets(track: &[TrackEvent]) -> Vec<u32> {
+    let mut cur_time = 0;
+    let mut onsets = Vec::new();
+    for event in track {
+        cur_time += event.delta.as_int();
+        if let TrackEventKind::Midi { message: MidiMessage::NoteOn { .. }, .. } = event.kind {
+            onsets.push(cur_time);
+        }
+    }
+    onsets
+}
+
+#[test]
+fn merge_tracks_1() {
+    let tracks = vec![vec![note_on(0, 60)], vec![note_on(3, 64)]];
+    assert_eq!(vec![0, 0], onsets(&merge_tracks(&tracks, 5)));
+    assert_eq!(vec![0, 3], onsets(&merge_tracks(&tracks, 2)));
+}
+
+#[test]
+fn merge_tracks_2() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let options = ParseOptions::new()
+        .merge_tracks(true)
+        .simultaneity_window(SimultaneityWindow::Milliseconds(10.0));
+    let midi = Midi::parse_with_options(dir, &options).unwrap();
+    let merged = midi.merged_track().unwrap();
+    assert_eq!("Merged", merged.name);
+    assert!(!merged.notes.is_empty());
+}
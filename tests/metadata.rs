@@ -0,0 +1,14 @@
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::metadata::SmfFormat;
+
+#[test]
+fn metadata_1() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let midi = Midi::parse(dir).unwrap();
+    let metadata = midi.metadata();
+    assert_eq!(SmfFormat::Parallel, metadata.format);
+    assert_eq!(3, metadata.track_count);
+    assert_eq!(Some(String::from("control track")), metadata.sequence_name);
+    assert_eq!(None, metadata.copyright);
+    assert_eq!(vec!["creator:", "GNU LilyPond 2.11.47"], metadata.text);
+}
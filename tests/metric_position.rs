@@ -0,0 +1,36 @@
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::eighth;
+use beatblox_midi::parsing::duration::half;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::measure::MetricPosition;
+
+#[test]
+fn metric_position_1() {
+    let midi = MidiBuilder::new()
+        .track("Lead")
+        .note(60, quarter().dotted(), 80)
+        .note(62, eighth(), 80)
+        .note(64, half(), 80)
+        .rest(quarter())
+        .note(65, half(), 80)
+        .build();
+    let track = &midi.tracks()[0];
+    let positions = track.metric_positions(midi.time_signatures(), midi.ticks_per_beat());
+    let expected = vec![
+        MetricPosition { measure: 1, beat: 1, subdivision: 0.0 },
+        MetricPosition { measure: 1, beat: 2, subdivision: 0.5 },
+        MetricPosition { measure: 1, beat: 3, subdivision: 0.0 },
+        MetricPosition { measure: 2, beat: 1, subdivision: 0.0 },
+        MetricPosition { measure: 2, beat: 2, subdivision: 0.0 },
+    ];
+    assert_eq!(expected, positions);
+}
+
+#[test]
+fn metric_position_2() {
+    // Beats follow the time signature, so 6/8 has six eighth note beats in each measure.
+    let midi = MidiBuilder::new().time_signature(6, 8).build();
+    let position = midi.metric_position(9.5);
+    assert_eq!(MetricPosition { measure: 2, beat: 4, subdivision: 0.5 }, position);
+    assert_eq!(MetricPosition { measure: 1, beat: 1, subdivision: 0.0 }, midi.metric_position(0.0));
+}
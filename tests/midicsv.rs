@@ -0,0 +1,92 @@
+use beatblox_midi::Midi;
+use beatblox_midi::error::ParseError;
+use beatblox_midi::parsing::events::EventFile;
+use beatblox_midi::parsing::events::EventKind;
+use beatblox_midi::parsing::events::TextKind;
+use beatblox_midi::parsing::options::ParseOptions;
+use std::fs;
+
+const SCALE: &str = "\
+0, 0, Header, 1, 1, 96
+1, 0, Start_track
+1, 0, Title_t, \"Say \"\"hi\"\", C:\\\\\"
+1, 0, Time_signature, 4, 2, 24, 8
+1, 0, Note_on_c, 0, 60, 100
+1, 96, Note_off_c, 0, 60, 0
+1, 96, Note_on_c, 0, 62, 100
+1, 192, Note_off_c, 0, 62, 0
+1, 192, End_track
+0, 0, End_of_file
+";
+
+#[test]
+fn midicsv_1() {
+    let file = EventFile::from_midicsv(SCALE).unwrap();
+    assert_eq!(96, file.ticks_per_beat);
+    assert_eq!(1, file.tracks.len());
+    assert_eq!(
+        EventKind::Text(TextKind::TrackName, b"Say \"hi\", C:\\".to_vec()),
+        file.tracks[0][0].kind
+    );
+    assert_eq!(96, file.tracks[0][3].tick);
+    assert_eq!(SCALE, file.to_midicsv());
+}
+
+#[test]
+fn midicsv_2() {
+    let bytes = fs::read("tests/test_files/test-1.mid").unwrap();
+    let file = EventFile::from_smf_bytes(&bytes).unwrap();
+    let reread = EventFile::from_midicsv(&file.to_midicsv()).unwrap();
+    assert_eq!(file, reread);
+
+    let options = ParseOptions::new();
+    let direct = Midi::parse_bytes(&bytes, &options).unwrap();
+    let from_csv = Midi::parse_events(&reread, &options).unwrap();
+    assert_eq!(direct.to_json(), from_csv.to_json());
+}
+
+#[test]
+fn midicsv_3() {
+    let midi = Midi::parse_midicsv(SCALE, &ParseOptions::new()).unwrap();
+    let names: Vec<String> = midi.tracks()[0].notes.iter()
+        .flat_map(|n| n.notes())
+        .map(|n| n.name())
+        .collect();
+    assert_eq!(vec!["C4", "D4"], names);
+}
+
+#[test]
+fn midicsv_4() {
+    let text = "0, 0, Header, 1, 1, 96\n1, 0, Note_on_c, 16, 60, 100\n";
+    match EventFile::from_midicsv(text) {
+        Err(ParseError::Csv { line, .. }) => assert_eq!(2, line),
+        _ => panic!("expected a csv error"),
+    }
+}
+
+#[test]
+fn midicsv_5() {
+    let texts = [
+        "1, 0, Start_track\n0, 0, Header, 1, 1, 96\n",
+        "0, 0, Header, 1, 1, 96\n4000000000, 0, Start_track\n",
+        "0, 0, Header, 1, 1, 96\n1, 0, System_exclusive, 4000000000, 1\n",
+    ];
+    for text in texts {
+        assert!(matches!(EventFile::from_midicsv(text), Err(ParseError::Csv { line: 1 | 2, .. })));
+    }
+}
+
+#[test]
+fn midicsv_6() {
+    // A Header can't claim more tracks than the file could hold.
+    let texts = [
+        "0, 0, Header, 1, 4000000000, 480\n4000000000, 0, Start_track\n",
+        "0, 0, Header, 1, 70000, 480\n",
+        "0, 0, Header, 1, 3, 480\n1, 0, Start_track\n",
+    ];
+    for text in texts {
+        assert!(matches!(EventFile::from_midicsv(text), Err(ParseError::Csv { line: 1, .. })));
+    }
+    let text = "0, 0, Header, 1, 1, 480\n1, 0, Start_track\n1, 0, End_track\n";
+    assert_eq!(1, EventFile::from_midicsv(text).unwrap().tracks.len());
+}
@@ -0,0 +1,50 @@
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::cleanup::ShortNotePolicy;
+use beatblox_midi::parsing::duration::sixteenth;
+use beatblox_midi::parsing::events::Event;
+use beatblox_midi::parsing::events::EventFile;
+use beatblox_midi::parsing::events::EventKind;
+use beatblox_midi::parsing::metadata::SmfFormat;
+use beatblox_midi::parsing::options::ParseOptions;
+
+fn note(start: u32, end: u32, key: u8) -> Vec<Event> {
+    vec![
+        Event { tick: start, kind: EventKind::NoteOn { channel: 0, key, velocity: 100 } },
+        Event { tick: end, kind: EventKind::NoteOff { channel: 0, key, velocity: 0 } },
+    ]
+}
+
+/// A quarter note C4 that bounces at its start, a quarter note D4, and a stray tap on E4.
+fn bouncy_file() -> EventFile {
+    let mut events = Vec::new();
+    events.extend(note(0, 8, 60));
+    events.extend(note(12, 480, 60));
+    events.extend(note(480, 960, 62));
+    events.extend(note(1440, 1446, 64));
+    events.sort_by_key(|e| e.tick);
+    events.push(Event { tick: 1920, kind: EventKind::EndOfTrack });
+    let conductor = vec![Event { tick: 0, kind: EventKind::EndOfTrack }];
+    EventFile { format: SmfFormat::Parallel, ticks_per_beat: 480, tracks: vec![conductor, events] }
+}
+
+fn values(midi: &Midi) -> Vec<Option<u8>> {
+    midi.tracks()[1].notes.iter().flat_map(|n| n.notes()).map(|n| n.value()).collect()
+}
+
+#[test]
+fn min_note_length_1() {
+    let options = ParseOptions::new().min_note_length(sixteenth(), ShortNotePolicy::Discard);
+    let midi = Midi::parse_events(&bouncy_file(), &options).unwrap();
+    assert_eq!(vec![Some(60), Some(62)], values(&midi));
+    let unfiltered = Midi::parse_events(&bouncy_file(), &ParseOptions::new()).unwrap();
+    assert!(values(&unfiltered).contains(&Some(64)));
+}
+
+#[test]
+fn min_note_length_2() {
+    let options = ParseOptions::new().min_note_length(sixteenth(), ShortNotePolicy::Merge);
+    let midi = Midi::parse_events(&bouncy_file(), &options).unwrap();
+    assert_eq!(vec![Some(60), Some(62)], values(&midi));
+    let first = midi.tracks()[1].notes[0].notes()[0].duration.get_beat_count(2);
+    assert_eq!(1.0, first);
+}
@@ -0,0 +1,18 @@
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::model::NoteDuration;
+use beatblox_midi::model::NoteWrapper;
+use beatblox_midi::model::Track;
+use beatblox_midi::parsing::duration::quarter;
+
+#[test]
+fn model_1() {
+    let midi = MidiBuilder::new()
+        .track("Lead")
+        .note(60, quarter(), 90)
+        .build();
+    let track: &Track = &midi.tracks()[0];
+    match &track.notes[0] {
+        NoteWrapper::PlainNote(note) => assert_eq!(NoteDuration::QUARTER, note.duration.duration),
+        _ => panic!("expected a plain note"),
+    }
+}
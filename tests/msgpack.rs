@@ -0,0 +1,41 @@
+#![cfg(feature = "msgpack")]
+
+use beatblox_midi::Midi;
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::error::ParseError;
+use beatblox_midi::parsing::duration::half;
+use beatblox_midi::parsing::duration::whole;
+
+#[test]
+fn msgpack_1() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let midi = Midi::parse(dir).unwrap();
+    let bytes = midi.to_msgpack();
+    let decoded = Midi::from_msgpack(&bytes).unwrap();
+    assert_eq!(midi.to_json(), decoded.to_json());
+    assert!(bytes.len() < midi.to_json().len());
+}
+
+#[test]
+fn msgpack_2() {
+    match Midi::from_msgpack(b"not a midi object") {
+        Err(ParseError::Msgpack(_)) => (),
+        _ => panic!("expected a msgpack error"),
+    }
+}
+
+#[test]
+fn msgpack_3() {
+    // A decoded piece without any time signatures is read in 4/4 time.
+    let midi = MidiBuilder::new().track("Lead").note(60, half(), 90).note(62, whole(), 90).build();
+    let mut bytes = midi.to_msgpack();
+    let time_signatures = [0x91, 0x93, 4, 2, 0];
+    let start = bytes.windows(5).position(|w| w == time_signatures).unwrap();
+    bytes.splice(start..start + 5, [0x90]);
+    let decoded = Midi::from_msgpack(&bytes).unwrap();
+    assert!(decoded.time_signatures().is_empty());
+    assert_eq!(2, decoded.beat_type());
+    assert_eq!(2, decoded.total_measures());
+    assert_eq!(midi.stats(), decoded.stats());
+    assert_eq!(midi.to_musicxml(), decoded.to_musicxml());
+}
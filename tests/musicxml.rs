@@ -0,0 +1,59 @@
+use beatblox_midi::Midi;
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::export::musicxml::DIVISIONS;
+use beatblox_midi::parsing::duration::eighth;
+use beatblox_midi::parsing::duration::half;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::duration::whole;
+
+#[test]
+fn musicxml_1() {
+    let midi = MidiBuilder::new()
+        .bpm(90)
+        .time_signature(3, 4)
+        .key_signature(-1, false)
+        .track("Lead & Harmony")
+        .note(70, quarter().dotted(), 90)
+        .note(62, eighth(), 90)
+        .chord(&[60, 64, 67], quarter(), 80)
+        .rest(half().dotted())
+        .build();
+    let xml = midi.to_musicxml();
+    assert!(xml.starts_with("<?xml"));
+    assert!(xml.contains("<part-name>Lead &amp; Harmony</part-name>"));
+    assert!(xml.contains(&format!("<divisions>{}</divisions>", DIVISIONS)));
+    assert!(xml.contains("<key><fifths>-1</fifths><mode>major</mode></key>"));
+    assert!(xml.contains("<time><beats>3</beats><beat-type>4</beat-type></time>"));
+    assert!(xml.contains("<sound tempo=\"90\"/>"));
+    // B flat is spelled with an alter in F major.
+    assert!(xml.contains("<pitch><step>B</step><alter>-1</alter><octave>4</octave></pitch>"));
+    assert!(xml.contains(&format!("<duration>{}</duration>", DIVISIONS * 3 / 2)));
+    assert!(xml.contains("<dot/>"));
+    assert_eq!(2, xml.matches("<chord/>").count());
+    assert_eq!(2, xml.matches("<measure number=").count());
+    assert!(xml.trim_end().ends_with("</score-partwise>"));
+}
+
+#[test]
+fn musicxml_2() {
+    let midi = MidiBuilder::new()
+        .track("Piano")
+        .chord(&[36, 72], whole(), 80)
+        .track("Drums")
+        .percussion()
+        .note(38, half(), 100)
+        .build();
+    let xml = midi.to_musicxml();
+    assert_eq!(2, xml.matches("<score-part id=").count());
+    // The wide piano part is written on a grand staff, with the bass staff after a backup.
+    assert!(xml.contains("<staves>2</staves>"));
+    assert!(xml.contains("<clef number=\"2\"><sign>F</sign><line>4</line></clef>"));
+    assert!(xml.contains(&format!("<backup><duration>{}</duration></backup>", DIVISIONS * 4)));
+    assert!(xml.contains("<staff>2</staff>"));
+    assert!(xml.contains("<sign>percussion</sign>"));
+    assert!(xml.contains("<unpitched>"));
+    let parsed = Midi::parse(String::from("tests/test_files/test-1.mid")).unwrap();
+    let xml = parsed.to_musicxml();
+    assert_eq!(xml.matches("<note>").count(), xml.matches("</note>").count());
+    assert_eq!(xml.matches("<measure ").count(), xml.matches("</measure>").count());
+}
@@ -0,0 +1,41 @@
+use beatblox_midi::Midi;
+use beatblox_midi::export::netsblox::ListValue;
+
+fn as_list(value: &ListValue) -> &[ListValue] {
+    match value {
+        ListValue::List(v) => v,
+        _ => panic!("expected a list"),
+    }
+}
+
+fn entry_beats(entry: &ListValue) -> f32 {
+    match as_list(entry)[1] {
+        ListValue::Number(n) => n,
+        _ => panic!("expected a number"),
+    }
+}
+
+#[test]
+fn netsblox_lists_1() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let midi = Midi::parse(dir).unwrap();
+    let lists = midi.to_netsblox_lists();
+    let tracks = as_list(&lists);
+    assert_eq!(midi.tracks().len(), tracks.len());
+    for measure in as_list(&tracks[1]) {
+        for entry in as_list(measure) {
+            assert_eq!(3, as_list(entry).len());
+        }
+    }
+}
+
+#[test]
+fn netsblox_lists_2() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let midi = Midi::parse(dir).unwrap();
+    let lists = midi.to_netsblox_lists();
+    let measures = as_list(&as_list(&lists)[1]);
+    let beats: f32 = measures.iter().flat_map(as_list).map(entry_beats).sum();
+    assert!((midi.tracks()[1].length - beats).abs() < 1e-3);
+    assert_eq!(2.0, as_list(&measures[0]).iter().map(entry_beats).sum::<f32>());
+}
@@ -0,0 +1,53 @@
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::eighth;
+use beatblox_midi::parsing::duration::half;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::duration::sixteenth;
+use beatblox_midi::parsing::symbols::NoteWrapper;
+
+#[test]
+fn normalize_rests_1() {
+    let mut midi = MidiBuilder::new()
+        .track("Lead")
+        .note(60, quarter(), 90)
+        .rest(sixteenth())
+        .rest(sixteenth())
+        .rest(eighth())
+        .rest(half())
+        .note(62, quarter(), 90)
+        .rest(eighth())
+        .rest(eighth())
+        .rest(half())
+        .build();
+    midi.normalize_rests(None);
+    let track = &midi.tracks()[0];
+    let lengths: Vec<f32> = track.notes.iter().map(|n| n.beat_count(2)).collect();
+    assert_eq!(vec![1.0, 3.0, 1.0, 3.0], lengths);
+    assert!(matches!(track.notes[1], NoteWrapper::Rest(_)));
+    assert!(matches!(track.notes[3], NoteWrapper::Rest(_)));
+    assert_eq!(8.0, track.length);
+}
+
+#[test]
+fn normalize_rests_2() {
+    let mut midi = MidiBuilder::new()
+        .track("Lead")
+        .note(60, quarter(), 90)
+        .rest(sixteenth())
+        .note(62, half(), 90)
+        .rest(eighth())
+        .rest(eighth())
+        .rest(quarter())
+        .note(64, eighth(), 90)
+        .build();
+    midi.normalize_rests(Some(eighth()));
+    let track = &midi.tracks()[0];
+    let values: Vec<_> = track.notes.iter().flat_map(|n| n.notes()).map(|n| n.value()).collect();
+    // The rests after the half note are split at the bar line on beat 4.
+    let expected = vec![Some(60), Some(60), Some(62), None, None, None, Some(64)];
+    assert_eq!(expected, values);
+    assert_eq!(0.75, track.notes[2].beat_count(2));
+    let total: f32 = track.notes.iter().map(|n| n.beat_count(2)).sum();
+    assert_eq!(1.25 + 2.0 + 2.0 + 0.5, total);
+    assert_eq!(1.25, track.notes[0].beat_count(2));
+}
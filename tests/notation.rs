@@ -0,0 +1,38 @@
+use beatblox_midi::error::ParseError;
+use beatblox_midi::parsing::Track;
+use beatblox_midi::parsing::TrackKind;
+use beatblox_midi::parsing::symbols::NoteModifier;
+use beatblox_midi::parsing::symbols::NoteWrapper;
+
+fn names(track: &Track) -> Vec<String> {
+    track.notes.iter().flat_map(|n| n.notes()).map(|n| n.name()).collect()
+}
+
+#[test]
+fn notation_1() {
+    let track = Track::from_notation(String::from("Melody"), "C4:q D4:e E4:e | F4:h r:q").unwrap();
+    assert_eq!(vec!["C4", "D4", "E4", "F4", "Rest"], names(&track));
+    assert_eq!(5.0, track.length);
+    assert_eq!(TrackKind::Notes, track.kind);
+}
+
+#[test]
+fn notation_2() {
+    let track = Track::from_notation(String::from("Chords"), "Db4+F4+Ab4:h. C4:h~e:60").unwrap();
+    assert_eq!(vec!["Db4", "F4", "Ab4", "C4", "C4"], names(&track));
+    assert!(matches!(track.notes[0], NoteWrapper::ModifiedNote(NoteModifier::Chord(_))));
+    assert!(matches!(track.notes[1], NoteWrapper::ModifiedNote(NoteModifier::TiedNote(_))));
+    assert_eq!(3.0, track.notes[0].beat_count(2));
+    assert_eq!(2.5, track.notes[1].beat_count(2));
+    assert_eq!(60, track.notes[1].notes()[0].velocity);
+}
+
+#[test]
+fn notation_3() {
+    match Track::from_notation(String::from("Bad"), "C4:q H4:q") {
+        Err(ParseError::Notation { token, .. }) => assert_eq!("H4:q", token),
+        _ => panic!("expected a notation error"),
+    }
+    assert!(Track::from_notation(String::from("Bad"), "C4:x").is_err());
+    assert!(Track::from_notation(String::from("Bad"), "G#9:q").is_err());
+}
@@ -0,0 +1,34 @@
+use beatblox_midi::parsing::duration::DEFAULT_DURATION_PRECISION;
+use beatblox_midi::parsing::pitch::Pitch;
+use beatblox_midi::parsing::symbols::KeySignature;
+use beatblox_midi::parsing::symbols::Note;
+
+fn note(value: u8) -> Note {
+    Note::new(value, DEFAULT_DURATION_PRECISION, 100)
+}
+
+#[test]
+fn note_name_1() {
+    assert_eq!("C4", note(60).name());
+    assert_eq!("F#3", note(54).name());
+    assert_eq!("C-1", note(0).name());
+    assert_eq!("Rest", Note::rest(DEFAULT_DURATION_PRECISION).name());
+}
+
+#[test]
+fn note_name_2() {
+    let note = note(60);
+    assert_eq!(Some(0), note.pitch_class());
+    assert_eq!(Some(4), note.octave());
+    assert_eq!(Some(3), note.octave_with_middle_c(3));
+    assert_eq!("C5", note.name_with_middle_c(5));
+}
+
+#[test]
+fn note_name_3() {
+    let mut note = note(54);
+    let key = KeySignature { sharps: -5, minor: false, time_of_occurance: 0 };
+    note.pitch = Some(Pitch::spell(54, Some(key)));
+    assert_eq!("Gb3", note.name());
+    assert_eq!(Some(6), note.pitch_class());
+}
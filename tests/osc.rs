@@ -0,0 +1,63 @@
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::export::osc;
+use beatblox_midi::export::osc::OscNote;
+use beatblox_midi::parsing::duration::half;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::symbols::NoteModifier;
+use beatblox_midi::parsing::symbols::NoteWrapper;
+use std::net::UdpSocket;
+
+#[test]
+fn osc_1() {
+    let mut midi = MidiBuilder::new()
+        .track("Lead")
+        .note(60, quarter(), 90)
+        .chord(&[64, 67], quarter(), 80)
+        .rest(quarter())
+        .build();
+    let tie = NoteWrapper::ModifiedNote(NoteModifier::TiedNote(vec![
+        NoteWrapper::build_note_wrapper(Some(62), quarter(), 70),
+        NoteWrapper::build_note_wrapper(Some(62), half(), 70),
+    ]));
+    midi.tracks_mut()[0].notes.push(tie);
+    let notes = osc::get_osc_notes(&midi);
+    assert_eq!(notes.len(), 4);
+    assert_eq!(notes[0], OscNote { track: 0, pitch: 60, velocity: 90, duration: 1.0, beat: 0.0 });
+    assert_eq!((notes[1].beat, notes[2].beat), (1.0, 1.0));
+    assert_eq!(notes[3], OscNote { track: 0, pitch: 62, velocity: 70, duration: 3.0, beat: 3.0 });
+}
+
+#[test]
+fn osc_2() {
+    let note = OscNote { track: 0, pitch: 60, velocity: 100, duration: 1.5, beat: 2.0 };
+    let message = osc::note_message(&note);
+    assert_eq!(&message[..8], b"/note\0\0\0");
+    assert_eq!(&message[8..16], b",iiff\0\0\0");
+    assert_eq!(&message[16..20], &60i32.to_be_bytes());
+    assert_eq!(&message[24..28], &1.5f32.to_be_bytes());
+    assert_eq!(message.len(), 32);
+    let midi = MidiBuilder::new().track("Lead").note(60, quarter(), 90).build();
+    let bundle = midi.to_osc_bundle();
+    assert_eq!(&bundle[..8], b"#bundle\0");
+    assert_eq!(&bundle[16..20], &32i32.to_be_bytes());
+    assert_eq!(bundle.len(), 52);
+}
+
+#[test]
+fn osc_3() {
+    let midi = MidiBuilder::new()
+        .bpm(6000)
+        .track("Lead")
+        .note(60, quarter(), 90)
+        .note(62, quarter(), 90)
+        .build();
+    let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+    osc::stream_osc(&midi, &sender, receiver.local_addr().unwrap()).unwrap();
+    let mut buffer = [0; 64];
+    for pitch in [60i32, 62] {
+        let size = receiver.recv(&mut buffer).unwrap();
+        assert_eq!(size, 32);
+        assert_eq!(&buffer[16..20], &pitch.to_be_bytes());
+    }
+}
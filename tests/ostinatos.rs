@@ -0,0 +1,42 @@
+use beatblox_midi::analysis::OstinatoOptions;
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::eighth;
+use beatblox_midi::parsing::duration::half;
+use beatblox_midi::parsing::duration::quarter;
+
+#[test]
+fn ostinatos_1() {
+    let mut builder = MidiBuilder::new().track("Bass").note(48, half(), 90);
+    for i in 0..6 {
+        // Velocities don't have to match.
+        builder = builder
+            .note(40, eighth(), 100 - i)
+            .note(40, eighth(), 80)
+            .note(43, quarter(), 80);
+    }
+    let midi = builder.note(45, half(), 90).build();
+    let ostinatos = midi.tracks()[0].ostinatos(midi.beat_type(), &OstinatoOptions::new());
+    assert_eq!(ostinatos.len(), 1);
+    let ostinato = &ostinatos[0];
+    assert_eq!((ostinato.position, ostinato.cell_length, ostinato.repeats), (1, 3, 6));
+    assert_eq!((ostinato.beat, ostinato.cell_beats), (2.0, 2.0));
+    let strict = OstinatoOptions::new().min_repeats(7);
+    assert!(midi.tracks()[0].ostinatos(midi.beat_type(), &strict).is_empty());
+}
+
+#[test]
+fn ostinatos_2() {
+    // A repeated note is read as the shortest cell, and runs of rests aren't ostinatos.
+    let mut builder = MidiBuilder::new().track("Drums");
+    for _ in 0..8 {
+        builder = builder.note(42, eighth(), 90);
+    }
+    for _ in 0..8 {
+        builder = builder.rest(quarter());
+    }
+    let midi = builder.build();
+    let options = OstinatoOptions::new().max_cell_length(4);
+    let ostinatos = midi.tracks()[0].ostinatos(midi.beat_type(), &options);
+    assert_eq!(ostinatos.len(), 1);
+    assert_eq!((ostinatos[0].cell_length, ostinatos[0].repeats), (1, 8));
+}
@@ -0,0 +1,63 @@
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::events::Event;
+use beatblox_midi::parsing::events::EventFile;
+use beatblox_midi::parsing::events::EventKind;
+use beatblox_midi::parsing::metadata::SmfFormat;
+use beatblox_midi::parsing::options::OverlapPolicy;
+use beatblox_midi::parsing::options::ParseOptions;
+
+fn on(tick: u32, key: u8) -> Event {
+    Event { tick, kind: EventKind::NoteOn { channel: 0, key, velocity: 100 } }
+}
+
+fn off(tick: u32, key: u8) -> Event {
+    Event { tick, kind: EventKind::NoteOff { channel: 0, key, velocity: 0 } }
+}
+
+fn file(mut events: Vec<Event>) -> EventFile {
+    events.push(Event { tick: 1920, kind: EventKind::EndOfTrack });
+    let conductor = vec![Event { tick: 0, kind: EventKind::EndOfTrack }];
+    EventFile { format: SmfFormat::Parallel, ticks_per_beat: 480, tracks: vec![conductor, events] }
+}
+
+/// Returns the value and length in beats of each note in the first note track.
+fn notes(events: Vec<Event>, policy: OverlapPolicy) -> Vec<(Option<u8>, f32)> {
+    let options = ParseOptions::new().overlap_policy(policy).pad_to_score_length(true);
+    let midi = Midi::parse_events(&file(events), &options).unwrap();
+    midi.tracks()[1].notes
+        .iter()
+        .map(|n| (n.notes()[0].value(), n.beat_count(2)))
+        .collect()
+}
+
+#[test]
+fn overlap_1() {
+    // Note ons with a velocity of 0 release the note.
+    let released = vec![
+        on(0, 60),
+        Event { tick: 480, kind: EventKind::NoteOn { channel: 0, key: 60, velocity: 0 } },
+        on(480, 62),
+        Event { tick: 960, kind: EventKind::NoteOn { channel: 0, key: 62, velocity: 0 } },
+    ];
+    let expected = vec![(Some(60), 1.0), (Some(62), 1.0), (None, 2.0)];
+    assert_eq!(expected, notes(released, OverlapPolicy::TruncatePrevious));
+}
+
+#[test]
+fn overlap_2() {
+    // A note that starts before the previous note is released doesn't take its onset.
+    let events = vec![on(0, 60), on(480, 62), off(720, 60), off(960, 62)];
+    let expected = vec![(Some(60), 1.0), (Some(62), 1.0), (None, 2.0)];
+    assert_eq!(expected, notes(events, OverlapPolicy::TruncatePrevious));
+}
+
+#[test]
+fn overlap_3() {
+    let events = vec![on(0, 60), on(480, 60), off(960, 60), off(1440, 60)];
+    let truncated = vec![(Some(60), 1.0), (Some(60), 1.0), (None, 2.0)];
+    assert_eq!(truncated, notes(events.clone(), OverlapPolicy::TruncatePrevious));
+    let merged = vec![(Some(60), 3.0), (None, 1.0)];
+    assert_eq!(merged, notes(events.clone(), OverlapPolicy::Merge));
+    let restruck = vec![(Some(60), 1.0), (Some(60), 2.0), (None, 1.0)];
+    assert_eq!(restruck, notes(events, OverlapPolicy::Restrike));
+}
@@ -0,0 +1,51 @@
+use beatblox_midi::Midi;
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::events::EventKind;
+
+fn drums() -> Midi {
+    MidiBuilder::new()
+        .track("Drums")
+        .percussion()
+        .note(36, quarter(), 100)
+        .note(38, quarter(), 100)
+        .build()
+}
+
+#[test]
+fn overlay_1() {
+    let mut midi = MidiBuilder::new()
+        .track("Drums")
+        .note(60, quarter(), 90)
+        .build();
+    midi.overlay(&drums(), 2.0);
+    let tracks = midi.tracks();
+    assert_eq!(2, tracks.len());
+    assert_eq!("Drums (2)", tracks[1].name);
+    assert!(tracks[1].percussion);
+    assert_eq!(4.0, tracks[1].length);
+    assert_eq!(1, tracks[1].index);
+    let onsets: Vec<u32> = midi.to_events().tracks[2]
+        .iter()
+        .filter_map(|e| match e.kind {
+            EventKind::NoteOn { .. } => Some(e.tick),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(vec![960, 1440], onsets);
+}
+
+#[test]
+fn overlay_2() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let mut midi = Midi::parse(dir).unwrap();
+    let count = midi.tracks().len();
+    midi.overlay(&drums(), 0.0);
+    midi.overlay(&drums(), 1.0);
+    let tracks = midi.tracks();
+    assert_eq!(count + 2, tracks.len());
+    assert_eq!("Drums", tracks[count].name);
+    assert_eq!("Drums (2)", tracks[count + 1].name);
+    let ticks = midi.ticks_per_beat() * 2.0;
+    assert_eq!(ticks.round() as u32, tracks[count].end_of_track);
+}
@@ -0,0 +1,67 @@
+use beatblox_midi::Midi;
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::duration::whole;
+use beatblox_midi::parsing::events::Event;
+use beatblox_midi::parsing::events::EventFile;
+use beatblox_midi::parsing::events::EventKind;
+use beatblox_midi::parsing::metadata::SmfFormat;
+use beatblox_midi::parsing::options::ParseOptions;
+
+#[test]
+fn pad_final_measure_1() {
+    let mut midi = MidiBuilder::new()
+        .track("Lead")
+        .note(60, whole(), 80)
+        .note(62, quarter(), 80)
+        .track("Bass")
+        .note(36, whole(), 80)
+        .note(38, whole(), 80)
+        .build();
+    assert_eq!(1, midi.pad_final_measure());
+    let lead = &midi.tracks()[0];
+    assert_eq!(8.0, lead.length);
+    assert_eq!(Some(5.0), lead.unpadded_length);
+    assert_eq!(3, lead.notes.len());
+    assert!(lead.notes[2].notes()[0].is_rest());
+    assert_eq!(3.0, lead.notes[2].beat_count(2));
+    assert_eq!(None, midi.tracks()[1].unpadded_length);
+    assert_eq!(0, midi.pad_final_measure());
+}
+
+#[test]
+fn pad_final_measure_2() {
+    let on = |tick, key| Event { tick, kind: EventKind::NoteOn { channel: 0, key, velocity: 100 } };
+    let off = |tick, key| Event { tick, kind: EventKind::NoteOff { channel: 0, key, velocity: 0 } };
+    let events = vec![on(0, 60), off(2880, 60), Event { tick: 2880, kind: EventKind::EndOfTrack }];
+    let conductor = vec![Event { tick: 0, kind: EventKind::EndOfTrack }];
+    let file = EventFile {
+        format: SmfFormat::Parallel,
+        ticks_per_beat: 480,
+        tracks: vec![conductor, events],
+    };
+    let options = ParseOptions::new().pad_final_measure(true);
+    let midi = Midi::parse_events(&file, &options).unwrap();
+    let track = &midi.tracks()[1];
+    assert_eq!(8.0, track.length);
+    assert_eq!(Some(6.0), track.unpadded_length);
+    assert_eq!(8.0, track.notes.iter().map(|n| n.beat_count(2)).sum::<f32>());
+    let unpadded = Midi::parse_events(&file, &ParseOptions::new()).unwrap();
+    assert_eq!(6.0, unpadded.tracks()[1].length);
+}
+
+#[test]
+fn pad_final_measure_3() {
+    // These files end a tick after their second bar line, which is still on the bar line.
+    for file in ["test-3", "test-4", "test-5"] {
+        let dir = format!("tests/test_files/{}.mid", file);
+        let midi = Midi::parse(dir.clone()).unwrap();
+        let options = ParseOptions::new().pad_final_measure(true);
+        let padded = Midi::parse_with_options(dir, &options).unwrap();
+        for (track, padded) in midi.tracks().iter().zip(padded.tracks()) {
+            assert_eq!(track.length, padded.length);
+            assert_eq!(track.notes.len(), padded.notes.len());
+            assert_eq!(None, padded.unpadded_length);
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::TrackKind;
+use beatblox_midi::parsing::options::ParseOptions;
+
+fn track_beats(midi: &Midi, index: usize) -> f32 {
+    midi.tracks()[index].notes.iter().map(|n| n.beat_count(2)).sum()
+}
+
+#[test]
+fn pad_tracks_1() {
+    let dir = String::from("tests/test_files/test-2.mid");
+    let options = ParseOptions::new().pad_to_score_length(true);
+    let midi = Midi::parse_with_options(dir, &options).unwrap();
+    assert_eq!(TrackKind::Notes, midi.tracks()[1].kind);
+    assert_eq!(track_beats(&midi, 1), track_beats(&midi, 2));
+    let end_of_track = midi.tracks().iter().map(|t| t.end_of_track).max().unwrap();
+    assert_eq!(end_of_track as f32 / 384.0, track_beats(&midi, 1));
+}
+
+#[test]
+fn pad_tracks_2() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let midi = Midi::parse(dir).unwrap();
+    assert!(midi.truncated_tracks().is_empty());
+}
+
+#[test]
+fn pad_tracks_3() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let options = ParseOptions::new().pad_to_score_length(false);
+    let midi = Midi::parse_with_options(dir, &options).unwrap();
+    assert!(track_beats(&midi, 1) > 0.0);
+    // Tracks are only padded when asked to.
+    let unpadded = Midi::parse(String::from("tests/test_files/test-1.mid")).unwrap();
+    assert_eq!(track_beats(&midi, 1), track_beats(&unpadded, 1));
+}
@@ -0,0 +1,19 @@
+use beatblox_midi::Midi;
+use beatblox_midi::error::ParseError;
+use std::error::Error;
+
+#[test]
+fn parse_error_1() {
+    let dir = String::from("tests/test_files/does-not-exist.mid");
+    let error = Midi::parse(dir).err().unwrap();
+    assert!(matches!(error, ParseError::Io(_)));
+    assert!(error.source().is_some());
+}
+
+#[test]
+fn parse_error_2() {
+    let dir = String::from("Cargo.toml");
+    let error = Midi::parse(dir).err().unwrap();
+    assert!(matches!(error, ParseError::Midly(_)));
+    assert!(error.to_string().starts_with("file is not a valid standard midi file"));
+}
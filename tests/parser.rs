@@ -0,0 +1,26 @@
+use beatblox_midi::Midi;
+use beatblox_midi::parser::Parser;
+use beatblox_midi::parsing::options::ParseOptions;
+
+#[test]
+fn parser_1() {
+    let mut parser = Parser::new(ParseOptions::new());
+    for i in 1..=5 {
+        let dir = format!("tests/test_files/test-{}.mid", i);
+        let pooled = parser.parse(dir.clone()).unwrap();
+        let fresh = Midi::parse(dir).unwrap();
+        assert_eq!(fresh.tracks().len(), pooled.tracks().len());
+        for (a, b) in fresh.tracks().iter().zip(pooled.tracks()) {
+            assert_eq!(a.notes.len(), b.notes.len());
+        }
+    }
+}
+
+#[test]
+fn parser_2() {
+    let mut parser = Parser::new(ParseOptions::new().triplet(true));
+    let bytes = std::fs::read("tests/test_files/test-5.mid").unwrap();
+    let first = parser.parse_bytes(&bytes).unwrap();
+    let second = parser.parse_bytes(&bytes).unwrap();
+    assert_eq!(first.tracks()[1].notes.len(), second.tracks()[1].notes.len());
+}
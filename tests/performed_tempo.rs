@@ -0,0 +1,57 @@
+use beatblox_midi::Midi;
+use beatblox_midi::error::ParseError;
+use beatblox_midi::parsing::events::Event;
+use beatblox_midi::parsing::events::EventFile;
+use beatblox_midi::parsing::events::EventKind;
+use beatblox_midi::parsing::metadata::SmfFormat;
+use beatblox_midi::parsing::options::ParseOptions;
+
+/// Quarter notes at 120 bpm whose gaps shrink by `factor` after each note.
+fn performed_file(factor: f32) -> EventFile {
+    let mut events = vec![Event { tick: 0, kind: EventKind::Tempo(500_000) }];
+    let (mut tick, mut gap) = (0.0, 480.0);
+    for i in 0..24 {
+        let on = tick as u32;
+        events.push(Event {
+            tick: on,
+            kind: EventKind::NoteOn { channel: 0, key: 60 + i % 5, velocity: 90 },
+        });
+        events.push(Event {
+            tick: on + 100,
+            kind: EventKind::NoteOff { channel: 0, key: 60 + i % 5, velocity: 0 },
+        });
+        tick += gap;
+        gap *= factor;
+    }
+    events.sort_by_key(|e| e.tick);
+    let end = events.last().unwrap().tick;
+    events.push(Event { tick: end, kind: EventKind::EndOfTrack });
+    EventFile { format: SmfFormat::SingleTrack, ticks_per_beat: 480, tracks: vec![events] }
+}
+
+#[test]
+fn performed_tempo_1() {
+    let options = ParseOptions::new().retain_timing(true);
+    let midi = Midi::parse_events(&performed_file(1.0), &options).unwrap();
+    let curve = midi.performed_tempo_curve().unwrap();
+    assert_eq!(23, curve.len());
+    assert!((curve[0].0 - 0.5).abs() < 0.01);
+    assert!(curve.iter().all(|(_, bpm)| (bpm - 120.0).abs() < 1.0));
+}
+
+#[test]
+fn performed_tempo_2() {
+    let options = ParseOptions::new().retain_timing(true);
+    let midi = Midi::parse_events(&performed_file(0.97), &options).unwrap();
+    let curve = midi.performed_tempo_curve().unwrap();
+    let first = curve[2].1;
+    let last = curve[curve.len() - 3].1;
+    assert!(last > first * 1.3);
+    assert!(curve.windows(2).all(|w| w[1].0 > w[0].0));
+}
+
+#[test]
+fn performed_tempo_3() {
+    let midi = Midi::parse_events(&performed_file(1.0), &ParseOptions::new()).unwrap();
+    assert!(matches!(midi.performed_tempo_curve(), Err(ParseError::TimingNotRetained)));
+}
@@ -0,0 +1,37 @@
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::pitch::Accidental;
+use beatblox_midi::parsing::pitch::Letter;
+use beatblox_midi::parsing::pitch::Pitch;
+use beatblox_midi::parsing::pitch::SpelledPitch;
+use beatblox_midi::parsing::symbols::KeySignature;
+
+#[test]
+fn pitch_1() {
+    let pitch = Pitch::from(61);
+    assert_eq!(61, u8::from(pitch));
+    assert_eq!(None, pitch.spelling);
+    assert_eq!("C#4", pitch.spelled().name());
+
+    let key = KeySignature { sharps: -2, minor: false, time_of_occurance: 0 };
+    let pitch = Pitch::spell(61, Some(key));
+    assert_eq!("Db4", SpelledPitch::from(pitch).name());
+    let spelling = SpelledPitch { letter: Letter::C, accidental: Accidental::Flat, octave: 5 };
+    assert_eq!(Pitch { value: 71, spelling: Some(spelling) }, Pitch::from(spelling));
+}
+
+#[test]
+fn pitch_2() {
+    let mut midi = MidiBuilder::new()
+        .track("Lead")
+        .note(61, quarter(), 90)
+        .build();
+    let track = &mut midi.tracks_mut()[0];
+    let key = KeySignature { sharps: -2, minor: false, time_of_occurance: 0 };
+    track.notes[0].notes_mut()[0].pitch = Some(Pitch::spell(61, Some(key)));
+    // Transposing a spelled note keeps it spelled, with sharps.
+    track.transpose(2);
+    let pitch = track.notes[0].notes()[0].pitch.unwrap();
+    assert_eq!(63, pitch.value);
+    assert_eq!("D#4", pitch.spelling.unwrap().name());
+}
@@ -0,0 +1,43 @@
+use beatblox_midi::parsing::duration::DurationType;
+use beatblox_midi::parsing::expression::PitchBendMode;
+use beatblox_midi::parsing::expression::attach_pitch_bends;
+use beatblox_midi::parsing::expression::get_pitch_bends;
+use beatblox_midi::parsing::symbols::NoteWrapper;
+use midly::MidiMessage;
+use midly::PitchBend;
+use midly::TrackEvent;
+use midly::TrackEventKind;
+
+fn midi(delta: u32, message: MidiMessage) -> TrackEvent<'static> {
+    TrackEvent { delta: delta.into(), kind: TrackEventKind::Midi { channel: 0.into(), message } }
+}
+
+fn quarter(value: u8) -> NoteWrapper {
+    NoteWrapper::build_note_wrapper(Some(value), DurationType::beat_type_map(1.0, 2), 100)
+}
+
+#[test]
+fn pitch_bend_1() {
+    let track = vec![
+        midi(0, MidiMessage::PitchBend { bend: PitchBend::from_f32(0.5) }),
+        midi(0, MidiMessage::Controller { controller: 101.into(), value: 0.into() }),
+        midi(0, MidiMessage::Controller { controller: 100.into(), value: 0.into() }),
+        midi(0, MidiMessage::Controller { controller: 6.into(), value: 12.into() }),
+        midi(10, MidiMessage::PitchBend { bend: PitchBend::from_f32(-0.5) }),
+    ];
+    let bends = get_pitch_bends(&track);
+    assert_eq!(vec![(0, 100.0), (10, -600.0)], bends);
+}
+
+#[test]
+fn pitch_bend_2() {
+    let bends = vec![(0, 50.0), (48, 100.0), (96, 0.0)];
+    let mut notes = vec![quarter(60), quarter(62)];
+    attach_pitch_bends(&mut notes, &bends, PitchBendMode::Curve, 2, 96.0);
+    assert_eq!(vec![(0.0, 50.0), (0.5, 100.0)], notes[0].notes()[0].bend);
+    assert!(notes[1].notes()[0].bend.is_empty());
+
+    let mut notes = vec![quarter(60)];
+    attach_pitch_bends(&mut notes, &bends, PitchBendMode::Detune, 2, 96.0);
+    assert_eq!(Some(75.0), notes[0].notes()[0].detune);
+}
@@ -0,0 +1,61 @@
+use beatblox_midi::Midi;
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::half;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::duration::thirty_second;
+use beatblox_midi::playability::BeatbloxConstraints;
+use beatblox_midi::playability::Simplification;
+
+#[test]
+fn playability_1() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let midi = Midi::parse(dir).unwrap();
+    let constraints = BeatbloxConstraints {
+        dynamics_levels: vec![64, 100],
+        ..Default::default()
+    };
+    let report = midi.playability(&constraints);
+    assert!(!report.is_playable());
+    assert!(report.simplifications.iter().all(|s| matches!(
+        s,
+        Simplification::SnapVelocity { from: 127, to: 100, .. }
+    )));
+}
+
+#[test]
+fn playability_2() {
+    let dir = String::from("tests/test_files/test-2.mid");
+    let mut midi = Midi::parse(dir).unwrap();
+    let constraints = BeatbloxConstraints::default();
+    let before: f32 = midi.tracks()[1].notes.iter().map(|n| n.beat_count(2)).sum();
+    midi.make_playable(&constraints);
+    assert!(midi.playability(&constraints).is_playable());
+    let after: f32 = midi.tracks()[1].notes.iter().map(|n| n.beat_count(2)).sum();
+    assert_eq!(before, after);
+}
+
+#[test]
+fn playability_3() {
+    let mut midi = MidiBuilder::new()
+        .track("Lead")
+        .rest(thirty_second())
+        .note(60, quarter(), 100)
+        .note(62, thirty_second(), 100)
+        .note(64, half(), 100)
+        .build();
+    let constraints = BeatbloxConstraints::default();
+    let before: f32 = midi.tracks()[0].notes.iter().map(|n| n.beat_count(2)).sum();
+    midi.make_playable(&constraints);
+    assert!(midi.playability(&constraints).is_playable());
+    let track = &midi.tracks()[0];
+    let after: f32 = track.notes.iter().map(|n| n.beat_count(2)).sum();
+    assert_eq!(before, after);
+    let values: Vec<Option<u8>> = track.notes
+        .iter()
+        .flat_map(|n| n.notes())
+        .map(|n| n.value())
+        .collect();
+    assert!(values.contains(&Some(60)));
+    assert!(values.contains(&Some(64)));
+    assert!(!values.contains(&None));
+}
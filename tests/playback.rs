@@ -0,0 +1,45 @@
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::half;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::playback;
+
+#[test]
+fn playback_1() {
+    let midi = MidiBuilder::new()
+        .bpm(120)
+        .track("Lead")
+        .program(0)
+        .note(60, quarter(), 90)
+        .note(60, quarter(), 90)
+        .chord(&[64, 67], half(), 80)
+        .build();
+    let messages = playback::schedule(&midi);
+    let notes: Vec<(f64, Vec<u8>)> = messages
+        .iter()
+        .filter(|m| m.bytes[0] & 0xF0 == 0x90 || m.bytes[0] & 0xF0 == 0x80)
+        .map(|m| (m.time, m.bytes.clone()))
+        .collect();
+    // At 120 bpm each quarter note lasts half a second, and the repeated note is released
+    // before it's struck again.
+    assert_eq!(notes[0], (0.0, vec![0x90, 60, 90]));
+    assert_eq!(notes[1].0, 0.5);
+    assert_eq!(notes[1].1[0] & 0xF0, 0x80);
+    assert_eq!(notes[2], (0.5, vec![0x90, 60, 90]));
+    let chord = notes.iter().filter(|(time, bytes)| *time == 1.0 && bytes[0] == 0x90);
+    assert_eq!(chord.count(), 2);
+    assert_eq!(notes.last().unwrap().0, 2.0);
+    assert!(messages.iter().any(|m| m.bytes == vec![0xC0, 0]));
+}
+
+#[test]
+fn playback_2() {
+    let midi = MidiBuilder::new()
+        .bpm(60)
+        .track("Lead")
+        .note(60, half(), 90)
+        .build();
+    let messages = playback::schedule(&midi);
+    let times: Vec<f64> = messages.iter().map(|m| m.time).collect();
+    assert!(times.windows(2).all(|w| w[0] <= w[1]));
+    assert_eq!(times.last(), Some(&2.0));
+}
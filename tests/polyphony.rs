@@ -0,0 +1,47 @@
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::half;
+use beatblox_midi::parsing::duration::quarter;
+
+#[test]
+fn polyphony_1() {
+    let midi = MidiBuilder::new()
+        .track("Lead")
+        .note(60, quarter(), 80)
+        .note(62, quarter(), 80)
+        .chord(&[60, 64, 67], half(), 100)
+        .rest(half())
+        .chord(&[60, 64], half(), 100)
+        .build();
+    let profile = midi.tracks()[0].polyphony_profile(midi.beat_type());
+    assert_eq!(3, profile.max);
+    assert_eq!(vec![(0.0, 1), (2.0, 3), (4.0, 0), (6.0, 2), (8.0, 0)], profile.series);
+    assert_eq!((2.0 + 6.0 + 4.0) / 8.0, profile.mean);
+    assert!(!profile.is_monophonic());
+}
+
+#[test]
+fn polyphony_2() {
+    let midi = MidiBuilder::new()
+        .track("Lead")
+        .note(60, quarter(), 80)
+        .rest(quarter())
+        .note(62, half(), 80)
+        .build();
+    let profile = midi.tracks()[0].polyphony_profile(midi.beat_type());
+    assert!(profile.is_monophonic());
+    assert_eq!(vec![(0.0, 1), (1.0, 0), (2.0, 1), (4.0, 0)], profile.series);
+    assert_eq!(0.75, profile.mean);
+}
+
+#[test]
+fn polyphony_3() {
+    // Beats are eighth notes in 6/8 time.
+    let midi = MidiBuilder::new()
+        .time_signature(6, 8)
+        .track("Lead")
+        .note(60, quarter(), 80)
+        .chord(&[60, 64], half(), 80)
+        .build();
+    let profile = midi.tracks()[0].polyphony_profile(midi.beat_type());
+    assert_eq!(vec![(0.0, 1), (2.0, 2), (6.0, 0)], profile.series);
+}
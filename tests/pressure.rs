@@ -0,0 +1,29 @@
+use beatblox_midi::parsing::duration::DurationType;
+use beatblox_midi::parsing::expression::PressureEvent;
+use beatblox_midi::parsing::expression::attach_pressure;
+use beatblox_midi::parsing::expression::resample_pressure;
+use beatblox_midi::parsing::symbols::NoteWrapper;
+
+fn quarter(value: u8) -> NoteWrapper {
+    NoteWrapper::build_note_wrapper(Some(value), DurationType::beat_type_map(1.0, 2), 100)
+}
+
+#[test]
+fn pressure_1() {
+    let events = vec![
+        PressureEvent { key: None, beat: 0.5, value: 40 },
+        PressureEvent { key: Some(62), beat: 1.25, value: 90 },
+        PressureEvent { key: Some(60), beat: 1.5, value: 10 },
+    ];
+    let mut notes = vec![quarter(60), quarter(62)];
+    attach_pressure(&mut notes, &events, 2);
+    assert_eq!(vec![(0.5, 40)], notes[0].notes()[0].pressure);
+    assert_eq!(vec![(0.25, 90)], notes[1].notes()[0].pressure);
+}
+
+#[test]
+fn pressure_2() {
+    let events = vec![PressureEvent { key: None, beat: 0.5, value: 40 }];
+    let curve = resample_pressure(&events, 1.0, 2);
+    assert_eq!(vec![(0.0, 0), (0.5, 40)], curve);
+}
@@ -0,0 +1,29 @@
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::options::ParseOptions;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+fn parse_with_progress(options: ParseOptions) -> Vec<f32> {
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&reports);
+    let options = options.on_progress(move |fraction| sink.lock().unwrap().push(fraction));
+    Midi::parse_with_options(String::from("tests/test_files/test-1.mid"), &options).unwrap();
+    let reports = reports.lock().unwrap().clone();
+    reports
+}
+
+#[test]
+fn progress_1() {
+    let reports = parse_with_progress(ParseOptions::new());
+    assert!(!reports.is_empty());
+    assert!(reports.windows(2).all(|w| w[1] >= w[0]));
+    assert!(reports.iter().all(|f| (0.0..=1.0).contains(f)));
+    assert_eq!(Some(&1.0), reports.last());
+}
+
+#[test]
+fn progress_2() {
+    let reports = parse_with_progress(ParseOptions::new().tracks(&[1]).merge_tracks(true));
+    assert!(reports.contains(&0.5));
+    assert_eq!(Some(&1.0), reports.last());
+}
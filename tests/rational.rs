@@ -0,0 +1,46 @@
+use beatblox_midi::parsing::duration::DurationType;
+use beatblox_midi::parsing::duration::NoteDuration;
+use beatblox_midi::parsing::duration::NoteDurationModifier;
+use beatblox_midi::parsing::duration::Rational;
+use beatblox_midi::parsing::duration::hundred_twenty_eighth;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::duration::whole;
+
+#[test]
+fn rational_1() {
+    assert_eq!(Rational::new(3, 8), Rational::new(6, 16));
+    assert_eq!(Some(Rational::new(3, 8)), quarter().dotted().to_rational());
+    assert_eq!(Some(Rational::new(7, 16)), quarter().double_dotted().to_rational());
+    assert_eq!(Rational::new(3, 8), Rational::from_beats(1.5, 2));
+    assert_eq!(Rational::new(3, 8), Rational::from_beats(3.0, 3));
+    assert_eq!(3.0, Rational::new(3, 8).to_beats(3));
+    // A whole note lasts eight beats when eighth notes get the beat.
+    assert_eq!(8.0, whole().get_beat_count(3));
+}
+
+#[test]
+fn rational_2() {
+    // Lengths a rounding error away from a written duration still map to it.
+    let duration = DurationType::beat_type_map(0.37499997, 2);
+    assert_eq!(NoteDuration::SIXTEENTH, duration.duration);
+    assert_eq!(NoteDurationModifier::Dotted, duration.modifier);
+    let duration = DurationType::beat_type_map(1.0 / 480.0 * 479.0, 2);
+    assert_eq!(NoteDuration::QUARTER, duration.duration);
+    // Lengths that can't be written as one note don't.
+    for beats in [1.0 / 3.0, 1.25, 5.0, 8.0, 0.0] {
+        assert_eq!(NoteDuration::NaN, DurationType::beat_type_map(beats, 2).duration);
+    }
+    assert_eq!(None, DurationType::beat_type_map(5.0, 2).to_rational());
+}
+
+#[test]
+fn rational_3() {
+    // A triple dotted hundred twenty-eighth note is the finest written duration.
+    let duration = hundred_twenty_eighth().triple_dotted();
+    let length = duration.to_rational().unwrap();
+    assert_eq!(Rational::new(15, 1024), length);
+    assert_eq!(length, Rational::from_beats(duration.get_beat_count(2), 2));
+    assert_eq!(duration, DurationType::beat_type_map(duration.get_beat_count(2), 2));
+    assert_eq!(vec![duration], length.to_tie());
+    assert_eq!(vec![quarter().triple_dotted()], Rational::new(15, 32).to_tie());
+}
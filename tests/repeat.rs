@@ -0,0 +1,45 @@
+use beatblox_midi::Midi;
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::whole;
+
+/// Returns the pitch, duration, and velocity columns of a piece's CSV rows in the given measures.
+fn rows(midi: &Midi, measures: std::ops::RangeInclusive<u32>) -> Vec<String> {
+    midi.to_csv()
+        .lines()
+        .skip(1)
+        .filter(|row| measures.contains(&row.split(',').nth(1).unwrap().parse::<u32>().unwrap()))
+        .map(|row| row.split(',').skip(4).collect::<Vec<_>>().join(","))
+        .collect()
+}
+
+#[test]
+fn repeat_1() {
+    let midi = MidiBuilder::new()
+        .track("Lead")
+        .note(60, whole(), 90)
+        .note(62, whole(), 90)
+        .note(64, whole(), 90)
+        .build();
+    let repeated = midi.repeat_measures(2..=2, 3);
+    let values: Vec<u8> = repeated.tracks()[0].notes
+        .iter()
+        .flat_map(|n| n.notes())
+        .map(|n| n.value().unwrap())
+        .collect();
+    assert_eq!(vec![60, 62, 62, 62, 64], values);
+    assert_eq!(20.0, repeated.tracks()[0].length);
+    let removed = midi.repeat_measures(2..=2, 0);
+    assert_eq!(2, removed.tracks()[0].notes.len());
+}
+
+#[test]
+fn repeat_2() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let midi = Midi::parse(dir).unwrap();
+    let repeated = midi.repeat_measures(1..=2, 2);
+    assert!(!rows(&midi, 1..=2).is_empty());
+    assert_eq!(rows(&midi, 1..=2), rows(&repeated, 1..=2));
+    assert_eq!(rows(&midi, 1..=2), rows(&repeated, 3..=4));
+    assert_eq!(rows(&midi, 3..=1000), rows(&repeated, 5..=1002));
+    assert_eq!(1, repeated.time_signatures().len());
+}
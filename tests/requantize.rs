@@ -0,0 +1,43 @@
+use beatblox_midi::Midi;
+use beatblox_midi::error::ParseError;
+use beatblox_midi::parsing::duration::eighth;
+use beatblox_midi::parsing::duration::thirty_second;
+use beatblox_midi::parsing::options::ParseOptions;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+#[test]
+fn requantize_1() {
+    let bytes = std::fs::read("tests/test_files/test-1.mid").unwrap();
+    let options = ParseOptions::new().retain_timing(true);
+    let mut midi = Midi::parse_bytes(&bytes, &options).unwrap();
+    midi.requantize(eighth(), true).unwrap();
+    let coarse = ParseOptions::new().precision(eighth()).triplet(true);
+    assert_eq!(Midi::parse_bytes(&bytes, &coarse).unwrap().to_csv(), midi.to_csv());
+    midi.requantize(thirty_second(), false).unwrap();
+    assert_eq!(Midi::parse_bytes(&bytes, &ParseOptions::new()).unwrap().to_csv(), midi.to_csv());
+}
+
+#[test]
+fn requantize_2() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let mut midi = Midi::parse(dir).unwrap();
+    assert!(matches!(midi.requantize(eighth(), false), Err(ParseError::TimingNotRetained)));
+}
+
+#[test]
+fn requantize_3() {
+    // The options that shape the notes are kept, while the cancel token of the first parse isn't.
+    let bytes = std::fs::read("tests/test_files/test-1.mid").unwrap();
+    let token = Arc::new(AtomicBool::new(false));
+    let options = ParseOptions::new()
+        .retain_timing(true)
+        .tracks(&[1])
+        .cancel_token(token.clone());
+    let mut midi = Midi::parse_bytes(&bytes, &options).unwrap();
+    token.store(true, Ordering::Relaxed);
+    midi.requantize(eighth(), false).unwrap();
+    let coarse = ParseOptions::new().tracks(&[1]).precision(eighth());
+    assert_eq!(Midi::parse_bytes(&bytes, &coarse).unwrap().to_csv(), midi.to_csv());
+}
@@ -0,0 +1,50 @@
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::half;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::duration::whole;
+
+#[test]
+fn score_1() {
+    let midi = MidiBuilder::new()
+        .key_signature(1, false)
+        .track("Lead")
+        .note(67, half(), 80)
+        .note(69, half(), 80)
+        .note(71, whole(), 80)
+        .note(72, quarter(), 80)
+        .track("Bass")
+        .chord(&[43, 50], whole(), 80)
+        .build();
+    let score = midi.score();
+    assert_eq!(2, score.parts.len());
+    assert_eq!(3, score.measure_count());
+    assert_eq!("Lead", score.parts[0].name);
+    let lead = &score.parts[0].staves[0].voices[0].measures;
+    assert_eq!(vec![2, 1, 1], lead.iter().map(|m| m.notes.len()).collect::<Vec<_>>());
+    assert_eq!(vec![0.0, 4.0, 8.0], lead.iter().map(|m| m.start).collect::<Vec<_>>());
+    assert_eq!(4.0, lead[2].length);
+    assert_eq!(3, lead[2].number);
+    assert_eq!(1, lead[0].key_signature.unwrap().sharps);
+    // The bass only plays in the first measure, but its measures still line up with the lead.
+    let bass = &score.parts[1].staves[0].voices[0].measures;
+    assert_eq!(vec![1, 0, 0], bass.iter().map(|m| m.notes.len()).collect::<Vec<_>>());
+}
+
+#[test]
+fn score_2() {
+    let midi = MidiBuilder::new()
+        .time_signature(3, 4)
+        .track("Waltz")
+        .note(60, half(), 80)
+        .note(64, quarter(), 80)
+        .note(67, half(), 80)
+        .note(72, quarter(), 80)
+        .build();
+    let score = midi.score();
+    let measures = &score.parts[0].staves[0].voices[0].measures;
+    assert_eq!(2, measures.len());
+    assert_eq!(3.0, measures[0].length);
+    assert_eq!(3, measures[1].time_signature.beat_count);
+    let names: Vec<_> = measures[1].notes.iter().map(|n| n.notes()[0].name()).collect();
+    assert_eq!(vec!["G4", "C5"], names);
+}
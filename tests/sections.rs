@@ -0,0 +1,62 @@
+use beatblox_midi::Midi;
+use beatblox_midi::form::NamedSection;
+use beatblox_midi::parsing::options::ParseOptions;
+use midly::Format;
+use midly::Header;
+use midly::MetaMessage;
+use midly::MidiMessage;
+use midly::Smf;
+use midly::Timing;
+use midly::TrackEvent;
+use midly::TrackEventKind;
+
+fn meta(delta: u32, message: MetaMessage<'static>) -> TrackEvent<'static> {
+    TrackEvent { delta: delta.into(), kind: TrackEventKind::Meta(message) }
+}
+
+fn note(delta: u32, message: MidiMessage) -> TrackEvent<'static> {
+    TrackEvent { delta: delta.into(), kind: TrackEventKind::Midi { channel: 0.into(), message } }
+}
+
+/// Parses a five measure piece with the given markers as `(tick, text)` pairs, at 96 ticks per
+/// beat.
+fn parse(markers: &[(u32, &'static str)]) -> Midi {
+    let mut smf = Smf::new(Header::new(Format::Parallel, Timing::Metrical(96.into())));
+    let mut conductor = Vec::new();
+    let mut tick = 0;
+    for (at, text) in markers {
+        conductor.push(meta(at - tick, MetaMessage::Marker(text.as_bytes())));
+        tick = *at;
+    }
+    conductor.push(meta(0, MetaMessage::EndOfTrack));
+    smf.tracks.push(conductor);
+    smf.tracks.push(vec![
+        note(0, MidiMessage::NoteOn { key: 60.into(), vel: 100.into() }),
+        note(96, MidiMessage::NoteOff { key: 60.into(), vel: 0.into() }),
+        meta(96 * 19, MetaMessage::EndOfTrack),
+    ]);
+    let mut bytes = Vec::new();
+    smf.write_std(&mut bytes).unwrap();
+    Midi::parse_bytes(&bytes, &ParseOptions::new().pad_to_score_length(true)).unwrap()
+}
+
+fn section(name: &str, start_measure: u32, end_measure: u32) -> NamedSection {
+    NamedSection { name: String::from(name), start_measure, end_measure }
+}
+
+#[test]
+fn sections_1() {
+    let midi = parse(&[(0, "Intro"), (96 * 8, "Verse"), (96 * 9, "Chorus"), (96 * 16, "Outro")]);
+    let expected = vec![section("Intro", 1, 2), section("Chorus", 3, 4), section("Outro", 5, 5)];
+    assert_eq!(expected, midi.sections());
+}
+
+#[test]
+fn sections_2() {
+    let midi = parse(&[(96 * 4, "Verse")]);
+    assert_eq!(vec![section("Verse", 2, 5)], midi.sections());
+    let score = midi.score();
+    let measures = &score.parts[0].staves[0].voices[0].measures;
+    let names: Vec<_> = measures.iter().map(|m| m.section.as_deref()).collect();
+    assert_eq!(vec![None, Some("Verse"), Some("Verse"), Some("Verse"), Some("Verse")], names);
+}
@@ -0,0 +1,55 @@
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::TrackKind;
+use beatblox_midi::parsing::events::Event;
+use beatblox_midi::parsing::events::EventFile;
+use beatblox_midi::parsing::events::EventKind;
+use beatblox_midi::parsing::metadata::SmfFormat;
+use beatblox_midi::parsing::options::ParseOptions;
+
+fn pattern(tempo: u32, numerator: u8, key: u8) -> Vec<Event> {
+    let mut events = vec![
+        Event { tick: 0, kind: EventKind::Tempo(tempo) },
+        Event { tick: 0, kind: EventKind::TimeSignature(numerator, 2, 24, 8) },
+    ];
+    for i in 0..numerator as u32 {
+        let on = EventKind::NoteOn { channel: 9, key, velocity: 100 };
+        let off = EventKind::NoteOff { channel: 9, key, velocity: 0 };
+        events.push(Event { tick: i * 480, kind: on });
+        events.push(Event { tick: i * 480 + 240, kind: off });
+    }
+    events.push(Event { tick: numerator as u32 * 480, kind: EventKind::EndOfTrack });
+    events
+}
+
+fn sequential_file() -> EventFile {
+    EventFile {
+        format: SmfFormat::Sequential,
+        ticks_per_beat: 480,
+        tracks: vec![pattern(500_000, 4, 36), pattern(400_000, 3, 38)],
+    }
+}
+
+#[test]
+fn sequences_1() {
+    let midi = Midi::parse_events(&sequential_file(), &ParseOptions::new()).unwrap();
+    assert!(midi.tracks().is_empty());
+    let sequences = midi.sequences();
+    assert_eq!(2, sequences.len());
+    assert_eq!(4, sequences[0].time_signatures()[0].beat_count);
+    assert_eq!(3, sequences[1].time_signatures()[0].beat_count);
+    let tracks: Vec<_> = sequences[1]
+        .tracks()
+        .iter()
+        .filter(|t| t.kind == TrackKind::Notes)
+        .collect();
+    assert_eq!(1, tracks.len());
+    assert!(tracks[0].percussion);
+    assert_eq!(3.0, tracks[0].length);
+}
+
+#[test]
+fn sequences_2() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let midi = Midi::parse(dir).unwrap();
+    assert!(midi.sequences().is_empty());
+}
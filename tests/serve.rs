@@ -0,0 +1,42 @@
+#![cfg(feature = "serve")]
+
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::options::ParseOptions;
+use beatblox_midi::parsing::options::ResourceLimits;
+use beatblox_midi::serve::Server;
+
+#[test]
+fn serve_1() {
+    let bytes = std::fs::read("tests/test_files/test-1.mid").unwrap();
+    let midi = Midi::parse_bytes(&bytes, &ParseOptions::new()).unwrap();
+    let server = Server::new(ParseOptions::new());
+    let response = server.handle("POST", "/parse", &bytes);
+    assert_eq!(response.status, 200);
+    assert_eq!(response.body, midi.to_json().into_bytes());
+    let response = server.handle("POST", "/parse?format=lists", &bytes);
+    assert_eq!(response.body.first(), Some(&b'['));
+    assert_eq!(response.content_type, "application/json");
+    let response = server.handle("POST", "/stats", &bytes);
+    let text = String::from_utf8(response.body).unwrap();
+    assert!(text.starts_with("{\"stats\":{\"note_count\":"));
+    let response = server.handle("POST", "/export?format=smf", &bytes);
+    assert_eq!(response.content_type, "audio/midi");
+    assert_eq!(response.body, midi.to_smf_bytes());
+}
+
+#[test]
+fn serve_2() {
+    let bytes = std::fs::read("tests/test_files/test-1.mid").unwrap();
+    let limits = ResourceLimits::new().max_file_size(16);
+    let server = Server::new(ParseOptions::new().limits(limits));
+    assert_eq!(server.handle("POST", "/parse", &bytes).status, 413);
+    let server = Server::new(ParseOptions::new());
+    assert_eq!(server.handle("POST", "/parse", &[1, 2, 3]).status, 422);
+    assert_eq!(server.handle("GET", "/parse", &bytes).status, 405);
+    assert_eq!(server.handle("POST", "/missing", &bytes).status, 404);
+    assert_eq!(server.handle("POST", "/export", &bytes).status, 400);
+    assert_eq!(server.handle("POST", "/export?format=musicxml", &bytes).status, 400);
+    assert_eq!(server.handle("POST", "/parse?precision=tiny", &bytes).status, 400);
+    let response = server.handle("POST", "/parse?tracks=x", &bytes);
+    assert_eq!(response.body, b"{\"error\":\"bad tracks x\"}");
+}
@@ -0,0 +1,39 @@
+use beatblox_midi::Midi;
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::eighth;
+use beatblox_midi::parsing::duration::quarter;
+
+fn melody(values: &[u8], last: u8) -> Midi {
+    let mut builder = MidiBuilder::new().track("Lead");
+    for value in values {
+        builder = builder.note(*value, quarter(), 90);
+    }
+    builder.note(last, eighth(), 90).build()
+}
+
+#[test]
+fn similarity_1() {
+    let reference = melody(&[60, 62, 64], 65);
+    let track = &reference.tracks()[0];
+    let beat_type = reference.beat_type();
+    assert_eq!(1.0, track.similarity(track, beat_type));
+    // One wrong pitch out of four notes.
+    let wrong_pitch = melody(&[60, 61, 64], 65);
+    assert_eq!(1.0 - 0.5 / 4.0, track.similarity(&wrong_pitch.tracks()[0], beat_type));
+    // One missing note.
+    let missing = melody(&[60, 64], 65);
+    assert_eq!(0.75, track.similarity(&missing.tracks()[0], beat_type));
+    let unrelated = melody(&[40, 41, 42, 43, 44, 45], 46);
+    assert!(track.similarity(&unrelated.tracks()[0], beat_type) < 0.5);
+}
+
+#[test]
+fn similarity_2() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let midi = Midi::parse(dir.clone()).unwrap();
+    assert_eq!(1.0, midi.similarity(&Midi::parse(dir).unwrap()));
+    let other = melody(&[60, 62, 64], 65);
+    let score = midi.similarity(&other);
+    assert!((0.0..0.5).contains(&score));
+    assert_eq!(score, other.similarity(&midi));
+}
@@ -0,0 +1,76 @@
+use beatblox_midi::Midi;
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::half;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::duration::whole;
+use beatblox_midi::parsing::symbols::NoteModifier;
+use beatblox_midi::parsing::symbols::NoteWrapper;
+
+/// Returns the pitch, duration, and velocity columns of a piece's CSV rows in the given measures.
+fn rows(midi: &Midi, measures: std::ops::RangeInclusive<u32>) -> Vec<String> {
+    midi.to_csv()
+        .lines()
+        .skip(1)
+        .filter(|row| measures.contains(&row.split(',').nth(1).unwrap().parse::<u32>().unwrap()))
+        .map(|row| row.split(',').skip(4).collect::<Vec<_>>().join(","))
+        .collect()
+}
+
+#[test]
+fn slice_1() {
+    let dir = String::from("tests/test_files/test-2.mid");
+    let midi = Midi::parse(dir).unwrap();
+    let sliced = midi.slice_measures(2, 3);
+    for track in sliced.tracks() {
+        assert!(track.length <= 8.0);
+    }
+    assert!(!rows(&midi, 2..=3).is_empty());
+    assert_eq!(rows(&midi, 2..=3), rows(&sliced, 1..=2));
+    assert!(rows(&sliced, 3..=100).is_empty());
+}
+
+#[test]
+fn slice_2() {
+    let midi = MidiBuilder::new()
+        .track("Lead")
+        .note(60, whole(), 90)
+        .chord(&[64, 67], half(), 80)
+        .note(62, half(), 70)
+        .build();
+    let sliced = midi.slice_beats(1.0, 5.0);
+    let notes = &sliced.tracks()[0].notes;
+    assert_eq!(2, notes.len());
+    match &notes[0] {
+        NoteWrapper::PlainNote(n) => {
+            assert_eq!(Some(60), n.value());
+            assert_eq!(3.0, n.duration.get_beat_count(2));
+        },
+        _ => panic!("expected a plain note"),
+    }
+    assert!(matches!(notes[1], NoteWrapper::ModifiedNote(NoteModifier::Chord(_))));
+    assert_eq!(1.0, notes[1].beat_count(2));
+    assert_eq!(4.0, sliced.tracks()[0].length);
+}
+
+#[test]
+fn slice_3() {
+    let midi = MidiBuilder::new()
+        .time_signature(3, 4)
+        .key_signature(2, false)
+        .track("Lead")
+        .note(60, quarter(), 90)
+        .note(62, half(), 90)
+        .note(64, half().dotted(), 90)
+        .build();
+    let sliced = midi.slice_measures(2, 2);
+    assert_eq!(3, sliced.time_signatures()[0].beat_count);
+    assert_eq!(0, sliced.time_signatures()[0].time_of_occurance);
+    assert_eq!(2, sliced.key_signatures()[0].sharps);
+    let values: Vec<u8> = sliced.tracks()[0].notes
+        .iter()
+        .flat_map(|n| n.notes())
+        .map(|n| n.value().unwrap())
+        .collect();
+    assert_eq!(vec![64], values);
+    assert!(midi.slice_measures(5, 6).tracks()[0].notes.is_empty());
+}
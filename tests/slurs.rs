@@ -0,0 +1,70 @@
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::duration::sixteenth;
+use beatblox_midi::parsing::events::Event;
+use beatblox_midi::parsing::events::EventFile;
+use beatblox_midi::parsing::events::EventKind;
+use beatblox_midi::parsing::metadata::SmfFormat;
+use beatblox_midi::parsing::options::ParseOptions;
+
+fn on(tick: u32, key: u8) -> Event {
+    Event { tick, kind: EventKind::NoteOn { channel: 0, key, velocity: 100 } }
+}
+
+fn off(tick: u32, key: u8) -> Event {
+    Event { tick, kind: EventKind::NoteOff { channel: 0, key, velocity: 0 } }
+}
+
+/// Parses a single track of events and returns whether each of its notes is slurred.
+fn slurs(mut events: Vec<Event>, options: &ParseOptions) -> Vec<(Option<u8>, bool)> {
+    events.sort_by_key(|e| e.tick);
+    events.push(Event { tick: 1920, kind: EventKind::EndOfTrack });
+    let conductor = vec![Event { tick: 0, kind: EventKind::EndOfTrack }];
+    let file = EventFile {
+        format: SmfFormat::Parallel,
+        ticks_per_beat: 480,
+        tracks: vec![conductor, events],
+    };
+    let midi = Midi::parse_events(&file, options).unwrap();
+    midi.tracks()[1].notes
+        .iter()
+        .flat_map(|n| n.notes())
+        .map(|n| (n.value(), n.slur))
+        .collect()
+}
+
+#[test]
+fn slurs_1() {
+    // C and D overlap slightly, D and E are detached, and E is held for a whole beat into F.
+    let events = vec![
+        on(0, 60), on(480, 62), off(520, 60), off(900, 62),
+        on(960, 64), on(1440, 65), off(1920, 64), off(1900, 65),
+    ];
+    let options = ParseOptions::new().slurs(sixteenth());
+    let expected = vec![
+        (Some(60), true),
+        (Some(62), false),
+        (None, false),
+        (Some(64), false),
+        (Some(65), false),
+    ];
+    assert_eq!(expected, slurs(events.clone(), &options));
+    let plain = slurs(events, &ParseOptions::new());
+    assert!(plain.iter().all(|(_, slur)| !slur));
+}
+
+#[test]
+fn slurs_2() {
+    // A run of legato notes is slurred up to the last, and repeated keys aren't slurred.
+    let events = vec![
+        on(0, 60), on(240, 62), off(250, 60), on(480, 64), off(500, 62),
+        on(720, 64), off(730, 64), off(900, 64),
+    ];
+    let options = ParseOptions::new().slurs(sixteenth());
+    let expected = vec![
+        (Some(60), true),
+        (Some(62), true),
+        (Some(64), false),
+        (Some(64), false),
+    ];
+    assert_eq!(expected, slurs(events, &options));
+}
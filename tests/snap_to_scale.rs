@@ -0,0 +1,25 @@
+use beatblox_midi::parsing::Track;
+use beatblox_midi::parsing::duration::eighth;
+use beatblox_midi::parsing::pitch::Scale;
+
+fn values(track: &Track) -> Vec<Option<u8>> {
+    track.notes.iter().flat_map(|n| n.notes()).map(|n| n.value()).collect()
+}
+
+#[test]
+fn snap_to_scale_1() {
+    let text = "C4:q C#4:q F#4:q Bb4:q r:q";
+    let mut track = Track::from_notation(String::from("melody"), text).unwrap();
+    assert_eq!(3, track.snap_to_scale(0, Scale::Major));
+    assert_eq!(vec![Some(60), Some(60), Some(65), Some(69), None], values(&track));
+    assert_eq!(0, track.snap_to_scale(0, Scale::Major));
+}
+
+#[test]
+fn snap_to_scale_2() {
+    let mut track = Track::from_notation(String::from("melody"), "A4:q G#4:s A4:s C#5:q").unwrap();
+    assert_eq!(1, track.snap_to_scale_with_threshold(9, Scale::NaturalMinor, eighth()));
+    assert_eq!(vec![Some(69), Some(68), Some(69), Some(72)], values(&track));
+    assert!(Scale::MinorPentatonic.contains(72, 9));
+    assert!(!Scale::MinorPentatonic.contains(71, 9));
+}
@@ -0,0 +1,39 @@
+use beatblox_midi::parsing::pitch::Accidental;
+use beatblox_midi::parsing::pitch::Letter;
+use beatblox_midi::parsing::pitch::SpelledPitch;
+use beatblox_midi::parsing::symbols::KeySignature;
+
+fn key(sharps: i8) -> Option<KeySignature> {
+    Some(KeySignature { sharps, minor: false, time_of_occurance: 0 })
+}
+
+#[test]
+fn spell_1() {
+    let pitch = SpelledPitch::spell(66, key(1));
+    assert_eq!(Letter::F, pitch.letter);
+    assert_eq!(Accidental::Sharp, pitch.accidental);
+    assert_eq!(4, pitch.octave);
+}
+
+#[test]
+fn spell_2() {
+    let pitch = SpelledPitch::spell(66, key(-5));
+    assert_eq!(Letter::G, pitch.letter);
+    assert_eq!(Accidental::Flat, pitch.accidental);
+    assert_eq!("Gb4", pitch.name());
+}
+
+#[test]
+fn spell_3() {
+    let pitch = SpelledPitch::spell(71, key(-6));
+    assert_eq!("Cb5", pitch.name());
+    assert_eq!(71, pitch.to_value());
+}
+
+#[test]
+fn spell_4() {
+    assert_eq!("F4", SpelledPitch::spell(65, key(2)).name());
+    assert_eq!("Eb4", SpelledPitch::spell(63, key(-1)).name());
+    assert_eq!("C#4", SpelledPitch::spell(61, None).name());
+    assert_eq!("E#4", SpelledPitch::spell(65, key(6)).name());
+}
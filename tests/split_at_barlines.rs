@@ -0,0 +1,71 @@
+use beatblox_midi::Midi;
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::half;
+use beatblox_midi::parsing::duration::whole;
+use beatblox_midi::parsing::events::Event;
+use beatblox_midi::parsing::events::EventFile;
+use beatblox_midi::parsing::events::EventKind;
+use beatblox_midi::parsing::metadata::SmfFormat;
+use beatblox_midi::parsing::options::ParseOptions;
+use beatblox_midi::parsing::symbols::NoteModifier;
+use beatblox_midi::parsing::symbols::NoteWrapper;
+
+/// Returns the length in beats of each piece of a tie, or of the only piece of anything else.
+fn pieces(wrapper: &NoteWrapper) -> Vec<f32> {
+    match wrapper {
+        NoteWrapper::ModifiedNote(NoteModifier::TiedNote(t)) => {
+            t.iter().map(|n| n.beat_count(2)).collect()
+        },
+        _ => vec![wrapper.beat_count(2)],
+    }
+}
+
+#[test]
+fn split_at_barlines_1() {
+    let mut midi = MidiBuilder::new()
+        .track("Lead")
+        .note(60, half(), 80)
+        .note(62, whole(), 80)
+        .chord(&[64, 67], whole(), 80)
+        .rest(whole())
+        .note(65, half(), 80)
+        .build();
+    assert_eq!(3, midi.split_at_barlines());
+    let notes = &midi.tracks()[0].notes;
+    assert_eq!(6, notes.len());
+    assert_eq!(vec![2.0, 2.0], pieces(&notes[1]));
+    let NoteWrapper::ModifiedNote(NoteModifier::Chord(members)) = &notes[2] else {
+        panic!("expected a chord");
+    };
+    assert!(members.iter().all(|m| pieces(m) == vec![2.0, 2.0]));
+    assert!(notes[3].notes()[0].is_rest() && notes[4].notes()[0].is_rest());
+    assert_eq!(2.0, notes[3].beat_count(2));
+    assert_eq!(16.0, notes.iter().map(|n| n.beat_count(2)).sum::<f32>());
+    // Splitting again leaves the piece as it is.
+    assert_eq!(0, midi.split_at_barlines());
+}
+
+#[test]
+fn split_at_barlines_2() {
+    let on = |tick, key| Event { tick, kind: EventKind::NoteOn { channel: 0, key, velocity: 100 } };
+    let off = |tick, key| Event { tick, kind: EventKind::NoteOff { channel: 0, key, velocity: 0 } };
+    let events = vec![
+        on(0, 60), off(1440, 60),
+        on(1440, 62), off(2400, 62),
+        on(2400, 64), off(3840, 64),
+        Event { tick: 3840, kind: EventKind::EndOfTrack },
+    ];
+    let conductor = vec![Event { tick: 0, kind: EventKind::EndOfTrack }];
+    let file = EventFile {
+        format: SmfFormat::Parallel,
+        ticks_per_beat: 480,
+        tracks: vec![conductor, events],
+    };
+    let options = ParseOptions::new().split_at_barlines(true);
+    let midi = Midi::parse_events(&file, &options).unwrap();
+    let notes = &midi.tracks()[1].notes;
+    assert_eq!(vec![1.0, 1.0], pieces(&notes[1]));
+    assert_eq!(Some(62), notes[1].notes()[1].value());
+    let unsplit = Midi::parse_events(&file, &ParseOptions::new()).unwrap();
+    assert_eq!(1, unsplit.tracks()[1].notes[1].notes().len());
+}
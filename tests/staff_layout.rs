@@ -0,0 +1,51 @@
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::half;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::score::Clef;
+use beatblox_midi::score::StaffLayout;
+
+#[test]
+fn staff_layout_1() {
+    let midi = MidiBuilder::new()
+        .track("Flute")
+        .note(72, quarter(), 80)
+        .note(79, quarter(), 80)
+        .note(57, half(), 80)
+        .track("Cello")
+        .note(36, half(), 80)
+        .note(48, half(), 80)
+        .track("Drums")
+        .percussion()
+        .note(36, half(), 80)
+        .build();
+    let tracks = midi.tracks();
+    assert_eq!(StaffLayout::Single(Clef::Treble), tracks[0].staff_layout());
+    assert_eq!(StaffLayout::Single(Clef::Bass), tracks[1].staff_layout());
+    assert_eq!(StaffLayout::Single(Clef::Percussion), tracks[2].staff_layout());
+    let score = midi.score();
+    assert_eq!(Clef::Bass, score.parts[1].staves[0].clef);
+}
+
+#[test]
+fn staff_layout_2() {
+    let midi = MidiBuilder::new()
+        .track("Piano")
+        .chord(&[48, 64, 67], half(), 80)
+        .note(36, quarter(), 80)
+        .note(76, quarter(), 80)
+        .build();
+    assert_eq!(StaffLayout::Grand { split: 60 }, midi.tracks()[0].staff_layout());
+    let score = midi.score();
+    let part = &score.parts[0];
+    assert_eq!(StaffLayout::Grand { split: 60 }, part.layout);
+    let clefs: Vec<_> = part.staves.iter().map(|s| s.clef).collect();
+    assert_eq!(vec![Clef::Treble, Clef::Bass], clefs);
+    let values = |staff: usize| -> Vec<Vec<Option<u8>>> {
+        part.staves[staff].voices[0].measures[0].notes
+            .iter()
+            .map(|n| n.notes().iter().map(|n| n.value()).collect())
+            .collect()
+    };
+    assert_eq!(vec![vec![Some(64), Some(67)], vec![None], vec![Some(76)]], values(0));
+    assert_eq!(vec![vec![Some(48)], vec![Some(36)], vec![None]], values(1));
+}
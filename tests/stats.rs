@@ -0,0 +1,57 @@
+use beatblox_midi::Midi;
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::eighth;
+use beatblox_midi::parsing::duration::half;
+use beatblox_midi::parsing::duration::quarter;
+
+#[test]
+fn stats_1() {
+    let midi = MidiBuilder::new()
+        .track("Lead")
+        .note(60, quarter(), 80)
+        .chord(&[64, 67], quarter(), 100)
+        .rest(half())
+        .note(72, eighth(), 60)
+        .rest(eighth())
+        .build();
+    let stats = midi.tracks()[0].stats(2);
+    assert_eq!(4, stats.note_count);
+    assert_eq!(Some(60), stats.min_pitch);
+    assert_eq!(Some(72), stats.max_pitch);
+    assert_eq!(Some(65.75), stats.mean_pitch);
+    assert_eq!(Some(85.0), stats.mean_velocity);
+    assert_eq!([0, 0, 3, 1, 0, 0, 0, 0], stats.duration_histogram);
+    assert_eq!(5.0, stats.total_beats);
+    assert_eq!(50.0, stats.rest_percentage);
+}
+
+#[test]
+fn stats_2() {
+    let dir = String::from("tests/test_files/test-2.mid");
+    let midi = Midi::parse(dir).unwrap();
+    let stats = midi.stats();
+    let beat_type = midi.time_signatures()[0].beat_type;
+    let track_stats: Vec<_> = midi.tracks().iter().map(|t| t.stats(beat_type)).collect();
+    let count: usize = track_stats.iter().map(|s| s.note_count).sum();
+    assert!(count > 0);
+    assert_eq!(count, stats.note_count);
+    let lowest = track_stats.iter().filter_map(|s| s.min_pitch).min();
+    assert_eq!(lowest, stats.min_pitch);
+    assert!(stats.rest_percentage >= 0.0 && stats.rest_percentage <= 100.0);
+}
+
+#[test]
+fn stats_3() {
+    // Beats are counted in the beat type of the time signature, so eighth notes in 6/8.
+    let midi = MidiBuilder::new()
+        .time_signature(6, 8)
+        .track("Lead")
+        .note(60, quarter().dotted(), 80)
+        .note(62, eighth(), 80)
+        .rest(quarter())
+        .build();
+    let stats = midi.tracks()[0].stats(3);
+    assert_eq!(6.0, stats.total_beats);
+    assert_eq!(stats, midi.stats());
+    assert_eq!(3.0, midi.tracks()[0].stats(2).total_beats);
+}
@@ -0,0 +1,52 @@
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::options::ParseOptions;
+use beatblox_midi::stream::MidiStream;
+
+#[test]
+fn stream_1() {
+    let bytes = std::fs::read("tests/test_files/test-1.mid").unwrap();
+    let options = ParseOptions::new();
+    let midi = Midi::parse_bytes(&bytes, &options).unwrap();
+    let mut stream = MidiStream::new(&bytes, &options).unwrap();
+    assert!(stream.piece().tracks().is_empty());
+    assert_eq!(midi.time_signatures().len(), stream.piece().time_signatures().len());
+    for track in midi.tracks() {
+        let streamed = stream.next_track().unwrap().unwrap();
+        assert_eq!(track.name, streamed.name);
+        assert_eq!(track.index, streamed.index);
+        assert_eq!(track.notes.len(), streamed.notes.len());
+        assert_eq!(track.stats(2), streamed.stats(2));
+    }
+    assert!(stream.next_track().is_none());
+}
+
+#[test]
+fn stream_2() {
+    let bytes = std::fs::read("tests/test_files/test-2.mid").unwrap();
+    let options = ParseOptions::new();
+    let midi = Midi::parse_bytes(&bytes, &options).unwrap();
+    let notes: Vec<_> = MidiStream::new(&bytes, &options)
+        .unwrap()
+        .map(|note| note.unwrap())
+        .map(|(index, note)| (index, note.beat_count(2)))
+        .collect();
+    let expected: Vec<_> = midi.tracks()
+        .iter()
+        .flat_map(|t| t.notes.iter().map(|n| (t.index, n.beat_count(2))))
+        .collect();
+    assert_eq!(expected, notes);
+}
+
+#[test]
+fn stream_3() {
+    let bytes = std::fs::read("tests/test_files/test-1.mid").unwrap();
+    let options = ParseOptions::new().skip_empty_tracks(true);
+    let indices: Vec<usize> = std::iter::from_fn({
+        let mut stream = MidiStream::new(&bytes, &options).unwrap();
+        move || stream.next_track()
+    })
+    .map(|t| t.unwrap().index)
+    .collect();
+    assert_eq!(vec![1, 2], indices);
+    assert!(MidiStream::new(&[0, 1, 2], &options).is_err());
+}
@@ -0,0 +1,55 @@
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::events::EventKind;
+
+fn tempo(midi: &Midi) -> u32 {
+    midi.to_events().tracks[0].iter().find_map(|e| match e.kind {
+        EventKind::Tempo(us) => Some(us),
+        _ => None,
+    }).unwrap()
+}
+
+fn lengths(midi: &Midi) -> Vec<f32> {
+    midi.tracks().iter().map(|t| t.length).collect()
+}
+
+#[test]
+fn stretch_1() {
+    let dir = String::from("tests/test_files/test-2.mid");
+    let mut midi = Midi::parse(dir).unwrap();
+    let csv = midi.to_csv();
+    let us = tempo(&midi);
+    midi.scale_tempo(0.5);
+    assert_eq!(us * 2, tempo(&midi));
+    assert_eq!(csv, midi.to_csv());
+}
+
+#[test]
+fn stretch_2() {
+    let dir = String::from("tests/test_files/test-2.mid");
+    let mut midi = Midi::parse(dir).unwrap();
+    let original = midi.clone();
+    let beat_count = midi.time_signatures()[0].beat_count;
+    midi.stretch_durations(2.0);
+    assert_eq!(beat_count * 2, midi.time_signatures()[0].beat_count);
+    let doubled: Vec<f32> = lengths(&original).iter().map(|l| l * 2.0).collect();
+    assert_eq!(doubled, lengths(&midi));
+    for (a, b) in original.tracks().iter().zip(midi.tracks()) {
+        let beats_a: f32 = a.notes.iter().map(|n| n.beat_count(2)).sum();
+        let beats_b: f32 = b.notes.iter().map(|n| n.beat_count(2)).sum();
+        assert_eq!(beats_a * 2.0, beats_b);
+    }
+}
+
+#[test]
+fn stretch_3() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let mut midi = Midi::parse(dir).unwrap();
+    let original = midi.clone();
+    assert_eq!(2, midi.time_signatures()[0].beat_count);
+    midi.stretch_durations(0.25);
+    assert_eq!(1, midi.time_signatures()[0].beat_count);
+    assert_eq!(3, midi.time_signatures()[0].beat_type);
+    let quartered: Vec<f32> = lengths(&original).iter().map(|l| l / 4.0).collect();
+    assert_eq!(quartered, lengths(&midi));
+    assert_eq!(original.to_csv().lines().count(), midi.to_csv().lines().count());
+}
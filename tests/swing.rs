@@ -0,0 +1,54 @@
+use beatblox_midi::Midi;
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::eighth;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::events::EventKind;
+
+fn note_ticks(midi: &Midi) -> Vec<(u32, bool)> {
+    midi.to_events().tracks[1]
+        .iter()
+        .filter_map(|e| match e.kind {
+            EventKind::NoteOn { .. } => Some((e.tick, true)),
+            EventKind::NoteOff { .. } => Some((e.tick, false)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn swing_1() {
+    let mut midi = MidiBuilder::new()
+        .track("Lead")
+        .note(60, eighth(), 90)
+        .note(62, eighth(), 90)
+        .note(64, quarter(), 90)
+        .build();
+    let csv = midi.to_csv();
+    midi.apply_swing(2.0);
+    assert_eq!(Some(2.0), midi.swing());
+    assert_eq!(csv, midi.to_csv());
+    let expected = vec![
+        (0, true),
+        (320, false),
+        (320, true),
+        (480, false),
+        (480, true),
+        (960, false),
+    ];
+    assert_eq!(expected, note_ticks(&midi));
+}
+
+#[test]
+fn swing_2() {
+    let mut midi = MidiBuilder::new()
+        .track("Lead")
+        .note(60, eighth(), 90)
+        .note(62, eighth(), 90)
+        .build();
+    let straight = note_ticks(&midi);
+    midi.apply_swing(3.0);
+    assert_eq!(360, note_ticks(&midi)[2].0);
+    midi.apply_swing(1.0);
+    assert_eq!(None, midi.swing());
+    assert_eq!(straight, note_ticks(&midi));
+}
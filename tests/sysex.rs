@@ -0,0 +1,33 @@
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::options::ParseOptions;
+use midly::Format;
+use midly::Header;
+use midly::MetaMessage;
+use midly::Smf;
+use midly::Timing;
+use midly::TrackEvent;
+use midly::TrackEventKind;
+
+const GM_RESET: [u8; 5] = [0x7E, 0x7F, 0x09, 0x01, 0xF7];
+
+#[test]
+fn sysex_1() {
+    let mut smf = Smf::new(Header::new(Format::Parallel, Timing::Metrical(96.into())));
+    smf.tracks.push(vec![
+        TrackEvent { delta: 0.into(), kind: TrackEventKind::SysEx(&GM_RESET) },
+        TrackEvent { delta: 96.into(), kind: TrackEventKind::Escape(&[0xF3, 0x01]) },
+        TrackEvent { delta: 0.into(), kind: TrackEventKind::Meta(MetaMessage::EndOfTrack) },
+    ]);
+    let mut bytes = Vec::new();
+    smf.write_std(&mut bytes).unwrap();
+
+    let midi = Midi::parse_bytes(&bytes, &ParseOptions::new()).unwrap();
+    let events = midi.sysex_events();
+    assert_eq!(2, events.len());
+    assert_eq!(GM_RESET.to_vec(), events[0].data);
+    assert_eq!(0, events[0].tick);
+    assert!(!events[0].escape);
+    assert_eq!(vec![0xF3, 0x01], events[1].data);
+    assert_eq!(96, events[1].tick);
+    assert!(events[1].escape);
+}
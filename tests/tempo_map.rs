@@ -0,0 +1,39 @@
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::half;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::symbols::Note;
+use beatblox_midi::parsing::tempo::TempoMap;
+
+#[test]
+fn tempo_map_1() {
+    assert_eq!(0.5, quarter().to_seconds(120, 2));
+    assert_eq!(1.5, half().dotted().to_seconds(120, 2));
+    // In 6/8 time at 120 eighth notes per minute, a quarter note lasts two beats.
+    assert_eq!(1.0, quarter().to_seconds(120, 3));
+}
+
+#[test]
+fn tempo_map_2() {
+    // The tempo halves after two beats, so a half note starting on beat 1 lasts half a second
+    // at the old tempo and a second at the new one.
+    let tempo_map = TempoMap::new(120).tempo_change(2.0, 60);
+    let note = Note::new(60, half(), 90);
+    assert_eq!(1.0, note.duration_seconds(&tempo_map, 0.0));
+    assert_eq!(1.5, note.duration_seconds(&tempo_map, 1.0));
+    assert_eq!(60.0, tempo_map.bpm_at(3.0));
+    assert_eq!(2.0, tempo_map.seconds_at(3.0));
+}
+
+#[test]
+fn tempo_map_3() {
+    let midi = MidiBuilder::new()
+        .bpm(90)
+        .track("Lead")
+        .note(60, quarter(), 90)
+        .build();
+    let tempo_map = midi.tempo_map();
+    assert!((tempo_map.bpm_at(0.0) - 90.0).abs() < 0.01);
+    let note = Note::new(60, quarter(), 90);
+    let seconds = note.duration_seconds(&tempo_map, 0.0);
+    assert!((seconds - quarter().to_seconds(90, 2)).abs() < 1e-5);
+}
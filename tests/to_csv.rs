@@ -0,0 +1,34 @@
+use beatblox_midi::Midi;
+use beatblox_midi::export::csv::CSV_HEADER;
+use beatblox_midi::export::csv::escape_csv;
+
+#[test]
+fn to_csv_1() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let midi = Midi::parse(dir).unwrap();
+    let csv = midi.to_csv();
+    let mut lines = csv.lines();
+    assert_eq!(Some(CSV_HEADER), lines.next());
+    let columns = CSV_HEADER.split(',').count();
+    for line in lines {
+        assert_eq!(columns, line.split(',').count());
+    }
+}
+
+#[test]
+fn to_csv_2() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let midi = Midi::parse(dir).unwrap();
+    let track = &midi.tracks()[1];
+    let csv = track.to_csv(midi.time_signatures(), midi.ticks_per_beat());
+    let first = csv.lines().nth(1).unwrap();
+    assert!(first.starts_with("viola,1,1,1,67,G4,1,"));
+    assert!(midi.to_csv().contains(first));
+}
+
+#[test]
+fn to_csv_3() {
+    assert_eq!("Piano", escape_csv("Piano"));
+    assert_eq!("\"Piano, left\"", escape_csv("Piano, left"));
+    assert_eq!("\"say \"\"hi\"\"\"", escape_csv("say \"hi\""));
+}
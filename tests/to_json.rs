@@ -0,0 +1,31 @@
+use beatblox_midi::Midi;
+use beatblox_midi::export::escape_json;
+use beatblox_midi::export::json::JSON_SCHEMA_VERSION;
+
+#[test]
+fn to_json_1() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let json = Midi::parse(dir).unwrap().to_json();
+    assert!(json.starts_with(&format!("{{\"version\":{},", JSON_SCHEMA_VERSION)));
+    assert!(json.contains("\"time_signatures\":[{\"beat_count\":2,\"beat_type\":2,\"tick\":0}]"));
+    assert!(json.contains("\"key_signatures\":[{\"sharps\":1,\"minor\":false,\"tick\":0}]"));
+    assert!(json.contains("\"name\":\"viola\""));
+    assert!(json.contains("{\"type\":\"note\",\"pitch\":"));
+    assert!(json.ends_with("]}]}"));
+}
+
+#[test]
+fn to_json_2() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let json = Midi::parse(dir).unwrap().to_json();
+    let opened = json.matches(['{', '[']).count();
+    let closed = json.matches(['}', ']']).count();
+    assert_eq!(opened, closed);
+    assert!(!json.contains("NaN"));
+}
+
+#[test]
+fn to_json_3() {
+    assert_eq!("say \\\"hi\\\"\\n", escape_json("say \"hi\"\n"));
+    assert_eq!("\\u0001", escape_json("\u{1}"));
+}
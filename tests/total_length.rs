@@ -0,0 +1,48 @@
+use beatblox_midi::Midi;
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::duration::half;
+use beatblox_midi::parsing::duration::quarter;
+use beatblox_midi::parsing::duration::whole;
+
+#[test]
+fn total_length_1() {
+    let midi = MidiBuilder::new()
+        .bpm(120)
+        .track("Lead")
+        .note(60, whole(), 80)
+        .note(62, whole(), 80)
+        .note(64, quarter(), 80)
+        .track("Bass")
+        .note(36, whole(), 80)
+        .build();
+    assert_eq!(9.0, midi.total_beats());
+    assert_eq!(3, midi.total_measures());
+    assert!((midi.total_duration_seconds() - 4.5).abs() < 1e-6);
+}
+
+#[test]
+fn total_length_2() {
+    let midi = MidiBuilder::new()
+        .time_signature(3, 4)
+        .bpm(60)
+        .track("Waltz")
+        .note(60, half(), 80)
+        .note(62, quarter(), 80)
+        .note(64, half(), 80)
+        .note(65, quarter(), 80)
+        .build();
+    assert_eq!(6.0, midi.total_beats());
+    assert_eq!(2, midi.total_measures());
+    assert!((midi.total_duration_seconds() - 6.0).abs() < 1e-6);
+    assert_eq!(0, MidiBuilder::new().build().total_measures());
+}
+
+#[test]
+fn total_length_3() {
+    // The end of track event a tick after the second bar line doesn't start a third measure.
+    for file in ["test-3", "test-4", "test-5"] {
+        let midi = Midi::parse(format!("tests/test_files/{}.mid", file)).unwrap();
+        assert!(midi.total_beats() > 8.0);
+        assert_eq!(2, midi.total_measures());
+    }
+}
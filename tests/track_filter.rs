@@ -0,0 +1,31 @@
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::options::ParseOptions;
+
+fn names(midi: &Midi) -> Vec<&str> {
+    midi.tracks().iter().map(|t| t.name.as_str()).collect()
+}
+
+#[test]
+fn track_filter_1() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let options = ParseOptions::new().tracks(&[2]);
+    let midi = Midi::parse_with_options(dir.clone(), &options).unwrap();
+    assert_eq!(vec!["cello"], names(&midi));
+    assert_eq!(2, midi.tracks()[0].index);
+    let everything = Midi::parse(dir).unwrap();
+    let cello = everything.tracks().iter().find(|t| t.name == "cello").unwrap();
+    assert_eq!(cello.notes.len(), midi.tracks()[0].notes.len());
+}
+
+#[test]
+fn track_filter_2() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let options = ParseOptions::new().track_name_filter("VIO").merge_tracks(true);
+    let midi = Midi::parse_with_options(dir.clone(), &options).unwrap();
+    assert_eq!(vec!["viola"], names(&midi));
+    let viola = midi.tracks()[0].notes.iter().flat_map(|n| n.notes()).count();
+    let merged = midi.merged_track().unwrap().notes.iter().flat_map(|n| n.notes()).count();
+    assert_eq!(viola, merged);
+    let options = ParseOptions::new().tracks(&[0, 1]).track_name_filter("cello");
+    assert!(Midi::parse_with_options(dir, &options).unwrap().tracks().is_empty());
+}
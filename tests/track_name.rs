@@ -0,0 +1,20 @@
+use beatblox_midi::Midi;
+
+#[test]
+fn track_name_1() {
+    let dir = String::from("tests/test_files/test-2.mid");
+    let midi = Midi::parse(dir).unwrap();
+    assert_eq!(Some(String::from("one")), midi.tracks()[1].track_name);
+    assert_eq!("one", midi.tracks()[1].name);
+}
+
+#[test]
+fn track_name_2() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let midi = Midi::parse(dir).unwrap();
+    let track = &midi.tracks()[1];
+    assert_eq!(None, track.track_name);
+    assert_eq!(Some(String::from("viola")), track.instrument_name);
+    assert_eq!(Some(String::from("Viola")), track.instrument);
+    assert_eq!("viola", track.name);
+}
@@ -0,0 +1,39 @@
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::Track;
+use beatblox_midi::parsing::options::ParseOptions;
+use beatblox_midi::parsing::symbols::TimeSignature;
+
+#[test]
+fn track_write_smf_1() {
+    let dir = String::from("tests/test_files/test-2.mid");
+    let midi = Midi::parse(dir).unwrap();
+    let time_signature = &midi.time_signatures()[0];
+    let track = midi.tracks().iter().find(|t| !t.notes.is_empty()).unwrap();
+    let path = std::env::temp_dir().join("beatblox_midi_track_write_smf_1.mid");
+    let path = path.to_string_lossy().to_string();
+    track.write_smf(path.clone(), 90, time_signature).unwrap();
+    let written = Midi::parse_with_options(path.clone(), &ParseOptions::new()).unwrap();
+    std::fs::remove_file(path).unwrap();
+    let tracks: Vec<&Track> = written.tracks().iter().filter(|t| !t.notes.is_empty()).collect();
+    assert_eq!(1, tracks.len());
+    assert_eq!(track.name, tracks[0].name);
+    let time_signatures = midi.time_signatures();
+    assert_eq!(track.to_csv(time_signatures, 480.0), tracks[0].to_csv(time_signatures, 480.0));
+}
+
+#[test]
+fn track_write_smf_2() {
+    let track = Track::from_notation(String::from("bass"), "C2:q E2:q G2:h").unwrap();
+    let time_signature = TimeSignature { beat_count: 4, beat_type: 2, time_of_occurance: 0 };
+    let path = std::env::temp_dir().join("beatblox_midi_track_write_smf_2.mid");
+    let path = path.to_string_lossy().to_string();
+    track.write_smf(path.clone(), 100, &time_signature).unwrap();
+    let written = Midi::parse(path.clone()).unwrap();
+    std::fs::remove_file(path).unwrap();
+    let pitches: Vec<u8> = written.tracks().iter()
+        .flat_map(|t| t.notes.iter())
+        .flat_map(|n| n.notes())
+        .filter_map(|n| n.value())
+        .collect();
+    assert_eq!(vec![36, 40, 43], pitches);
+}
@@ -0,0 +1,31 @@
+use beatblox_midi::parsing::Track;
+use beatblox_midi::parsing::duration::DurationType;
+use beatblox_midi::parsing::duration::NoteDuration;
+use beatblox_midi::parsing::duration::NoteDurationModifier;
+use beatblox_midi::parsing::duration::Rational;
+use beatblox_midi::parsing::duration::quarter;
+
+#[test]
+fn triple_dotted_1() {
+    assert_eq!(1.875, quarter().triple_dotted().get_beat_count(2));
+    assert_eq!(Some(Rational::new(15, 32)), quarter().triple_dotted().to_rational());
+    assert_eq!("triple dotted", NoteDurationModifier::TripleDotted.to_string());
+}
+
+#[test]
+fn triple_dotted_2() {
+    let duration = DurationType::beat_type_map(1.875, 2);
+    assert_eq!(NoteDuration::QUARTER, duration.duration);
+    assert_eq!(NoteDurationModifier::TripleDotted, duration.modifier);
+    let duration = DurationType::beat_type_map(7.5, 2);
+    assert_eq!(NoteDuration::WHOLE, duration.duration);
+    assert_eq!(NoteDurationModifier::TripleDotted, duration.modifier);
+}
+
+#[test]
+fn triple_dotted_3() {
+    let track = Track::from_notation(String::from("Melody"), "C4:h... r:t").unwrap();
+    assert_eq!(3.875, track.length);
+    assert_eq!(3.75, track.notes[0].beat_count(2));
+    assert!(Track::from_notation(String::from("Bad"), "C4:q....").is_err());
+}
@@ -0,0 +1,34 @@
+use beatblox_midi::builder::MidiBuilder;
+use beatblox_midi::parsing::Track;
+use beatblox_midi::parsing::duration::quarter;
+
+fn track(velocities: &[u8]) -> Track {
+    let mut builder = MidiBuilder::new().track("Lead");
+    for velocity in velocities {
+        builder = builder.note(60, quarter(), *velocity).rest(quarter());
+    }
+    builder.build().tracks()[0].clone()
+}
+
+fn velocities(track: &Track) -> Vec<u8> {
+    track.notes.iter().flat_map(|n| n.notes()).map(|n| n.velocity).collect()
+}
+
+#[test]
+fn velocities_1() {
+    let mut spread = track(&[20, 60, 100]);
+    spread.normalize_velocities(40..=80);
+    assert_eq!(vec![40, 0, 60, 0, 80, 0], velocities(&spread));
+    let mut flat = track(&[90, 90]);
+    flat.normalize_velocities(40..=80);
+    assert_eq!(vec![60, 0, 60, 0], velocities(&flat));
+}
+
+#[test]
+fn velocities_2() {
+    let mut track = track(&[1, 40, 70, 100, 127]);
+    track.quantize_velocities(4);
+    assert_eq!(vec![32, 0, 32, 0, 64, 0, 95, 0, 127, 0], velocities(&track));
+    track.quantize_velocities(1);
+    assert_eq!(vec![127, 0, 127, 0, 127, 0, 127, 0, 127, 0], velocities(&track));
+}
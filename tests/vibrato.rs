@@ -0,0 +1,56 @@
+use beatblox_midi::parsing::duration::DurationType;
+use beatblox_midi::parsing::expression::ControllerEvent;
+use beatblox_midi::parsing::expression::PitchBendMode;
+use beatblox_midi::parsing::expression::VibratoSource;
+use beatblox_midi::parsing::expression::attach_modulation;
+use beatblox_midi::parsing::expression::attach_pitch_bends;
+use beatblox_midi::parsing::expression::find_vibrato;
+use beatblox_midi::parsing::symbols::NoteWrapper;
+
+fn quarter(value: u8) -> NoteWrapper {
+    NoteWrapper::build_note_wrapper(Some(value), DurationType::beat_type_map(1.0, 2), 100)
+}
+
+fn modulation(beat: f32, value: u8) -> ControllerEvent {
+    ControllerEvent { controller: 1, beat, value }
+}
+
+#[test]
+fn vibrato_1() {
+    // Three cycles of a 30 cent vibrato over one beat.
+    let bend: Vec<(f32, f32)> = (0..12)
+        .map(|i| (i as f32 / 12.0, [0.0, 30.0, 0.0, -30.0][i % 4]))
+        .collect();
+    let vibrato = find_vibrato(&bend).unwrap();
+    assert_eq!(vibrato.source, VibratoSource::PitchBend);
+    assert_eq!(vibrato.depth, 30.0);
+    let rate = vibrato.rate.unwrap();
+    assert!((rate - 3.0).abs() < 0.5, "{}", rate);
+    // A single wobble and a wide sweep aren't vibrato.
+    assert!(find_vibrato(&bend[..5]).is_none());
+    let wide: Vec<(f32, f32)> = bend.iter().map(|(beat, cents)| (*beat, cents * 10.0)).collect();
+    assert!(find_vibrato(&wide).is_none());
+
+    let bends: Vec<(u32, f32)> = bend
+        .iter()
+        .map(|(beat, cents)| ((beat * 96.0).round() as u32, *cents))
+        .collect();
+    let mut notes = vec![quarter(60), quarter(62)];
+    attach_pitch_bends(&mut notes, &bends, PitchBendMode::Detune, 2, 96.0);
+    assert!(notes[0].notes()[0].vibrato.is_some());
+    assert!(notes[1].notes()[0].vibrato.is_none());
+}
+
+#[test]
+fn vibrato_2() {
+    let events = vec![modulation(0.5, 64), modulation(1.0, 0), modulation(2.5, 90)];
+    let mut notes = vec![quarter(60), quarter(62), quarter(64), quarter(65)];
+    attach_modulation(&mut notes, &events, 2);
+    let vibratos: Vec<Option<f32>> = notes
+        .iter()
+        .map(|n| n.notes()[0].vibrato.map(|v| v.depth))
+        .collect();
+    assert_eq!(vec![Some(64.0), None, Some(90.0), Some(90.0)], vibratos);
+    let vibrato = notes[0].notes()[0].vibrato.unwrap();
+    assert_eq!((vibrato.source, vibrato.rate), (VibratoSource::Modulation, None));
+}
@@ -0,0 +1,36 @@
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::options::ParseOptions;
+use beatblox_midi::view::MidiRef;
+use std::borrow::Cow;
+
+#[test]
+fn view_1() {
+    let bytes = std::fs::read("tests/test_files/test-1.mid").unwrap();
+    let options = ParseOptions::new();
+    let midi = Midi::parse_bytes(&bytes, &options).unwrap();
+    let view = MidiRef::parse(&bytes).unwrap();
+    assert_eq!(midi.tracks().len(), view.track_count());
+    assert_eq!(midi.ticks_per_beat(), view.ticks_per_beat());
+    for track in midi.tracks() {
+        let notes = view.notes(track.index, &options).unwrap().unwrap();
+        assert_eq!(track.notes.len(), notes.len());
+        assert_eq!(track.track_name.as_deref(), view.track_name(track.index).as_deref());
+    }
+    assert!(view.notes(view.track_count(), &options).unwrap().is_none());
+}
+
+#[test]
+fn view_2() {
+    let bytes = std::fs::read("tests/test_files/test-2.mid").unwrap();
+    let view = MidiRef::parse(&bytes).unwrap();
+    for index in 0..view.track_count() {
+        if let Some(name) = view.track_name(index) {
+            assert!(matches!(name, Cow::Borrowed(_)));
+        }
+    }
+    let options = ParseOptions::new();
+    let midi = view.to_midi(&options).unwrap();
+    let expected = Midi::parse_bytes(&bytes, &options).unwrap();
+    assert_eq!(expected.tracks().len(), midi.tracks().len());
+    assert_eq!(expected.time_signatures().len(), view.time_signatures().len());
+}
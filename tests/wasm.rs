@@ -0,0 +1,14 @@
+#![cfg(feature = "wasm")]
+
+use beatblox_midi::Midi;
+use beatblox_midi::wasm;
+
+#[test]
+fn wasm_1() {
+    let bytes = std::fs::read("tests/test_files/test-1.mid").unwrap();
+    let midi = Midi::parse(String::from("tests/test_files/test-1.mid")).unwrap();
+    let parsed = wasm::parse_bytes(&bytes, false).unwrap();
+    assert_eq!(parsed.track_count(), midi.tracks().len());
+    assert_eq!(parsed.to_json(), midi.to_json());
+    assert_eq!(wasm::to_json(&bytes, false).unwrap(), midi.to_json());
+}
@@ -0,0 +1,30 @@
+#![cfg(feature = "watch")]
+
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::options::ParseOptions;
+use beatblox_midi::watch::MidiWatcher;
+use std::sync::mpsc;
+use std::time::Duration;
+
+#[test]
+fn watch_1() {
+    let directory = std::env::temp_dir().join(format!("beatblox_watch_{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("song.mid");
+    std::fs::write(&path, b"").unwrap();
+    let (sender, receiver) = mpsc::channel();
+    let watcher = MidiWatcher::new(&path, ParseOptions::new(), move |midi| {
+        let _ = sender.send(midi);
+    }).unwrap();
+    assert_eq!(watcher.path(), path);
+    let bytes = std::fs::read("tests/test_files/test-1.mid").unwrap();
+    std::fs::write(&path, &bytes).unwrap();
+    let midi = receiver.recv_timeout(Duration::from_secs(10)).unwrap().unwrap();
+    let expected = Midi::parse_bytes(&bytes, &ParseOptions::new()).unwrap();
+    assert_eq!(midi.to_json(), expected.to_json());
+    // Saving the same contents again doesn't parse the file again.
+    std::fs::write(&path, &bytes).unwrap();
+    assert!(receiver.recv_timeout(Duration::from_millis(500)).is_err());
+    drop(watcher);
+    std::fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,25 @@
+use beatblox_midi::Midi;
+use beatblox_midi::parsing::options::ParseOptions;
+
+#[test]
+fn write_smf_1() {
+    let dir = String::from("tests/test_files/test-1.mid");
+    let midi = Midi::parse(dir).unwrap();
+    let written = Midi::parse_bytes(&midi.to_smf_bytes(), &ParseOptions::new()).unwrap();
+    assert_eq!(midi.to_csv(), written.to_csv());
+    assert_eq!(midi.key_signatures(), written.key_signatures());
+    assert_eq!(2, written.time_signatures()[0].beat_count);
+}
+
+#[test]
+fn write_smf_2() {
+    let dir = String::from("tests/test_files/test-2.mid");
+    let options = ParseOptions::new();
+    let midi = Midi::parse_with_options(dir, &options).unwrap();
+    let path = std::env::temp_dir().join("beatblox_midi_write_smf_2.mid");
+    let path = path.to_string_lossy().to_string();
+    midi.write_smf(path.clone()).unwrap();
+    let written = Midi::parse_with_options(path.clone(), &options).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(midi.to_csv(), written.to_csv());
+}